@@ -0,0 +1,53 @@
+// Linear memory access benchmarks
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+use wasm_o::wasm::*;
+
+/// (func (export "run") (param $n i32) (result i32) (local $acc i32)
+///   ;; store $acc to memory, load it back and add $n, until $n reaches 0
+/// )
+const MODULE: [u8; 97] = [
+    0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F, 0x01, 0x7F,
+    0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x07, 0x01, 0x03, 0x72, 0x75, 0x6E,
+    0x00, 0x00, 0x0A, 0x3D, 0x01, 0x3B, 0x01, 0x01, 0x7F, 0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x45,
+    0x0D, 0x01, 0x20, 0x00, 0x41, 0x02, 0x74, 0x41, 0xFC, 0xFF, 0x03, 0x71, 0x20, 0x01, 0x36, 0x02,
+    0x00, 0x20, 0x00, 0x41, 0x02, 0x74, 0x41, 0xFC, 0xFF, 0x03, 0x71, 0x28, 0x02, 0x00, 0x20, 0x00,
+    0x6A, 0x21, 0x01, 0x20, 0x00, 0x41, 0x01, 0x6B, 0x21, 0x00, 0x0C, 0x00, 0x0B, 0x0B, 0x20, 0x01,
+    0x0B,
+];
+
+const N_LOOPS: i32 = 10000;
+
+fn run(b: &mut Bencher, module: WasmModule) {
+    let runnable = module.func("run").unwrap();
+    b.iter(|| {
+        let result = runnable.invoke(&[N_LOOPS.into()]).unwrap();
+        assert_eq!(result.get_i32().unwrap(), N_LOOPS * (N_LOOPS + 1) / 2);
+    });
+}
+
+#[bench]
+fn checked_memory(b: &mut Bencher) {
+    let module =
+        WasmLoader::instantiate(&MODULE, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+            .unwrap();
+    assert!(!module.memory(0).unwrap().is_guarded());
+    run(b, module);
+}
+
+#[cfg(all(feature = "mmap", target_os = "linux", target_arch = "x86_64"))]
+#[bench]
+fn guarded_memory(b: &mut Bencher) {
+    use wasm_o::mmap::MmapMemoryBackend;
+    let module = WasmLoader::instantiate_with_allocator(
+        &MODULE,
+        &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+        &MmapMemoryBackend::allocator,
+    )
+    .unwrap();
+    assert!(module.memory(0).unwrap().is_guarded());
+    run(b, module);
+}
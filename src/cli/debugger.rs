@@ -0,0 +1,408 @@
+// Interactive debugger
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::rc::Rc;
+use wasm_o::wasm::*;
+use wasm_o::wasmintr::WasmFrame;
+
+/// How the debugger stops the execution of the guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepMode {
+    /// Stops only at the breakpoints
+    Continue,
+    /// Stops at the next instruction
+    Step,
+    /// Stops at the next instruction of the frame of the depth or outer
+    Next(usize),
+}
+
+struct Debugger {
+    /// Pairs of the function index and the offset of the instruction
+    breakpoints: Vec<(usize, usize)>,
+    mode: StepMode,
+    /// The user quits while the guest is stopped
+    is_quitting: bool,
+}
+
+impl Debugger {
+    const fn new() -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            mode: StepMode::Continue,
+            is_quitting: false,
+        }
+    }
+
+    fn should_stop(&self, frame: &WasmFrame) -> bool {
+        match self.mode {
+            StepMode::Step => true,
+            StepMode::Next(depth) if frame.depth() <= depth => true,
+            _ => self
+                .breakpoints
+                .contains(&(frame.func_index(), frame.position())),
+        }
+    }
+}
+
+const HELP: &str = "\
+commands:
+  invoke NAME ARGS...   invoke the export NAME, stops at the breakpoints
+  step NAME ARGS...     invoke the export NAME, stops at the first instruction
+  start                 run the start function
+  break FUNC[:OFFSET]   set the breakpoint at the offset of the function, 0 if omitted
+  delete FUNC[:OFFSET]  delete the breakpoint
+  breaks                list the breakpoints
+  disas FUNC            print the disassembly of the function with the offsets
+  exports               list the exports
+  globals               print the globals
+  memory ADDR [LEN] [MEMIDX]
+                        dump the memory
+  help                  print this help
+  quit                  quit the debugger
+
+commands while stopped:
+  step, s               execute the instruction, stepping into the calls
+  next, n               execute the instruction, stepping over the calls
+  continue, c           continue to the next breakpoint
+  locals                print the parameters and locals of the frame
+  stack                 print the value stack of the frame, bottom first
+  where                 print the current instruction
+  kill                  abort the invocation";
+
+/// Runs the interactive debugger on the module, returns the exit code of the process
+pub fn run(mut module: WasmModule) -> i32 {
+    let debugger = Rc::new(RefCell::new(Debugger::new()));
+    {
+        let debugger = debugger.clone();
+        module.set_debug_hook(move |frame| {
+            if !debugger.borrow().should_stop(frame) {
+                return Ok(());
+            }
+            stopped(&debugger, frame)
+        });
+    }
+
+    println!("type \"help\" for the commands");
+    if let Some(start) = module.start() {
+        println!("the start function (func {}) has not run yet", start);
+    }
+    loop {
+        if debugger.borrow().is_quitting {
+            return 0;
+        }
+        let line = match read_line("(wasm-o) ") {
+            Some(v) => v,
+            None => return 0,
+        };
+        let args = line.split_whitespace().collect::<Vec<_>>();
+        let (command, args) = match args.split_first() {
+            Some((command, args)) => (*command, args),
+            None => continue,
+        };
+        match command {
+            "invoke" | "call" | "step" | "s" => {
+                let mode = if matches!(command, "step" | "s") {
+                    StepMode::Step
+                } else {
+                    StepMode::Continue
+                };
+                match args.split_first() {
+                    Some((name, args)) => {
+                        debugger.borrow_mut().mode = mode;
+                        invoke(&module, name, args);
+                    }
+                    None => println!("usage: {} NAME ARGS...", command),
+                }
+            }
+            "start" => {
+                debugger.borrow_mut().mode = StepMode::Continue;
+                match module.entry_point().and_then(|v| v.invoke(&[])) {
+                    Ok(_) => (),
+                    Err(err) => println!("error: {:?}", err),
+                }
+            }
+            "quit" | "q" => return 0,
+            _ => {
+                if !common_command(&debugger, &module, command, args) {
+                    println!("unknown command: {}", command);
+                }
+            }
+        }
+    }
+}
+
+/// Interacts with the user while the guest is stopped before the instruction
+fn stopped(debugger: &RefCell<Debugger>, frame: &WasmFrame) -> Result<(), WasmRuntimeError> {
+    print_where(frame);
+    loop {
+        let line = match read_line("(wasm-o stopped) ") {
+            Some(v) => v,
+            None => {
+                debugger.borrow_mut().is_quitting = true;
+                return Err(WasmRuntimeError::Interrupted);
+            }
+        };
+        let args = line.split_whitespace().collect::<Vec<_>>();
+        let (command, args) = match args.split_first() {
+            Some((command, args)) => (*command, args),
+            None => continue,
+        };
+        match command {
+            "step" | "s" => {
+                debugger.borrow_mut().mode = StepMode::Step;
+                return Ok(());
+            }
+            "next" | "n" => {
+                debugger.borrow_mut().mode = StepMode::Next(frame.depth());
+                return Ok(());
+            }
+            "continue" | "c" => {
+                debugger.borrow_mut().mode = StepMode::Continue;
+                return Ok(());
+            }
+            "kill" => return Err(WasmRuntimeError::Interrupted),
+            "quit" | "q" => {
+                debugger.borrow_mut().is_quitting = true;
+                return Err(WasmRuntimeError::Interrupted);
+            }
+            "where" => print_where(frame),
+            "locals" => print_locals(frame),
+            "stack" => {
+                for (index, value) in frame.value_stack().iter().enumerate() {
+                    let bits = value.get_u64();
+                    println!("  [{}] 0x{:016x} ({})", index, bits, bits as i64);
+                }
+            }
+            "invoke" | "call" | "start" => {
+                println!("the guest is running, continue or kill it first");
+            }
+            _ => {
+                if !common_command(debugger, frame.module(), command, args) {
+                    println!("unknown command: {}", command);
+                }
+            }
+        }
+    }
+}
+
+/// Executes the command available both while running and stopped, returns false if unknown
+fn common_command(
+    debugger: &RefCell<Debugger>,
+    module: &WasmModule,
+    command: &str,
+    args: &[&str],
+) -> bool {
+    match command {
+        "help" | "h" => println!("{}", HELP),
+        "break" | "b" | "delete" | "d" => {
+            let location = match args.first().and_then(|v| parse_location(v)) {
+                Some(v) => v,
+                None => {
+                    println!("usage: {} FUNC[:OFFSET]", command);
+                    return true;
+                }
+            };
+            let mut debugger = debugger.borrow_mut();
+            if matches!(command, "break" | "b") {
+                match module.functions().get(location.0) {
+                    Some(function) if function.body().is_some() => {
+                        if !debugger.breakpoints.contains(&location) {
+                            debugger.breakpoints.push(location);
+                        }
+                        println!("breakpoint at func {} @{:04x}", location.0, location.1);
+                    }
+                    _ => println!("func {} has no body", location.0),
+                }
+            } else {
+                debugger.breakpoints.retain(|v| *v != location);
+            }
+        }
+        "breaks" => {
+            for (func_index, position) in &debugger.borrow().breakpoints {
+                println!("  func {} @{:04x}", func_index, position);
+            }
+        }
+        "disas" => match args.first().and_then(|v| parse_usize(v)) {
+            Some(func_index) if func_index < module.functions().len() => {
+                if let Err(err) = module.disassemble(func_index) {
+                    println!("error: {:?}", err);
+                }
+            }
+            _ => println!("usage: disas FUNC"),
+        },
+        "exports" => {
+            for export in module.exports() {
+                match export.index() {
+                    WasmExportIndex::Function(index) => {
+                        let func_type = module
+                            .functions()
+                            .get(index)
+                            .and_then(|v| module.type_by_ref(v.type_index()));
+                        match func_type {
+                            Some(func_type) => {
+                                println!("  func {} \"{}\"{}", index, export.name(), func_type)
+                            }
+                            None => println!("  func {} \"{}\"", index, export.name()),
+                        }
+                    }
+                    WasmExportIndex::Table(index) => {
+                        println!("  table {} \"{}\"", index, export.name())
+                    }
+                    WasmExportIndex::Memory(index) => {
+                        println!("  memory {} \"{}\"", index, export.name())
+                    }
+                    WasmExportIndex::Global(index) => {
+                        println!("  global {} \"{}\"", index, export.name())
+                    }
+                    WasmExportIndex::Tag(index) => {
+                        println!("  tag {} \"{}\"", index, export.name())
+                    }
+                }
+            }
+        }
+        "globals" => {
+            for (index, global) in module.globals().iter().enumerate() {
+                let val_type = if global.is_mutable() {
+                    format!("(mut {})", global.val_type())
+                } else {
+                    format!("{}", global.val_type())
+                };
+                println!("  global {} {} = {}", index, val_type, global.get());
+            }
+        }
+        "memory" | "x" => {
+            let base = args.first().and_then(|v| parse_usize(v));
+            let len = args.get(1).map_or(Some(64), |v| parse_usize(v));
+            let memidx = args.get(2).map_or(Some(0), |v| parse_usize(v));
+            match (base, len, memidx) {
+                (Some(base), Some(len), Some(memidx)) => match module.memory(memidx) {
+                    Some(memory) => dump_memory(memory, base, len),
+                    None => println!("no memory {}", memidx),
+                },
+                _ => println!("usage: memory ADDR [LEN] [MEMIDX]"),
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Invokes the export with the arguments parsed by its parameter types
+fn invoke(module: &WasmModule, name: &str, args: &[&str]) {
+    let runnable = match module.func(name) {
+        Ok(v) => v,
+        Err(err) => {
+            println!("error: {}: {:?}", name, err);
+            return;
+        }
+    };
+    let param_types = runnable.function().param_types();
+    if args.len() != param_types.len() {
+        println!(
+            "error: {} expects {} arguments, but {} given",
+            name,
+            param_types.len(),
+            args.len()
+        );
+        return;
+    }
+    let mut params = Vec::with_capacity(args.len());
+    for (arg, val_type) in args.iter().zip(param_types) {
+        match super::parse_value(arg, *val_type) {
+            Some(v) => params.push(v),
+            None => {
+                println!("error: invalid argument for {}: {}", val_type, arg);
+                return;
+            }
+        }
+    }
+    match runnable.invoke(&params) {
+        Ok(WasmValue::Empty) => (),
+        Ok(v) => println!("{}", v),
+        Err(WasmRuntimeError::Interrupted) => println!("killed"),
+        Err(err) => println!("error: {:?}", err),
+    }
+}
+
+fn print_where(frame: &WasmFrame) {
+    println!(
+        "func {} @{:04x} {} (depth {})",
+        frame.func_index(),
+        frame.position(),
+        frame.opcode().to_str(),
+        frame.depth()
+    );
+}
+
+fn print_locals(frame: &WasmFrame) {
+    let module = frame.module();
+    let function = match module.functions().get(frame.func_index()) {
+        Some(v) => v,
+        None => return,
+    };
+    let local_types = function.body().map(|v| v.local_types()).unwrap_or_default();
+    for (index, (val_type, value)) in function
+        .param_types()
+        .iter()
+        .chain(local_types)
+        .zip(frame.locals())
+        .enumerate()
+    {
+        println!("  local {} {} = {}", index, val_type, value);
+    }
+}
+
+fn dump_memory(memory: &WasmMemory, base: usize, len: usize) {
+    let bytes = match memory.read_bytes(base, len) {
+        Ok(v) => v,
+        Err(err) => {
+            println!("error: {:?}", err);
+            return;
+        }
+    };
+    for (index, line) in bytes.chunks(16).enumerate() {
+        let hex = line
+            .iter()
+            .map(|v| format!("{:02x}", v))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = line
+            .iter()
+            .map(|&v| {
+                if v.is_ascii_graphic() || v == b' ' {
+                    v as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        println!("  {:08x}  {:<47}  {}", base + index * 16, hex, ascii);
+    }
+}
+
+/// Parses "FUNC" or "FUNC:OFFSET", the offset is hexadecimal as in the disassembly
+fn parse_location(s: &str) -> Option<(usize, usize)> {
+    match s.split_once(':') {
+        Some((func_index, position)) => Some((
+            parse_usize(func_index)?,
+            usize::from_str_radix(position.trim_start_matches("0x"), 16).ok()?,
+        )),
+        None => Some((parse_usize(s)?, 0)),
+    }
+}
+
+fn parse_usize(s: &str) -> Option<usize> {
+    super::parse_int(s).and_then(|v| usize::try_from(v).ok())
+}
+
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line),
+    }
+}
@@ -0,0 +1,465 @@
+// Summary of the module
+
+use std::fmt::Write;
+use wasm_o::wasm::*;
+
+/// Decodes the module only to inspect it, without instantiating it
+pub fn load(blob: &[u8]) -> Result<WasmModule, WasmDecodeError> {
+    let mut loader = WasmLoader::with_features(WasmFeatures::available());
+    loader.decode(blob)?;
+    Ok(loader.into_module())
+}
+
+/// Number of the globals imported, which precede the globals of the module
+fn n_imported_globals(module: &WasmModule) -> usize {
+    module
+        .imports()
+        .iter()
+        .filter(|v| matches!(v.index(), WasmImportIndex::Global(_, _)))
+        .count()
+}
+
+fn limit_text(limit: WasmLimit) -> String {
+    let mut s = String::new();
+    if limit.is_64() {
+        s.push_str("i64 ");
+    }
+    let _ = write!(s, "{}", limit.min());
+    if let Some(max) = limit.max() {
+        let _ = write!(s, " {}", max);
+    }
+    if limit.is_shared() {
+        s.push_str(" shared");
+    }
+    s
+}
+
+fn global_type_text(val_type: WasmValType, is_mutable: bool) -> String {
+    if is_mutable {
+        format!("(mut {})", val_type)
+    } else {
+        format!("{}", val_type)
+    }
+}
+
+fn func_type_text(module: &WasmModule, type_index: usize) -> String {
+    module
+        .type_by_ref(type_index)
+        .map(|v| format!("{}", v))
+        .unwrap_or_default()
+}
+
+fn segment_mode_text(mode: WasmSegmentMode) -> String {
+    match mode {
+        WasmSegmentMode::Active(index, offset) => {
+            format!("active memory {} offset 0x{:x}", index, offset)
+        }
+        WasmSegmentMode::Passive => "passive".to_string(),
+        _ => "declarative".to_string(),
+    }
+}
+
+/// Prints the summary as text
+pub fn print_text(module: &WasmModule) {
+    println!("sections:");
+    for section in module.sections() {
+        print!(
+            "  {:<10} offset 0x{:08x} size {}",
+            section.name(),
+            section.offset(),
+            section.size()
+        );
+        match section.custom_name() {
+            Some(name) => println!(" \"{}\"", name),
+            None => println!(),
+        }
+    }
+
+    println!("imports:");
+    let mut n_funcs = 0;
+    let mut n_tables = 0;
+    let mut n_memories = 0;
+    let mut n_globals = 0;
+    let mut n_tags = 0;
+    for import in module.imports() {
+        let (kind, index, desc) = match import.index() {
+            WasmImportIndex::Type(v) => {
+                n_funcs += 1;
+                ("func", n_funcs - 1, func_type_text(module, v))
+            }
+            WasmImportIndex::Table(elem_type, limit) => {
+                n_tables += 1;
+                (
+                    "table",
+                    n_tables - 1,
+                    format!(" {} {}", limit_text(limit), elem_type),
+                )
+            }
+            WasmImportIndex::Memory(v) => {
+                n_memories += 1;
+                ("memory", n_memories - 1, format!(" {}", limit_text(v)))
+            }
+            WasmImportIndex::Global(val_type, is_mutable) => {
+                n_globals += 1;
+                (
+                    "global",
+                    n_globals - 1,
+                    format!(" {}", global_type_text(val_type, is_mutable)),
+                )
+            }
+            WasmImportIndex::Tag(v) => {
+                n_tags += 1;
+                ("tag", n_tags - 1, func_type_text(module, v))
+            }
+        };
+        println!(
+            "  {} {} (import \"{}\".\"{}\"){}",
+            kind,
+            index,
+            import.mod_name(),
+            import.name(),
+            desc
+        );
+    }
+
+    println!("exports:");
+    for export in module.exports() {
+        let (kind, index, desc) = match export.index() {
+            WasmExportIndex::Function(v) => (
+                "func",
+                v,
+                module
+                    .functions()
+                    .get(v)
+                    .map(|f| func_type_text(module, f.type_index()))
+                    .unwrap_or_default(),
+            ),
+            WasmExportIndex::Table(v) => (
+                "table",
+                v,
+                module
+                    .table(v)
+                    .map(|t| format!(" {} {}", limit_text(t.limit()), t.elem_type()))
+                    .unwrap_or_default(),
+            ),
+            WasmExportIndex::Memory(v) => (
+                "memory",
+                v,
+                module
+                    .memory(v)
+                    .map(|m| format!(" {}", limit_text(m.limit())))
+                    .unwrap_or_default(),
+            ),
+            WasmExportIndex::Global(v) => (
+                "global",
+                v,
+                module
+                    .global(v)
+                    .map(|g| format!(" {}", global_type_text(g.val_type(), g.is_mutable())))
+                    .unwrap_or_default(),
+            ),
+            WasmExportIndex::Tag(v) => (
+                "tag",
+                v,
+                module
+                    .tag(v)
+                    .map(|t| func_type_text(module, t.type_index()))
+                    .unwrap_or_default(),
+            ),
+        };
+        println!(
+            "  {} {} (export \"{}\"){}",
+            kind,
+            index,
+            export.name(),
+            desc
+        );
+    }
+
+    println!("tables:");
+    let mut index = 0;
+    while let Some(table) = module.table(index) {
+        println!(
+            "  table {} {} {}",
+            index,
+            limit_text(table.limit()),
+            table.elem_type()
+        );
+        index += 1;
+    }
+
+    println!("memories:");
+    let mut index = 0;
+    while let Some(memory) = module.memory(index) {
+        println!("  memory {} {}", index, limit_text(memory.limit()));
+        index += 1;
+    }
+
+    println!("globals:");
+    let n_imported = n_imported_globals(module);
+    for (index, global) in module.globals().iter().enumerate().skip(n_imported) {
+        println!(
+            "  global {} {} {}",
+            index,
+            global_type_text(global.val_type(), global.is_mutable()),
+            global.get()
+        );
+    }
+
+    println!("data:");
+    for (index, segment) in module.data_segments().iter().enumerate() {
+        println!(
+            "  data {} {} size {}",
+            index,
+            segment_mode_text(segment.mode()),
+            segment.size()
+        );
+    }
+
+    if let Some(start) = module.start() {
+        println!("start: func {}", start);
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(result, "\\u{:04x}", c as u32);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_types(types: &[WasmValType]) -> String {
+    let types = types
+        .iter()
+        .map(|v| json_str(&v.to_string()))
+        .collect::<Vec<_>>();
+    format!("[{}]", types.join(","))
+}
+
+fn json_func_type(module: &WasmModule, type_index: usize) -> String {
+    match module.type_by_ref(type_index) {
+        Some(v) => format!(
+            "{{\"params\":{},\"results\":{}}}",
+            json_types(v.param_types()),
+            json_types(v.result_types())
+        ),
+        None => "null".to_string(),
+    }
+}
+
+fn json_limit(limit: WasmLimit) -> String {
+    format!(
+        "{{\"min\":{},\"max\":{},\"index_type\":{},\"shared\":{}}}",
+        limit.min(),
+        limit
+            .max()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "null".to_string()),
+        json_str(&limit.index_type().to_string()),
+        limit.is_shared()
+    )
+}
+
+fn json_value(value: WasmValue) -> String {
+    match value {
+        WasmValue::I32(v) => v.to_string(),
+        WasmValue::I64(v) => v.to_string(),
+        WasmValue::F32(v) if v.is_finite() => v.to_string(),
+        WasmValue::F64(v) if v.is_finite() => v.to_string(),
+        _ => json_str(&value.to_string()),
+    }
+}
+
+fn json_segment_mode(mode: WasmSegmentMode) -> String {
+    match mode {
+        WasmSegmentMode::Active(index, offset) => format!(
+            "\"mode\":\"active\",\"memory\":{},\"offset\":{}",
+            index, offset
+        ),
+        WasmSegmentMode::Passive => "\"mode\":\"passive\"".to_string(),
+        _ => "\"mode\":\"declarative\"".to_string(),
+    }
+}
+
+/// Returns the summary as a JSON object
+pub fn to_json(module: &WasmModule) -> String {
+    let sections = module
+        .sections()
+        .iter()
+        .map(|v| {
+            format!(
+                "{{\"name\":{},\"custom_name\":{},\"offset\":{},\"size\":{}}}",
+                json_str(v.name()),
+                v.custom_name()
+                    .map(json_str)
+                    .unwrap_or_else(|| "null".to_string()),
+                v.offset(),
+                v.size()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let imports = module
+        .imports()
+        .iter()
+        .map(|v| {
+            let desc = match v.index() {
+                WasmImportIndex::Type(index) => {
+                    format!(
+                        "\"kind\":\"func\",\"type\":{}",
+                        json_func_type(module, index)
+                    )
+                }
+                WasmImportIndex::Table(elem_type, limit) => format!(
+                    "\"kind\":\"table\",\"type\":{},\"limits\":{}",
+                    json_str(&elem_type.to_string()),
+                    json_limit(limit)
+                ),
+                WasmImportIndex::Memory(limit) => {
+                    format!("\"kind\":\"memory\",\"limits\":{}", json_limit(limit))
+                }
+                WasmImportIndex::Global(val_type, is_mutable) => format!(
+                    "\"kind\":\"global\",\"type\":{},\"mutable\":{}",
+                    json_str(&val_type.to_string()),
+                    is_mutable
+                ),
+                WasmImportIndex::Tag(index) => {
+                    format!(
+                        "\"kind\":\"tag\",\"type\":{}",
+                        json_func_type(module, index)
+                    )
+                }
+            };
+            format!(
+                "{{\"module\":{},\"name\":{},{}}}",
+                json_str(v.mod_name()),
+                json_str(v.name()),
+                desc
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let exports = module
+        .exports()
+        .iter()
+        .map(|v| {
+            let desc = match v.index() {
+                WasmExportIndex::Function(index) => format!(
+                    "\"kind\":\"func\",\"index\":{},\"type\":{}",
+                    index,
+                    module
+                        .functions()
+                        .get(index)
+                        .map(|f| json_func_type(module, f.type_index()))
+                        .unwrap_or_else(|| "null".to_string())
+                ),
+                WasmExportIndex::Table(index) => format!(
+                    "\"kind\":\"table\",\"index\":{},\"limits\":{}",
+                    index,
+                    module
+                        .table(index)
+                        .map(|t| json_limit(t.limit()))
+                        .unwrap_or_else(|| "null".to_string())
+                ),
+                WasmExportIndex::Memory(index) => format!(
+                    "\"kind\":\"memory\",\"index\":{},\"limits\":{}",
+                    index,
+                    module
+                        .memory(index)
+                        .map(|m| json_limit(m.limit()))
+                        .unwrap_or_else(|| "null".to_string())
+                ),
+                WasmExportIndex::Global(index) => format!(
+                    "\"kind\":\"global\",\"index\":{},\"type\":{},\"mutable\":{}",
+                    index,
+                    module
+                        .global(index)
+                        .map(|g| json_str(&g.val_type().to_string()))
+                        .unwrap_or_else(|| "null".to_string()),
+                    module
+                        .global(index)
+                        .map(|g| g.is_mutable())
+                        .unwrap_or_default()
+                ),
+                WasmExportIndex::Tag(index) => format!(
+                    "\"kind\":\"tag\",\"index\":{},\"type\":{}",
+                    index,
+                    module
+                        .tag(index)
+                        .map(|t| json_func_type(module, t.type_index()))
+                        .unwrap_or_else(|| "null".to_string())
+                ),
+            };
+            format!("{{\"name\":{},{}}}", json_str(v.name()), desc)
+        })
+        .collect::<Vec<_>>();
+
+    let mut tables = Vec::new();
+    while let Some(table) = module.table(tables.len()) {
+        tables.push(format!(
+            "{{\"elem_type\":{},\"limits\":{}}}",
+            json_str(&table.elem_type().to_string()),
+            json_limit(table.limit())
+        ));
+    }
+
+    let mut memories = Vec::new();
+    while let Some(memory) = module.memory(memories.len()) {
+        memories.push(format!("{{\"limits\":{}}}", json_limit(memory.limit())));
+    }
+
+    let n_imported = n_imported_globals(module);
+    let globals = module
+        .globals()
+        .iter()
+        .enumerate()
+        .map(|(index, global)| {
+            format!(
+                "{{\"index\":{},\"type\":{},\"mutable\":{},\"imported\":{},\"init\":{}}}",
+                index,
+                json_str(&global.val_type().to_string()),
+                global.is_mutable(),
+                index < n_imported,
+                if index < n_imported {
+                    "null".to_string()
+                } else {
+                    json_value(global.get())
+                }
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let data = module
+        .data_segments()
+        .iter()
+        .map(|v| format!("{{{},\"size\":{}}}", json_segment_mode(v.mode()), v.size()))
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"sections\":[{}],\"imports\":[{}],\"exports\":[{}],\"tables\":[{}],\"memories\":[{}],\"globals\":[{}],\"data\":[{}],\"start\":{}}}",
+        sections.join(","),
+        imports.join(","),
+        exports.join(","),
+        tables.join(","),
+        memories.join(","),
+        globals.join(","),
+        data.join(","),
+        module
+            .start()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "null".to_string())
+    )
+}
@@ -0,0 +1,34 @@
+// Command line interface
+
+pub mod debugger;
+pub mod inspect;
+pub mod wasi;
+
+use wasm_o::wasm::*;
+
+/// Parses the argument as the value of the type, integers may be hexadecimal with "0x"
+pub fn parse_value(s: &str, val_type: WasmValType) -> Option<WasmValue> {
+    match val_type {
+        WasmValType::I32 => parse_int(s)
+            .filter(|v| *v >= i32::MIN as i128 && *v <= u32::MAX as i128)
+            .map(|v| WasmValue::I32(v as i32)),
+        WasmValType::I64 => parse_int(s)
+            .filter(|v| *v >= i64::MIN as i128 && *v <= u64::MAX as i128)
+            .map(|v| WasmValue::I64(v as i64)),
+        WasmValType::F32 => s.parse().ok().map(WasmValue::F32),
+        WasmValType::F64 => s.parse().ok().map(WasmValue::F64),
+        _ => None,
+    }
+}
+
+pub fn parse_int(s: &str) -> Option<i128> {
+    let (is_negative, s) = match s.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, s),
+    };
+    let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(v) => i128::from_str_radix(v, 16).ok()?,
+        None => s.parse::<i128>().ok()?,
+    };
+    Some(if is_negative { -value } else { value })
+}
@@ -0,0 +1,681 @@
+// WASI Preview 1 for the command line runner
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use wasm_o::wasm::*;
+
+pub const MOD_NAME: &str = "wasi_snapshot_preview1";
+
+/// errno of WASI
+#[allow(dead_code)]
+mod errno {
+    pub const SUCCESS: i32 = 0;
+    pub const ACCES: i32 = 2;
+    pub const BADF: i32 = 8;
+    pub const EXIST: i32 = 20;
+    pub const INVAL: i32 = 28;
+    pub const IO: i32 = 29;
+    pub const ISDIR: i32 = 31;
+    pub const NOENT: i32 = 44;
+    pub const NOSYS: i32 = 52;
+    pub const NOTDIR: i32 = 54;
+    pub const SPIPE: i32 = 70;
+    pub const NOTCAPABLE: i32 = 76;
+}
+
+/// filetype of WASI
+mod filetype {
+    pub const CHARACTER_DEVICE: u8 = 2;
+    pub const DIRECTORY: u8 = 3;
+    pub const REGULAR_FILE: u8 = 4;
+}
+
+const OFLAGS_CREAT: i32 = 0x0001;
+const OFLAGS_DIRECTORY: i32 = 0x0002;
+const OFLAGS_EXCL: i32 = 0x0004;
+const OFLAGS_TRUNC: i32 = 0x0008;
+const FDFLAGS_APPEND: i32 = 0x0001;
+const RIGHTS_FD_READ: i64 = 1 << 1;
+const RIGHTS_FD_WRITE: i64 = 1 << 6;
+
+enum WasiFd {
+    Stdin,
+    Stdout,
+    Stderr,
+    /// The canonical path of the directory, with the guest path if it is preopened
+    Dir(PathBuf, Option<String>),
+    File(File),
+}
+
+/// The state of WASI, which the instance holds as the host data
+pub struct WasiCtx {
+    args: Vec<String>,
+    env: Vec<String>,
+    fds: RefCell<Vec<Option<WasiFd>>>,
+    epoch: Instant,
+}
+
+impl WasiCtx {
+    pub fn new(args: Vec<String>, env: Vec<(String, String)>) -> Self {
+        Self {
+            args,
+            env: env
+                .into_iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect(),
+            fds: RefCell::new(vec![
+                Some(WasiFd::Stdin),
+                Some(WasiFd::Stdout),
+                Some(WasiFd::Stderr),
+            ]),
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Preopens the directory of the host as the same path in the guest
+    pub fn preopen(&mut self, dir: &str) -> io::Result<()> {
+        let path = fs::canonicalize(dir)?;
+        if !path.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, dir));
+        }
+        self.fds
+            .get_mut()
+            .push(Some(WasiFd::Dir(path, Some(dir.to_string()))));
+        Ok(())
+    }
+
+    /// Defines the functions of WASI in the loader
+    pub fn define(loader: &mut WasmLoader) {
+        loader
+            .define_func(MOD_NAME, "args_sizes_get", args_sizes_get)
+            .define_func(MOD_NAME, "args_get", args_get)
+            .define_func(MOD_NAME, "environ_sizes_get", environ_sizes_get)
+            .define_func(MOD_NAME, "environ_get", environ_get)
+            .define_func(MOD_NAME, "fd_write", fd_write)
+            .define_func(MOD_NAME, "fd_read", fd_read)
+            .define_func(MOD_NAME, "fd_close", fd_close)
+            .define_func(MOD_NAME, "fd_seek", fd_seek)
+            .define_func(MOD_NAME, "fd_fdstat_get", fd_fdstat_get)
+            .define_func(MOD_NAME, "fd_prestat_get", fd_prestat_get)
+            .define_func(MOD_NAME, "fd_prestat_dir_name", fd_prestat_dir_name)
+            .define_func(MOD_NAME, "path_open", path_open)
+            .define_func(MOD_NAME, "proc_exit", proc_exit)
+            .define_func(MOD_NAME, "clock_time_get", clock_time_get)
+            .define_func(MOD_NAME, "random_get", random_get)
+            .define_func(MOD_NAME, "sched_yield", |_: &WasmCaller| errno::SUCCESS);
+    }
+
+    /// The other functions of WASI, which are not supported
+    pub fn unsupported(_: &WasmModule, _: &[WasmValue]) -> Result<WasmValue, WasmRuntimeError> {
+        Ok(WasmValue::I32(errno::NOSYS))
+    }
+}
+
+fn context<'a>(caller: &WasmCaller<'a>) -> Result<&'a WasiCtx, WasmRuntimeError> {
+    caller.data().ok_or(WasmRuntimeError::InvalidParameter)
+}
+
+fn memory<'a>(caller: &WasmCaller<'a>) -> Result<&'a WasmMemory, WasmRuntimeError> {
+    caller.memory("memory").ok_or(WasmRuntimeError::OutOfMemory)
+}
+
+fn errno_of(err: &io::Error) -> i32 {
+    match err.kind() {
+        io::ErrorKind::NotFound => errno::NOENT,
+        io::ErrorKind::PermissionDenied => errno::ACCES,
+        io::ErrorKind::AlreadyExists => errno::EXIST,
+        io::ErrorKind::InvalidInput => errno::INVAL,
+        _ => errno::IO,
+    }
+}
+
+/// Writes the sizes of the strings, with the terminators
+fn sizes_get(
+    caller: &WasmCaller,
+    strings: &[String],
+    count_ptr: u32,
+    buf_size_ptr: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let buf_size = strings.iter().map(|v| v.len() + 1).sum::<usize>();
+    memory.write_u32(count_ptr as usize, strings.len() as u32)?;
+    memory.write_u32(buf_size_ptr as usize, buf_size as u32)?;
+    Ok(errno::SUCCESS)
+}
+
+/// Writes the pointers to the strings, and the NUL-terminated strings
+fn strings_get(
+    caller: &WasmCaller,
+    strings: &[String],
+    ptr: u32,
+    buf: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let mut ptr = ptr as usize;
+    let mut buf = buf as usize;
+    for string in strings {
+        memory.write_u32(ptr, buf as u32)?;
+        memory.write_bytes(buf, string.as_bytes())?;
+        memory.write_u8(buf + string.len(), 0)?;
+        ptr += 4;
+        buf += string.len() + 1;
+    }
+    Ok(errno::SUCCESS)
+}
+
+fn args_sizes_get(caller: &WasmCaller, argc: u32, buf_size: u32) -> Result<i32, WasmRuntimeError> {
+    sizes_get(caller, &context(caller)?.args, argc, buf_size)
+}
+
+fn args_get(caller: &WasmCaller, argv: u32, buf: u32) -> Result<i32, WasmRuntimeError> {
+    strings_get(caller, &context(caller)?.args, argv, buf)
+}
+
+fn environ_sizes_get(
+    caller: &WasmCaller,
+    count: u32,
+    buf_size: u32,
+) -> Result<i32, WasmRuntimeError> {
+    sizes_get(caller, &context(caller)?.env, count, buf_size)
+}
+
+fn environ_get(caller: &WasmCaller, environ: u32, buf: u32) -> Result<i32, WasmRuntimeError> {
+    strings_get(caller, &context(caller)?.env, environ, buf)
+}
+
+fn fd_write(
+    caller: &WasmCaller,
+    fd: u32,
+    iovs: u32,
+    iovs_len: u32,
+    nwritten: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let mut fds = context(caller)?.fds.borrow_mut();
+    let mut len = 0;
+    for buf in memory.read_iovecs(iovs as usize, iovs_len as usize)? {
+        let result = match fds.get_mut(fd as usize) {
+            Some(Some(WasiFd::Stdout)) => io::stdout().write_all(buf),
+            Some(Some(WasiFd::Stderr)) => io::stderr().write_all(buf),
+            Some(Some(WasiFd::File(file))) => file.write_all(buf),
+            Some(Some(WasiFd::Dir(_, _))) => return Ok(errno::ISDIR),
+            _ => return Ok(errno::BADF),
+        };
+        if let Err(err) = result {
+            return Ok(errno_of(&err));
+        }
+        len += buf.len();
+    }
+    let _ = io::stdout().flush();
+    memory.write_u32(nwritten as usize, len as u32)?;
+    Ok(errno::SUCCESS)
+}
+
+fn fd_read(
+    caller: &WasmCaller,
+    fd: u32,
+    iovs: u32,
+    iovs_len: u32,
+    nread: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let mut fds = context(caller)?.fds.borrow_mut();
+    let capacity = memory
+        .read_iovecs(iovs as usize, iovs_len as usize)?
+        .iter()
+        .map(|v| v.len())
+        .sum();
+    let mut data = vec![0; capacity];
+    let result = match fds.get_mut(fd as usize) {
+        Some(Some(WasiFd::Stdin)) => io::stdin().read(&mut data),
+        Some(Some(WasiFd::File(file))) => file.read(&mut data),
+        Some(Some(WasiFd::Dir(_, _))) => return Ok(errno::ISDIR),
+        _ => return Ok(errno::BADF),
+    };
+    let size = match result {
+        Ok(v) => v,
+        Err(err) => return Ok(errno_of(&err)),
+    };
+    let size = memory.write_iovecs(iovs as usize, iovs_len as usize, &data[..size])?;
+    memory.write_u32(nread as usize, size as u32)?;
+    Ok(errno::SUCCESS)
+}
+
+fn fd_close(caller: &WasmCaller, fd: u32) -> Result<i32, WasmRuntimeError> {
+    let mut fds = context(caller)?.fds.borrow_mut();
+    match fds.get_mut(fd as usize) {
+        Some(entry @ Some(_)) => {
+            *entry = None;
+            Ok(errno::SUCCESS)
+        }
+        _ => Ok(errno::BADF),
+    }
+}
+
+fn fd_seek(
+    caller: &WasmCaller,
+    fd: u32,
+    offset: i64,
+    whence: u32,
+    newoffset: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let mut fds = context(caller)?.fds.borrow_mut();
+    let file = match fds.get_mut(fd as usize) {
+        Some(Some(WasiFd::File(file))) => file,
+        Some(Some(_)) => return Ok(errno::SPIPE),
+        _ => return Ok(errno::BADF),
+    };
+    let pos = match whence {
+        0 if offset >= 0 => SeekFrom::Start(offset as u64),
+        1 => SeekFrom::Current(offset),
+        2 => SeekFrom::End(offset),
+        _ => return Ok(errno::INVAL),
+    };
+    match file.seek(pos) {
+        Ok(v) => {
+            memory.write_u64(newoffset as usize, v)?;
+            Ok(errno::SUCCESS)
+        }
+        Err(err) => Ok(errno_of(&err)),
+    }
+}
+
+fn fd_fdstat_get(caller: &WasmCaller, fd: u32, stat: u32) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let fds = context(caller)?.fds.borrow();
+    let filetype = match fds.get(fd as usize) {
+        Some(Some(WasiFd::Dir(_, _))) => filetype::DIRECTORY,
+        Some(Some(WasiFd::File(_))) => filetype::REGULAR_FILE,
+        Some(Some(_)) => filetype::CHARACTER_DEVICE,
+        _ => return Ok(errno::BADF),
+    };
+    let stat = stat as usize;
+    memory.fill(stat, 0, 24)?;
+    memory.write_u8(stat, filetype)?;
+    memory.write_u64(stat + 8, u64::MAX)?;
+    memory.write_u64(stat + 16, u64::MAX)?;
+    Ok(errno::SUCCESS)
+}
+
+fn fd_prestat_get(caller: &WasmCaller, fd: u32, prestat: u32) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let fds = context(caller)?.fds.borrow();
+    match fds.get(fd as usize) {
+        Some(Some(WasiFd::Dir(_, Some(name)))) => {
+            memory.write_u32(prestat as usize, 0)?;
+            memory.write_u32(prestat as usize + 4, name.len() as u32)?;
+            Ok(errno::SUCCESS)
+        }
+        _ => Ok(errno::BADF),
+    }
+}
+
+fn fd_prestat_dir_name(
+    caller: &WasmCaller,
+    fd: u32,
+    path: u32,
+    path_len: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let fds = context(caller)?.fds.borrow();
+    match fds.get(fd as usize) {
+        Some(Some(WasiFd::Dir(_, Some(name)))) => {
+            let len = usize::min(name.len(), path_len as usize);
+            memory.write_bytes(path as usize, &name.as_bytes()[..len])?;
+            Ok(errno::SUCCESS)
+        }
+        _ => Ok(errno::BADF),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn path_open(
+    caller: &WasmCaller,
+    dirfd: u32,
+    _dirflags: u32,
+    path: u32,
+    path_len: u32,
+    oflags: i32,
+    rights_base: i64,
+    _rights_inheriting: i64,
+    fdflags: i32,
+    fd: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let path = memory.read_str(path as usize, path_len as usize)?;
+    let mut fds = context(caller)?.fds.borrow_mut();
+    let dir = match fds.get(dirfd as usize) {
+        Some(Some(WasiFd::Dir(dir, _))) => dir,
+        Some(Some(_)) => return Ok(errno::NOTDIR),
+        _ => return Ok(errno::BADF),
+    };
+    // The path must not escape from the directory
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|v| matches!(v, Component::Normal(_) | Component::CurDir))
+    {
+        return Ok(errno::NOTCAPABLE);
+    }
+    let host_path = match resolve(dir, relative) {
+        Ok(Some(v)) => v,
+        Ok(None) => return Ok(errno::NOTCAPABLE),
+        Err(err) => return Ok(errno_of(&err)),
+    };
+
+    let entry = if host_path.is_dir() {
+        WasiFd::Dir(host_path, None)
+    } else if (oflags & OFLAGS_DIRECTORY) != 0 {
+        return Ok(errno::NOTDIR);
+    } else {
+        let is_write = (rights_base & RIGHTS_FD_WRITE) != 0;
+        let mut options = OpenOptions::new();
+        options
+            .read((rights_base & RIGHTS_FD_READ) != 0 || !is_write)
+            .write(is_write)
+            .append((fdflags & FDFLAGS_APPEND) != 0)
+            .truncate((oflags & OFLAGS_TRUNC) != 0);
+        if (oflags & OFLAGS_EXCL) != 0 {
+            options.create_new(true);
+        } else if (oflags & OFLAGS_CREAT) != 0 {
+            options.create(true);
+        }
+        match options.open(&host_path) {
+            Ok(file) => WasiFd::File(file),
+            Err(err) => return Ok(errno_of(&err)),
+        }
+    };
+
+    let new_fd = match fds.iter().position(|v| v.is_none()) {
+        Some(v) => {
+            fds[v] = Some(entry);
+            v
+        }
+        None => {
+            fds.push(Some(entry));
+            fds.len() - 1
+        }
+    };
+    memory.write_u32(fd as usize, new_fd as u32)?;
+    Ok(errno::SUCCESS)
+}
+
+/// Resolves the symbolic links in the path, which must stay in the directory
+///
+/// The path which does not exist yet is resolved from its parent, to be created.
+fn resolve(dir: &Path, relative: &Path) -> io::Result<Option<PathBuf>> {
+    let path = dir.join(relative);
+    let resolved = match fs::canonicalize(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            if fs::symlink_metadata(&path).is_ok() {
+                // The dangling link, which might be created outside
+                return Ok(None);
+            }
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => fs::canonicalize(parent)?.join(name),
+                _ => return Err(err),
+            }
+        }
+        Err(err) => return Err(err),
+    };
+    Ok(resolved.starts_with(dir).then_some(resolved))
+}
+
+fn proc_exit(_caller: &WasmCaller, code: i32) -> Result<(), WasmRuntimeError> {
+    Err(WasmRuntimeError::Exit(code))
+}
+
+fn clock_time_get(
+    caller: &WasmCaller,
+    id: u32,
+    _precision: i64,
+    time: u32,
+) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    let nanos = match id {
+        // realtime
+        0 => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.as_nanos())
+            .unwrap_or(0),
+        // monotonic, process and thread cputime
+        1..=3 => context(caller)?.epoch.elapsed().as_nanos(),
+        _ => return Ok(errno::INVAL),
+    };
+    memory.write_u64(time as usize, nanos as u64)?;
+    Ok(errno::SUCCESS)
+}
+
+fn random_get(caller: &WasmCaller, buf: u32, buf_len: u32) -> Result<i32, WasmRuntimeError> {
+    let memory = memory(caller)?;
+    // The keys of RandomState come from the random source of the OS
+    let mut bytes = Vec::with_capacity(buf_len as usize + 8);
+    while bytes.len() < buf_len as usize {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_usize(bytes.len());
+        bytes.extend_from_slice(&hasher.finish().to_le_bytes());
+    }
+    memory.write_bytes(buf as usize, &bytes[..buf_len as usize])?;
+    Ok(errno::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    /// Loads the module which exports the imported functions of WASI as they are
+    fn load(wasi: WasiCtx) -> WasmModule {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x20, 0x04, 0x60, 0x02, 0x7F,
+            0x7F, 0x01, 0x7F, 0x60, 0x04, 0x7F, 0x7F, 0x7F, 0x7F, 0x01, 0x7F, 0x60, 0x09, 0x7F,
+            0x7F, 0x7F, 0x7F, 0x7F, 0x7E, 0x7E, 0x7F, 0x7F, 0x01, 0x7F, 0x60, 0x01, 0x7F, 0x00,
+            0x02, 0xBC, 0x01, 0x05, 0x16, 0x77, 0x61, 0x73, 0x69, 0x5F, 0x73, 0x6E, 0x61, 0x70,
+            0x73, 0x68, 0x6F, 0x74, 0x5F, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65, 0x77, 0x31, 0x0E,
+            0x61, 0x72, 0x67, 0x73, 0x5F, 0x73, 0x69, 0x7A, 0x65, 0x73, 0x5F, 0x67, 0x65, 0x74,
+            0x00, 0x00, 0x16, 0x77, 0x61, 0x73, 0x69, 0x5F, 0x73, 0x6E, 0x61, 0x70, 0x73, 0x68,
+            0x6F, 0x74, 0x5F, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65, 0x77, 0x31, 0x11, 0x65, 0x6E,
+            0x76, 0x69, 0x72, 0x6F, 0x6E, 0x5F, 0x73, 0x69, 0x7A, 0x65, 0x73, 0x5F, 0x67, 0x65,
+            0x74, 0x00, 0x00, 0x16, 0x77, 0x61, 0x73, 0x69, 0x5F, 0x73, 0x6E, 0x61, 0x70, 0x73,
+            0x68, 0x6F, 0x74, 0x5F, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65, 0x77, 0x31, 0x08, 0x66,
+            0x64, 0x5F, 0x77, 0x72, 0x69, 0x74, 0x65, 0x00, 0x01, 0x16, 0x77, 0x61, 0x73, 0x69,
+            0x5F, 0x73, 0x6E, 0x61, 0x70, 0x73, 0x68, 0x6F, 0x74, 0x5F, 0x70, 0x72, 0x65, 0x76,
+            0x69, 0x65, 0x77, 0x31, 0x09, 0x70, 0x61, 0x74, 0x68, 0x5F, 0x6F, 0x70, 0x65, 0x6E,
+            0x00, 0x02, 0x16, 0x77, 0x61, 0x73, 0x69, 0x5F, 0x73, 0x6E, 0x61, 0x70, 0x73, 0x68,
+            0x6F, 0x74, 0x5F, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65, 0x77, 0x31, 0x09, 0x70, 0x72,
+            0x6F, 0x63, 0x5F, 0x65, 0x78, 0x69, 0x74, 0x00, 0x03, 0x05, 0x03, 0x01, 0x00, 0x01,
+            0x07, 0x52, 0x06, 0x0E, 0x61, 0x72, 0x67, 0x73, 0x5F, 0x73, 0x69, 0x7A, 0x65, 0x73,
+            0x5F, 0x67, 0x65, 0x74, 0x00, 0x00, 0x11, 0x65, 0x6E, 0x76, 0x69, 0x72, 0x6F, 0x6E,
+            0x5F, 0x73, 0x69, 0x7A, 0x65, 0x73, 0x5F, 0x67, 0x65, 0x74, 0x00, 0x01, 0x08, 0x66,
+            0x64, 0x5F, 0x77, 0x72, 0x69, 0x74, 0x65, 0x00, 0x02, 0x09, 0x70, 0x61, 0x74, 0x68,
+            0x5F, 0x6F, 0x70, 0x65, 0x6E, 0x00, 0x03, 0x09, 0x70, 0x72, 0x6F, 0x63, 0x5F, 0x65,
+            0x78, 0x69, 0x74, 0x00, 0x04, 0x06, 0x6D, 0x65, 0x6D, 0x6F, 0x72, 0x79, 0x02, 0x00,
+        ];
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader.set_host_data(wasi);
+        WasiCtx::define(&mut loader);
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        loader.into_module()
+    }
+
+    fn call(module: &WasmModule, name: &str, params: &[WasmValue]) -> i32 {
+        module
+            .func(name)
+            .unwrap()
+            .invoke(params)
+            .unwrap()
+            .get_i32()
+            .unwrap()
+    }
+
+    /// Opens the path in the preopened directory, and returns the errno and the new fd
+    fn open(module: &WasmModule, path: &str, oflags: i32, rights: i64) -> (i32, u32) {
+        let memory = module.memory(0).unwrap();
+        memory.write_bytes(0x100, path.as_bytes()).unwrap();
+        memory.write_u32(0x10, u32::MAX).unwrap();
+        let errno = call(
+            module,
+            "path_open",
+            &[
+                3.into(),
+                0.into(),
+                0x100.into(),
+                (path.len() as i32).into(),
+                oflags.into(),
+                rights.into(),
+                0i64.into(),
+                0.into(),
+                0x10.into(),
+            ],
+        );
+        (errno, memory.read_u32(0x10).unwrap())
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("wasm-o-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn sizes() {
+        let wasi = WasiCtx::new(
+            vec!["a.wasm".to_string(), "hello".to_string()],
+            vec![("HOME".to_string(), "/home".to_string())],
+        );
+        let module = load(wasi);
+        let memory = module.memory(0).unwrap();
+
+        assert_eq!(
+            call(&module, "args_sizes_get", &[0.into(), 4.into()]),
+            errno::SUCCESS
+        );
+        assert_eq!(memory.read_u32(0).unwrap(), 2);
+        assert_eq!(memory.read_u32(4).unwrap(), 13);
+
+        assert_eq!(
+            call(&module, "environ_sizes_get", &[0.into(), 4.into()]),
+            errno::SUCCESS
+        );
+        assert_eq!(memory.read_u32(0).unwrap(), 1);
+        assert_eq!(memory.read_u32(4).unwrap(), 11);
+    }
+
+    #[test]
+    fn proc_exit() {
+        let module = load(WasiCtx::new(Vec::new(), Vec::new()));
+        assert!(matches!(
+            module.func("proc_exit").unwrap().invoke(&[3.into()]),
+            Err(WasmRuntimeError::Exit(3))
+        ));
+    }
+
+    #[test]
+    fn fd_write() {
+        let dir = temp_dir("fd_write");
+        let mut wasi = WasiCtx::new(Vec::new(), Vec::new());
+        wasi.preopen(dir.to_str().unwrap()).unwrap();
+        let module = load(wasi);
+        let memory = module.memory(0).unwrap();
+
+        let (errno, fd) = open(&module, "out.txt", OFLAGS_CREAT, RIGHTS_FD_WRITE);
+        assert_eq!(errno, errno::SUCCESS);
+        assert_eq!(fd, 4);
+
+        // Two iovecs, which are written in order
+        memory.write_bytes(0x200, b"hello, world").unwrap();
+        memory.write_u32(0x20, 0x200).unwrap();
+        memory.write_u32(0x24, 5).unwrap();
+        memory.write_u32(0x28, 0x205).unwrap();
+        memory.write_u32(0x2C, 7).unwrap();
+        assert_eq!(
+            call(
+                &module,
+                "fd_write",
+                &[(fd as i32).into(), 0x20.into(), 2.into(), 0x30.into()]
+            ),
+            errno::SUCCESS
+        );
+        assert_eq!(memory.read_u32(0x30).unwrap(), 12);
+        assert_eq!(fs::read(dir.join("out.txt")).unwrap(), b"hello, world");
+
+        // The bad fd
+        assert_eq!(
+            call(
+                &module,
+                "fd_write",
+                &[99.into(), 0x20.into(), 2.into(), 0x30.into()]
+            ),
+            errno::BADF
+        );
+
+        drop(module);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn path_open_escape() {
+        use std::os::unix::fs::symlink;
+
+        let dir = temp_dir("path_open");
+        let outside = temp_dir("path_open_outside");
+        fs::write(outside.join("secret"), b"secret").unwrap();
+        symlink(&outside, dir.join("link")).unwrap();
+        symlink(outside.join("secret"), dir.join("secret")).unwrap();
+        symlink(outside.join("dangling"), dir.join("dangling")).unwrap();
+        fs::create_dir(dir.join("sub")).unwrap();
+        symlink("../sub", dir.join("sub/up")).unwrap();
+
+        let mut wasi = WasiCtx::new(Vec::new(), Vec::new());
+        wasi.preopen(dir.to_str().unwrap()).unwrap();
+        let module = load(wasi);
+
+        for path in [
+            "../path_open",
+            "/etc/passwd",
+            "link",
+            "link/secret",
+            "link/new",
+            "secret",
+            "dangling",
+        ] {
+            assert_eq!(
+                open(&module, path, OFLAGS_CREAT, RIGHTS_FD_WRITE).0,
+                errno::NOTCAPABLE,
+                "{}",
+                path
+            );
+        }
+        assert!(!outside.join("new").exists());
+        assert!(!outside.join("dangling").exists());
+
+        // The links which stay in the directory
+        assert_eq!(open(&module, "sub/up", 0, RIGHTS_FD_READ).0, errno::SUCCESS);
+        assert_eq!(
+            open(&module, "sub/up/new", OFLAGS_CREAT, RIGHTS_FD_WRITE).0,
+            errno::SUCCESS
+        );
+        assert!(dir.join("sub/new").exists());
+
+        drop(module);
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+}
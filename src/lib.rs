@@ -1,8 +1,62 @@
 // Wasm-O
-#![feature(try_reserve)]
 
 pub mod opcode;
+#[cfg(feature = "simd")]
+pub mod simd;
+#[cfg(feature = "threads")]
+pub mod threads;
+pub mod typed;
 pub mod wasm;
 pub mod wasmintr;
 
 extern crate alloc;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "mmap", target_os = "linux", target_arch = "x86_64"))] {
+        pub mod mmap;
+        use mmap as guard;
+    } else {
+        /// Stand-in for the guarded accesses of the mmap module
+        ///
+        /// No memory is guarded in this build, so the accesses are never reached.
+        mod guard {
+            use crate::wasm::WasmRuntimeError;
+
+            pub(crate) const AVAILABLE: bool = false;
+
+            pub(crate) fn install_trap_handler() {}
+
+            pub(crate) unsafe fn load_u8(_ptr: *const u8) -> Result<u8, WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn load_u16(_ptr: *const u8) -> Result<u16, WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn load_u32(_ptr: *const u8) -> Result<u32, WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn load_u64(_ptr: *const u8) -> Result<u64, WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn store_u8(_ptr: *mut u8, _val: u8) -> Result<(), WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn store_u16(_ptr: *mut u8, _val: u16) -> Result<(), WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn store_u32(_ptr: *mut u8, _val: u32) -> Result<(), WasmRuntimeError> {
+                unreachable!()
+            }
+
+            pub(crate) unsafe fn store_u64(_ptr: *mut u8, _val: u64) -> Result<(), WasmRuntimeError> {
+                unreachable!()
+            }
+        }
+    }
+}
@@ -1,31 +1,91 @@
 // Wasm Runner
 
+mod cli;
+
+use cli::inspect;
+use cli::wasi::{self, WasiCtx};
 use std::env;
 use std::fs::File;
 use std::io::Read;
+use std::process;
 use wasm_o::wasm::*;
 
 fn usage() {
     let arg = env::args().next().unwrap();
-    println!("usage: {} WASMFILE [FUNCTION]", arg);
+    println!("usage: {} [OPTIONS] WASMFILE [ARGS...]", arg);
+    println!("       {} inspect [--json] WASMFILE", arg);
+    println!("       {} validate [--features LIST] WASMFILE...", arg);
+    println!();
+    println!("options:");
+    println!("  -d                print the disassembly of the functions");
+    println!("  -i                start the interactive debugger instead of running the module");
+    println!("  --invoke NAME     invoke the export NAME with ARGS as its parameters");
+    println!("  --env KEY=VALUE   set the environment variable of WASI");
+    println!("  --dir DIR         preopen the directory DIR for WASI");
 }
 
+/// Exit code of the runner when the module fails to load or traps
+const EXIT_FAILURE: i32 = 1;
+
 fn main() {
-    let mut args = env::args();
+    process::exit(run());
+}
+
+fn run() -> i32 {
+    let mut args = env::args().peekable();
     let _ = args.next().unwrap();
 
+    if args.peek().map(|v| v.as_str()) == Some("inspect") {
+        args.next();
+        return run_inspect(args);
+    }
+    if args.peek().map(|v| v.as_str()) == Some("validate") {
+        args.next();
+        return run_validate(args);
+    }
+
     let mut option_d = false;
+    let mut option_i = false;
+    let mut invoke = None;
+    let mut envs = Vec::new();
+    let mut dirs = Vec::new();
 
     let in_file = loop {
         match args.next() {
             Some(v) => {
-                if v.starts_with("-") {
-                    match &*v {
-                        "-d" => option_d = true,
+                if v.starts_with('-') {
+                    let option = match &*v {
+                        "-d" => {
+                            option_d = true;
+                            continue;
+                        }
+                        "-i" => {
+                            option_i = true;
+                            continue;
+                        }
+                        "--invoke" | "--env" | "--dir" => v,
                         _ => {
                             usage();
-                            return;
+                            return EXIT_FAILURE;
+                        }
+                    };
+                    let value = match args.next() {
+                        Some(v) => v,
+                        None => {
+                            usage();
+                            return EXIT_FAILURE;
                         }
+                    };
+                    match &*option {
+                        "--invoke" => invoke = Some(value),
+                        "--env" => match value.split_once('=') {
+                            Some((key, value)) => envs.push((key.to_string(), value.to_string())),
+                            None => {
+                                usage();
+                                return EXIT_FAILURE;
+                            }
+                        },
+                        _ => dirs.push(value),
                     }
                 } else {
                     break v;
@@ -33,54 +93,264 @@ fn main() {
             }
             None => {
                 usage();
-                return;
+                return EXIT_FAILURE;
             }
         }
     };
+    let guest_args = args.collect::<Vec<_>>();
 
-    let func_name = args.next().unwrap_or("_start".to_string());
+    let blob = match read_file(&in_file) {
+        Some(v) => v,
+        None => return EXIT_FAILURE,
+    };
 
-    let mut is = File::open(in_file).unwrap();
-    let mut blob = Vec::new();
-    let _ = is.read_to_end(&mut blob).unwrap();
-
-    let mut module =
-        WasmLoader::instantiate(blob.as_slice(), &|_mod_name, name, _type_ref| match name {
-            "fd_write" => Ok(WasmLib::wasm_fd_write),
-            "syscall0" => Ok(WasmLib::syscall),
-            "syscall1" => Ok(WasmLib::syscall),
-            "syscall2" => Ok(WasmLib::syscall),
-            "syscall3" => Ok(WasmLib::syscall),
-            "syscall4" => Ok(WasmLib::syscall),
+    let mut argv = vec![in_file];
+    argv.extend_from_slice(&guest_args);
+    let mut wasi = WasiCtx::new(argv, envs);
+    for dir in &dirs {
+        if let Err(err) = wasi.preopen(dir) {
+            eprintln!("error: {}: {}", dir, err);
+            return EXIT_FAILURE;
+        }
+    }
+
+    let mut loader = WasmLoader::with_features(WasmFeatures::available());
+    loader.set_host_data(wasi);
+    // The debugger runs the start function on demand
+    loader.set_run_start(!option_i);
+    WasiCtx::define(&mut loader);
+    let result = loader.load(
+        &blob,
+        &|mod_name, name, func_type| match name {
+            "syscall0" | "syscall1" | "syscall2" | "syscall3" | "syscall4" => Ok(WasmLib::syscall),
+            _ if mod_name == wasi::MOD_NAME && func_type.result_types() == [WasmValType::I32] => {
+                Ok(WasiCtx::unsupported)
+            }
             _ => Err(WasmDecodeError::DynamicLinkError),
-        })
-        .unwrap();
+        },
+        &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+        &VecMemoryBackend::allocator,
+    );
+    match result {
+        Ok(_) => (),
+        Err(WasmDecodeError::Trap(WasmRuntimeError::Exit(code))) => return code,
+        Err(err) => {
+            match loader.error_import() {
+                Some((mod_name, name)) => {
+                    eprintln!("error: {:?} in import \"{}\".\"{}\"", err, mod_name, name)
+                }
+                None => eprintln!("error: {:?}", err),
+            }
+            return EXIT_FAILURE;
+        }
+    }
+    let mut module = loader.into_module();
 
     if option_d {
         module.print_stat();
+        return 0;
+    }
+    if option_i {
+        return cli::debugger::run(module);
+    }
+
+    let func_name = match invoke.as_deref() {
+        Some(v) => v,
+        // The command of WASI, or the module whose start function has already run
+        None if module.func("_start").is_ok() => "_start",
+        None => return 0,
+    };
+    let runnable = match module.func(func_name) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("error: {}: {:?}", func_name, err);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let param_types = runnable.function().param_types();
+    let params = if invoke.is_some() {
+        if guest_args.len() != param_types.len() {
+            eprintln!(
+                "error: {} expects {} arguments, but {} given",
+                func_name,
+                param_types.len(),
+                guest_args.len()
+            );
+            return EXIT_FAILURE;
+        }
+        let mut params = Vec::with_capacity(param_types.len());
+        for (arg, val_type) in guest_args.iter().zip(param_types) {
+            match cli::parse_value(arg, *val_type) {
+                Some(v) => params.push(v),
+                None => {
+                    eprintln!("error: invalid argument for {}: {}", val_type, arg);
+                    return EXIT_FAILURE;
+                }
+            }
+        }
+        params
+    } else {
+        Vec::new()
+    };
+
+    match runnable.invoke(&params) {
+        Ok(v) => {
+            if invoke.is_some() {
+                let results = if v.is_empty() { &[][..] } else { &[v][..] };
+                print_results(results);
+            }
+            0
+        }
+        Err(WasmRuntimeError::Exit(code)) => code,
+        Err(err) => {
+            eprintln!("error: {:?}", err);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Prints the summary of the module
+fn run_inspect(args: impl Iterator<Item = String>) -> i32 {
+    let mut option_json = false;
+    let mut in_file = None;
+    for arg in args {
+        match &*arg {
+            "--json" => option_json = true,
+            _ if arg.starts_with('-') || in_file.is_some() => {
+                usage();
+                return EXIT_FAILURE;
+            }
+            _ => in_file = Some(arg),
+        }
+    }
+    let in_file = match in_file {
+        Some(v) => v,
+        None => {
+            usage();
+            return EXIT_FAILURE;
+        }
+    };
+    let blob = match read_file(&in_file) {
+        Some(v) => v,
+        None => return EXIT_FAILURE,
+    };
+    let module = match inspect::load(&blob) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("error: {:?}", err);
+            return EXIT_FAILURE;
+        }
+    };
+    if option_json {
+        println!("{}", inspect::to_json(&module));
     } else {
-        match module
-            .func(&func_name)
-            .and_then(|v| v.invoke(&[7.into(), 1.into()]))
-        {
-            Ok(v) => {
-                println!("result: {}", v);
+        inspect::print_text(&module);
+    }
+    0
+}
+
+/// Decodes and validates the modules without running them
+fn run_validate(mut args: impl Iterator<Item = String>) -> i32 {
+    let mut features = WasmFeatures::available();
+    let mut in_files = Vec::new();
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--features" => {
+                let list = match args.next() {
+                    Some(v) => v,
+                    None => {
+                        usage();
+                        return EXIT_FAILURE;
+                    }
+                };
+                match parse_features(&list) {
+                    Ok(v) => features = v,
+                    Err(name) => {
+                        eprintln!("error: unknown feature: {}", name);
+                        return EXIT_FAILURE;
+                    }
+                }
+            }
+            _ if arg.starts_with('-') => {
+                usage();
+                return EXIT_FAILURE;
             }
+            _ => in_files.push(arg),
+        }
+    }
+    if in_files.is_empty() {
+        usage();
+        return EXIT_FAILURE;
+    }
+
+    let mut exit_code = 0;
+    for in_file in &in_files {
+        let blob = match read_file(in_file) {
+            Some(v) => v,
+            None => {
+                exit_code = EXIT_FAILURE;
+                continue;
+            }
+        };
+        let mut loader = WasmLoader::with_features(features);
+        match loader.decode(&blob) {
+            Ok(_) => println!("{}: ok", in_file),
             Err(err) => {
-                println!("error: {:?}", err);
+                exit_code = EXIT_FAILURE;
+                match loader.error_location() {
+                    Some((section, offset)) => eprintln!(
+                        "{}: error: {:?} in {} section at offset 0x{:x}",
+                        in_file, err, section, offset
+                    ),
+                    None => eprintln!("{}: error: {:?}", in_file, err),
+                }
             }
         }
     }
+    exit_code
+}
+
+/// Parses the comma separated names of the proposals, returns the unknown name if any
+///
+/// The proposals not compiled in are accepted but remain disabled in the loader.
+fn parse_features(list: &str) -> Result<WasmFeatures, &str> {
+    let mut features = WasmFeatures::empty();
+    for name in list.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        features |= WasmFeatures::from_name(name).ok_or(name)?;
+    }
+    Ok(features)
+}
+
+fn read_file(path: &str) -> Option<Vec<u8>> {
+    let mut blob = Vec::new();
+    match File::open(path).and_then(|mut v| v.read_to_end(&mut blob)) {
+        Ok(_) => Some(blob),
+        Err(err) => {
+            eprintln!("error: {}: {}", path, err);
+            None
+        }
+    }
+}
+
+/// Prints each of the results in a line
+fn print_results(results: &[WasmValue]) {
+    for result in results {
+        println!("{}", result);
+    }
 }
 
 struct WasmLib {}
 
 impl WasmLib {
     fn syscall(module: &WasmModule, params: &[WasmValue]) -> Result<WasmValue, WasmRuntimeError> {
-        let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
+        let memory = module
+            .memory_by_name("memory")
+            .or_else(|| module.memory(0))
+            .ok_or(WasmRuntimeError::OutOfMemory)?;
 
         let func = params
-            .get(0)
+            .first()
             .ok_or(WasmRuntimeError::InvalidParameter)
             .and_then(|v| v.get_u32())? as usize;
         if func != 1 {
@@ -95,35 +365,7 @@ impl WasmLib {
             .ok_or(WasmRuntimeError::InvalidParameter)
             .and_then(|v| v.get_u32())? as usize;
 
-        let slice = memory.read_bytes(base, len)?;
-        let s = core::str::from_utf8(slice).map_err(|_| WasmRuntimeError::InvalidParameter)?;
-        print!("{}", s);
-
-        Ok(WasmValue::I32(s.len() as i32))
-    }
-
-    fn wasm_fd_write(
-        module: &WasmModule,
-        params: &[WasmValue],
-    ) -> Result<WasmValue, WasmRuntimeError> {
-        // fd_write (i32 i32 i32 i32) -> i32
-
-        let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-
-        let iovs = params
-            .get(1)
-            .ok_or(WasmRuntimeError::InvalidParameter)
-            .and_then(|v| v.get_u32())? as usize;
-        // let iovs_len = params
-        //     .get(2)
-        //     .ok_or(WasmRuntimeError::InvalidParameter)
-        //     .and_then(|v| v.get_i32())?;
-
-        let iov_base = memory.read_u32(iovs)? as usize;
-        let iov_len = memory.read_u32(iovs + 4)? as usize;
-
-        let slice = memory.read_bytes(iov_base, iov_len)?;
-        let s = core::str::from_utf8(slice).map_err(|_| WasmRuntimeError::InvalidParameter)?;
+        let s = memory.read_str(base, len)?;
         print!("{}", s);
 
         Ok(WasmValue::I32(s.len() as i32))
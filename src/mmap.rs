@@ -0,0 +1,422 @@
+// Guard-page based Linear Memory (Linux x86_64)
+
+use super::wasm::*;
+use alloc::boxed::Box;
+use core::arch::asm;
+use core::mem::MaybeUninit;
+use core::ptr::{self, addr_of, addr_of_mut};
+use libc::{c_int, c_void, siginfo_t, ucontext_t};
+use std::sync::Once;
+
+/// Memory backend that reserves the whole address space a 32-bit memory can reach
+///
+/// Accessible pages are mapped as read/write, the rest of the reservation is left
+/// as PROT_NONE, so any out-of-bounds access faults instead of being checked
+/// in advance. The fault is converted to [WasmRuntimeError::OutOfBounds] by the
+/// trap handler installed on first use.
+pub struct MmapMemoryBackend {
+    base: *mut u8,
+    len: usize,
+}
+
+/// Guarded memories are available in this build
+pub(crate) const AVAILABLE: bool = true;
+
+impl MmapMemoryBackend {
+    /// Size of the reserved address space, 4GiB of memory + 4GiB of offset
+    /// + a page for the width of the access
+    pub const RESERVED_SIZE: usize = 0x2_0001_0000;
+    /// Maximum accessible size of the memory
+    pub const MAX_SIZE: usize = 0x1_0000_0000;
+
+    pub fn new() -> Result<Self, WasmRuntimeError> {
+        let base = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                Self::RESERVED_SIZE,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+                -1,
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return Err(WasmRuntimeError::OutOfMemory);
+        }
+        install_trap_handler();
+        Ok(Self {
+            base: base as *mut u8,
+            len: 0,
+        })
+    }
+
+    /// Memory allocator for guarded memories
+    pub fn allocator(_limit: WasmLimit) -> Result<Box<dyn MemoryBackend>, WasmDecodeError> {
+        Self::new()
+            .map(|v| Box::new(v) as Box<dyn MemoryBackend>)
+            .map_err(|_| WasmDecodeError::OutOfMemory)
+    }
+}
+
+unsafe impl MemoryBackend for MmapMemoryBackend {
+    fn allocate(&mut self, size: usize) -> Result<(), WasmRuntimeError> {
+        self.grow(size, size).map(|_| ())
+    }
+
+    fn grow(&mut self, delta: usize, max_size: usize) -> Result<usize, WasmRuntimeError> {
+        let old_size = self.len;
+        let new_size = match old_size.checked_add(delta) {
+            Some(v) if v <= max_size && v <= Self::MAX_SIZE => v,
+            _ => return Err(WasmRuntimeError::OutOfMemory),
+        };
+        if new_size > old_size {
+            let result = unsafe {
+                libc::mprotect(
+                    self.base as *mut c_void,
+                    new_size,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                )
+            };
+            if result != 0 {
+                return Err(WasmRuntimeError::OutOfMemory);
+            }
+        }
+        self.len = new_size;
+        Ok(old_size)
+    }
+
+    #[inline]
+    fn base_ptr(&self) -> *mut u8 {
+        self.base
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_guarded(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for MmapMemoryBackend {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base as *mut c_void, Self::RESERVED_SIZE);
+        }
+    }
+}
+
+/// Entry of the fixup table, both addresses are relative to the field itself
+#[repr(C)]
+struct GuardFixup {
+    insn: i32,
+    fixup: i32,
+}
+
+impl GuardFixup {
+    #[inline]
+    fn insn(&self) -> usize {
+        (addr_of!(self.insn) as isize + self.insn as isize) as usize
+    }
+
+    #[inline]
+    fn fixup(&self) -> usize {
+        (addr_of!(self.fixup) as isize + self.fixup as isize) as usize
+    }
+}
+
+extern "C" {
+    static __start_wasm_o_guard_fixup: GuardFixup;
+    static __stop_wasm_o_guard_fixup: GuardFixup;
+}
+
+/// Emits an access that may fault, inlined into the caller.
+///
+/// The instruction is recorded in the fixup table with the code to resume at,
+/// which sets the fault flag instead of the fall-through path clearing it.
+macro_rules! guarded_access {
+    ($insn:literal, $($operands:tt)*) => {
+        asm!(
+            "2:",
+            $insn,
+            "xor {fault:e}, {fault:e}",
+            "3:",
+            ".pushsection .text.wasm_o_guard, \"ax\", @progbits",
+            "4:",
+            "mov {fault:e}, 1",
+            "jmp 3b",
+            ".popsection",
+            ".pushsection wasm_o_guard_fixup, \"aR\", @progbits",
+            ".balign 4",
+            ".long 2b - .",
+            ".long 4b - .",
+            ".popsection",
+            $($operands)*
+            options(nostack),
+        )
+    };
+}
+
+#[inline]
+fn into_result<T>(value: T, fault: u32) -> Result<T, WasmRuntimeError> {
+    if fault == 0 {
+        Ok(value)
+    } else {
+        Err(WasmRuntimeError::OutOfBounds)
+    }
+}
+
+#[inline]
+pub(crate) unsafe fn load_u8(ptr: *const u8) -> Result<u8, WasmRuntimeError> {
+    let value: u32;
+    let fault: u32;
+    guarded_access!(
+        "movzx {value:e}, byte ptr [{ptr}]",
+        ptr = in(reg) ptr,
+        value = out(reg) value,
+        fault = out(reg) fault,
+    );
+    into_result(value as u8, fault)
+}
+
+#[inline]
+pub(crate) unsafe fn load_u16(ptr: *const u8) -> Result<u16, WasmRuntimeError> {
+    let value: u32;
+    let fault: u32;
+    guarded_access!(
+        "movzx {value:e}, word ptr [{ptr}]",
+        ptr = in(reg) ptr,
+        value = out(reg) value,
+        fault = out(reg) fault,
+    );
+    into_result(value as u16, fault)
+}
+
+#[inline]
+pub(crate) unsafe fn load_u32(ptr: *const u8) -> Result<u32, WasmRuntimeError> {
+    let value: u32;
+    let fault: u32;
+    guarded_access!(
+        "mov {value:e}, dword ptr [{ptr}]",
+        ptr = in(reg) ptr,
+        value = out(reg) value,
+        fault = out(reg) fault,
+    );
+    into_result(value, fault)
+}
+
+#[inline]
+pub(crate) unsafe fn load_u64(ptr: *const u8) -> Result<u64, WasmRuntimeError> {
+    let value: u64;
+    let fault: u32;
+    guarded_access!(
+        "mov {value}, qword ptr [{ptr}]",
+        ptr = in(reg) ptr,
+        value = out(reg) value,
+        fault = out(reg) fault,
+    );
+    into_result(value, fault)
+}
+
+#[inline]
+pub(crate) unsafe fn store_u8(ptr: *mut u8, val: u8) -> Result<(), WasmRuntimeError> {
+    let fault: u32;
+    guarded_access!(
+        "mov byte ptr [{ptr}], {val}",
+        ptr = in(reg) ptr,
+        val = in(reg_byte) val,
+        fault = out(reg) fault,
+    );
+    into_result((), fault)
+}
+
+#[inline]
+pub(crate) unsafe fn store_u16(ptr: *mut u8, val: u16) -> Result<(), WasmRuntimeError> {
+    let fault: u32;
+    guarded_access!(
+        "mov word ptr [{ptr}], {val:x}",
+        ptr = in(reg) ptr,
+        val = in(reg) val,
+        fault = out(reg) fault,
+    );
+    into_result((), fault)
+}
+
+#[inline]
+pub(crate) unsafe fn store_u32(ptr: *mut u8, val: u32) -> Result<(), WasmRuntimeError> {
+    let fault: u32;
+    guarded_access!(
+        "mov dword ptr [{ptr}], {val:e}",
+        ptr = in(reg) ptr,
+        val = in(reg) val,
+        fault = out(reg) fault,
+    );
+    into_result((), fault)
+}
+
+#[inline]
+pub(crate) unsafe fn store_u64(ptr: *mut u8, val: u64) -> Result<(), WasmRuntimeError> {
+    let fault: u32;
+    guarded_access!(
+        "mov qword ptr [{ptr}], {val}",
+        ptr = in(reg) ptr,
+        val = in(reg) val,
+        fault = out(reg) fault,
+    );
+    into_result((), fault)
+}
+
+static INSTALL_HANDLER: Once = Once::new();
+static mut PREV_SIGSEGV: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+static mut PREV_SIGBUS: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+
+/// Installs the signal handler that converts faults of the guarded accesses into traps
+pub(crate) fn install_trap_handler() {
+    INSTALL_HANDLER.call_once(|| unsafe {
+        let mut action: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        action.sa_sigaction = trap_handler as *const () as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK | libc::SA_NODEFER;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(
+            libc::SIGSEGV,
+            &action,
+            addr_of_mut!(PREV_SIGSEGV) as *mut libc::sigaction,
+        );
+        libc::sigaction(
+            libc::SIGBUS,
+            &action,
+            addr_of_mut!(PREV_SIGBUS) as *mut libc::sigaction,
+        );
+    });
+}
+
+/// Signal handler, must be async-signal-safe
+unsafe extern "C" fn trap_handler(signum: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    let context = context as *mut ucontext_t;
+    let rip = &mut (*context).uc_mcontext.gregs[libc::REG_RIP as usize];
+    let pc = *rip as usize;
+    let mut entry = addr_of!(__start_wasm_o_guard_fixup);
+    while entry < addr_of!(__stop_wasm_o_guard_fixup) {
+        if (*entry).insn() == pc {
+            *rip = (*entry).fixup() as i64;
+            return;
+        }
+        entry = entry.add(1);
+    }
+
+    // Not ours, pass it to the previous handler
+    let prev = if signum == libc::SIGBUS {
+        addr_of!(PREV_SIGBUS) as *const libc::sigaction
+    } else {
+        addr_of!(PREV_SIGSEGV) as *const libc::sigaction
+    };
+    let handler = (*prev).sa_sigaction;
+    if handler == libc::SIG_DFL || handler == libc::SIG_IGN {
+        // Restore the previous disposition and let the fault happen again
+        libc::sigaction(signum, prev, ptr::null_mut());
+    } else if ((*prev).sa_flags & libc::SA_SIGINFO) != 0 {
+        let handler: extern "C" fn(c_int, *mut siginfo_t, *mut c_void) =
+            core::mem::transmute(handler);
+        handler(signum, info, context as *mut c_void);
+    } else {
+        let handler: extern "C" fn(c_int) = core::mem::transmute(handler);
+        handler(signum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MmapMemoryBackend;
+    use crate::wasm::*;
+
+    #[test]
+    fn guarded_memory() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x0B,
+            0x0A, 0x01, 0x00, 0x41, 0x10, 0x0B, 0x04, 0x61, 0x62, 0x63, 0x64,
+        ];
+
+        let module = WasmLoader::instantiate_with_allocator(
+            &slice,
+            &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+            &MmapMemoryBackend::allocator,
+        )
+        .unwrap();
+        let memory = module.memory(0).unwrap();
+        assert!(memory.is_guarded());
+        assert_eq!(memory.size(), 1);
+        assert_eq!(memory.read_bytes(0x10, 4).unwrap(), b"abcd");
+        assert_eq!(memory.read_u32(0x10).unwrap(), 0x64636261);
+        assert_eq!(memory.read_u32(0xFFFC).unwrap(), 0);
+
+        memory.write_u64(0xFFF8, 0x1234_5678_9ABC_DEF0).unwrap();
+        assert_eq!(memory.read_u64(0xFFF8).unwrap(), 0x1234_5678_9ABC_DEF0);
+        assert_eq!(memory.read_u16(0xFFFE).unwrap(), 0x1234);
+
+        assert!(matches!(
+            memory.read_u32(0x10000),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        // The accesses of the memory instructions fault instead of being checked
+        assert_eq!(memory.load_u32(0x10).unwrap(), 0x64636261);
+        assert!(matches!(
+            memory.load_u32(0xFFFE),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            memory.store_u8(0xFFFF_FFFF, 1),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            memory.load_u64(0x1_FFFF_FFFE),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        assert_eq!(memory.grow(1), 1);
+        assert_eq!(memory.load_u32(0x10000).unwrap(), 0);
+        memory.store_u32(0x1FFFC, 0xDEADBEEF).unwrap();
+        assert_eq!(memory.load_u8(0x1FFFF).unwrap(), 0xDE);
+        assert_eq!(memory.read_u8(0x1FFFF).unwrap(), 0xDE);
+        assert!(matches!(
+            memory.load_u8(0x20000),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn guarded_instructions() {
+        // (func (export "load") (param i32) (result i32) local.get 0 i32.load offset=16)
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x08, 0x01,
+            0x04, 0x6C, 0x6F, 0x61, 0x64, 0x00, 0x00, 0x0A, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00,
+            0x28, 0x02, 0x10, 0x0B, 0x0B, 0x0A, 0x01, 0x00, 0x41, 0x10, 0x0B, 0x04, 0x61, 0x62,
+            0x63, 0x64,
+        ];
+
+        let module = WasmLoader::instantiate_with_allocator(
+            &slice,
+            &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+            &MmapMemoryBackend::allocator,
+        )
+        .unwrap();
+        assert!(module.memory(0).unwrap().is_guarded());
+        let load = module.func("load").unwrap();
+        assert_eq!(
+            load.invoke(&[0.into()]).unwrap().get_u32().unwrap(),
+            0x64636261
+        );
+        assert!(matches!(
+            load.invoke(&[0xFFF0.into()]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            load.invoke(&[0xFFFF_FFFFu32.into()]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+    }
+}
@@ -16,6 +16,10 @@ pub enum WasmOpcode {
     If = 0x04,
     /// 05 (mvp) else expr; end
     Else = 0x05,
+    /// 08 (exceptions) throw tagidx
+    Throw = 0x08,
+    /// 0A (exceptions) throw_ref
+    ThrowRef = 0x0A,
     /// 0B (mvp) end
     End = 0x0B,
     /// 0C (mvp) br labelidx
@@ -38,6 +42,10 @@ pub enum WasmOpcode {
     Drop = 0x1A,
     /// 1B (mvp) select
     Select = 0x1B,
+    /// 1C (reference_types) select vec(valtype)
+    SelectT = 0x1C,
+    /// 1F (exceptions) try_table block_type vec(catch); expr; end
+    TryTable = 0x1F,
     /// 20 (mvp) local.get localidx
     LocalGet = 0x20,
     /// 21 (mvp) local.set localidx
@@ -48,6 +56,10 @@ pub enum WasmOpcode {
     GlobalGet = 0x23,
     /// 24 (mvp) global.set globalidx
     GlobalSet = 0x24,
+    /// 25 (reference_types) table.get tableidx
+    TableGet = 0x25,
+    /// 26 (reference_types) table.set tableidx
+    TableSet = 0x26,
     /// 28 (mvp) i32.load align offset
     I32Load = 0x28,
     /// 29 (mvp_i64) i64.load align offset
@@ -362,6 +374,654 @@ pub enum WasmOpcode {
     I64Extend16S = 0xC3,
     /// C4 (sign_extend) i64.extend32_s
     I64Extend32S = 0xC4,
+    /// D0 (reference_types) ref.null reftype
+    RefNull = 0xD0,
+    /// D1 (reference_types) ref.is_null
+    RefIsNull = 0xD1,
+    /// D2 (reference_types) ref.func funcidx
+    RefFunc = 0xD2,
+    /// FC 00 (nontrapping_fptoint) i32.trunc_sat_f32_s
+    I32TruncSatF32S = 0xFC_0000,
+    /// FC 01 (nontrapping_fptoint) i32.trunc_sat_f32_u
+    I32TruncSatF32U = 0xFC_0001,
+    /// FC 02 (nontrapping_fptoint) i32.trunc_sat_f64_s
+    I32TruncSatF64S = 0xFC_0002,
+    /// FC 03 (nontrapping_fptoint) i32.trunc_sat_f64_u
+    I32TruncSatF64U = 0xFC_0003,
+    /// FC 04 (nontrapping_fptoint) i64.trunc_sat_f32_s
+    I64TruncSatF32S = 0xFC_0004,
+    /// FC 05 (nontrapping_fptoint) i64.trunc_sat_f32_u
+    I64TruncSatF32U = 0xFC_0005,
+    /// FC 06 (nontrapping_fptoint) i64.trunc_sat_f64_s
+    I64TruncSatF64S = 0xFC_0006,
+    /// FC 07 (nontrapping_fptoint) i64.trunc_sat_f64_u
+    I64TruncSatF64U = 0xFC_0007,
+    /// FC 08 (bulk_memory) memory.init dataidx memidx
+    MemoryInit = 0xFC_0008,
+    /// FC 09 (bulk_memory) data.drop dataidx
+    DataDrop = 0xFC_0009,
+    /// FC 0A (bulk_memory) memory.copy memidx memidx
+    MemoryCopy = 0xFC_000A,
+    /// FC 0B (bulk_memory) memory.fill memidx
+    MemoryFill = 0xFC_000B,
+    /// FC 0C (bulk_memory) table.init elemidx tableidx
+    TableInit = 0xFC_000C,
+    /// FC 0D (bulk_memory) elem.drop elemidx
+    ElemDrop = 0xFC_000D,
+    /// FC 0E (bulk_memory) table.copy tableidx tableidx
+    TableCopy = 0xFC_000E,
+    /// FC 0F (reference_types) table.grow tableidx
+    TableGrow = 0xFC_000F,
+    /// FC 10 (reference_types) table.size tableidx
+    TableSize = 0xFC_0010,
+    /// FC 11 (reference_types) table.fill tableidx
+    TableFill = 0xFC_0011,
+    /// FD 00 (simd) v128.load memarg
+    V128Load = 0xFD_0000,
+    /// FD 01 (simd) v128.load8x8_s memarg
+    V128Load8x8S = 0xFD_0001,
+    /// FD 02 (simd) v128.load8x8_u memarg
+    V128Load8x8U = 0xFD_0002,
+    /// FD 03 (simd) v128.load16x4_s memarg
+    V128Load16x4S = 0xFD_0003,
+    /// FD 04 (simd) v128.load16x4_u memarg
+    V128Load16x4U = 0xFD_0004,
+    /// FD 05 (simd) v128.load32x2_s memarg
+    V128Load32x2S = 0xFD_0005,
+    /// FD 06 (simd) v128.load32x2_u memarg
+    V128Load32x2U = 0xFD_0006,
+    /// FD 07 (simd) v128.load8_splat memarg
+    V128Load8Splat = 0xFD_0007,
+    /// FD 08 (simd) v128.load16_splat memarg
+    V128Load16Splat = 0xFD_0008,
+    /// FD 09 (simd) v128.load32_splat memarg
+    V128Load32Splat = 0xFD_0009,
+    /// FD 0A (simd) v128.load64_splat memarg
+    V128Load64Splat = 0xFD_000A,
+    /// FD 0B (simd) v128.store memarg
+    V128Store = 0xFD_000B,
+    /// FD 0C (simd) v128.const i128
+    V128Const = 0xFD_000C,
+    /// FD 0D (simd) i8x16.shuffle laneidx*16
+    I8x16Shuffle = 0xFD_000D,
+    /// FD 0E (simd) i8x16.swizzle
+    I8x16Swizzle = 0xFD_000E,
+    /// FD 0F (simd) i8x16.splat
+    I8x16Splat = 0xFD_000F,
+    /// FD 10 (simd) i16x8.splat
+    I16x8Splat = 0xFD_0010,
+    /// FD 11 (simd) i32x4.splat
+    I32x4Splat = 0xFD_0011,
+    /// FD 12 (simd) i64x2.splat
+    I64x2Splat = 0xFD_0012,
+    /// FD 13 (simd) f32x4.splat
+    F32x4Splat = 0xFD_0013,
+    /// FD 14 (simd) f64x2.splat
+    F64x2Splat = 0xFD_0014,
+    /// FD 15 (simd) i8x16.extract_lane_s laneidx
+    I8x16ExtractLaneS = 0xFD_0015,
+    /// FD 16 (simd) i8x16.extract_lane_u laneidx
+    I8x16ExtractLaneU = 0xFD_0016,
+    /// FD 17 (simd) i8x16.replace_lane laneidx
+    I8x16ReplaceLane = 0xFD_0017,
+    /// FD 18 (simd) i16x8.extract_lane_s laneidx
+    I16x8ExtractLaneS = 0xFD_0018,
+    /// FD 19 (simd) i16x8.extract_lane_u laneidx
+    I16x8ExtractLaneU = 0xFD_0019,
+    /// FD 1A (simd) i16x8.replace_lane laneidx
+    I16x8ReplaceLane = 0xFD_001A,
+    /// FD 1B (simd) i32x4.extract_lane laneidx
+    I32x4ExtractLane = 0xFD_001B,
+    /// FD 1C (simd) i32x4.replace_lane laneidx
+    I32x4ReplaceLane = 0xFD_001C,
+    /// FD 1D (simd) i64x2.extract_lane laneidx
+    I64x2ExtractLane = 0xFD_001D,
+    /// FD 1E (simd) i64x2.replace_lane laneidx
+    I64x2ReplaceLane = 0xFD_001E,
+    /// FD 1F (simd) f32x4.extract_lane laneidx
+    F32x4ExtractLane = 0xFD_001F,
+    /// FD 20 (simd) f32x4.replace_lane laneidx
+    F32x4ReplaceLane = 0xFD_0020,
+    /// FD 21 (simd) f64x2.extract_lane laneidx
+    F64x2ExtractLane = 0xFD_0021,
+    /// FD 22 (simd) f64x2.replace_lane laneidx
+    F64x2ReplaceLane = 0xFD_0022,
+    /// FD 23 (simd) i8x16.eq
+    I8x16Eq = 0xFD_0023,
+    /// FD 24 (simd) i8x16.ne
+    I8x16Ne = 0xFD_0024,
+    /// FD 25 (simd) i8x16.lt_s
+    I8x16LtS = 0xFD_0025,
+    /// FD 26 (simd) i8x16.lt_u
+    I8x16LtU = 0xFD_0026,
+    /// FD 27 (simd) i8x16.gt_s
+    I8x16GtS = 0xFD_0027,
+    /// FD 28 (simd) i8x16.gt_u
+    I8x16GtU = 0xFD_0028,
+    /// FD 29 (simd) i8x16.le_s
+    I8x16LeS = 0xFD_0029,
+    /// FD 2A (simd) i8x16.le_u
+    I8x16LeU = 0xFD_002A,
+    /// FD 2B (simd) i8x16.ge_s
+    I8x16GeS = 0xFD_002B,
+    /// FD 2C (simd) i8x16.ge_u
+    I8x16GeU = 0xFD_002C,
+    /// FD 2D (simd) i16x8.eq
+    I16x8Eq = 0xFD_002D,
+    /// FD 2E (simd) i16x8.ne
+    I16x8Ne = 0xFD_002E,
+    /// FD 2F (simd) i16x8.lt_s
+    I16x8LtS = 0xFD_002F,
+    /// FD 30 (simd) i16x8.lt_u
+    I16x8LtU = 0xFD_0030,
+    /// FD 31 (simd) i16x8.gt_s
+    I16x8GtS = 0xFD_0031,
+    /// FD 32 (simd) i16x8.gt_u
+    I16x8GtU = 0xFD_0032,
+    /// FD 33 (simd) i16x8.le_s
+    I16x8LeS = 0xFD_0033,
+    /// FD 34 (simd) i16x8.le_u
+    I16x8LeU = 0xFD_0034,
+    /// FD 35 (simd) i16x8.ge_s
+    I16x8GeS = 0xFD_0035,
+    /// FD 36 (simd) i16x8.ge_u
+    I16x8GeU = 0xFD_0036,
+    /// FD 37 (simd) i32x4.eq
+    I32x4Eq = 0xFD_0037,
+    /// FD 38 (simd) i32x4.ne
+    I32x4Ne = 0xFD_0038,
+    /// FD 39 (simd) i32x4.lt_s
+    I32x4LtS = 0xFD_0039,
+    /// FD 3A (simd) i32x4.lt_u
+    I32x4LtU = 0xFD_003A,
+    /// FD 3B (simd) i32x4.gt_s
+    I32x4GtS = 0xFD_003B,
+    /// FD 3C (simd) i32x4.gt_u
+    I32x4GtU = 0xFD_003C,
+    /// FD 3D (simd) i32x4.le_s
+    I32x4LeS = 0xFD_003D,
+    /// FD 3E (simd) i32x4.le_u
+    I32x4LeU = 0xFD_003E,
+    /// FD 3F (simd) i32x4.ge_s
+    I32x4GeS = 0xFD_003F,
+    /// FD 40 (simd) i32x4.ge_u
+    I32x4GeU = 0xFD_0040,
+    /// FD 41 (simd) f32x4.eq
+    F32x4Eq = 0xFD_0041,
+    /// FD 42 (simd) f32x4.ne
+    F32x4Ne = 0xFD_0042,
+    /// FD 43 (simd) f32x4.lt
+    F32x4Lt = 0xFD_0043,
+    /// FD 44 (simd) f32x4.gt
+    F32x4Gt = 0xFD_0044,
+    /// FD 45 (simd) f32x4.le
+    F32x4Le = 0xFD_0045,
+    /// FD 46 (simd) f32x4.ge
+    F32x4Ge = 0xFD_0046,
+    /// FD 47 (simd) f64x2.eq
+    F64x2Eq = 0xFD_0047,
+    /// FD 48 (simd) f64x2.ne
+    F64x2Ne = 0xFD_0048,
+    /// FD 49 (simd) f64x2.lt
+    F64x2Lt = 0xFD_0049,
+    /// FD 4A (simd) f64x2.gt
+    F64x2Gt = 0xFD_004A,
+    /// FD 4B (simd) f64x2.le
+    F64x2Le = 0xFD_004B,
+    /// FD 4C (simd) f64x2.ge
+    F64x2Ge = 0xFD_004C,
+    /// FD 4D (simd) v128.not
+    V128Not = 0xFD_004D,
+    /// FD 4E (simd) v128.and
+    V128And = 0xFD_004E,
+    /// FD 4F (simd) v128.andnot
+    V128Andnot = 0xFD_004F,
+    /// FD 50 (simd) v128.or
+    V128Or = 0xFD_0050,
+    /// FD 51 (simd) v128.xor
+    V128Xor = 0xFD_0051,
+    /// FD 52 (simd) v128.bitselect
+    V128Bitselect = 0xFD_0052,
+    /// FD 53 (simd) v128.any_true
+    V128AnyTrue = 0xFD_0053,
+    /// FD 54 (simd) v128.load8_lane memarg laneidx
+    V128Load8Lane = 0xFD_0054,
+    /// FD 55 (simd) v128.load16_lane memarg laneidx
+    V128Load16Lane = 0xFD_0055,
+    /// FD 56 (simd) v128.load32_lane memarg laneidx
+    V128Load32Lane = 0xFD_0056,
+    /// FD 57 (simd) v128.load64_lane memarg laneidx
+    V128Load64Lane = 0xFD_0057,
+    /// FD 58 (simd) v128.store8_lane memarg laneidx
+    V128Store8Lane = 0xFD_0058,
+    /// FD 59 (simd) v128.store16_lane memarg laneidx
+    V128Store16Lane = 0xFD_0059,
+    /// FD 5A (simd) v128.store32_lane memarg laneidx
+    V128Store32Lane = 0xFD_005A,
+    /// FD 5B (simd) v128.store64_lane memarg laneidx
+    V128Store64Lane = 0xFD_005B,
+    /// FD 5C (simd) v128.load32_zero memarg
+    V128Load32Zero = 0xFD_005C,
+    /// FD 5D (simd) v128.load64_zero memarg
+    V128Load64Zero = 0xFD_005D,
+    /// FD 5E (simd) f32x4.demote_f64x2_zero
+    F32x4DemoteF64x2Zero = 0xFD_005E,
+    /// FD 5F (simd) f64x2.promote_low_f32x4
+    F64x2PromoteLowF32x4 = 0xFD_005F,
+    /// FD 60 (simd) i8x16.abs
+    I8x16Abs = 0xFD_0060,
+    /// FD 61 (simd) i8x16.neg
+    I8x16Neg = 0xFD_0061,
+    /// FD 62 (simd) i8x16.popcnt
+    I8x16Popcnt = 0xFD_0062,
+    /// FD 63 (simd) i8x16.all_true
+    I8x16AllTrue = 0xFD_0063,
+    /// FD 64 (simd) i8x16.bitmask
+    I8x16Bitmask = 0xFD_0064,
+    /// FD 65 (simd) i8x16.narrow_i16x8_s
+    I8x16NarrowI16x8S = 0xFD_0065,
+    /// FD 66 (simd) i8x16.narrow_i16x8_u
+    I8x16NarrowI16x8U = 0xFD_0066,
+    /// FD 67 (simd) f32x4.ceil
+    F32x4Ceil = 0xFD_0067,
+    /// FD 68 (simd) f32x4.floor
+    F32x4Floor = 0xFD_0068,
+    /// FD 69 (simd) f32x4.trunc
+    F32x4Trunc = 0xFD_0069,
+    /// FD 6A (simd) f32x4.nearest
+    F32x4Nearest = 0xFD_006A,
+    /// FD 6B (simd) i8x16.shl
+    I8x16Shl = 0xFD_006B,
+    /// FD 6C (simd) i8x16.shr_s
+    I8x16ShrS = 0xFD_006C,
+    /// FD 6D (simd) i8x16.shr_u
+    I8x16ShrU = 0xFD_006D,
+    /// FD 6E (simd) i8x16.add
+    I8x16Add = 0xFD_006E,
+    /// FD 6F (simd) i8x16.add_sat_s
+    I8x16AddSatS = 0xFD_006F,
+    /// FD 70 (simd) i8x16.add_sat_u
+    I8x16AddSatU = 0xFD_0070,
+    /// FD 71 (simd) i8x16.sub
+    I8x16Sub = 0xFD_0071,
+    /// FD 72 (simd) i8x16.sub_sat_s
+    I8x16SubSatS = 0xFD_0072,
+    /// FD 73 (simd) i8x16.sub_sat_u
+    I8x16SubSatU = 0xFD_0073,
+    /// FD 74 (simd) f64x2.ceil
+    F64x2Ceil = 0xFD_0074,
+    /// FD 75 (simd) f64x2.floor
+    F64x2Floor = 0xFD_0075,
+    /// FD 76 (simd) i8x16.min_s
+    I8x16MinS = 0xFD_0076,
+    /// FD 77 (simd) i8x16.min_u
+    I8x16MinU = 0xFD_0077,
+    /// FD 78 (simd) i8x16.max_s
+    I8x16MaxS = 0xFD_0078,
+    /// FD 79 (simd) i8x16.max_u
+    I8x16MaxU = 0xFD_0079,
+    /// FD 7A (simd) f64x2.trunc
+    F64x2Trunc = 0xFD_007A,
+    /// FD 7B (simd) i8x16.avgr_u
+    I8x16AvgrU = 0xFD_007B,
+    /// FD 7C (simd) i16x8.extadd_pairwise_i8x16_s
+    I16x8ExtaddPairwiseI8x16S = 0xFD_007C,
+    /// FD 7D (simd) i16x8.extadd_pairwise_i8x16_u
+    I16x8ExtaddPairwiseI8x16U = 0xFD_007D,
+    /// FD 7E (simd) i32x4.extadd_pairwise_i16x8_s
+    I32x4ExtaddPairwiseI16x8S = 0xFD_007E,
+    /// FD 7F (simd) i32x4.extadd_pairwise_i16x8_u
+    I32x4ExtaddPairwiseI16x8U = 0xFD_007F,
+    /// FD 80 (simd) i16x8.abs
+    I16x8Abs = 0xFD_0080,
+    /// FD 81 (simd) i16x8.neg
+    I16x8Neg = 0xFD_0081,
+    /// FD 82 (simd) i16x8.q15mulr_sat_s
+    I16x8Q15mulrSatS = 0xFD_0082,
+    /// FD 83 (simd) i16x8.all_true
+    I16x8AllTrue = 0xFD_0083,
+    /// FD 84 (simd) i16x8.bitmask
+    I16x8Bitmask = 0xFD_0084,
+    /// FD 85 (simd) i16x8.narrow_i32x4_s
+    I16x8NarrowI32x4S = 0xFD_0085,
+    /// FD 86 (simd) i16x8.narrow_i32x4_u
+    I16x8NarrowI32x4U = 0xFD_0086,
+    /// FD 87 (simd) i16x8.extend_low_i8x16_s
+    I16x8ExtendLowI8x16S = 0xFD_0087,
+    /// FD 88 (simd) i16x8.extend_high_i8x16_s
+    I16x8ExtendHighI8x16S = 0xFD_0088,
+    /// FD 89 (simd) i16x8.extend_low_i8x16_u
+    I16x8ExtendLowI8x16U = 0xFD_0089,
+    /// FD 8A (simd) i16x8.extend_high_i8x16_u
+    I16x8ExtendHighI8x16U = 0xFD_008A,
+    /// FD 8B (simd) i16x8.shl
+    I16x8Shl = 0xFD_008B,
+    /// FD 8C (simd) i16x8.shr_s
+    I16x8ShrS = 0xFD_008C,
+    /// FD 8D (simd) i16x8.shr_u
+    I16x8ShrU = 0xFD_008D,
+    /// FD 8E (simd) i16x8.add
+    I16x8Add = 0xFD_008E,
+    /// FD 8F (simd) i16x8.add_sat_s
+    I16x8AddSatS = 0xFD_008F,
+    /// FD 90 (simd) i16x8.add_sat_u
+    I16x8AddSatU = 0xFD_0090,
+    /// FD 91 (simd) i16x8.sub
+    I16x8Sub = 0xFD_0091,
+    /// FD 92 (simd) i16x8.sub_sat_s
+    I16x8SubSatS = 0xFD_0092,
+    /// FD 93 (simd) i16x8.sub_sat_u
+    I16x8SubSatU = 0xFD_0093,
+    /// FD 94 (simd) f64x2.nearest
+    F64x2Nearest = 0xFD_0094,
+    /// FD 95 (simd) i16x8.mul
+    I16x8Mul = 0xFD_0095,
+    /// FD 96 (simd) i16x8.min_s
+    I16x8MinS = 0xFD_0096,
+    /// FD 97 (simd) i16x8.min_u
+    I16x8MinU = 0xFD_0097,
+    /// FD 98 (simd) i16x8.max_s
+    I16x8MaxS = 0xFD_0098,
+    /// FD 99 (simd) i16x8.max_u
+    I16x8MaxU = 0xFD_0099,
+    /// FD 9B (simd) i16x8.avgr_u
+    I16x8AvgrU = 0xFD_009B,
+    /// FD 9C (simd) i16x8.extmul_low_i8x16_s
+    I16x8ExtmulLowI8x16S = 0xFD_009C,
+    /// FD 9D (simd) i16x8.extmul_high_i8x16_s
+    I16x8ExtmulHighI8x16S = 0xFD_009D,
+    /// FD 9E (simd) i16x8.extmul_low_i8x16_u
+    I16x8ExtmulLowI8x16U = 0xFD_009E,
+    /// FD 9F (simd) i16x8.extmul_high_i8x16_u
+    I16x8ExtmulHighI8x16U = 0xFD_009F,
+    /// FD A0 (simd) i32x4.abs
+    I32x4Abs = 0xFD_00A0,
+    /// FD A1 (simd) i32x4.neg
+    I32x4Neg = 0xFD_00A1,
+    /// FD A3 (simd) i32x4.all_true
+    I32x4AllTrue = 0xFD_00A3,
+    /// FD A4 (simd) i32x4.bitmask
+    I32x4Bitmask = 0xFD_00A4,
+    /// FD A7 (simd) i32x4.extend_low_i16x8_s
+    I32x4ExtendLowI16x8S = 0xFD_00A7,
+    /// FD A8 (simd) i32x4.extend_high_i16x8_s
+    I32x4ExtendHighI16x8S = 0xFD_00A8,
+    /// FD A9 (simd) i32x4.extend_low_i16x8_u
+    I32x4ExtendLowI16x8U = 0xFD_00A9,
+    /// FD AA (simd) i32x4.extend_high_i16x8_u
+    I32x4ExtendHighI16x8U = 0xFD_00AA,
+    /// FD AB (simd) i32x4.shl
+    I32x4Shl = 0xFD_00AB,
+    /// FD AC (simd) i32x4.shr_s
+    I32x4ShrS = 0xFD_00AC,
+    /// FD AD (simd) i32x4.shr_u
+    I32x4ShrU = 0xFD_00AD,
+    /// FD AE (simd) i32x4.add
+    I32x4Add = 0xFD_00AE,
+    /// FD B1 (simd) i32x4.sub
+    I32x4Sub = 0xFD_00B1,
+    /// FD B5 (simd) i32x4.mul
+    I32x4Mul = 0xFD_00B5,
+    /// FD B6 (simd) i32x4.min_s
+    I32x4MinS = 0xFD_00B6,
+    /// FD B7 (simd) i32x4.min_u
+    I32x4MinU = 0xFD_00B7,
+    /// FD B8 (simd) i32x4.max_s
+    I32x4MaxS = 0xFD_00B8,
+    /// FD B9 (simd) i32x4.max_u
+    I32x4MaxU = 0xFD_00B9,
+    /// FD BA (simd) i32x4.dot_i16x8_s
+    I32x4DotI16x8S = 0xFD_00BA,
+    /// FD BC (simd) i32x4.extmul_low_i16x8_s
+    I32x4ExtmulLowI16x8S = 0xFD_00BC,
+    /// FD BD (simd) i32x4.extmul_high_i16x8_s
+    I32x4ExtmulHighI16x8S = 0xFD_00BD,
+    /// FD BE (simd) i32x4.extmul_low_i16x8_u
+    I32x4ExtmulLowI16x8U = 0xFD_00BE,
+    /// FD BF (simd) i32x4.extmul_high_i16x8_u
+    I32x4ExtmulHighI16x8U = 0xFD_00BF,
+    /// FD C0 (simd) i64x2.abs
+    I64x2Abs = 0xFD_00C0,
+    /// FD C1 (simd) i64x2.neg
+    I64x2Neg = 0xFD_00C1,
+    /// FD C3 (simd) i64x2.all_true
+    I64x2AllTrue = 0xFD_00C3,
+    /// FD C4 (simd) i64x2.bitmask
+    I64x2Bitmask = 0xFD_00C4,
+    /// FD C7 (simd) i64x2.extend_low_i32x4_s
+    I64x2ExtendLowI32x4S = 0xFD_00C7,
+    /// FD C8 (simd) i64x2.extend_high_i32x4_s
+    I64x2ExtendHighI32x4S = 0xFD_00C8,
+    /// FD C9 (simd) i64x2.extend_low_i32x4_u
+    I64x2ExtendLowI32x4U = 0xFD_00C9,
+    /// FD CA (simd) i64x2.extend_high_i32x4_u
+    I64x2ExtendHighI32x4U = 0xFD_00CA,
+    /// FD CB (simd) i64x2.shl
+    I64x2Shl = 0xFD_00CB,
+    /// FD CC (simd) i64x2.shr_s
+    I64x2ShrS = 0xFD_00CC,
+    /// FD CD (simd) i64x2.shr_u
+    I64x2ShrU = 0xFD_00CD,
+    /// FD CE (simd) i64x2.add
+    I64x2Add = 0xFD_00CE,
+    /// FD D1 (simd) i64x2.sub
+    I64x2Sub = 0xFD_00D1,
+    /// FD D5 (simd) i64x2.mul
+    I64x2Mul = 0xFD_00D5,
+    /// FD D6 (simd) i64x2.eq
+    I64x2Eq = 0xFD_00D6,
+    /// FD D7 (simd) i64x2.ne
+    I64x2Ne = 0xFD_00D7,
+    /// FD D8 (simd) i64x2.lt_s
+    I64x2LtS = 0xFD_00D8,
+    /// FD D9 (simd) i64x2.gt_s
+    I64x2GtS = 0xFD_00D9,
+    /// FD DA (simd) i64x2.le_s
+    I64x2LeS = 0xFD_00DA,
+    /// FD DB (simd) i64x2.ge_s
+    I64x2GeS = 0xFD_00DB,
+    /// FD DC (simd) i64x2.extmul_low_i32x4_s
+    I64x2ExtmulLowI32x4S = 0xFD_00DC,
+    /// FD DD (simd) i64x2.extmul_high_i32x4_s
+    I64x2ExtmulHighI32x4S = 0xFD_00DD,
+    /// FD DE (simd) i64x2.extmul_low_i32x4_u
+    I64x2ExtmulLowI32x4U = 0xFD_00DE,
+    /// FD DF (simd) i64x2.extmul_high_i32x4_u
+    I64x2ExtmulHighI32x4U = 0xFD_00DF,
+    /// FD E0 (simd) f32x4.abs
+    F32x4Abs = 0xFD_00E0,
+    /// FD E1 (simd) f32x4.neg
+    F32x4Neg = 0xFD_00E1,
+    /// FD E3 (simd) f32x4.sqrt
+    F32x4Sqrt = 0xFD_00E3,
+    /// FD E4 (simd) f32x4.add
+    F32x4Add = 0xFD_00E4,
+    /// FD E5 (simd) f32x4.sub
+    F32x4Sub = 0xFD_00E5,
+    /// FD E6 (simd) f32x4.mul
+    F32x4Mul = 0xFD_00E6,
+    /// FD E7 (simd) f32x4.div
+    F32x4Div = 0xFD_00E7,
+    /// FD E8 (simd) f32x4.min
+    F32x4Min = 0xFD_00E8,
+    /// FD E9 (simd) f32x4.max
+    F32x4Max = 0xFD_00E9,
+    /// FD EA (simd) f32x4.pmin
+    F32x4Pmin = 0xFD_00EA,
+    /// FD EB (simd) f32x4.pmax
+    F32x4Pmax = 0xFD_00EB,
+    /// FD EC (simd) f64x2.abs
+    F64x2Abs = 0xFD_00EC,
+    /// FD ED (simd) f64x2.neg
+    F64x2Neg = 0xFD_00ED,
+    /// FD EF (simd) f64x2.sqrt
+    F64x2Sqrt = 0xFD_00EF,
+    /// FD F0 (simd) f64x2.add
+    F64x2Add = 0xFD_00F0,
+    /// FD F1 (simd) f64x2.sub
+    F64x2Sub = 0xFD_00F1,
+    /// FD F2 (simd) f64x2.mul
+    F64x2Mul = 0xFD_00F2,
+    /// FD F3 (simd) f64x2.div
+    F64x2Div = 0xFD_00F3,
+    /// FD F4 (simd) f64x2.min
+    F64x2Min = 0xFD_00F4,
+    /// FD F5 (simd) f64x2.max
+    F64x2Max = 0xFD_00F5,
+    /// FD F6 (simd) f64x2.pmin
+    F64x2Pmin = 0xFD_00F6,
+    /// FD F7 (simd) f64x2.pmax
+    F64x2Pmax = 0xFD_00F7,
+    /// FD F8 (simd) i32x4.trunc_sat_f32x4_s
+    I32x4TruncSatF32x4S = 0xFD_00F8,
+    /// FD F9 (simd) i32x4.trunc_sat_f32x4_u
+    I32x4TruncSatF32x4U = 0xFD_00F9,
+    /// FD FA (simd) f32x4.convert_i32x4_s
+    F32x4ConvertI32x4S = 0xFD_00FA,
+    /// FD FB (simd) f32x4.convert_i32x4_u
+    F32x4ConvertI32x4U = 0xFD_00FB,
+    /// FD FC (simd) i32x4.trunc_sat_f64x2_s_zero
+    I32x4TruncSatF64x2SZero = 0xFD_00FC,
+    /// FD FD (simd) i32x4.trunc_sat_f64x2_u_zero
+    I32x4TruncSatF64x2UZero = 0xFD_00FD,
+    /// FD FE (simd) f64x2.convert_low_i32x4_s
+    F64x2ConvertLowI32x4S = 0xFD_00FE,
+    /// FD FF (simd) f64x2.convert_low_i32x4_u
+    F64x2ConvertLowI32x4U = 0xFD_00FF,
+    /// FE 00 (threads) memory.atomic.notify memarg
+    MemoryAtomicNotify = 0xFE_0000,
+    /// FE 01 (threads) memory.atomic.wait32 memarg
+    MemoryAtomicWait32 = 0xFE_0001,
+    /// FE 02 (threads) memory.atomic.wait64 memarg
+    MemoryAtomicWait64 = 0xFE_0002,
+    /// FE 03 (threads) atomic.fence 0x00
+    AtomicFence = 0xFE_0003,
+    /// FE 10 (threads) i32.atomic.load memarg
+    I32AtomicLoad = 0xFE_0010,
+    /// FE 11 (threads) i64.atomic.load memarg
+    I64AtomicLoad = 0xFE_0011,
+    /// FE 12 (threads) i32.atomic.load8_u memarg
+    I32AtomicLoad8U = 0xFE_0012,
+    /// FE 13 (threads) i32.atomic.load16_u memarg
+    I32AtomicLoad16U = 0xFE_0013,
+    /// FE 14 (threads) i64.atomic.load8_u memarg
+    I64AtomicLoad8U = 0xFE_0014,
+    /// FE 15 (threads) i64.atomic.load16_u memarg
+    I64AtomicLoad16U = 0xFE_0015,
+    /// FE 16 (threads) i64.atomic.load32_u memarg
+    I64AtomicLoad32U = 0xFE_0016,
+    /// FE 17 (threads) i32.atomic.store memarg
+    I32AtomicStore = 0xFE_0017,
+    /// FE 18 (threads) i64.atomic.store memarg
+    I64AtomicStore = 0xFE_0018,
+    /// FE 19 (threads) i32.atomic.store8 memarg
+    I32AtomicStore8 = 0xFE_0019,
+    /// FE 1A (threads) i32.atomic.store16 memarg
+    I32AtomicStore16 = 0xFE_001A,
+    /// FE 1B (threads) i64.atomic.store8 memarg
+    I64AtomicStore8 = 0xFE_001B,
+    /// FE 1C (threads) i64.atomic.store16 memarg
+    I64AtomicStore16 = 0xFE_001C,
+    /// FE 1D (threads) i64.atomic.store32 memarg
+    I64AtomicStore32 = 0xFE_001D,
+    /// FE 1E (threads) i32.atomic.rmw.add memarg
+    I32AtomicRmwAdd = 0xFE_001E,
+    /// FE 1F (threads) i64.atomic.rmw.add memarg
+    I64AtomicRmwAdd = 0xFE_001F,
+    /// FE 20 (threads) i32.atomic.rmw8.add_u memarg
+    I32AtomicRmw8AddU = 0xFE_0020,
+    /// FE 21 (threads) i32.atomic.rmw16.add_u memarg
+    I32AtomicRmw16AddU = 0xFE_0021,
+    /// FE 22 (threads) i64.atomic.rmw8.add_u memarg
+    I64AtomicRmw8AddU = 0xFE_0022,
+    /// FE 23 (threads) i64.atomic.rmw16.add_u memarg
+    I64AtomicRmw16AddU = 0xFE_0023,
+    /// FE 24 (threads) i64.atomic.rmw32.add_u memarg
+    I64AtomicRmw32AddU = 0xFE_0024,
+    /// FE 25 (threads) i32.atomic.rmw.sub memarg
+    I32AtomicRmwSub = 0xFE_0025,
+    /// FE 26 (threads) i64.atomic.rmw.sub memarg
+    I64AtomicRmwSub = 0xFE_0026,
+    /// FE 27 (threads) i32.atomic.rmw8.sub_u memarg
+    I32AtomicRmw8SubU = 0xFE_0027,
+    /// FE 28 (threads) i32.atomic.rmw16.sub_u memarg
+    I32AtomicRmw16SubU = 0xFE_0028,
+    /// FE 29 (threads) i64.atomic.rmw8.sub_u memarg
+    I64AtomicRmw8SubU = 0xFE_0029,
+    /// FE 2A (threads) i64.atomic.rmw16.sub_u memarg
+    I64AtomicRmw16SubU = 0xFE_002A,
+    /// FE 2B (threads) i64.atomic.rmw32.sub_u memarg
+    I64AtomicRmw32SubU = 0xFE_002B,
+    /// FE 2C (threads) i32.atomic.rmw.and memarg
+    I32AtomicRmwAnd = 0xFE_002C,
+    /// FE 2D (threads) i64.atomic.rmw.and memarg
+    I64AtomicRmwAnd = 0xFE_002D,
+    /// FE 2E (threads) i32.atomic.rmw8.and_u memarg
+    I32AtomicRmw8AndU = 0xFE_002E,
+    /// FE 2F (threads) i32.atomic.rmw16.and_u memarg
+    I32AtomicRmw16AndU = 0xFE_002F,
+    /// FE 30 (threads) i64.atomic.rmw8.and_u memarg
+    I64AtomicRmw8AndU = 0xFE_0030,
+    /// FE 31 (threads) i64.atomic.rmw16.and_u memarg
+    I64AtomicRmw16AndU = 0xFE_0031,
+    /// FE 32 (threads) i64.atomic.rmw32.and_u memarg
+    I64AtomicRmw32AndU = 0xFE_0032,
+    /// FE 33 (threads) i32.atomic.rmw.or memarg
+    I32AtomicRmwOr = 0xFE_0033,
+    /// FE 34 (threads) i64.atomic.rmw.or memarg
+    I64AtomicRmwOr = 0xFE_0034,
+    /// FE 35 (threads) i32.atomic.rmw8.or_u memarg
+    I32AtomicRmw8OrU = 0xFE_0035,
+    /// FE 36 (threads) i32.atomic.rmw16.or_u memarg
+    I32AtomicRmw16OrU = 0xFE_0036,
+    /// FE 37 (threads) i64.atomic.rmw8.or_u memarg
+    I64AtomicRmw8OrU = 0xFE_0037,
+    /// FE 38 (threads) i64.atomic.rmw16.or_u memarg
+    I64AtomicRmw16OrU = 0xFE_0038,
+    /// FE 39 (threads) i64.atomic.rmw32.or_u memarg
+    I64AtomicRmw32OrU = 0xFE_0039,
+    /// FE 3A (threads) i32.atomic.rmw.xor memarg
+    I32AtomicRmwXor = 0xFE_003A,
+    /// FE 3B (threads) i64.atomic.rmw.xor memarg
+    I64AtomicRmwXor = 0xFE_003B,
+    /// FE 3C (threads) i32.atomic.rmw8.xor_u memarg
+    I32AtomicRmw8XorU = 0xFE_003C,
+    /// FE 3D (threads) i32.atomic.rmw16.xor_u memarg
+    I32AtomicRmw16XorU = 0xFE_003D,
+    /// FE 3E (threads) i64.atomic.rmw8.xor_u memarg
+    I64AtomicRmw8XorU = 0xFE_003E,
+    /// FE 3F (threads) i64.atomic.rmw16.xor_u memarg
+    I64AtomicRmw16XorU = 0xFE_003F,
+    /// FE 40 (threads) i64.atomic.rmw32.xor_u memarg
+    I64AtomicRmw32XorU = 0xFE_0040,
+    /// FE 41 (threads) i32.atomic.rmw.xchg memarg
+    I32AtomicRmwXchg = 0xFE_0041,
+    /// FE 42 (threads) i64.atomic.rmw.xchg memarg
+    I64AtomicRmwXchg = 0xFE_0042,
+    /// FE 43 (threads) i32.atomic.rmw8.xchg_u memarg
+    I32AtomicRmw8XchgU = 0xFE_0043,
+    /// FE 44 (threads) i32.atomic.rmw16.xchg_u memarg
+    I32AtomicRmw16XchgU = 0xFE_0044,
+    /// FE 45 (threads) i64.atomic.rmw8.xchg_u memarg
+    I64AtomicRmw8XchgU = 0xFE_0045,
+    /// FE 46 (threads) i64.atomic.rmw16.xchg_u memarg
+    I64AtomicRmw16XchgU = 0xFE_0046,
+    /// FE 47 (threads) i64.atomic.rmw32.xchg_u memarg
+    I64AtomicRmw32XchgU = 0xFE_0047,
+    /// FE 48 (threads) i32.atomic.rmw.cmpxchg memarg
+    I32AtomicRmwCmpxchg = 0xFE_0048,
+    /// FE 49 (threads) i64.atomic.rmw.cmpxchg memarg
+    I64AtomicRmwCmpxchg = 0xFE_0049,
+    /// FE 4A (threads) i32.atomic.rmw8.cmpxchg_u memarg
+    I32AtomicRmw8CmpxchgU = 0xFE_004A,
+    /// FE 4B (threads) i32.atomic.rmw16.cmpxchg_u memarg
+    I32AtomicRmw16CmpxchgU = 0xFE_004B,
+    /// FE 4C (threads) i64.atomic.rmw8.cmpxchg_u memarg
+    I64AtomicRmw8CmpxchgU = 0xFE_004C,
+    /// FE 4D (threads) i64.atomic.rmw16.cmpxchg_u memarg
+    I64AtomicRmw16CmpxchgU = 0xFE_004D,
+    /// FE 4E (threads) i64.atomic.rmw32.cmpxchg_u memarg
+    I64AtomicRmw32CmpxchgU = 0xFE_004E,
 }
 
 #[non_exhaustive]
@@ -383,6 +1043,24 @@ pub enum WasmOperandType {
     I64,
     F32,
     F64,
+    MemoryInit,
+    DataDrop,
+    MemoryCopy,
+    MemoryFill,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+    SelectT,
+    Table,
+    RefType,
+    RefFunc,
+    V128,
+    Shuffle,
+    Lane,
+    MemoryLane,
+    Fence,
+    Tag,
+    TryTable,
 }
 
 #[non_exhaustive]
@@ -394,6 +1072,12 @@ pub enum WasmProposalType {
     MvpF32,
     MvpF64,
     SignExtend,
+    BulkMemory,
+    NonTrappingFloatToInt,
+    ReferenceTypes,
+    Simd,
+    Threads,
+    Exceptions,
 }
 
 impl WasmOpcode {
@@ -401,6 +1085,340 @@ impl WasmOpcode {
         Self::try_from(value).unwrap_or(Self::Unreachable)
     }
 
+    /// Returns whether the byte is a prefix of the multi-byte opcodes
+    pub const fn is_prefix(value: u8) -> bool {
+        matches!(value, 0xFC..=0xFE)
+    }
+
+    /// Decode the multi-byte opcode from its prefix and the following index
+    #[allow(clippy::result_unit_err)]
+    pub fn try_from_prefixed(prefix: u8, value: u32) -> Result<Self, ()> {
+        match (prefix, value) {
+            (0xFC, 0x00) => Ok(Self::I32TruncSatF32S),
+            (0xFC, 0x01) => Ok(Self::I32TruncSatF32U),
+            (0xFC, 0x02) => Ok(Self::I32TruncSatF64S),
+            (0xFC, 0x03) => Ok(Self::I32TruncSatF64U),
+            (0xFC, 0x04) => Ok(Self::I64TruncSatF32S),
+            (0xFC, 0x05) => Ok(Self::I64TruncSatF32U),
+            (0xFC, 0x06) => Ok(Self::I64TruncSatF64S),
+            (0xFC, 0x07) => Ok(Self::I64TruncSatF64U),
+            (0xFC, 0x08) => Ok(Self::MemoryInit),
+            (0xFC, 0x09) => Ok(Self::DataDrop),
+            (0xFC, 0x0A) => Ok(Self::MemoryCopy),
+            (0xFC, 0x0B) => Ok(Self::MemoryFill),
+            (0xFC, 0x0C) => Ok(Self::TableInit),
+            (0xFC, 0x0D) => Ok(Self::ElemDrop),
+            (0xFC, 0x0E) => Ok(Self::TableCopy),
+            (0xFC, 0x0F) => Ok(Self::TableGrow),
+            (0xFC, 0x10) => Ok(Self::TableSize),
+            (0xFC, 0x11) => Ok(Self::TableFill),
+            (0xFD, 0x00) => Ok(Self::V128Load),
+            (0xFD, 0x01) => Ok(Self::V128Load8x8S),
+            (0xFD, 0x02) => Ok(Self::V128Load8x8U),
+            (0xFD, 0x03) => Ok(Self::V128Load16x4S),
+            (0xFD, 0x04) => Ok(Self::V128Load16x4U),
+            (0xFD, 0x05) => Ok(Self::V128Load32x2S),
+            (0xFD, 0x06) => Ok(Self::V128Load32x2U),
+            (0xFD, 0x07) => Ok(Self::V128Load8Splat),
+            (0xFD, 0x08) => Ok(Self::V128Load16Splat),
+            (0xFD, 0x09) => Ok(Self::V128Load32Splat),
+            (0xFD, 0x0A) => Ok(Self::V128Load64Splat),
+            (0xFD, 0x0B) => Ok(Self::V128Store),
+            (0xFD, 0x0C) => Ok(Self::V128Const),
+            (0xFD, 0x0D) => Ok(Self::I8x16Shuffle),
+            (0xFD, 0x0E) => Ok(Self::I8x16Swizzle),
+            (0xFD, 0x0F) => Ok(Self::I8x16Splat),
+            (0xFD, 0x10) => Ok(Self::I16x8Splat),
+            (0xFD, 0x11) => Ok(Self::I32x4Splat),
+            (0xFD, 0x12) => Ok(Self::I64x2Splat),
+            (0xFD, 0x13) => Ok(Self::F32x4Splat),
+            (0xFD, 0x14) => Ok(Self::F64x2Splat),
+            (0xFD, 0x15) => Ok(Self::I8x16ExtractLaneS),
+            (0xFD, 0x16) => Ok(Self::I8x16ExtractLaneU),
+            (0xFD, 0x17) => Ok(Self::I8x16ReplaceLane),
+            (0xFD, 0x18) => Ok(Self::I16x8ExtractLaneS),
+            (0xFD, 0x19) => Ok(Self::I16x8ExtractLaneU),
+            (0xFD, 0x1A) => Ok(Self::I16x8ReplaceLane),
+            (0xFD, 0x1B) => Ok(Self::I32x4ExtractLane),
+            (0xFD, 0x1C) => Ok(Self::I32x4ReplaceLane),
+            (0xFD, 0x1D) => Ok(Self::I64x2ExtractLane),
+            (0xFD, 0x1E) => Ok(Self::I64x2ReplaceLane),
+            (0xFD, 0x1F) => Ok(Self::F32x4ExtractLane),
+            (0xFD, 0x20) => Ok(Self::F32x4ReplaceLane),
+            (0xFD, 0x21) => Ok(Self::F64x2ExtractLane),
+            (0xFD, 0x22) => Ok(Self::F64x2ReplaceLane),
+            (0xFD, 0x23) => Ok(Self::I8x16Eq),
+            (0xFD, 0x24) => Ok(Self::I8x16Ne),
+            (0xFD, 0x25) => Ok(Self::I8x16LtS),
+            (0xFD, 0x26) => Ok(Self::I8x16LtU),
+            (0xFD, 0x27) => Ok(Self::I8x16GtS),
+            (0xFD, 0x28) => Ok(Self::I8x16GtU),
+            (0xFD, 0x29) => Ok(Self::I8x16LeS),
+            (0xFD, 0x2A) => Ok(Self::I8x16LeU),
+            (0xFD, 0x2B) => Ok(Self::I8x16GeS),
+            (0xFD, 0x2C) => Ok(Self::I8x16GeU),
+            (0xFD, 0x2D) => Ok(Self::I16x8Eq),
+            (0xFD, 0x2E) => Ok(Self::I16x8Ne),
+            (0xFD, 0x2F) => Ok(Self::I16x8LtS),
+            (0xFD, 0x30) => Ok(Self::I16x8LtU),
+            (0xFD, 0x31) => Ok(Self::I16x8GtS),
+            (0xFD, 0x32) => Ok(Self::I16x8GtU),
+            (0xFD, 0x33) => Ok(Self::I16x8LeS),
+            (0xFD, 0x34) => Ok(Self::I16x8LeU),
+            (0xFD, 0x35) => Ok(Self::I16x8GeS),
+            (0xFD, 0x36) => Ok(Self::I16x8GeU),
+            (0xFD, 0x37) => Ok(Self::I32x4Eq),
+            (0xFD, 0x38) => Ok(Self::I32x4Ne),
+            (0xFD, 0x39) => Ok(Self::I32x4LtS),
+            (0xFD, 0x3A) => Ok(Self::I32x4LtU),
+            (0xFD, 0x3B) => Ok(Self::I32x4GtS),
+            (0xFD, 0x3C) => Ok(Self::I32x4GtU),
+            (0xFD, 0x3D) => Ok(Self::I32x4LeS),
+            (0xFD, 0x3E) => Ok(Self::I32x4LeU),
+            (0xFD, 0x3F) => Ok(Self::I32x4GeS),
+            (0xFD, 0x40) => Ok(Self::I32x4GeU),
+            (0xFD, 0x41) => Ok(Self::F32x4Eq),
+            (0xFD, 0x42) => Ok(Self::F32x4Ne),
+            (0xFD, 0x43) => Ok(Self::F32x4Lt),
+            (0xFD, 0x44) => Ok(Self::F32x4Gt),
+            (0xFD, 0x45) => Ok(Self::F32x4Le),
+            (0xFD, 0x46) => Ok(Self::F32x4Ge),
+            (0xFD, 0x47) => Ok(Self::F64x2Eq),
+            (0xFD, 0x48) => Ok(Self::F64x2Ne),
+            (0xFD, 0x49) => Ok(Self::F64x2Lt),
+            (0xFD, 0x4A) => Ok(Self::F64x2Gt),
+            (0xFD, 0x4B) => Ok(Self::F64x2Le),
+            (0xFD, 0x4C) => Ok(Self::F64x2Ge),
+            (0xFD, 0x4D) => Ok(Self::V128Not),
+            (0xFD, 0x4E) => Ok(Self::V128And),
+            (0xFD, 0x4F) => Ok(Self::V128Andnot),
+            (0xFD, 0x50) => Ok(Self::V128Or),
+            (0xFD, 0x51) => Ok(Self::V128Xor),
+            (0xFD, 0x52) => Ok(Self::V128Bitselect),
+            (0xFD, 0x53) => Ok(Self::V128AnyTrue),
+            (0xFD, 0x54) => Ok(Self::V128Load8Lane),
+            (0xFD, 0x55) => Ok(Self::V128Load16Lane),
+            (0xFD, 0x56) => Ok(Self::V128Load32Lane),
+            (0xFD, 0x57) => Ok(Self::V128Load64Lane),
+            (0xFD, 0x58) => Ok(Self::V128Store8Lane),
+            (0xFD, 0x59) => Ok(Self::V128Store16Lane),
+            (0xFD, 0x5A) => Ok(Self::V128Store32Lane),
+            (0xFD, 0x5B) => Ok(Self::V128Store64Lane),
+            (0xFD, 0x5C) => Ok(Self::V128Load32Zero),
+            (0xFD, 0x5D) => Ok(Self::V128Load64Zero),
+            (0xFD, 0x5E) => Ok(Self::F32x4DemoteF64x2Zero),
+            (0xFD, 0x5F) => Ok(Self::F64x2PromoteLowF32x4),
+            (0xFD, 0x60) => Ok(Self::I8x16Abs),
+            (0xFD, 0x61) => Ok(Self::I8x16Neg),
+            (0xFD, 0x62) => Ok(Self::I8x16Popcnt),
+            (0xFD, 0x63) => Ok(Self::I8x16AllTrue),
+            (0xFD, 0x64) => Ok(Self::I8x16Bitmask),
+            (0xFD, 0x65) => Ok(Self::I8x16NarrowI16x8S),
+            (0xFD, 0x66) => Ok(Self::I8x16NarrowI16x8U),
+            (0xFD, 0x67) => Ok(Self::F32x4Ceil),
+            (0xFD, 0x68) => Ok(Self::F32x4Floor),
+            (0xFD, 0x69) => Ok(Self::F32x4Trunc),
+            (0xFD, 0x6A) => Ok(Self::F32x4Nearest),
+            (0xFD, 0x6B) => Ok(Self::I8x16Shl),
+            (0xFD, 0x6C) => Ok(Self::I8x16ShrS),
+            (0xFD, 0x6D) => Ok(Self::I8x16ShrU),
+            (0xFD, 0x6E) => Ok(Self::I8x16Add),
+            (0xFD, 0x6F) => Ok(Self::I8x16AddSatS),
+            (0xFD, 0x70) => Ok(Self::I8x16AddSatU),
+            (0xFD, 0x71) => Ok(Self::I8x16Sub),
+            (0xFD, 0x72) => Ok(Self::I8x16SubSatS),
+            (0xFD, 0x73) => Ok(Self::I8x16SubSatU),
+            (0xFD, 0x74) => Ok(Self::F64x2Ceil),
+            (0xFD, 0x75) => Ok(Self::F64x2Floor),
+            (0xFD, 0x76) => Ok(Self::I8x16MinS),
+            (0xFD, 0x77) => Ok(Self::I8x16MinU),
+            (0xFD, 0x78) => Ok(Self::I8x16MaxS),
+            (0xFD, 0x79) => Ok(Self::I8x16MaxU),
+            (0xFD, 0x7A) => Ok(Self::F64x2Trunc),
+            (0xFD, 0x7B) => Ok(Self::I8x16AvgrU),
+            (0xFD, 0x7C) => Ok(Self::I16x8ExtaddPairwiseI8x16S),
+            (0xFD, 0x7D) => Ok(Self::I16x8ExtaddPairwiseI8x16U),
+            (0xFD, 0x7E) => Ok(Self::I32x4ExtaddPairwiseI16x8S),
+            (0xFD, 0x7F) => Ok(Self::I32x4ExtaddPairwiseI16x8U),
+            (0xFD, 0x80) => Ok(Self::I16x8Abs),
+            (0xFD, 0x81) => Ok(Self::I16x8Neg),
+            (0xFD, 0x82) => Ok(Self::I16x8Q15mulrSatS),
+            (0xFD, 0x83) => Ok(Self::I16x8AllTrue),
+            (0xFD, 0x84) => Ok(Self::I16x8Bitmask),
+            (0xFD, 0x85) => Ok(Self::I16x8NarrowI32x4S),
+            (0xFD, 0x86) => Ok(Self::I16x8NarrowI32x4U),
+            (0xFD, 0x87) => Ok(Self::I16x8ExtendLowI8x16S),
+            (0xFD, 0x88) => Ok(Self::I16x8ExtendHighI8x16S),
+            (0xFD, 0x89) => Ok(Self::I16x8ExtendLowI8x16U),
+            (0xFD, 0x8A) => Ok(Self::I16x8ExtendHighI8x16U),
+            (0xFD, 0x8B) => Ok(Self::I16x8Shl),
+            (0xFD, 0x8C) => Ok(Self::I16x8ShrS),
+            (0xFD, 0x8D) => Ok(Self::I16x8ShrU),
+            (0xFD, 0x8E) => Ok(Self::I16x8Add),
+            (0xFD, 0x8F) => Ok(Self::I16x8AddSatS),
+            (0xFD, 0x90) => Ok(Self::I16x8AddSatU),
+            (0xFD, 0x91) => Ok(Self::I16x8Sub),
+            (0xFD, 0x92) => Ok(Self::I16x8SubSatS),
+            (0xFD, 0x93) => Ok(Self::I16x8SubSatU),
+            (0xFD, 0x94) => Ok(Self::F64x2Nearest),
+            (0xFD, 0x95) => Ok(Self::I16x8Mul),
+            (0xFD, 0x96) => Ok(Self::I16x8MinS),
+            (0xFD, 0x97) => Ok(Self::I16x8MinU),
+            (0xFD, 0x98) => Ok(Self::I16x8MaxS),
+            (0xFD, 0x99) => Ok(Self::I16x8MaxU),
+            (0xFD, 0x9B) => Ok(Self::I16x8AvgrU),
+            (0xFD, 0x9C) => Ok(Self::I16x8ExtmulLowI8x16S),
+            (0xFD, 0x9D) => Ok(Self::I16x8ExtmulHighI8x16S),
+            (0xFD, 0x9E) => Ok(Self::I16x8ExtmulLowI8x16U),
+            (0xFD, 0x9F) => Ok(Self::I16x8ExtmulHighI8x16U),
+            (0xFD, 0xA0) => Ok(Self::I32x4Abs),
+            (0xFD, 0xA1) => Ok(Self::I32x4Neg),
+            (0xFD, 0xA3) => Ok(Self::I32x4AllTrue),
+            (0xFD, 0xA4) => Ok(Self::I32x4Bitmask),
+            (0xFD, 0xA7) => Ok(Self::I32x4ExtendLowI16x8S),
+            (0xFD, 0xA8) => Ok(Self::I32x4ExtendHighI16x8S),
+            (0xFD, 0xA9) => Ok(Self::I32x4ExtendLowI16x8U),
+            (0xFD, 0xAA) => Ok(Self::I32x4ExtendHighI16x8U),
+            (0xFD, 0xAB) => Ok(Self::I32x4Shl),
+            (0xFD, 0xAC) => Ok(Self::I32x4ShrS),
+            (0xFD, 0xAD) => Ok(Self::I32x4ShrU),
+            (0xFD, 0xAE) => Ok(Self::I32x4Add),
+            (0xFD, 0xB1) => Ok(Self::I32x4Sub),
+            (0xFD, 0xB5) => Ok(Self::I32x4Mul),
+            (0xFD, 0xB6) => Ok(Self::I32x4MinS),
+            (0xFD, 0xB7) => Ok(Self::I32x4MinU),
+            (0xFD, 0xB8) => Ok(Self::I32x4MaxS),
+            (0xFD, 0xB9) => Ok(Self::I32x4MaxU),
+            (0xFD, 0xBA) => Ok(Self::I32x4DotI16x8S),
+            (0xFD, 0xBC) => Ok(Self::I32x4ExtmulLowI16x8S),
+            (0xFD, 0xBD) => Ok(Self::I32x4ExtmulHighI16x8S),
+            (0xFD, 0xBE) => Ok(Self::I32x4ExtmulLowI16x8U),
+            (0xFD, 0xBF) => Ok(Self::I32x4ExtmulHighI16x8U),
+            (0xFD, 0xC0) => Ok(Self::I64x2Abs),
+            (0xFD, 0xC1) => Ok(Self::I64x2Neg),
+            (0xFD, 0xC3) => Ok(Self::I64x2AllTrue),
+            (0xFD, 0xC4) => Ok(Self::I64x2Bitmask),
+            (0xFD, 0xC7) => Ok(Self::I64x2ExtendLowI32x4S),
+            (0xFD, 0xC8) => Ok(Self::I64x2ExtendHighI32x4S),
+            (0xFD, 0xC9) => Ok(Self::I64x2ExtendLowI32x4U),
+            (0xFD, 0xCA) => Ok(Self::I64x2ExtendHighI32x4U),
+            (0xFD, 0xCB) => Ok(Self::I64x2Shl),
+            (0xFD, 0xCC) => Ok(Self::I64x2ShrS),
+            (0xFD, 0xCD) => Ok(Self::I64x2ShrU),
+            (0xFD, 0xCE) => Ok(Self::I64x2Add),
+            (0xFD, 0xD1) => Ok(Self::I64x2Sub),
+            (0xFD, 0xD5) => Ok(Self::I64x2Mul),
+            (0xFD, 0xD6) => Ok(Self::I64x2Eq),
+            (0xFD, 0xD7) => Ok(Self::I64x2Ne),
+            (0xFD, 0xD8) => Ok(Self::I64x2LtS),
+            (0xFD, 0xD9) => Ok(Self::I64x2GtS),
+            (0xFD, 0xDA) => Ok(Self::I64x2LeS),
+            (0xFD, 0xDB) => Ok(Self::I64x2GeS),
+            (0xFD, 0xDC) => Ok(Self::I64x2ExtmulLowI32x4S),
+            (0xFD, 0xDD) => Ok(Self::I64x2ExtmulHighI32x4S),
+            (0xFD, 0xDE) => Ok(Self::I64x2ExtmulLowI32x4U),
+            (0xFD, 0xDF) => Ok(Self::I64x2ExtmulHighI32x4U),
+            (0xFD, 0xE0) => Ok(Self::F32x4Abs),
+            (0xFD, 0xE1) => Ok(Self::F32x4Neg),
+            (0xFD, 0xE3) => Ok(Self::F32x4Sqrt),
+            (0xFD, 0xE4) => Ok(Self::F32x4Add),
+            (0xFD, 0xE5) => Ok(Self::F32x4Sub),
+            (0xFD, 0xE6) => Ok(Self::F32x4Mul),
+            (0xFD, 0xE7) => Ok(Self::F32x4Div),
+            (0xFD, 0xE8) => Ok(Self::F32x4Min),
+            (0xFD, 0xE9) => Ok(Self::F32x4Max),
+            (0xFD, 0xEA) => Ok(Self::F32x4Pmin),
+            (0xFD, 0xEB) => Ok(Self::F32x4Pmax),
+            (0xFD, 0xEC) => Ok(Self::F64x2Abs),
+            (0xFD, 0xED) => Ok(Self::F64x2Neg),
+            (0xFD, 0xEF) => Ok(Self::F64x2Sqrt),
+            (0xFD, 0xF0) => Ok(Self::F64x2Add),
+            (0xFD, 0xF1) => Ok(Self::F64x2Sub),
+            (0xFD, 0xF2) => Ok(Self::F64x2Mul),
+            (0xFD, 0xF3) => Ok(Self::F64x2Div),
+            (0xFD, 0xF4) => Ok(Self::F64x2Min),
+            (0xFD, 0xF5) => Ok(Self::F64x2Max),
+            (0xFD, 0xF6) => Ok(Self::F64x2Pmin),
+            (0xFD, 0xF7) => Ok(Self::F64x2Pmax),
+            (0xFD, 0xF8) => Ok(Self::I32x4TruncSatF32x4S),
+            (0xFD, 0xF9) => Ok(Self::I32x4TruncSatF32x4U),
+            (0xFD, 0xFA) => Ok(Self::F32x4ConvertI32x4S),
+            (0xFD, 0xFB) => Ok(Self::F32x4ConvertI32x4U),
+            (0xFD, 0xFC) => Ok(Self::I32x4TruncSatF64x2SZero),
+            (0xFD, 0xFD) => Ok(Self::I32x4TruncSatF64x2UZero),
+            (0xFD, 0xFE) => Ok(Self::F64x2ConvertLowI32x4S),
+            (0xFD, 0xFF) => Ok(Self::F64x2ConvertLowI32x4U),
+            (0xFE, 0x00) => Ok(Self::MemoryAtomicNotify),
+            (0xFE, 0x01) => Ok(Self::MemoryAtomicWait32),
+            (0xFE, 0x02) => Ok(Self::MemoryAtomicWait64),
+            (0xFE, 0x03) => Ok(Self::AtomicFence),
+            (0xFE, 0x10) => Ok(Self::I32AtomicLoad),
+            (0xFE, 0x11) => Ok(Self::I64AtomicLoad),
+            (0xFE, 0x12) => Ok(Self::I32AtomicLoad8U),
+            (0xFE, 0x13) => Ok(Self::I32AtomicLoad16U),
+            (0xFE, 0x14) => Ok(Self::I64AtomicLoad8U),
+            (0xFE, 0x15) => Ok(Self::I64AtomicLoad16U),
+            (0xFE, 0x16) => Ok(Self::I64AtomicLoad32U),
+            (0xFE, 0x17) => Ok(Self::I32AtomicStore),
+            (0xFE, 0x18) => Ok(Self::I64AtomicStore),
+            (0xFE, 0x19) => Ok(Self::I32AtomicStore8),
+            (0xFE, 0x1A) => Ok(Self::I32AtomicStore16),
+            (0xFE, 0x1B) => Ok(Self::I64AtomicStore8),
+            (0xFE, 0x1C) => Ok(Self::I64AtomicStore16),
+            (0xFE, 0x1D) => Ok(Self::I64AtomicStore32),
+            (0xFE, 0x1E) => Ok(Self::I32AtomicRmwAdd),
+            (0xFE, 0x1F) => Ok(Self::I64AtomicRmwAdd),
+            (0xFE, 0x20) => Ok(Self::I32AtomicRmw8AddU),
+            (0xFE, 0x21) => Ok(Self::I32AtomicRmw16AddU),
+            (0xFE, 0x22) => Ok(Self::I64AtomicRmw8AddU),
+            (0xFE, 0x23) => Ok(Self::I64AtomicRmw16AddU),
+            (0xFE, 0x24) => Ok(Self::I64AtomicRmw32AddU),
+            (0xFE, 0x25) => Ok(Self::I32AtomicRmwSub),
+            (0xFE, 0x26) => Ok(Self::I64AtomicRmwSub),
+            (0xFE, 0x27) => Ok(Self::I32AtomicRmw8SubU),
+            (0xFE, 0x28) => Ok(Self::I32AtomicRmw16SubU),
+            (0xFE, 0x29) => Ok(Self::I64AtomicRmw8SubU),
+            (0xFE, 0x2A) => Ok(Self::I64AtomicRmw16SubU),
+            (0xFE, 0x2B) => Ok(Self::I64AtomicRmw32SubU),
+            (0xFE, 0x2C) => Ok(Self::I32AtomicRmwAnd),
+            (0xFE, 0x2D) => Ok(Self::I64AtomicRmwAnd),
+            (0xFE, 0x2E) => Ok(Self::I32AtomicRmw8AndU),
+            (0xFE, 0x2F) => Ok(Self::I32AtomicRmw16AndU),
+            (0xFE, 0x30) => Ok(Self::I64AtomicRmw8AndU),
+            (0xFE, 0x31) => Ok(Self::I64AtomicRmw16AndU),
+            (0xFE, 0x32) => Ok(Self::I64AtomicRmw32AndU),
+            (0xFE, 0x33) => Ok(Self::I32AtomicRmwOr),
+            (0xFE, 0x34) => Ok(Self::I64AtomicRmwOr),
+            (0xFE, 0x35) => Ok(Self::I32AtomicRmw8OrU),
+            (0xFE, 0x36) => Ok(Self::I32AtomicRmw16OrU),
+            (0xFE, 0x37) => Ok(Self::I64AtomicRmw8OrU),
+            (0xFE, 0x38) => Ok(Self::I64AtomicRmw16OrU),
+            (0xFE, 0x39) => Ok(Self::I64AtomicRmw32OrU),
+            (0xFE, 0x3A) => Ok(Self::I32AtomicRmwXor),
+            (0xFE, 0x3B) => Ok(Self::I64AtomicRmwXor),
+            (0xFE, 0x3C) => Ok(Self::I32AtomicRmw8XorU),
+            (0xFE, 0x3D) => Ok(Self::I32AtomicRmw16XorU),
+            (0xFE, 0x3E) => Ok(Self::I64AtomicRmw8XorU),
+            (0xFE, 0x3F) => Ok(Self::I64AtomicRmw16XorU),
+            (0xFE, 0x40) => Ok(Self::I64AtomicRmw32XorU),
+            (0xFE, 0x41) => Ok(Self::I32AtomicRmwXchg),
+            (0xFE, 0x42) => Ok(Self::I64AtomicRmwXchg),
+            (0xFE, 0x43) => Ok(Self::I32AtomicRmw8XchgU),
+            (0xFE, 0x44) => Ok(Self::I32AtomicRmw16XchgU),
+            (0xFE, 0x45) => Ok(Self::I64AtomicRmw8XchgU),
+            (0xFE, 0x46) => Ok(Self::I64AtomicRmw16XchgU),
+            (0xFE, 0x47) => Ok(Self::I64AtomicRmw32XchgU),
+            (0xFE, 0x48) => Ok(Self::I32AtomicRmwCmpxchg),
+            (0xFE, 0x49) => Ok(Self::I64AtomicRmwCmpxchg),
+            (0xFE, 0x4A) => Ok(Self::I32AtomicRmw8CmpxchgU),
+            (0xFE, 0x4B) => Ok(Self::I32AtomicRmw16CmpxchgU),
+            (0xFE, 0x4C) => Ok(Self::I64AtomicRmw8CmpxchgU),
+            (0xFE, 0x4D) => Ok(Self::I64AtomicRmw16CmpxchgU),
+            (0xFE, 0x4E) => Ok(Self::I64AtomicRmw32CmpxchgU),
+            _ => Err(()),
+        }
+    }
+
     pub fn to_str(&self) -> &str {
         match *self {
             Self::Unreachable => "unreachable",
@@ -409,6 +1427,8 @@ impl WasmOpcode {
             Self::Loop => "loop",
             Self::If => "if",
             Self::Else => "else",
+            Self::Throw => "throw",
+            Self::ThrowRef => "throw_ref",
             Self::End => "end",
             Self::Br => "br",
             Self::BrIf => "br_if",
@@ -420,11 +1440,15 @@ impl WasmOpcode {
             Self::ReturnCallIndirect => "return_call_indirect",
             Self::Drop => "drop",
             Self::Select => "select",
+            Self::SelectT => "select",
+            Self::TryTable => "try_table",
             Self::LocalGet => "local.get",
             Self::LocalSet => "local.set",
             Self::LocalTee => "local.tee",
             Self::GlobalGet => "global.get",
             Self::GlobalSet => "global.set",
+            Self::TableGet => "table.get",
+            Self::TableSet => "table.set",
             Self::I32Load => "i32.load",
             Self::I64Load => "i64.load",
             Self::F32Load => "f32.load",
@@ -582,6 +1606,330 @@ impl WasmOpcode {
             Self::I64Extend8S => "i64.extend8_s",
             Self::I64Extend16S => "i64.extend16_s",
             Self::I64Extend32S => "i64.extend32_s",
+            Self::RefNull => "ref.null",
+            Self::RefIsNull => "ref.is_null",
+            Self::RefFunc => "ref.func",
+            Self::I32TruncSatF32S => "i32.trunc_sat_f32_s",
+            Self::I32TruncSatF32U => "i32.trunc_sat_f32_u",
+            Self::I32TruncSatF64S => "i32.trunc_sat_f64_s",
+            Self::I32TruncSatF64U => "i32.trunc_sat_f64_u",
+            Self::I64TruncSatF32S => "i64.trunc_sat_f32_s",
+            Self::I64TruncSatF32U => "i64.trunc_sat_f32_u",
+            Self::I64TruncSatF64S => "i64.trunc_sat_f64_s",
+            Self::I64TruncSatF64U => "i64.trunc_sat_f64_u",
+            Self::MemoryInit => "memory.init",
+            Self::DataDrop => "data.drop",
+            Self::MemoryCopy => "memory.copy",
+            Self::MemoryFill => "memory.fill",
+            Self::TableInit => "table.init",
+            Self::ElemDrop => "elem.drop",
+            Self::TableCopy => "table.copy",
+            Self::TableGrow => "table.grow",
+            Self::TableSize => "table.size",
+            Self::TableFill => "table.fill",
+            Self::V128Load => "v128.load",
+            Self::V128Load8x8S => "v128.load8x8_s",
+            Self::V128Load8x8U => "v128.load8x8_u",
+            Self::V128Load16x4S => "v128.load16x4_s",
+            Self::V128Load16x4U => "v128.load16x4_u",
+            Self::V128Load32x2S => "v128.load32x2_s",
+            Self::V128Load32x2U => "v128.load32x2_u",
+            Self::V128Load8Splat => "v128.load8_splat",
+            Self::V128Load16Splat => "v128.load16_splat",
+            Self::V128Load32Splat => "v128.load32_splat",
+            Self::V128Load64Splat => "v128.load64_splat",
+            Self::V128Store => "v128.store",
+            Self::V128Const => "v128.const",
+            Self::I8x16Shuffle => "i8x16.shuffle",
+            Self::I8x16Swizzle => "i8x16.swizzle",
+            Self::I8x16Splat => "i8x16.splat",
+            Self::I16x8Splat => "i16x8.splat",
+            Self::I32x4Splat => "i32x4.splat",
+            Self::I64x2Splat => "i64x2.splat",
+            Self::F32x4Splat => "f32x4.splat",
+            Self::F64x2Splat => "f64x2.splat",
+            Self::I8x16ExtractLaneS => "i8x16.extract_lane_s",
+            Self::I8x16ExtractLaneU => "i8x16.extract_lane_u",
+            Self::I8x16ReplaceLane => "i8x16.replace_lane",
+            Self::I16x8ExtractLaneS => "i16x8.extract_lane_s",
+            Self::I16x8ExtractLaneU => "i16x8.extract_lane_u",
+            Self::I16x8ReplaceLane => "i16x8.replace_lane",
+            Self::I32x4ExtractLane => "i32x4.extract_lane",
+            Self::I32x4ReplaceLane => "i32x4.replace_lane",
+            Self::I64x2ExtractLane => "i64x2.extract_lane",
+            Self::I64x2ReplaceLane => "i64x2.replace_lane",
+            Self::F32x4ExtractLane => "f32x4.extract_lane",
+            Self::F32x4ReplaceLane => "f32x4.replace_lane",
+            Self::F64x2ExtractLane => "f64x2.extract_lane",
+            Self::F64x2ReplaceLane => "f64x2.replace_lane",
+            Self::I8x16Eq => "i8x16.eq",
+            Self::I8x16Ne => "i8x16.ne",
+            Self::I8x16LtS => "i8x16.lt_s",
+            Self::I8x16LtU => "i8x16.lt_u",
+            Self::I8x16GtS => "i8x16.gt_s",
+            Self::I8x16GtU => "i8x16.gt_u",
+            Self::I8x16LeS => "i8x16.le_s",
+            Self::I8x16LeU => "i8x16.le_u",
+            Self::I8x16GeS => "i8x16.ge_s",
+            Self::I8x16GeU => "i8x16.ge_u",
+            Self::I16x8Eq => "i16x8.eq",
+            Self::I16x8Ne => "i16x8.ne",
+            Self::I16x8LtS => "i16x8.lt_s",
+            Self::I16x8LtU => "i16x8.lt_u",
+            Self::I16x8GtS => "i16x8.gt_s",
+            Self::I16x8GtU => "i16x8.gt_u",
+            Self::I16x8LeS => "i16x8.le_s",
+            Self::I16x8LeU => "i16x8.le_u",
+            Self::I16x8GeS => "i16x8.ge_s",
+            Self::I16x8GeU => "i16x8.ge_u",
+            Self::I32x4Eq => "i32x4.eq",
+            Self::I32x4Ne => "i32x4.ne",
+            Self::I32x4LtS => "i32x4.lt_s",
+            Self::I32x4LtU => "i32x4.lt_u",
+            Self::I32x4GtS => "i32x4.gt_s",
+            Self::I32x4GtU => "i32x4.gt_u",
+            Self::I32x4LeS => "i32x4.le_s",
+            Self::I32x4LeU => "i32x4.le_u",
+            Self::I32x4GeS => "i32x4.ge_s",
+            Self::I32x4GeU => "i32x4.ge_u",
+            Self::F32x4Eq => "f32x4.eq",
+            Self::F32x4Ne => "f32x4.ne",
+            Self::F32x4Lt => "f32x4.lt",
+            Self::F32x4Gt => "f32x4.gt",
+            Self::F32x4Le => "f32x4.le",
+            Self::F32x4Ge => "f32x4.ge",
+            Self::F64x2Eq => "f64x2.eq",
+            Self::F64x2Ne => "f64x2.ne",
+            Self::F64x2Lt => "f64x2.lt",
+            Self::F64x2Gt => "f64x2.gt",
+            Self::F64x2Le => "f64x2.le",
+            Self::F64x2Ge => "f64x2.ge",
+            Self::V128Not => "v128.not",
+            Self::V128And => "v128.and",
+            Self::V128Andnot => "v128.andnot",
+            Self::V128Or => "v128.or",
+            Self::V128Xor => "v128.xor",
+            Self::V128Bitselect => "v128.bitselect",
+            Self::V128AnyTrue => "v128.any_true",
+            Self::V128Load8Lane => "v128.load8_lane",
+            Self::V128Load16Lane => "v128.load16_lane",
+            Self::V128Load32Lane => "v128.load32_lane",
+            Self::V128Load64Lane => "v128.load64_lane",
+            Self::V128Store8Lane => "v128.store8_lane",
+            Self::V128Store16Lane => "v128.store16_lane",
+            Self::V128Store32Lane => "v128.store32_lane",
+            Self::V128Store64Lane => "v128.store64_lane",
+            Self::V128Load32Zero => "v128.load32_zero",
+            Self::V128Load64Zero => "v128.load64_zero",
+            Self::F32x4DemoteF64x2Zero => "f32x4.demote_f64x2_zero",
+            Self::F64x2PromoteLowF32x4 => "f64x2.promote_low_f32x4",
+            Self::I8x16Abs => "i8x16.abs",
+            Self::I8x16Neg => "i8x16.neg",
+            Self::I8x16Popcnt => "i8x16.popcnt",
+            Self::I8x16AllTrue => "i8x16.all_true",
+            Self::I8x16Bitmask => "i8x16.bitmask",
+            Self::I8x16NarrowI16x8S => "i8x16.narrow_i16x8_s",
+            Self::I8x16NarrowI16x8U => "i8x16.narrow_i16x8_u",
+            Self::F32x4Ceil => "f32x4.ceil",
+            Self::F32x4Floor => "f32x4.floor",
+            Self::F32x4Trunc => "f32x4.trunc",
+            Self::F32x4Nearest => "f32x4.nearest",
+            Self::I8x16Shl => "i8x16.shl",
+            Self::I8x16ShrS => "i8x16.shr_s",
+            Self::I8x16ShrU => "i8x16.shr_u",
+            Self::I8x16Add => "i8x16.add",
+            Self::I8x16AddSatS => "i8x16.add_sat_s",
+            Self::I8x16AddSatU => "i8x16.add_sat_u",
+            Self::I8x16Sub => "i8x16.sub",
+            Self::I8x16SubSatS => "i8x16.sub_sat_s",
+            Self::I8x16SubSatU => "i8x16.sub_sat_u",
+            Self::F64x2Ceil => "f64x2.ceil",
+            Self::F64x2Floor => "f64x2.floor",
+            Self::I8x16MinS => "i8x16.min_s",
+            Self::I8x16MinU => "i8x16.min_u",
+            Self::I8x16MaxS => "i8x16.max_s",
+            Self::I8x16MaxU => "i8x16.max_u",
+            Self::F64x2Trunc => "f64x2.trunc",
+            Self::I8x16AvgrU => "i8x16.avgr_u",
+            Self::I16x8ExtaddPairwiseI8x16S => "i16x8.extadd_pairwise_i8x16_s",
+            Self::I16x8ExtaddPairwiseI8x16U => "i16x8.extadd_pairwise_i8x16_u",
+            Self::I32x4ExtaddPairwiseI16x8S => "i32x4.extadd_pairwise_i16x8_s",
+            Self::I32x4ExtaddPairwiseI16x8U => "i32x4.extadd_pairwise_i16x8_u",
+            Self::I16x8Abs => "i16x8.abs",
+            Self::I16x8Neg => "i16x8.neg",
+            Self::I16x8Q15mulrSatS => "i16x8.q15mulr_sat_s",
+            Self::I16x8AllTrue => "i16x8.all_true",
+            Self::I16x8Bitmask => "i16x8.bitmask",
+            Self::I16x8NarrowI32x4S => "i16x8.narrow_i32x4_s",
+            Self::I16x8NarrowI32x4U => "i16x8.narrow_i32x4_u",
+            Self::I16x8ExtendLowI8x16S => "i16x8.extend_low_i8x16_s",
+            Self::I16x8ExtendHighI8x16S => "i16x8.extend_high_i8x16_s",
+            Self::I16x8ExtendLowI8x16U => "i16x8.extend_low_i8x16_u",
+            Self::I16x8ExtendHighI8x16U => "i16x8.extend_high_i8x16_u",
+            Self::I16x8Shl => "i16x8.shl",
+            Self::I16x8ShrS => "i16x8.shr_s",
+            Self::I16x8ShrU => "i16x8.shr_u",
+            Self::I16x8Add => "i16x8.add",
+            Self::I16x8AddSatS => "i16x8.add_sat_s",
+            Self::I16x8AddSatU => "i16x8.add_sat_u",
+            Self::I16x8Sub => "i16x8.sub",
+            Self::I16x8SubSatS => "i16x8.sub_sat_s",
+            Self::I16x8SubSatU => "i16x8.sub_sat_u",
+            Self::F64x2Nearest => "f64x2.nearest",
+            Self::I16x8Mul => "i16x8.mul",
+            Self::I16x8MinS => "i16x8.min_s",
+            Self::I16x8MinU => "i16x8.min_u",
+            Self::I16x8MaxS => "i16x8.max_s",
+            Self::I16x8MaxU => "i16x8.max_u",
+            Self::I16x8AvgrU => "i16x8.avgr_u",
+            Self::I16x8ExtmulLowI8x16S => "i16x8.extmul_low_i8x16_s",
+            Self::I16x8ExtmulHighI8x16S => "i16x8.extmul_high_i8x16_s",
+            Self::I16x8ExtmulLowI8x16U => "i16x8.extmul_low_i8x16_u",
+            Self::I16x8ExtmulHighI8x16U => "i16x8.extmul_high_i8x16_u",
+            Self::I32x4Abs => "i32x4.abs",
+            Self::I32x4Neg => "i32x4.neg",
+            Self::I32x4AllTrue => "i32x4.all_true",
+            Self::I32x4Bitmask => "i32x4.bitmask",
+            Self::I32x4ExtendLowI16x8S => "i32x4.extend_low_i16x8_s",
+            Self::I32x4ExtendHighI16x8S => "i32x4.extend_high_i16x8_s",
+            Self::I32x4ExtendLowI16x8U => "i32x4.extend_low_i16x8_u",
+            Self::I32x4ExtendHighI16x8U => "i32x4.extend_high_i16x8_u",
+            Self::I32x4Shl => "i32x4.shl",
+            Self::I32x4ShrS => "i32x4.shr_s",
+            Self::I32x4ShrU => "i32x4.shr_u",
+            Self::I32x4Add => "i32x4.add",
+            Self::I32x4Sub => "i32x4.sub",
+            Self::I32x4Mul => "i32x4.mul",
+            Self::I32x4MinS => "i32x4.min_s",
+            Self::I32x4MinU => "i32x4.min_u",
+            Self::I32x4MaxS => "i32x4.max_s",
+            Self::I32x4MaxU => "i32x4.max_u",
+            Self::I32x4DotI16x8S => "i32x4.dot_i16x8_s",
+            Self::I32x4ExtmulLowI16x8S => "i32x4.extmul_low_i16x8_s",
+            Self::I32x4ExtmulHighI16x8S => "i32x4.extmul_high_i16x8_s",
+            Self::I32x4ExtmulLowI16x8U => "i32x4.extmul_low_i16x8_u",
+            Self::I32x4ExtmulHighI16x8U => "i32x4.extmul_high_i16x8_u",
+            Self::I64x2Abs => "i64x2.abs",
+            Self::I64x2Neg => "i64x2.neg",
+            Self::I64x2AllTrue => "i64x2.all_true",
+            Self::I64x2Bitmask => "i64x2.bitmask",
+            Self::I64x2ExtendLowI32x4S => "i64x2.extend_low_i32x4_s",
+            Self::I64x2ExtendHighI32x4S => "i64x2.extend_high_i32x4_s",
+            Self::I64x2ExtendLowI32x4U => "i64x2.extend_low_i32x4_u",
+            Self::I64x2ExtendHighI32x4U => "i64x2.extend_high_i32x4_u",
+            Self::I64x2Shl => "i64x2.shl",
+            Self::I64x2ShrS => "i64x2.shr_s",
+            Self::I64x2ShrU => "i64x2.shr_u",
+            Self::I64x2Add => "i64x2.add",
+            Self::I64x2Sub => "i64x2.sub",
+            Self::I64x2Mul => "i64x2.mul",
+            Self::I64x2Eq => "i64x2.eq",
+            Self::I64x2Ne => "i64x2.ne",
+            Self::I64x2LtS => "i64x2.lt_s",
+            Self::I64x2GtS => "i64x2.gt_s",
+            Self::I64x2LeS => "i64x2.le_s",
+            Self::I64x2GeS => "i64x2.ge_s",
+            Self::I64x2ExtmulLowI32x4S => "i64x2.extmul_low_i32x4_s",
+            Self::I64x2ExtmulHighI32x4S => "i64x2.extmul_high_i32x4_s",
+            Self::I64x2ExtmulLowI32x4U => "i64x2.extmul_low_i32x4_u",
+            Self::I64x2ExtmulHighI32x4U => "i64x2.extmul_high_i32x4_u",
+            Self::F32x4Abs => "f32x4.abs",
+            Self::F32x4Neg => "f32x4.neg",
+            Self::F32x4Sqrt => "f32x4.sqrt",
+            Self::F32x4Add => "f32x4.add",
+            Self::F32x4Sub => "f32x4.sub",
+            Self::F32x4Mul => "f32x4.mul",
+            Self::F32x4Div => "f32x4.div",
+            Self::F32x4Min => "f32x4.min",
+            Self::F32x4Max => "f32x4.max",
+            Self::F32x4Pmin => "f32x4.pmin",
+            Self::F32x4Pmax => "f32x4.pmax",
+            Self::F64x2Abs => "f64x2.abs",
+            Self::F64x2Neg => "f64x2.neg",
+            Self::F64x2Sqrt => "f64x2.sqrt",
+            Self::F64x2Add => "f64x2.add",
+            Self::F64x2Sub => "f64x2.sub",
+            Self::F64x2Mul => "f64x2.mul",
+            Self::F64x2Div => "f64x2.div",
+            Self::F64x2Min => "f64x2.min",
+            Self::F64x2Max => "f64x2.max",
+            Self::F64x2Pmin => "f64x2.pmin",
+            Self::F64x2Pmax => "f64x2.pmax",
+            Self::I32x4TruncSatF32x4S => "i32x4.trunc_sat_f32x4_s",
+            Self::I32x4TruncSatF32x4U => "i32x4.trunc_sat_f32x4_u",
+            Self::F32x4ConvertI32x4S => "f32x4.convert_i32x4_s",
+            Self::F32x4ConvertI32x4U => "f32x4.convert_i32x4_u",
+            Self::I32x4TruncSatF64x2SZero => "i32x4.trunc_sat_f64x2_s_zero",
+            Self::I32x4TruncSatF64x2UZero => "i32x4.trunc_sat_f64x2_u_zero",
+            Self::F64x2ConvertLowI32x4S => "f64x2.convert_low_i32x4_s",
+            Self::F64x2ConvertLowI32x4U => "f64x2.convert_low_i32x4_u",
+            Self::MemoryAtomicNotify => "memory.atomic.notify",
+            Self::MemoryAtomicWait32 => "memory.atomic.wait32",
+            Self::MemoryAtomicWait64 => "memory.atomic.wait64",
+            Self::AtomicFence => "atomic.fence",
+            Self::I32AtomicLoad => "i32.atomic.load",
+            Self::I64AtomicLoad => "i64.atomic.load",
+            Self::I32AtomicLoad8U => "i32.atomic.load8_u",
+            Self::I32AtomicLoad16U => "i32.atomic.load16_u",
+            Self::I64AtomicLoad8U => "i64.atomic.load8_u",
+            Self::I64AtomicLoad16U => "i64.atomic.load16_u",
+            Self::I64AtomicLoad32U => "i64.atomic.load32_u",
+            Self::I32AtomicStore => "i32.atomic.store",
+            Self::I64AtomicStore => "i64.atomic.store",
+            Self::I32AtomicStore8 => "i32.atomic.store8",
+            Self::I32AtomicStore16 => "i32.atomic.store16",
+            Self::I64AtomicStore8 => "i64.atomic.store8",
+            Self::I64AtomicStore16 => "i64.atomic.store16",
+            Self::I64AtomicStore32 => "i64.atomic.store32",
+            Self::I32AtomicRmwAdd => "i32.atomic.rmw.add",
+            Self::I64AtomicRmwAdd => "i64.atomic.rmw.add",
+            Self::I32AtomicRmw8AddU => "i32.atomic.rmw8.add_u",
+            Self::I32AtomicRmw16AddU => "i32.atomic.rmw16.add_u",
+            Self::I64AtomicRmw8AddU => "i64.atomic.rmw8.add_u",
+            Self::I64AtomicRmw16AddU => "i64.atomic.rmw16.add_u",
+            Self::I64AtomicRmw32AddU => "i64.atomic.rmw32.add_u",
+            Self::I32AtomicRmwSub => "i32.atomic.rmw.sub",
+            Self::I64AtomicRmwSub => "i64.atomic.rmw.sub",
+            Self::I32AtomicRmw8SubU => "i32.atomic.rmw8.sub_u",
+            Self::I32AtomicRmw16SubU => "i32.atomic.rmw16.sub_u",
+            Self::I64AtomicRmw8SubU => "i64.atomic.rmw8.sub_u",
+            Self::I64AtomicRmw16SubU => "i64.atomic.rmw16.sub_u",
+            Self::I64AtomicRmw32SubU => "i64.atomic.rmw32.sub_u",
+            Self::I32AtomicRmwAnd => "i32.atomic.rmw.and",
+            Self::I64AtomicRmwAnd => "i64.atomic.rmw.and",
+            Self::I32AtomicRmw8AndU => "i32.atomic.rmw8.and_u",
+            Self::I32AtomicRmw16AndU => "i32.atomic.rmw16.and_u",
+            Self::I64AtomicRmw8AndU => "i64.atomic.rmw8.and_u",
+            Self::I64AtomicRmw16AndU => "i64.atomic.rmw16.and_u",
+            Self::I64AtomicRmw32AndU => "i64.atomic.rmw32.and_u",
+            Self::I32AtomicRmwOr => "i32.atomic.rmw.or",
+            Self::I64AtomicRmwOr => "i64.atomic.rmw.or",
+            Self::I32AtomicRmw8OrU => "i32.atomic.rmw8.or_u",
+            Self::I32AtomicRmw16OrU => "i32.atomic.rmw16.or_u",
+            Self::I64AtomicRmw8OrU => "i64.atomic.rmw8.or_u",
+            Self::I64AtomicRmw16OrU => "i64.atomic.rmw16.or_u",
+            Self::I64AtomicRmw32OrU => "i64.atomic.rmw32.or_u",
+            Self::I32AtomicRmwXor => "i32.atomic.rmw.xor",
+            Self::I64AtomicRmwXor => "i64.atomic.rmw.xor",
+            Self::I32AtomicRmw8XorU => "i32.atomic.rmw8.xor_u",
+            Self::I32AtomicRmw16XorU => "i32.atomic.rmw16.xor_u",
+            Self::I64AtomicRmw8XorU => "i64.atomic.rmw8.xor_u",
+            Self::I64AtomicRmw16XorU => "i64.atomic.rmw16.xor_u",
+            Self::I64AtomicRmw32XorU => "i64.atomic.rmw32.xor_u",
+            Self::I32AtomicRmwXchg => "i32.atomic.rmw.xchg",
+            Self::I64AtomicRmwXchg => "i64.atomic.rmw.xchg",
+            Self::I32AtomicRmw8XchgU => "i32.atomic.rmw8.xchg_u",
+            Self::I32AtomicRmw16XchgU => "i32.atomic.rmw16.xchg_u",
+            Self::I64AtomicRmw8XchgU => "i64.atomic.rmw8.xchg_u",
+            Self::I64AtomicRmw16XchgU => "i64.atomic.rmw16.xchg_u",
+            Self::I64AtomicRmw32XchgU => "i64.atomic.rmw32.xchg_u",
+            Self::I32AtomicRmwCmpxchg => "i32.atomic.rmw.cmpxchg",
+            Self::I64AtomicRmwCmpxchg => "i64.atomic.rmw.cmpxchg",
+            Self::I32AtomicRmw8CmpxchgU => "i32.atomic.rmw8.cmpxchg_u",
+            Self::I32AtomicRmw16CmpxchgU => "i32.atomic.rmw16.cmpxchg_u",
+            Self::I64AtomicRmw8CmpxchgU => "i64.atomic.rmw8.cmpxchg_u",
+            Self::I64AtomicRmw16CmpxchgU => "i64.atomic.rmw16.cmpxchg_u",
+            Self::I64AtomicRmw32CmpxchgU => "i64.atomic.rmw32.cmpxchg_u",
         }
     }
 
@@ -604,6 +1952,16 @@ impl WasmOpcode {
             Self::LocalTee => WasmOperandType::Local,
             Self::GlobalGet => WasmOperandType::Global,
             Self::GlobalSet => WasmOperandType::Global,
+            Self::SelectT => WasmOperandType::SelectT,
+            Self::Throw => WasmOperandType::Tag,
+            Self::TryTable => WasmOperandType::TryTable,
+            Self::TableGet => WasmOperandType::Table,
+            Self::TableSet => WasmOperandType::Table,
+            Self::RefNull => WasmOperandType::RefType,
+            Self::RefFunc => WasmOperandType::RefFunc,
+            Self::TableGrow => WasmOperandType::Table,
+            Self::TableSize => WasmOperandType::Table,
+            Self::TableFill => WasmOperandType::Table,
             Self::I32Load => WasmOperandType::Memory,
             Self::I64Load => WasmOperandType::Memory,
             Self::F32Load => WasmOperandType::Memory,
@@ -633,6 +1991,118 @@ impl WasmOpcode {
             Self::I64Const => WasmOperandType::I64,
             Self::F32Const => WasmOperandType::F32,
             Self::F64Const => WasmOperandType::F64,
+            Self::MemoryInit => WasmOperandType::MemoryInit,
+            Self::DataDrop => WasmOperandType::DataDrop,
+            Self::MemoryCopy => WasmOperandType::MemoryCopy,
+            Self::MemoryFill => WasmOperandType::MemoryFill,
+            Self::TableInit => WasmOperandType::TableInit,
+            Self::ElemDrop => WasmOperandType::ElemDrop,
+            Self::TableCopy => WasmOperandType::TableCopy,
+            Self::V128Load => WasmOperandType::Memory,
+            Self::V128Load8x8S => WasmOperandType::Memory,
+            Self::V128Load8x8U => WasmOperandType::Memory,
+            Self::V128Load16x4S => WasmOperandType::Memory,
+            Self::V128Load16x4U => WasmOperandType::Memory,
+            Self::V128Load32x2S => WasmOperandType::Memory,
+            Self::V128Load32x2U => WasmOperandType::Memory,
+            Self::V128Load8Splat => WasmOperandType::Memory,
+            Self::V128Load16Splat => WasmOperandType::Memory,
+            Self::V128Load32Splat => WasmOperandType::Memory,
+            Self::V128Load64Splat => WasmOperandType::Memory,
+            Self::V128Store => WasmOperandType::Memory,
+            Self::V128Const => WasmOperandType::V128,
+            Self::I8x16Shuffle => WasmOperandType::Shuffle,
+            Self::I8x16ExtractLaneS => WasmOperandType::Lane,
+            Self::I8x16ExtractLaneU => WasmOperandType::Lane,
+            Self::I8x16ReplaceLane => WasmOperandType::Lane,
+            Self::I16x8ExtractLaneS => WasmOperandType::Lane,
+            Self::I16x8ExtractLaneU => WasmOperandType::Lane,
+            Self::I16x8ReplaceLane => WasmOperandType::Lane,
+            Self::I32x4ExtractLane => WasmOperandType::Lane,
+            Self::I32x4ReplaceLane => WasmOperandType::Lane,
+            Self::I64x2ExtractLane => WasmOperandType::Lane,
+            Self::I64x2ReplaceLane => WasmOperandType::Lane,
+            Self::F32x4ExtractLane => WasmOperandType::Lane,
+            Self::F32x4ReplaceLane => WasmOperandType::Lane,
+            Self::F64x2ExtractLane => WasmOperandType::Lane,
+            Self::F64x2ReplaceLane => WasmOperandType::Lane,
+            Self::V128Load8Lane => WasmOperandType::MemoryLane,
+            Self::V128Load16Lane => WasmOperandType::MemoryLane,
+            Self::V128Load32Lane => WasmOperandType::MemoryLane,
+            Self::V128Load64Lane => WasmOperandType::MemoryLane,
+            Self::V128Store8Lane => WasmOperandType::MemoryLane,
+            Self::V128Store16Lane => WasmOperandType::MemoryLane,
+            Self::V128Store32Lane => WasmOperandType::MemoryLane,
+            Self::V128Store64Lane => WasmOperandType::MemoryLane,
+            Self::V128Load32Zero => WasmOperandType::Memory,
+            Self::V128Load64Zero => WasmOperandType::Memory,
+            Self::MemoryAtomicNotify => WasmOperandType::Memory,
+            Self::MemoryAtomicWait32 => WasmOperandType::Memory,
+            Self::MemoryAtomicWait64 => WasmOperandType::Memory,
+            Self::AtomicFence => WasmOperandType::Fence,
+            Self::I32AtomicLoad => WasmOperandType::Memory,
+            Self::I64AtomicLoad => WasmOperandType::Memory,
+            Self::I32AtomicLoad8U => WasmOperandType::Memory,
+            Self::I32AtomicLoad16U => WasmOperandType::Memory,
+            Self::I64AtomicLoad8U => WasmOperandType::Memory,
+            Self::I64AtomicLoad16U => WasmOperandType::Memory,
+            Self::I64AtomicLoad32U => WasmOperandType::Memory,
+            Self::I32AtomicStore => WasmOperandType::Memory,
+            Self::I64AtomicStore => WasmOperandType::Memory,
+            Self::I32AtomicStore8 => WasmOperandType::Memory,
+            Self::I32AtomicStore16 => WasmOperandType::Memory,
+            Self::I64AtomicStore8 => WasmOperandType::Memory,
+            Self::I64AtomicStore16 => WasmOperandType::Memory,
+            Self::I64AtomicStore32 => WasmOperandType::Memory,
+            Self::I32AtomicRmwAdd => WasmOperandType::Memory,
+            Self::I64AtomicRmwAdd => WasmOperandType::Memory,
+            Self::I32AtomicRmw8AddU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16AddU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8AddU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16AddU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32AddU => WasmOperandType::Memory,
+            Self::I32AtomicRmwSub => WasmOperandType::Memory,
+            Self::I64AtomicRmwSub => WasmOperandType::Memory,
+            Self::I32AtomicRmw8SubU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16SubU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8SubU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16SubU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32SubU => WasmOperandType::Memory,
+            Self::I32AtomicRmwAnd => WasmOperandType::Memory,
+            Self::I64AtomicRmwAnd => WasmOperandType::Memory,
+            Self::I32AtomicRmw8AndU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16AndU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8AndU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16AndU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32AndU => WasmOperandType::Memory,
+            Self::I32AtomicRmwOr => WasmOperandType::Memory,
+            Self::I64AtomicRmwOr => WasmOperandType::Memory,
+            Self::I32AtomicRmw8OrU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16OrU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8OrU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16OrU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32OrU => WasmOperandType::Memory,
+            Self::I32AtomicRmwXor => WasmOperandType::Memory,
+            Self::I64AtomicRmwXor => WasmOperandType::Memory,
+            Self::I32AtomicRmw8XorU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16XorU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8XorU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16XorU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32XorU => WasmOperandType::Memory,
+            Self::I32AtomicRmwXchg => WasmOperandType::Memory,
+            Self::I64AtomicRmwXchg => WasmOperandType::Memory,
+            Self::I32AtomicRmw8XchgU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16XchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8XchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16XchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32XchgU => WasmOperandType::Memory,
+            Self::I32AtomicRmwCmpxchg => WasmOperandType::Memory,
+            Self::I64AtomicRmwCmpxchg => WasmOperandType::Memory,
+            Self::I32AtomicRmw8CmpxchgU => WasmOperandType::Memory,
+            Self::I32AtomicRmw16CmpxchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw8CmpxchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw16CmpxchgU => WasmOperandType::Memory,
+            Self::I64AtomicRmw32CmpxchgU => WasmOperandType::Memory,
             _ => WasmOperandType::Implied,
         }
     }
@@ -758,6 +2228,336 @@ impl WasmOpcode {
             Self::I64Extend8S => WasmProposalType::SignExtend,
             Self::I64Extend16S => WasmProposalType::SignExtend,
             Self::I64Extend32S => WasmProposalType::SignExtend,
+            Self::I32TruncSatF32S => WasmProposalType::NonTrappingFloatToInt,
+            Self::I32TruncSatF32U => WasmProposalType::NonTrappingFloatToInt,
+            Self::I32TruncSatF64S => WasmProposalType::NonTrappingFloatToInt,
+            Self::I32TruncSatF64U => WasmProposalType::NonTrappingFloatToInt,
+            Self::I64TruncSatF32S => WasmProposalType::NonTrappingFloatToInt,
+            Self::I64TruncSatF32U => WasmProposalType::NonTrappingFloatToInt,
+            Self::I64TruncSatF64S => WasmProposalType::NonTrappingFloatToInt,
+            Self::I64TruncSatF64U => WasmProposalType::NonTrappingFloatToInt,
+            Self::MemoryInit => WasmProposalType::BulkMemory,
+            Self::DataDrop => WasmProposalType::BulkMemory,
+            Self::MemoryCopy => WasmProposalType::BulkMemory,
+            Self::MemoryFill => WasmProposalType::BulkMemory,
+            Self::TableInit => WasmProposalType::BulkMemory,
+            Self::ElemDrop => WasmProposalType::BulkMemory,
+            Self::TableCopy => WasmProposalType::BulkMemory,
+            Self::SelectT => WasmProposalType::ReferenceTypes,
+            Self::Throw => WasmProposalType::Exceptions,
+            Self::ThrowRef => WasmProposalType::Exceptions,
+            Self::TryTable => WasmProposalType::Exceptions,
+            Self::TableGet => WasmProposalType::ReferenceTypes,
+            Self::TableSet => WasmProposalType::ReferenceTypes,
+            Self::RefNull => WasmProposalType::ReferenceTypes,
+            Self::RefIsNull => WasmProposalType::ReferenceTypes,
+            Self::RefFunc => WasmProposalType::ReferenceTypes,
+            Self::TableGrow => WasmProposalType::ReferenceTypes,
+            Self::TableSize => WasmProposalType::ReferenceTypes,
+            Self::TableFill => WasmProposalType::ReferenceTypes,
+            Self::V128Load => WasmProposalType::Simd,
+            Self::V128Load8x8S => WasmProposalType::Simd,
+            Self::V128Load8x8U => WasmProposalType::Simd,
+            Self::V128Load16x4S => WasmProposalType::Simd,
+            Self::V128Load16x4U => WasmProposalType::Simd,
+            Self::V128Load32x2S => WasmProposalType::Simd,
+            Self::V128Load32x2U => WasmProposalType::Simd,
+            Self::V128Load8Splat => WasmProposalType::Simd,
+            Self::V128Load16Splat => WasmProposalType::Simd,
+            Self::V128Load32Splat => WasmProposalType::Simd,
+            Self::V128Load64Splat => WasmProposalType::Simd,
+            Self::V128Store => WasmProposalType::Simd,
+            Self::V128Const => WasmProposalType::Simd,
+            Self::I8x16Shuffle => WasmProposalType::Simd,
+            Self::I8x16Swizzle => WasmProposalType::Simd,
+            Self::I8x16Splat => WasmProposalType::Simd,
+            Self::I16x8Splat => WasmProposalType::Simd,
+            Self::I32x4Splat => WasmProposalType::Simd,
+            Self::I64x2Splat => WasmProposalType::Simd,
+            Self::F32x4Splat => WasmProposalType::Simd,
+            Self::F64x2Splat => WasmProposalType::Simd,
+            Self::I8x16ExtractLaneS => WasmProposalType::Simd,
+            Self::I8x16ExtractLaneU => WasmProposalType::Simd,
+            Self::I8x16ReplaceLane => WasmProposalType::Simd,
+            Self::I16x8ExtractLaneS => WasmProposalType::Simd,
+            Self::I16x8ExtractLaneU => WasmProposalType::Simd,
+            Self::I16x8ReplaceLane => WasmProposalType::Simd,
+            Self::I32x4ExtractLane => WasmProposalType::Simd,
+            Self::I32x4ReplaceLane => WasmProposalType::Simd,
+            Self::I64x2ExtractLane => WasmProposalType::Simd,
+            Self::I64x2ReplaceLane => WasmProposalType::Simd,
+            Self::F32x4ExtractLane => WasmProposalType::Simd,
+            Self::F32x4ReplaceLane => WasmProposalType::Simd,
+            Self::F64x2ExtractLane => WasmProposalType::Simd,
+            Self::F64x2ReplaceLane => WasmProposalType::Simd,
+            Self::I8x16Eq => WasmProposalType::Simd,
+            Self::I8x16Ne => WasmProposalType::Simd,
+            Self::I8x16LtS => WasmProposalType::Simd,
+            Self::I8x16LtU => WasmProposalType::Simd,
+            Self::I8x16GtS => WasmProposalType::Simd,
+            Self::I8x16GtU => WasmProposalType::Simd,
+            Self::I8x16LeS => WasmProposalType::Simd,
+            Self::I8x16LeU => WasmProposalType::Simd,
+            Self::I8x16GeS => WasmProposalType::Simd,
+            Self::I8x16GeU => WasmProposalType::Simd,
+            Self::I16x8Eq => WasmProposalType::Simd,
+            Self::I16x8Ne => WasmProposalType::Simd,
+            Self::I16x8LtS => WasmProposalType::Simd,
+            Self::I16x8LtU => WasmProposalType::Simd,
+            Self::I16x8GtS => WasmProposalType::Simd,
+            Self::I16x8GtU => WasmProposalType::Simd,
+            Self::I16x8LeS => WasmProposalType::Simd,
+            Self::I16x8LeU => WasmProposalType::Simd,
+            Self::I16x8GeS => WasmProposalType::Simd,
+            Self::I16x8GeU => WasmProposalType::Simd,
+            Self::I32x4Eq => WasmProposalType::Simd,
+            Self::I32x4Ne => WasmProposalType::Simd,
+            Self::I32x4LtS => WasmProposalType::Simd,
+            Self::I32x4LtU => WasmProposalType::Simd,
+            Self::I32x4GtS => WasmProposalType::Simd,
+            Self::I32x4GtU => WasmProposalType::Simd,
+            Self::I32x4LeS => WasmProposalType::Simd,
+            Self::I32x4LeU => WasmProposalType::Simd,
+            Self::I32x4GeS => WasmProposalType::Simd,
+            Self::I32x4GeU => WasmProposalType::Simd,
+            Self::F32x4Eq => WasmProposalType::Simd,
+            Self::F32x4Ne => WasmProposalType::Simd,
+            Self::F32x4Lt => WasmProposalType::Simd,
+            Self::F32x4Gt => WasmProposalType::Simd,
+            Self::F32x4Le => WasmProposalType::Simd,
+            Self::F32x4Ge => WasmProposalType::Simd,
+            Self::F64x2Eq => WasmProposalType::Simd,
+            Self::F64x2Ne => WasmProposalType::Simd,
+            Self::F64x2Lt => WasmProposalType::Simd,
+            Self::F64x2Gt => WasmProposalType::Simd,
+            Self::F64x2Le => WasmProposalType::Simd,
+            Self::F64x2Ge => WasmProposalType::Simd,
+            Self::V128Not => WasmProposalType::Simd,
+            Self::V128And => WasmProposalType::Simd,
+            Self::V128Andnot => WasmProposalType::Simd,
+            Self::V128Or => WasmProposalType::Simd,
+            Self::V128Xor => WasmProposalType::Simd,
+            Self::V128Bitselect => WasmProposalType::Simd,
+            Self::V128AnyTrue => WasmProposalType::Simd,
+            Self::V128Load8Lane => WasmProposalType::Simd,
+            Self::V128Load16Lane => WasmProposalType::Simd,
+            Self::V128Load32Lane => WasmProposalType::Simd,
+            Self::V128Load64Lane => WasmProposalType::Simd,
+            Self::V128Store8Lane => WasmProposalType::Simd,
+            Self::V128Store16Lane => WasmProposalType::Simd,
+            Self::V128Store32Lane => WasmProposalType::Simd,
+            Self::V128Store64Lane => WasmProposalType::Simd,
+            Self::V128Load32Zero => WasmProposalType::Simd,
+            Self::V128Load64Zero => WasmProposalType::Simd,
+            Self::F32x4DemoteF64x2Zero => WasmProposalType::Simd,
+            Self::F64x2PromoteLowF32x4 => WasmProposalType::Simd,
+            Self::I8x16Abs => WasmProposalType::Simd,
+            Self::I8x16Neg => WasmProposalType::Simd,
+            Self::I8x16Popcnt => WasmProposalType::Simd,
+            Self::I8x16AllTrue => WasmProposalType::Simd,
+            Self::I8x16Bitmask => WasmProposalType::Simd,
+            Self::I8x16NarrowI16x8S => WasmProposalType::Simd,
+            Self::I8x16NarrowI16x8U => WasmProposalType::Simd,
+            Self::F32x4Ceil => WasmProposalType::Simd,
+            Self::F32x4Floor => WasmProposalType::Simd,
+            Self::F32x4Trunc => WasmProposalType::Simd,
+            Self::F32x4Nearest => WasmProposalType::Simd,
+            Self::I8x16Shl => WasmProposalType::Simd,
+            Self::I8x16ShrS => WasmProposalType::Simd,
+            Self::I8x16ShrU => WasmProposalType::Simd,
+            Self::I8x16Add => WasmProposalType::Simd,
+            Self::I8x16AddSatS => WasmProposalType::Simd,
+            Self::I8x16AddSatU => WasmProposalType::Simd,
+            Self::I8x16Sub => WasmProposalType::Simd,
+            Self::I8x16SubSatS => WasmProposalType::Simd,
+            Self::I8x16SubSatU => WasmProposalType::Simd,
+            Self::F64x2Ceil => WasmProposalType::Simd,
+            Self::F64x2Floor => WasmProposalType::Simd,
+            Self::I8x16MinS => WasmProposalType::Simd,
+            Self::I8x16MinU => WasmProposalType::Simd,
+            Self::I8x16MaxS => WasmProposalType::Simd,
+            Self::I8x16MaxU => WasmProposalType::Simd,
+            Self::F64x2Trunc => WasmProposalType::Simd,
+            Self::I8x16AvgrU => WasmProposalType::Simd,
+            Self::I16x8ExtaddPairwiseI8x16S => WasmProposalType::Simd,
+            Self::I16x8ExtaddPairwiseI8x16U => WasmProposalType::Simd,
+            Self::I32x4ExtaddPairwiseI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtaddPairwiseI16x8U => WasmProposalType::Simd,
+            Self::I16x8Abs => WasmProposalType::Simd,
+            Self::I16x8Neg => WasmProposalType::Simd,
+            Self::I16x8Q15mulrSatS => WasmProposalType::Simd,
+            Self::I16x8AllTrue => WasmProposalType::Simd,
+            Self::I16x8Bitmask => WasmProposalType::Simd,
+            Self::I16x8NarrowI32x4S => WasmProposalType::Simd,
+            Self::I16x8NarrowI32x4U => WasmProposalType::Simd,
+            Self::I16x8ExtendLowI8x16S => WasmProposalType::Simd,
+            Self::I16x8ExtendHighI8x16S => WasmProposalType::Simd,
+            Self::I16x8ExtendLowI8x16U => WasmProposalType::Simd,
+            Self::I16x8ExtendHighI8x16U => WasmProposalType::Simd,
+            Self::I16x8Shl => WasmProposalType::Simd,
+            Self::I16x8ShrS => WasmProposalType::Simd,
+            Self::I16x8ShrU => WasmProposalType::Simd,
+            Self::I16x8Add => WasmProposalType::Simd,
+            Self::I16x8AddSatS => WasmProposalType::Simd,
+            Self::I16x8AddSatU => WasmProposalType::Simd,
+            Self::I16x8Sub => WasmProposalType::Simd,
+            Self::I16x8SubSatS => WasmProposalType::Simd,
+            Self::I16x8SubSatU => WasmProposalType::Simd,
+            Self::F64x2Nearest => WasmProposalType::Simd,
+            Self::I16x8Mul => WasmProposalType::Simd,
+            Self::I16x8MinS => WasmProposalType::Simd,
+            Self::I16x8MinU => WasmProposalType::Simd,
+            Self::I16x8MaxS => WasmProposalType::Simd,
+            Self::I16x8MaxU => WasmProposalType::Simd,
+            Self::I16x8AvgrU => WasmProposalType::Simd,
+            Self::I16x8ExtmulLowI8x16S => WasmProposalType::Simd,
+            Self::I16x8ExtmulHighI8x16S => WasmProposalType::Simd,
+            Self::I16x8ExtmulLowI8x16U => WasmProposalType::Simd,
+            Self::I16x8ExtmulHighI8x16U => WasmProposalType::Simd,
+            Self::I32x4Abs => WasmProposalType::Simd,
+            Self::I32x4Neg => WasmProposalType::Simd,
+            Self::I32x4AllTrue => WasmProposalType::Simd,
+            Self::I32x4Bitmask => WasmProposalType::Simd,
+            Self::I32x4ExtendLowI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtendHighI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtendLowI16x8U => WasmProposalType::Simd,
+            Self::I32x4ExtendHighI16x8U => WasmProposalType::Simd,
+            Self::I32x4Shl => WasmProposalType::Simd,
+            Self::I32x4ShrS => WasmProposalType::Simd,
+            Self::I32x4ShrU => WasmProposalType::Simd,
+            Self::I32x4Add => WasmProposalType::Simd,
+            Self::I32x4Sub => WasmProposalType::Simd,
+            Self::I32x4Mul => WasmProposalType::Simd,
+            Self::I32x4MinS => WasmProposalType::Simd,
+            Self::I32x4MinU => WasmProposalType::Simd,
+            Self::I32x4MaxS => WasmProposalType::Simd,
+            Self::I32x4MaxU => WasmProposalType::Simd,
+            Self::I32x4DotI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtmulLowI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtmulHighI16x8S => WasmProposalType::Simd,
+            Self::I32x4ExtmulLowI16x8U => WasmProposalType::Simd,
+            Self::I32x4ExtmulHighI16x8U => WasmProposalType::Simd,
+            Self::I64x2Abs => WasmProposalType::Simd,
+            Self::I64x2Neg => WasmProposalType::Simd,
+            Self::I64x2AllTrue => WasmProposalType::Simd,
+            Self::I64x2Bitmask => WasmProposalType::Simd,
+            Self::I64x2ExtendLowI32x4S => WasmProposalType::Simd,
+            Self::I64x2ExtendHighI32x4S => WasmProposalType::Simd,
+            Self::I64x2ExtendLowI32x4U => WasmProposalType::Simd,
+            Self::I64x2ExtendHighI32x4U => WasmProposalType::Simd,
+            Self::I64x2Shl => WasmProposalType::Simd,
+            Self::I64x2ShrS => WasmProposalType::Simd,
+            Self::I64x2ShrU => WasmProposalType::Simd,
+            Self::I64x2Add => WasmProposalType::Simd,
+            Self::I64x2Sub => WasmProposalType::Simd,
+            Self::I64x2Mul => WasmProposalType::Simd,
+            Self::I64x2Eq => WasmProposalType::Simd,
+            Self::I64x2Ne => WasmProposalType::Simd,
+            Self::I64x2LtS => WasmProposalType::Simd,
+            Self::I64x2GtS => WasmProposalType::Simd,
+            Self::I64x2LeS => WasmProposalType::Simd,
+            Self::I64x2GeS => WasmProposalType::Simd,
+            Self::I64x2ExtmulLowI32x4S => WasmProposalType::Simd,
+            Self::I64x2ExtmulHighI32x4S => WasmProposalType::Simd,
+            Self::I64x2ExtmulLowI32x4U => WasmProposalType::Simd,
+            Self::I64x2ExtmulHighI32x4U => WasmProposalType::Simd,
+            Self::F32x4Abs => WasmProposalType::Simd,
+            Self::F32x4Neg => WasmProposalType::Simd,
+            Self::F32x4Sqrt => WasmProposalType::Simd,
+            Self::F32x4Add => WasmProposalType::Simd,
+            Self::F32x4Sub => WasmProposalType::Simd,
+            Self::F32x4Mul => WasmProposalType::Simd,
+            Self::F32x4Div => WasmProposalType::Simd,
+            Self::F32x4Min => WasmProposalType::Simd,
+            Self::F32x4Max => WasmProposalType::Simd,
+            Self::F32x4Pmin => WasmProposalType::Simd,
+            Self::F32x4Pmax => WasmProposalType::Simd,
+            Self::F64x2Abs => WasmProposalType::Simd,
+            Self::F64x2Neg => WasmProposalType::Simd,
+            Self::F64x2Sqrt => WasmProposalType::Simd,
+            Self::F64x2Add => WasmProposalType::Simd,
+            Self::F64x2Sub => WasmProposalType::Simd,
+            Self::F64x2Mul => WasmProposalType::Simd,
+            Self::F64x2Div => WasmProposalType::Simd,
+            Self::F64x2Min => WasmProposalType::Simd,
+            Self::F64x2Max => WasmProposalType::Simd,
+            Self::F64x2Pmin => WasmProposalType::Simd,
+            Self::F64x2Pmax => WasmProposalType::Simd,
+            Self::I32x4TruncSatF32x4S => WasmProposalType::Simd,
+            Self::I32x4TruncSatF32x4U => WasmProposalType::Simd,
+            Self::F32x4ConvertI32x4S => WasmProposalType::Simd,
+            Self::F32x4ConvertI32x4U => WasmProposalType::Simd,
+            Self::I32x4TruncSatF64x2SZero => WasmProposalType::Simd,
+            Self::I32x4TruncSatF64x2UZero => WasmProposalType::Simd,
+            Self::F64x2ConvertLowI32x4S => WasmProposalType::Simd,
+            Self::F64x2ConvertLowI32x4U => WasmProposalType::Simd,
+            Self::MemoryAtomicNotify => WasmProposalType::Threads,
+            Self::MemoryAtomicWait32 => WasmProposalType::Threads,
+            Self::MemoryAtomicWait64 => WasmProposalType::Threads,
+            Self::AtomicFence => WasmProposalType::Threads,
+            Self::I32AtomicLoad => WasmProposalType::Threads,
+            Self::I64AtomicLoad => WasmProposalType::Threads,
+            Self::I32AtomicLoad8U => WasmProposalType::Threads,
+            Self::I32AtomicLoad16U => WasmProposalType::Threads,
+            Self::I64AtomicLoad8U => WasmProposalType::Threads,
+            Self::I64AtomicLoad16U => WasmProposalType::Threads,
+            Self::I64AtomicLoad32U => WasmProposalType::Threads,
+            Self::I32AtomicStore => WasmProposalType::Threads,
+            Self::I64AtomicStore => WasmProposalType::Threads,
+            Self::I32AtomicStore8 => WasmProposalType::Threads,
+            Self::I32AtomicStore16 => WasmProposalType::Threads,
+            Self::I64AtomicStore8 => WasmProposalType::Threads,
+            Self::I64AtomicStore16 => WasmProposalType::Threads,
+            Self::I64AtomicStore32 => WasmProposalType::Threads,
+            Self::I32AtomicRmwAdd => WasmProposalType::Threads,
+            Self::I64AtomicRmwAdd => WasmProposalType::Threads,
+            Self::I32AtomicRmw8AddU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16AddU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8AddU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16AddU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32AddU => WasmProposalType::Threads,
+            Self::I32AtomicRmwSub => WasmProposalType::Threads,
+            Self::I64AtomicRmwSub => WasmProposalType::Threads,
+            Self::I32AtomicRmw8SubU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16SubU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8SubU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16SubU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32SubU => WasmProposalType::Threads,
+            Self::I32AtomicRmwAnd => WasmProposalType::Threads,
+            Self::I64AtomicRmwAnd => WasmProposalType::Threads,
+            Self::I32AtomicRmw8AndU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16AndU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8AndU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16AndU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32AndU => WasmProposalType::Threads,
+            Self::I32AtomicRmwOr => WasmProposalType::Threads,
+            Self::I64AtomicRmwOr => WasmProposalType::Threads,
+            Self::I32AtomicRmw8OrU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16OrU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8OrU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16OrU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32OrU => WasmProposalType::Threads,
+            Self::I32AtomicRmwXor => WasmProposalType::Threads,
+            Self::I64AtomicRmwXor => WasmProposalType::Threads,
+            Self::I32AtomicRmw8XorU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16XorU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8XorU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16XorU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32XorU => WasmProposalType::Threads,
+            Self::I32AtomicRmwXchg => WasmProposalType::Threads,
+            Self::I64AtomicRmwXchg => WasmProposalType::Threads,
+            Self::I32AtomicRmw8XchgU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16XchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8XchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16XchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32XchgU => WasmProposalType::Threads,
+            Self::I32AtomicRmwCmpxchg => WasmProposalType::Threads,
+            Self::I64AtomicRmwCmpxchg => WasmProposalType::Threads,
+            Self::I32AtomicRmw8CmpxchgU => WasmProposalType::Threads,
+            Self::I32AtomicRmw16CmpxchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw8CmpxchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw16CmpxchgU => WasmProposalType::Threads,
+            Self::I64AtomicRmw32CmpxchgU => WasmProposalType::Threads,
             _ => WasmProposalType::Mvp,
         }
     }
@@ -774,6 +2574,8 @@ impl TryFrom<u8> for WasmOpcode {
             0x03 => Ok(Self::Loop),
             0x04 => Ok(Self::If),
             0x05 => Ok(Self::Else),
+            0x08 => Ok(Self::Throw),
+            0x0A => Ok(Self::ThrowRef),
             0x0B => Ok(Self::End),
             0x0C => Ok(Self::Br),
             0x0D => Ok(Self::BrIf),
@@ -785,11 +2587,15 @@ impl TryFrom<u8> for WasmOpcode {
             0x13 => Ok(Self::ReturnCallIndirect),
             0x1A => Ok(Self::Drop),
             0x1B => Ok(Self::Select),
+            0x1C => Ok(Self::SelectT),
+            0x1F => Ok(Self::TryTable),
             0x20 => Ok(Self::LocalGet),
             0x21 => Ok(Self::LocalSet),
             0x22 => Ok(Self::LocalTee),
             0x23 => Ok(Self::GlobalGet),
             0x24 => Ok(Self::GlobalSet),
+            0x25 => Ok(Self::TableGet),
+            0x26 => Ok(Self::TableSet),
             0x28 => Ok(Self::I32Load),
             0x29 => Ok(Self::I64Load),
             0x2A => Ok(Self::F32Load),
@@ -947,6 +2753,9 @@ impl TryFrom<u8> for WasmOpcode {
             0xC2 => Ok(Self::I64Extend8S),
             0xC3 => Ok(Self::I64Extend16S),
             0xC4 => Ok(Self::I64Extend32S),
+            0xD0 => Ok(Self::RefNull),
+            0xD1 => Ok(Self::RefIsNull),
+            0xD2 => Ok(Self::RefFunc),
             _ => Err(()),
         }
     }
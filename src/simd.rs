@@ -0,0 +1,1003 @@
+// Fixed-width SIMD
+
+use super::opcode::*;
+use super::wasm::*;
+use byteorder::*;
+use core::array;
+
+/// Operands and results of the SIMD instructions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SimdSignature {
+    /// v128.const i128
+    Const,
+    /// [v128] -> [v128]
+    Unary,
+    /// [v128, v128] -> [v128]
+    Binary,
+    /// [v128, v128, v128] -> [v128]
+    Ternary,
+    /// [v128] -> [i32]
+    Test,
+    /// [v128, i32] -> [v128]
+    Shift,
+    /// laneidx*16 [v128, v128] -> [v128]
+    Shuffle,
+    /// [t] -> [v128]
+    Splat(WasmValType),
+    /// laneidx [v128] -> [t]
+    ExtractLane(WasmValType, u8),
+    /// laneidx [v128, t] -> [v128]
+    ReplaceLane(WasmValType, u8),
+    /// memarg [i] -> [v128]
+    Load,
+    /// memarg [i, v128] -> []
+    Store,
+    /// memarg laneidx [i, v128] -> [v128]
+    LoadLane(u8),
+    /// memarg laneidx [i, v128] -> []
+    StoreLane(u8),
+}
+
+impl SimdSignature {
+    pub fn from_opcode(opcode: WasmOpcode) -> Option<Self> {
+        use WasmOpcode::*;
+        let signature = match opcode {
+            V128Const => Self::Const,
+
+            V128Load | V128Load8x8S | V128Load8x8U | V128Load16x4S | V128Load16x4U
+            | V128Load32x2S | V128Load32x2U | V128Load8Splat | V128Load16Splat
+            | V128Load32Splat | V128Load64Splat | V128Load32Zero | V128Load64Zero => Self::Load,
+            V128Store => Self::Store,
+            V128Load8Lane => Self::LoadLane(16),
+            V128Load16Lane => Self::LoadLane(8),
+            V128Load32Lane => Self::LoadLane(4),
+            V128Load64Lane => Self::LoadLane(2),
+            V128Store8Lane => Self::StoreLane(16),
+            V128Store16Lane => Self::StoreLane(8),
+            V128Store32Lane => Self::StoreLane(4),
+            V128Store64Lane => Self::StoreLane(2),
+
+            I8x16Shuffle => Self::Shuffle,
+
+            I8x16Splat | I16x8Splat | I32x4Splat => Self::Splat(WasmValType::I32),
+            I64x2Splat => Self::Splat(WasmValType::I64),
+            F32x4Splat => Self::Splat(WasmValType::F32),
+            F64x2Splat => Self::Splat(WasmValType::F64),
+
+            I8x16ExtractLaneS | I8x16ExtractLaneU => Self::ExtractLane(WasmValType::I32, 16),
+            I16x8ExtractLaneS | I16x8ExtractLaneU => Self::ExtractLane(WasmValType::I32, 8),
+            I32x4ExtractLane => Self::ExtractLane(WasmValType::I32, 4),
+            I64x2ExtractLane => Self::ExtractLane(WasmValType::I64, 2),
+            F32x4ExtractLane => Self::ExtractLane(WasmValType::F32, 4),
+            F64x2ExtractLane => Self::ExtractLane(WasmValType::F64, 2),
+            I8x16ReplaceLane => Self::ReplaceLane(WasmValType::I32, 16),
+            I16x8ReplaceLane => Self::ReplaceLane(WasmValType::I32, 8),
+            I32x4ReplaceLane => Self::ReplaceLane(WasmValType::I32, 4),
+            I64x2ReplaceLane => Self::ReplaceLane(WasmValType::I64, 2),
+            F32x4ReplaceLane => Self::ReplaceLane(WasmValType::F32, 4),
+            F64x2ReplaceLane => Self::ReplaceLane(WasmValType::F64, 2),
+
+            V128AnyTrue | I8x16AllTrue | I8x16Bitmask | I16x8AllTrue | I16x8Bitmask
+            | I32x4AllTrue | I32x4Bitmask | I64x2AllTrue | I64x2Bitmask => Self::Test,
+
+            I8x16Shl | I8x16ShrS | I8x16ShrU | I16x8Shl | I16x8ShrS | I16x8ShrU | I32x4Shl
+            | I32x4ShrS | I32x4ShrU | I64x2Shl | I64x2ShrS | I64x2ShrU => Self::Shift,
+
+            V128Bitselect => Self::Ternary,
+
+            V128Not
+            | I8x16Abs
+            | I8x16Neg
+            | I8x16Popcnt
+            | I16x8Abs
+            | I16x8Neg
+            | I32x4Abs
+            | I32x4Neg
+            | I64x2Abs
+            | I64x2Neg
+            | F32x4Abs
+            | F32x4Neg
+            | F32x4Sqrt
+            | F32x4Ceil
+            | F32x4Floor
+            | F32x4Trunc
+            | F32x4Nearest
+            | F64x2Abs
+            | F64x2Neg
+            | F64x2Sqrt
+            | F64x2Ceil
+            | F64x2Floor
+            | F64x2Trunc
+            | F64x2Nearest
+            | I16x8ExtaddPairwiseI8x16S
+            | I16x8ExtaddPairwiseI8x16U
+            | I32x4ExtaddPairwiseI16x8S
+            | I32x4ExtaddPairwiseI16x8U
+            | I16x8ExtendLowI8x16S
+            | I16x8ExtendHighI8x16S
+            | I16x8ExtendLowI8x16U
+            | I16x8ExtendHighI8x16U
+            | I32x4ExtendLowI16x8S
+            | I32x4ExtendHighI16x8S
+            | I32x4ExtendLowI16x8U
+            | I32x4ExtendHighI16x8U
+            | I64x2ExtendLowI32x4S
+            | I64x2ExtendHighI32x4S
+            | I64x2ExtendLowI32x4U
+            | I64x2ExtendHighI32x4U
+            | I32x4TruncSatF32x4S
+            | I32x4TruncSatF32x4U
+            | F32x4ConvertI32x4S
+            | F32x4ConvertI32x4U
+            | I32x4TruncSatF64x2SZero
+            | I32x4TruncSatF64x2UZero
+            | F64x2ConvertLowI32x4S
+            | F64x2ConvertLowI32x4U
+            | F32x4DemoteF64x2Zero
+            | F64x2PromoteLowF32x4 => Self::Unary,
+
+            _ => match opcode.proposal_type() {
+                WasmProposalType::Simd => Self::Binary,
+                _ => return None,
+            },
+        };
+        Some(signature)
+    }
+}
+
+macro_rules! lanes {
+    ($load:ident, $store:ident, $t:ty, $n:expr) => {
+        #[inline]
+        fn $load(v: u128) -> [$t; $n] {
+            let bytes = v.to_le_bytes();
+            let mut lanes = [<$t>::default(); $n];
+            for (lane, chunk) in lanes.iter_mut().zip(bytes.chunks_exact(16 / $n)) {
+                let mut raw = [0; 16 / $n];
+                raw.copy_from_slice(chunk);
+                *lane = <$t>::from_le_bytes(raw);
+            }
+            lanes
+        }
+
+        #[inline]
+        fn $store(lanes: [$t; $n]) -> u128 {
+            let mut bytes = [0; 16];
+            for (chunk, lane) in bytes.chunks_exact_mut(16 / $n).zip(lanes.iter()) {
+                chunk.copy_from_slice(&lane.to_le_bytes());
+            }
+            u128::from_le_bytes(bytes)
+        }
+    };
+}
+
+lanes!(i8x16, from_i8x16, i8, 16);
+lanes!(u8x16, from_u8x16, u8, 16);
+lanes!(i16x8, from_i16x8, i16, 8);
+lanes!(u16x8, from_u16x8, u16, 8);
+lanes!(i32x4, from_i32x4, i32, 4);
+lanes!(u32x4, from_u32x4, u32, 4);
+lanes!(i64x2, from_i64x2, i64, 2);
+lanes!(u64x2, from_u64x2, u64, 2);
+lanes!(f32x4, from_f32x4, f32, 4);
+lanes!(f64x2, from_f64x2, f64, 2);
+
+#[inline]
+fn zip<T: Copy, F, const N: usize>(a: [T; N], b: [T; N], f: F) -> [T; N]
+where
+    F: Fn(T, T) -> T,
+{
+    array::from_fn(|i| f(a[i], b[i]))
+}
+
+/// Compares each lane, the result lanes are all ones if true
+#[inline]
+fn cmp<T: Copy, F, const N: usize>(a: [T; N], b: [T; N], f: F) -> u128
+where
+    F: Fn(T, T) -> bool,
+{
+    let width = 128 / N;
+    let ones = u128::MAX >> (128 - width);
+    (0..N)
+        .filter(|&i| f(a[i], b[i]))
+        .fold(0, |acc, i| acc | (ones << (i * width)))
+}
+
+/// Collects the most significant bit of each lane
+#[inline]
+fn bitmask<T: Copy, F, const N: usize>(a: [T; N], is_negative: F) -> i32
+where
+    F: Fn(T) -> bool,
+{
+    (0..N)
+        .filter(|&i| is_negative(a[i]))
+        .fold(0, |acc, i| acc | (1 << i))
+}
+
+macro_rules! float_ops {
+    ($min:ident, $max:ident, $t:ty) => {
+        /// fmin of wasm, which propagates NaN and orders -0.0 below +0.0
+        #[inline]
+        fn $min(a: $t, b: $t) -> $t {
+            if a.is_nan() || b.is_nan() {
+                <$t>::NAN
+            } else if a == b {
+                <$t>::from_bits(a.to_bits() | b.to_bits())
+            } else {
+                a.min(b)
+            }
+        }
+
+        /// fmax of wasm, which propagates NaN and orders -0.0 below +0.0
+        #[inline]
+        fn $max(a: $t, b: $t) -> $t {
+            if a.is_nan() || b.is_nan() {
+                <$t>::NAN
+            } else if a == b {
+                <$t>::from_bits(a.to_bits() & b.to_bits())
+            } else {
+                a.max(b)
+            }
+        }
+    };
+}
+
+float_ops!(fmin32, fmax32, f32);
+float_ops!(fmin64, fmax64, f64);
+
+/// [v128] -> [v128]
+pub fn unary(opcode: WasmOpcode, a: u128) -> Result<u128, WasmRuntimeError> {
+    use WasmOpcode::*;
+    let result = match opcode {
+        V128Not => !a,
+
+        I8x16Abs => from_i8x16(i8x16(a).map(i8::wrapping_abs)),
+        I8x16Neg => from_i8x16(i8x16(a).map(i8::wrapping_neg)),
+        I8x16Popcnt => from_u8x16(u8x16(a).map(|v| v.count_ones() as u8)),
+        I16x8Abs => from_i16x8(i16x8(a).map(i16::wrapping_abs)),
+        I16x8Neg => from_i16x8(i16x8(a).map(i16::wrapping_neg)),
+        I32x4Abs => from_i32x4(i32x4(a).map(i32::wrapping_abs)),
+        I32x4Neg => from_i32x4(i32x4(a).map(i32::wrapping_neg)),
+        I64x2Abs => from_i64x2(i64x2(a).map(i64::wrapping_abs)),
+        I64x2Neg => from_i64x2(i64x2(a).map(i64::wrapping_neg)),
+
+        // abs and neg only touch the sign bits, even of NaN
+        F32x4Abs => a & !0x8000_0000_8000_0000_8000_0000_8000_0000,
+        F32x4Neg => a ^ 0x8000_0000_8000_0000_8000_0000_8000_0000,
+        F32x4Sqrt => from_f32x4(f32x4(a).map(f32::sqrt)),
+        F32x4Ceil => from_f32x4(f32x4(a).map(f32::ceil)),
+        F32x4Floor => from_f32x4(f32x4(a).map(f32::floor)),
+        F32x4Trunc => from_f32x4(f32x4(a).map(f32::trunc)),
+        F32x4Nearest => from_f32x4(f32x4(a).map(f32::round_ties_even)),
+        F64x2Abs => a & !0x8000_0000_0000_0000_8000_0000_0000_0000,
+        F64x2Neg => a ^ 0x8000_0000_0000_0000_8000_0000_0000_0000,
+        F64x2Sqrt => from_f64x2(f64x2(a).map(f64::sqrt)),
+        F64x2Ceil => from_f64x2(f64x2(a).map(f64::ceil)),
+        F64x2Floor => from_f64x2(f64x2(a).map(f64::floor)),
+        F64x2Trunc => from_f64x2(f64x2(a).map(f64::trunc)),
+        F64x2Nearest => from_f64x2(f64x2(a).map(f64::round_ties_even)),
+
+        I16x8ExtaddPairwiseI8x16S => {
+            let a = i8x16(a);
+            from_i16x8(array::from_fn(|i| a[i * 2] as i16 + a[i * 2 + 1] as i16))
+        }
+        I16x8ExtaddPairwiseI8x16U => {
+            let a = u8x16(a);
+            from_u16x8(array::from_fn(|i| a[i * 2] as u16 + a[i * 2 + 1] as u16))
+        }
+        I32x4ExtaddPairwiseI16x8S => {
+            let a = i16x8(a);
+            from_i32x4(array::from_fn(|i| a[i * 2] as i32 + a[i * 2 + 1] as i32))
+        }
+        I32x4ExtaddPairwiseI16x8U => {
+            let a = u16x8(a);
+            from_u32x4(array::from_fn(|i| a[i * 2] as u32 + a[i * 2 + 1] as u32))
+        }
+
+        I16x8ExtendLowI8x16S => {
+            let a = i8x16(a);
+            from_i16x8(array::from_fn(|i| a[i] as i16))
+        }
+        I16x8ExtendHighI8x16S => {
+            let a = i8x16(a);
+            from_i16x8(array::from_fn(|i| a[i + 8] as i16))
+        }
+        I16x8ExtendLowI8x16U => {
+            let a = u8x16(a);
+            from_u16x8(array::from_fn(|i| a[i] as u16))
+        }
+        I16x8ExtendHighI8x16U => {
+            let a = u8x16(a);
+            from_u16x8(array::from_fn(|i| a[i + 8] as u16))
+        }
+        I32x4ExtendLowI16x8S => {
+            let a = i16x8(a);
+            from_i32x4(array::from_fn(|i| a[i] as i32))
+        }
+        I32x4ExtendHighI16x8S => {
+            let a = i16x8(a);
+            from_i32x4(array::from_fn(|i| a[i + 4] as i32))
+        }
+        I32x4ExtendLowI16x8U => {
+            let a = u16x8(a);
+            from_u32x4(array::from_fn(|i| a[i] as u32))
+        }
+        I32x4ExtendHighI16x8U => {
+            let a = u16x8(a);
+            from_u32x4(array::from_fn(|i| a[i + 4] as u32))
+        }
+        I64x2ExtendLowI32x4S => {
+            let a = i32x4(a);
+            from_i64x2(array::from_fn(|i| a[i] as i64))
+        }
+        I64x2ExtendHighI32x4S => {
+            let a = i32x4(a);
+            from_i64x2(array::from_fn(|i| a[i + 2] as i64))
+        }
+        I64x2ExtendLowI32x4U => {
+            let a = u32x4(a);
+            from_u64x2(array::from_fn(|i| a[i] as u64))
+        }
+        I64x2ExtendHighI32x4U => {
+            let a = u32x4(a);
+            from_u64x2(array::from_fn(|i| a[i + 2] as u64))
+        }
+
+        // Float to int casts of Rust saturate and convert NaN to 0
+        I32x4TruncSatF32x4S => from_i32x4(f32x4(a).map(|v| v as i32)),
+        I32x4TruncSatF32x4U => from_u32x4(f32x4(a).map(|v| v as u32)),
+        I32x4TruncSatF64x2SZero => {
+            let a = f64x2(a);
+            from_i32x4([a[0] as i32, a[1] as i32, 0, 0])
+        }
+        I32x4TruncSatF64x2UZero => {
+            let a = f64x2(a);
+            from_u32x4([a[0] as u32, a[1] as u32, 0, 0])
+        }
+        F32x4ConvertI32x4S => from_f32x4(i32x4(a).map(|v| v as f32)),
+        F32x4ConvertI32x4U => from_f32x4(u32x4(a).map(|v| v as f32)),
+        F64x2ConvertLowI32x4S => {
+            let a = i32x4(a);
+            from_f64x2([a[0] as f64, a[1] as f64])
+        }
+        F64x2ConvertLowI32x4U => {
+            let a = u32x4(a);
+            from_f64x2([a[0] as f64, a[1] as f64])
+        }
+        F32x4DemoteF64x2Zero => {
+            let a = f64x2(a);
+            from_f32x4([a[0] as f32, a[1] as f32, 0.0, 0.0])
+        }
+        F64x2PromoteLowF32x4 => {
+            let a = f32x4(a);
+            from_f64x2([a[0] as f64, a[1] as f64])
+        }
+
+        _ => return Err(WasmRuntimeError::InvalidBytecode),
+    };
+    Ok(result)
+}
+
+/// [v128, v128] -> [v128]
+pub fn binary(opcode: WasmOpcode, a: u128, b: u128) -> Result<u128, WasmRuntimeError> {
+    #[cfg(all(feature = "simd-host", target_arch = "x86_64"))]
+    if let Some(result) = host::binary(opcode, a, b) {
+        return Ok(result);
+    }
+    binary_scalar(opcode, a, b)
+}
+
+fn binary_scalar(opcode: WasmOpcode, a: u128, b: u128) -> Result<u128, WasmRuntimeError> {
+    use WasmOpcode::*;
+    let result = match opcode {
+        V128And => a & b,
+        V128Andnot => a & !b,
+        V128Or => a | b,
+        V128Xor => a ^ b,
+
+        I8x16Eq => cmp(i8x16(a), i8x16(b), |a, b| a == b),
+        I8x16Ne => cmp(i8x16(a), i8x16(b), |a, b| a != b),
+        I8x16LtS => cmp(i8x16(a), i8x16(b), |a, b| a < b),
+        I8x16LtU => cmp(u8x16(a), u8x16(b), |a, b| a < b),
+        I8x16GtS => cmp(i8x16(a), i8x16(b), |a, b| a > b),
+        I8x16GtU => cmp(u8x16(a), u8x16(b), |a, b| a > b),
+        I8x16LeS => cmp(i8x16(a), i8x16(b), |a, b| a <= b),
+        I8x16LeU => cmp(u8x16(a), u8x16(b), |a, b| a <= b),
+        I8x16GeS => cmp(i8x16(a), i8x16(b), |a, b| a >= b),
+        I8x16GeU => cmp(u8x16(a), u8x16(b), |a, b| a >= b),
+        I16x8Eq => cmp(i16x8(a), i16x8(b), |a, b| a == b),
+        I16x8Ne => cmp(i16x8(a), i16x8(b), |a, b| a != b),
+        I16x8LtS => cmp(i16x8(a), i16x8(b), |a, b| a < b),
+        I16x8LtU => cmp(u16x8(a), u16x8(b), |a, b| a < b),
+        I16x8GtS => cmp(i16x8(a), i16x8(b), |a, b| a > b),
+        I16x8GtU => cmp(u16x8(a), u16x8(b), |a, b| a > b),
+        I16x8LeS => cmp(i16x8(a), i16x8(b), |a, b| a <= b),
+        I16x8LeU => cmp(u16x8(a), u16x8(b), |a, b| a <= b),
+        I16x8GeS => cmp(i16x8(a), i16x8(b), |a, b| a >= b),
+        I16x8GeU => cmp(u16x8(a), u16x8(b), |a, b| a >= b),
+        I32x4Eq => cmp(i32x4(a), i32x4(b), |a, b| a == b),
+        I32x4Ne => cmp(i32x4(a), i32x4(b), |a, b| a != b),
+        I32x4LtS => cmp(i32x4(a), i32x4(b), |a, b| a < b),
+        I32x4LtU => cmp(u32x4(a), u32x4(b), |a, b| a < b),
+        I32x4GtS => cmp(i32x4(a), i32x4(b), |a, b| a > b),
+        I32x4GtU => cmp(u32x4(a), u32x4(b), |a, b| a > b),
+        I32x4LeS => cmp(i32x4(a), i32x4(b), |a, b| a <= b),
+        I32x4LeU => cmp(u32x4(a), u32x4(b), |a, b| a <= b),
+        I32x4GeS => cmp(i32x4(a), i32x4(b), |a, b| a >= b),
+        I32x4GeU => cmp(u32x4(a), u32x4(b), |a, b| a >= b),
+        I64x2Eq => cmp(i64x2(a), i64x2(b), |a, b| a == b),
+        I64x2Ne => cmp(i64x2(a), i64x2(b), |a, b| a != b),
+        I64x2LtS => cmp(i64x2(a), i64x2(b), |a, b| a < b),
+        I64x2GtS => cmp(i64x2(a), i64x2(b), |a, b| a > b),
+        I64x2LeS => cmp(i64x2(a), i64x2(b), |a, b| a <= b),
+        I64x2GeS => cmp(i64x2(a), i64x2(b), |a, b| a >= b),
+        F32x4Eq => cmp(f32x4(a), f32x4(b), |a, b| a == b),
+        F32x4Ne => cmp(f32x4(a), f32x4(b), |a, b| a != b),
+        F32x4Lt => cmp(f32x4(a), f32x4(b), |a, b| a < b),
+        F32x4Gt => cmp(f32x4(a), f32x4(b), |a, b| a > b),
+        F32x4Le => cmp(f32x4(a), f32x4(b), |a, b| a <= b),
+        F32x4Ge => cmp(f32x4(a), f32x4(b), |a, b| a >= b),
+        F64x2Eq => cmp(f64x2(a), f64x2(b), |a, b| a == b),
+        F64x2Ne => cmp(f64x2(a), f64x2(b), |a, b| a != b),
+        F64x2Lt => cmp(f64x2(a), f64x2(b), |a, b| a < b),
+        F64x2Gt => cmp(f64x2(a), f64x2(b), |a, b| a > b),
+        F64x2Le => cmp(f64x2(a), f64x2(b), |a, b| a <= b),
+        F64x2Ge => cmp(f64x2(a), f64x2(b), |a, b| a >= b),
+
+        I8x16Swizzle => {
+            let (a, b) = (u8x16(a), u8x16(b));
+            from_u8x16(b.map(|i| a.get(i as usize).copied().unwrap_or(0)))
+        }
+        I8x16NarrowI16x8S => {
+            let (a, b) = (i16x8(a), i16x8(b));
+            from_i8x16(array::from_fn(|i| {
+                let v = if i < 8 { a[i] } else { b[i - 8] };
+                v.clamp(i8::MIN as i16, i8::MAX as i16) as i8
+            }))
+        }
+        I8x16NarrowI16x8U => {
+            let (a, b) = (i16x8(a), i16x8(b));
+            from_u8x16(array::from_fn(|i| {
+                let v = if i < 8 { a[i] } else { b[i - 8] };
+                v.clamp(0, u8::MAX as i16) as u8
+            }))
+        }
+        I16x8NarrowI32x4S => {
+            let (a, b) = (i32x4(a), i32x4(b));
+            from_i16x8(array::from_fn(|i| {
+                let v = if i < 4 { a[i] } else { b[i - 4] };
+                v.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+            }))
+        }
+        I16x8NarrowI32x4U => {
+            let (a, b) = (i32x4(a), i32x4(b));
+            from_u16x8(array::from_fn(|i| {
+                let v = if i < 4 { a[i] } else { b[i - 4] };
+                v.clamp(0, u16::MAX as i32) as u16
+            }))
+        }
+
+        I8x16Add => from_i8x16(zip(i8x16(a), i8x16(b), i8::wrapping_add)),
+        I8x16AddSatS => from_i8x16(zip(i8x16(a), i8x16(b), i8::saturating_add)),
+        I8x16AddSatU => from_u8x16(zip(u8x16(a), u8x16(b), u8::saturating_add)),
+        I8x16Sub => from_i8x16(zip(i8x16(a), i8x16(b), i8::wrapping_sub)),
+        I8x16SubSatS => from_i8x16(zip(i8x16(a), i8x16(b), i8::saturating_sub)),
+        I8x16SubSatU => from_u8x16(zip(u8x16(a), u8x16(b), u8::saturating_sub)),
+        I8x16MinS => from_i8x16(zip(i8x16(a), i8x16(b), i8::min)),
+        I8x16MinU => from_u8x16(zip(u8x16(a), u8x16(b), u8::min)),
+        I8x16MaxS => from_i8x16(zip(i8x16(a), i8x16(b), i8::max)),
+        I8x16MaxU => from_u8x16(zip(u8x16(a), u8x16(b), u8::max)),
+        I8x16AvgrU => from_u8x16(zip(u8x16(a), u8x16(b), |a, b| {
+            ((a as u16 + b as u16 + 1) >> 1) as u8
+        })),
+
+        I16x8Add => from_i16x8(zip(i16x8(a), i16x8(b), i16::wrapping_add)),
+        I16x8AddSatS => from_i16x8(zip(i16x8(a), i16x8(b), i16::saturating_add)),
+        I16x8AddSatU => from_u16x8(zip(u16x8(a), u16x8(b), u16::saturating_add)),
+        I16x8Sub => from_i16x8(zip(i16x8(a), i16x8(b), i16::wrapping_sub)),
+        I16x8SubSatS => from_i16x8(zip(i16x8(a), i16x8(b), i16::saturating_sub)),
+        I16x8SubSatU => from_u16x8(zip(u16x8(a), u16x8(b), u16::saturating_sub)),
+        I16x8Mul => from_i16x8(zip(i16x8(a), i16x8(b), i16::wrapping_mul)),
+        I16x8MinS => from_i16x8(zip(i16x8(a), i16x8(b), i16::min)),
+        I16x8MinU => from_u16x8(zip(u16x8(a), u16x8(b), u16::min)),
+        I16x8MaxS => from_i16x8(zip(i16x8(a), i16x8(b), i16::max)),
+        I16x8MaxU => from_u16x8(zip(u16x8(a), u16x8(b), u16::max)),
+        I16x8AvgrU => from_u16x8(zip(u16x8(a), u16x8(b), |a, b| {
+            ((a as u32 + b as u32 + 1) >> 1) as u16
+        })),
+        I16x8Q15mulrSatS => from_i16x8(zip(i16x8(a), i16x8(b), |a, b| {
+            let v = (a as i32 * b as i32 + 0x4000) >> 15;
+            v.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+        })),
+        I16x8ExtmulLowI8x16S => {
+            let (a, b) = (i8x16(a), i8x16(b));
+            from_i16x8(array::from_fn(|i| a[i] as i16 * b[i] as i16))
+        }
+        I16x8ExtmulHighI8x16S => {
+            let (a, b) = (i8x16(a), i8x16(b));
+            from_i16x8(array::from_fn(|i| a[i + 8] as i16 * b[i + 8] as i16))
+        }
+        I16x8ExtmulLowI8x16U => {
+            let (a, b) = (u8x16(a), u8x16(b));
+            from_u16x8(array::from_fn(|i| a[i] as u16 * b[i] as u16))
+        }
+        I16x8ExtmulHighI8x16U => {
+            let (a, b) = (u8x16(a), u8x16(b));
+            from_u16x8(array::from_fn(|i| a[i + 8] as u16 * b[i + 8] as u16))
+        }
+
+        I32x4Add => from_i32x4(zip(i32x4(a), i32x4(b), i32::wrapping_add)),
+        I32x4Sub => from_i32x4(zip(i32x4(a), i32x4(b), i32::wrapping_sub)),
+        I32x4Mul => from_i32x4(zip(i32x4(a), i32x4(b), i32::wrapping_mul)),
+        I32x4MinS => from_i32x4(zip(i32x4(a), i32x4(b), i32::min)),
+        I32x4MinU => from_u32x4(zip(u32x4(a), u32x4(b), u32::min)),
+        I32x4MaxS => from_i32x4(zip(i32x4(a), i32x4(b), i32::max)),
+        I32x4MaxU => from_u32x4(zip(u32x4(a), u32x4(b), u32::max)),
+        I32x4DotI16x8S => {
+            let (a, b) = (i16x8(a), i16x8(b));
+            from_i32x4(array::from_fn(|i| {
+                let lo = a[i * 2] as i32 * b[i * 2] as i32;
+                let hi = a[i * 2 + 1] as i32 * b[i * 2 + 1] as i32;
+                lo.wrapping_add(hi)
+            }))
+        }
+        I32x4ExtmulLowI16x8S => {
+            let (a, b) = (i16x8(a), i16x8(b));
+            from_i32x4(array::from_fn(|i| a[i] as i32 * b[i] as i32))
+        }
+        I32x4ExtmulHighI16x8S => {
+            let (a, b) = (i16x8(a), i16x8(b));
+            from_i32x4(array::from_fn(|i| a[i + 4] as i32 * b[i + 4] as i32))
+        }
+        I32x4ExtmulLowI16x8U => {
+            let (a, b) = (u16x8(a), u16x8(b));
+            from_u32x4(array::from_fn(|i| a[i] as u32 * b[i] as u32))
+        }
+        I32x4ExtmulHighI16x8U => {
+            let (a, b) = (u16x8(a), u16x8(b));
+            from_u32x4(array::from_fn(|i| a[i + 4] as u32 * b[i + 4] as u32))
+        }
+
+        I64x2Add => from_i64x2(zip(i64x2(a), i64x2(b), i64::wrapping_add)),
+        I64x2Sub => from_i64x2(zip(i64x2(a), i64x2(b), i64::wrapping_sub)),
+        I64x2Mul => from_i64x2(zip(i64x2(a), i64x2(b), i64::wrapping_mul)),
+        I64x2ExtmulLowI32x4S => {
+            let (a, b) = (i32x4(a), i32x4(b));
+            from_i64x2(array::from_fn(|i| a[i] as i64 * b[i] as i64))
+        }
+        I64x2ExtmulHighI32x4S => {
+            let (a, b) = (i32x4(a), i32x4(b));
+            from_i64x2(array::from_fn(|i| a[i + 2] as i64 * b[i + 2] as i64))
+        }
+        I64x2ExtmulLowI32x4U => {
+            let (a, b) = (u32x4(a), u32x4(b));
+            from_u64x2(array::from_fn(|i| a[i] as u64 * b[i] as u64))
+        }
+        I64x2ExtmulHighI32x4U => {
+            let (a, b) = (u32x4(a), u32x4(b));
+            from_u64x2(array::from_fn(|i| a[i + 2] as u64 * b[i + 2] as u64))
+        }
+
+        F32x4Add => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| a + b)),
+        F32x4Sub => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| a - b)),
+        F32x4Mul => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| a * b)),
+        F32x4Div => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| a / b)),
+        F32x4Min => from_f32x4(zip(f32x4(a), f32x4(b), fmin32)),
+        F32x4Max => from_f32x4(zip(f32x4(a), f32x4(b), fmax32)),
+        F32x4Pmin => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| if b < a { b } else { a })),
+        F32x4Pmax => from_f32x4(zip(f32x4(a), f32x4(b), |a, b| if a < b { b } else { a })),
+        F64x2Add => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| a + b)),
+        F64x2Sub => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| a - b)),
+        F64x2Mul => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| a * b)),
+        F64x2Div => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| a / b)),
+        F64x2Min => from_f64x2(zip(f64x2(a), f64x2(b), fmin64)),
+        F64x2Max => from_f64x2(zip(f64x2(a), f64x2(b), fmax64)),
+        F64x2Pmin => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| if b < a { b } else { a })),
+        F64x2Pmax => from_f64x2(zip(f64x2(a), f64x2(b), |a, b| if a < b { b } else { a })),
+
+        _ => return Err(WasmRuntimeError::InvalidBytecode),
+    };
+    Ok(result)
+}
+
+/// v128.bitselect
+#[inline]
+pub const fn bitselect(a: u128, b: u128, c: u128) -> u128 {
+    (a & c) | (b & !c)
+}
+
+/// [v128] -> [i32]
+pub fn test(opcode: WasmOpcode, a: u128) -> Result<i32, WasmRuntimeError> {
+    use WasmOpcode::*;
+    let result = match opcode {
+        V128AnyTrue => (a != 0) as i32,
+        I8x16AllTrue => i8x16(a).iter().all(|v| *v != 0) as i32,
+        I16x8AllTrue => i16x8(a).iter().all(|v| *v != 0) as i32,
+        I32x4AllTrue => i32x4(a).iter().all(|v| *v != 0) as i32,
+        I64x2AllTrue => i64x2(a).iter().all(|v| *v != 0) as i32,
+        I8x16Bitmask => bitmask(i8x16(a), |v| v < 0),
+        I16x8Bitmask => bitmask(i16x8(a), |v| v < 0),
+        I32x4Bitmask => bitmask(i32x4(a), |v| v < 0),
+        I64x2Bitmask => bitmask(i64x2(a), |v| v < 0),
+        _ => return Err(WasmRuntimeError::InvalidBytecode),
+    };
+    Ok(result)
+}
+
+/// [v128, i32] -> [v128], the shift count is taken modulo the lane width
+pub fn shift(opcode: WasmOpcode, a: u128, count: u32) -> Result<u128, WasmRuntimeError> {
+    use WasmOpcode::*;
+    let result = match opcode {
+        I8x16Shl => from_i8x16(i8x16(a).map(|v| v.wrapping_shl(count))),
+        I8x16ShrS => from_i8x16(i8x16(a).map(|v| v.wrapping_shr(count))),
+        I8x16ShrU => from_u8x16(u8x16(a).map(|v| v.wrapping_shr(count))),
+        I16x8Shl => from_i16x8(i16x8(a).map(|v| v.wrapping_shl(count))),
+        I16x8ShrS => from_i16x8(i16x8(a).map(|v| v.wrapping_shr(count))),
+        I16x8ShrU => from_u16x8(u16x8(a).map(|v| v.wrapping_shr(count))),
+        I32x4Shl => from_i32x4(i32x4(a).map(|v| v.wrapping_shl(count))),
+        I32x4ShrS => from_i32x4(i32x4(a).map(|v| v.wrapping_shr(count))),
+        I32x4ShrU => from_u32x4(u32x4(a).map(|v| v.wrapping_shr(count))),
+        I64x2Shl => from_i64x2(i64x2(a).map(|v| v.wrapping_shl(count))),
+        I64x2ShrS => from_i64x2(i64x2(a).map(|v| v.wrapping_shr(count))),
+        I64x2ShrU => from_u64x2(u64x2(a).map(|v| v.wrapping_shr(count))),
+        _ => return Err(WasmRuntimeError::InvalidBytecode),
+    };
+    Ok(result)
+}
+
+/// i8x16.shuffle, the lane indices select from the concatenation of a and b
+pub fn shuffle(a: u128, b: u128, lanes: &[u8]) -> u128 {
+    let (a, b) = (u8x16(a), u8x16(b));
+    from_u8x16(array::from_fn(|i| {
+        let lane = lanes[i] as usize;
+        if lane < 16 {
+            a[lane]
+        } else {
+            b[lane & 15]
+        }
+    }))
+}
+
+/// Returns the width of the lanes in bytes
+const fn lane_size(opcode: WasmOpcode) -> usize {
+    use WasmOpcode::*;
+    match opcode {
+        I8x16Splat | I8x16ExtractLaneS | I8x16ExtractLaneU | I8x16ReplaceLane | V128Load8Lane
+        | V128Store8Lane | V128Load8Splat => 1,
+        I16x8Splat | I16x8ExtractLaneS | I16x8ExtractLaneU | I16x8ReplaceLane | V128Load16Lane
+        | V128Store16Lane | V128Load16Splat => 2,
+        I32x4Splat | F32x4Splat | I32x4ExtractLane | F32x4ExtractLane | I32x4ReplaceLane
+        | F32x4ReplaceLane | V128Load32Lane | V128Store32Lane | V128Load32Splat
+        | V128Load32Zero => 4,
+        _ => 8,
+    }
+}
+
+/// Fills all lanes with the raw bits of the scalar
+pub fn splat(opcode: WasmOpcode, raw: u64) -> u128 {
+    let size = lane_size(opcode);
+    let bytes = raw.to_le_bytes();
+    let mut result = [0; 16];
+    for chunk in result.chunks_exact_mut(size) {
+        chunk.copy_from_slice(&bytes[..size]);
+    }
+    u128::from_le_bytes(result)
+}
+
+/// Returns the raw bits of the lane, the signed variants are sign-extended
+pub fn extract_lane(opcode: WasmOpcode, a: u128, lane: u8) -> u64 {
+    match opcode {
+        WasmOpcode::I8x16ExtractLaneS => i8x16(a)[lane as usize] as i32 as u32 as u64,
+        WasmOpcode::I16x8ExtractLaneS => i16x8(a)[lane as usize] as i32 as u32 as u64,
+        _ => {
+            let bits = lane_size(opcode) * 8;
+            let mask = u64::MAX >> (64 - bits);
+            (a >> (lane as usize * bits)) as u64 & mask
+        }
+    }
+}
+
+/// Replaces the lane with the low bits of the scalar
+pub fn replace_lane(opcode: WasmOpcode, a: u128, lane: u8, raw: u64) -> u128 {
+    let bits = lane_size(opcode) * 8;
+    let mask = (u64::MAX >> (64 - bits)) as u128;
+    let shift = lane as usize * bits;
+    (a & !(mask << shift)) | ((raw as u128 & mask) << shift)
+}
+
+/// Copies the bytes from memory, which other threads may write at the same time
+fn read_array<const N: usize>(
+    memory: &WasmMemory,
+    offset: usize,
+) -> Result<[u8; N], WasmRuntimeError> {
+    let mut bytes = [0; N];
+    memory.read_into(offset, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads the lane of `size` bytes from memory
+fn read_lane(memory: &WasmMemory, offset: usize, size: usize) -> Result<u64, WasmRuntimeError> {
+    let mut bytes = [0; 8];
+    memory.read_into(offset, &mut bytes[..size])?;
+    Ok(LE::read_uint(&bytes, size))
+}
+
+/// Loads v128 from memory, including the extending, splatting and zero-filling loads
+pub fn load(
+    opcode: WasmOpcode,
+    memory: &WasmMemory,
+    offset: usize,
+) -> Result<u128, WasmRuntimeError> {
+    use WasmOpcode::*;
+    let result = match opcode {
+        V128Load => u128::from_le_bytes(read_array(memory, offset)?),
+        V128Load8x8S => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_i16x8(array::from_fn(|i| src[i] as i8 as i16))
+        }
+        V128Load8x8U => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_u16x8(array::from_fn(|i| src[i] as u16))
+        }
+        V128Load16x4S => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_i32x4(array::from_fn(|i| LE::read_i16(&src[i * 2..]) as i32))
+        }
+        V128Load16x4U => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_u32x4(array::from_fn(|i| LE::read_u16(&src[i * 2..]) as u32))
+        }
+        V128Load32x2S => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_i64x2(array::from_fn(|i| LE::read_i32(&src[i * 4..]) as i64))
+        }
+        V128Load32x2U => {
+            let src: [u8; 8] = read_array(memory, offset)?;
+            from_u64x2(array::from_fn(|i| LE::read_u32(&src[i * 4..]) as u64))
+        }
+        V128Load8Splat | V128Load16Splat | V128Load32Splat | V128Load64Splat => {
+            splat(opcode, read_lane(memory, offset, lane_size(opcode))?)
+        }
+        V128Load32Zero => memory.read_u32(offset)? as u128,
+        V128Load64Zero => memory.read_u64(offset)? as u128,
+        _ => return Err(WasmRuntimeError::InvalidBytecode),
+    };
+    Ok(result)
+}
+
+/// Loads a lane from memory into the vector
+pub fn load_lane(
+    opcode: WasmOpcode,
+    memory: &WasmMemory,
+    offset: usize,
+    a: u128,
+    lane: u8,
+) -> Result<u128, WasmRuntimeError> {
+    let raw = read_lane(memory, offset, lane_size(opcode))?;
+    Ok(replace_lane(opcode, a, lane, raw))
+}
+
+/// Stores a lane of the vector to memory
+pub fn store_lane(
+    opcode: WasmOpcode,
+    memory: &WasmMemory,
+    offset: usize,
+    a: u128,
+    lane: u8,
+) -> Result<(), WasmRuntimeError> {
+    let size = lane_size(opcode);
+    let bytes = a.to_le_bytes();
+    let start = lane as usize * size;
+    memory.write_bytes(offset, &bytes[start..start + size])
+}
+
+/// Integer and bitwise operations on the host SIMD unit, which are bit-exact to the scalar ones
+#[cfg(all(feature = "simd-host", target_arch = "x86_64"))]
+mod host {
+    use super::WasmOpcode;
+    use core::arch::x86_64::*;
+    use core::mem::transmute;
+
+    pub fn binary(opcode: WasmOpcode, a: u128, b: u128) -> Option<u128> {
+        use WasmOpcode::*;
+        // SSE2 is always available on x86_64
+        unsafe {
+            let a = transmute::<u128, __m128i>(a);
+            let b = transmute::<u128, __m128i>(b);
+            let result = match opcode {
+                V128And => _mm_and_si128(a, b),
+                V128Andnot => _mm_andnot_si128(b, a),
+                V128Or => _mm_or_si128(a, b),
+                V128Xor => _mm_xor_si128(a, b),
+                I8x16Eq => _mm_cmpeq_epi8(a, b),
+                I8x16GtS => _mm_cmpgt_epi8(a, b),
+                I8x16LtS => _mm_cmplt_epi8(a, b),
+                I16x8Eq => _mm_cmpeq_epi16(a, b),
+                I16x8GtS => _mm_cmpgt_epi16(a, b),
+                I16x8LtS => _mm_cmplt_epi16(a, b),
+                I32x4Eq => _mm_cmpeq_epi32(a, b),
+                I32x4GtS => _mm_cmpgt_epi32(a, b),
+                I32x4LtS => _mm_cmplt_epi32(a, b),
+                I8x16Add => _mm_add_epi8(a, b),
+                I8x16AddSatS => _mm_adds_epi8(a, b),
+                I8x16AddSatU => _mm_adds_epu8(a, b),
+                I8x16Sub => _mm_sub_epi8(a, b),
+                I8x16SubSatS => _mm_subs_epi8(a, b),
+                I8x16SubSatU => _mm_subs_epu8(a, b),
+                I8x16MinU => _mm_min_epu8(a, b),
+                I8x16MaxU => _mm_max_epu8(a, b),
+                I8x16AvgrU => _mm_avg_epu8(a, b),
+                I16x8Add => _mm_add_epi16(a, b),
+                I16x8AddSatS => _mm_adds_epi16(a, b),
+                I16x8AddSatU => _mm_adds_epu16(a, b),
+                I16x8Sub => _mm_sub_epi16(a, b),
+                I16x8SubSatS => _mm_subs_epi16(a, b),
+                I16x8SubSatU => _mm_subs_epu16(a, b),
+                I16x8Mul => _mm_mullo_epi16(a, b),
+                I16x8MinS => _mm_min_epi16(a, b),
+                I16x8MaxS => _mm_max_epi16(a, b),
+                I16x8AvgrU => _mm_avg_epu16(a, b),
+                I32x4Add => _mm_add_epi32(a, b),
+                I32x4Sub => _mm_sub_epi32(a, b),
+                I64x2Add => _mm_add_epi64(a, b),
+                I64x2Sub => _mm_sub_epi64(a, b),
+                I32x4DotI16x8S => _mm_madd_epi16(a, b),
+                _ => {
+                    if is_x86_feature_detected!("sse4.1") {
+                        return binary_sse41(opcode, a, b).map(|v| transmute::<__m128i, u128>(v));
+                    }
+                    return None;
+                }
+            };
+            Some(transmute::<__m128i, u128>(result))
+        }
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn binary_sse41(opcode: WasmOpcode, a: __m128i, b: __m128i) -> Option<__m128i> {
+        use WasmOpcode::*;
+        let result = match opcode {
+            I8x16MinS => _mm_min_epi8(a, b),
+            I8x16MaxS => _mm_max_epi8(a, b),
+            I16x8MinU => _mm_min_epu16(a, b),
+            I16x8MaxU => _mm_max_epu16(a, b),
+            I32x4Mul => _mm_mullo_epi32(a, b),
+            I32x4MinS => _mm_min_epi32(a, b),
+            I32x4MinU => _mm_min_epu32(a, b),
+            I32x4MaxS => _mm_max_epi32(a, b),
+            I32x4MaxU => _mm_max_epu32(a, b),
+            I64x2Eq => _mm_cmpeq_epi64(a, b),
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lanes() {
+        let a = 0x0F0E_0D0C_0B0A_0908_0706_0504_0302_0100;
+        assert_eq!(u8x16(a)[0], 0x00);
+        assert_eq!(u8x16(a)[15], 0x0F);
+        assert_eq!(u32x4(a)[1], 0x0706_0504);
+        assert_eq!(from_u8x16(u8x16(a)), a);
+        assert_eq!(from_f64x2(f64x2(a)), a);
+
+        assert_eq!(
+            splat(WasmOpcode::I16x8Splat, 0x1234),
+            0x1234_1234_1234_1234_1234_1234_1234_1234
+        );
+        assert_eq!(
+            extract_lane(WasmOpcode::I8x16ExtractLaneS, 0xFF << 8, 1),
+            0xFFFF_FFFF
+        );
+        assert_eq!(
+            extract_lane(WasmOpcode::I8x16ExtractLaneU, 0xFF << 8, 1),
+            0xFF
+        );
+        assert_eq!(
+            extract_lane(WasmOpcode::I64x2ExtractLane, a, 1),
+            0x0F0E_0D0C_0B0A_0908
+        );
+        assert_eq!(
+            replace_lane(WasmOpcode::I32x4ReplaceLane, a, 3, 0xFFFF_FFFF_1234_5678),
+            0x1234_5678_0B0A_0908_0706_0504_0302_0100
+        );
+
+        let mut lanes = [0; 16];
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            *lane = (i * 2) as u8;
+        }
+        assert_eq!(
+            shuffle(a, a << 4, &lanes),
+            0xE0C0_A080_6040_2000_0E0C_0A08_0604_0200
+        );
+    }
+
+    #[test]
+    fn arith() {
+        use WasmOpcode::*;
+        let a = from_i8x16([1, -1, 127, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5]);
+        let b = from_i8x16([1, 1, 1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3]);
+        assert_eq!(
+            i8x16(binary(I8x16Add, a, b).unwrap())[..4],
+            [2, 0, -128, 127]
+        );
+        assert_eq!(
+            i8x16(binary(I8x16AddSatS, a, b).unwrap())[..4],
+            [2, 0, 127, -128]
+        );
+        assert_eq!(i8x16(binary(I8x16Eq, a, b).unwrap())[..4], [-1, 0, 0, 0]);
+        assert_eq!(test(I8x16Bitmask, a).unwrap(), 0b1010);
+        assert_eq!(test(I8x16AllTrue, a).unwrap(), 0);
+        assert_eq!(test(V128AnyTrue, a).unwrap(), 1);
+        assert_eq!(
+            i8x16(shift(I8x16ShrS, a, 9).unwrap())[..4],
+            [0, -1, 63, -64]
+        );
+        assert_eq!(
+            u8x16(shift(I8x16ShrU, a, 1).unwrap())[..4],
+            [0, 127, 63, 64]
+        );
+
+        let a = from_i16x8([i16::MIN, 0x4000, 300, -300, 1, 2, 3, 4]);
+        let b = from_i16x8([i16::MIN, 0x4000, 2, 2, 5, 6, 7, 8]);
+        assert_eq!(
+            i16x8(binary(I16x8Q15mulrSatS, a, b).unwrap())[..2],
+            [i16::MAX, 0x2000]
+        );
+        assert_eq!(
+            i8x16(binary(I8x16NarrowI16x8S, a, b).unwrap())[..4],
+            [-128, 127, 127, -128]
+        );
+        assert_eq!(
+            u8x16(binary(I8x16NarrowI16x8U, a, b).unwrap())[..4],
+            [0, 255, 255, 0]
+        );
+        assert_eq!(i32x4(binary(I32x4DotI16x8S, a, b).unwrap())[1], 600 - 600);
+        assert_eq!(i32x4(binary(I32x4DotI16x8S, a, b).unwrap())[2], 5 + 12);
+
+        let a = from_f32x4([0.0, -0.0, f32::NAN, 2.5]);
+        let b = from_f32x4([-0.0, 0.0, 1.0, 1.0]);
+        let min = f32x4(binary(F32x4Min, a, b).unwrap());
+        assert!(min[0].is_sign_negative() && min[1].is_sign_negative() && min[2].is_nan());
+        let max = f32x4(binary(F32x4Max, a, b).unwrap());
+        assert!(max[0].is_sign_positive() && max[1].is_sign_positive() && max[2].is_nan());
+        assert!(f32x4(binary(F32x4Pmin, a, b).unwrap())[2].is_nan());
+        assert_eq!(f32x4(unary(F32x4Nearest, a).unwrap())[3], 2.0);
+        assert_eq!(f32x4(unary(F32x4Neg, a).unwrap())[3], -2.5);
+        assert_eq!(i32x4(unary(I32x4TruncSatF32x4S, a).unwrap()), [0, 0, 0, 2]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "simd-host", target_arch = "x86_64"))]
+    fn host() {
+        let values = [
+            0,
+            u128::MAX,
+            0x8000_7FFF_0001_FFFE_8081_7F7E_0102_FEFD,
+            0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210,
+            0x8000_0000_7FFF_FFFF_8000_0000_0000_0001,
+        ];
+        for code in 0..=0xFF {
+            let opcode = match WasmOpcode::try_from_prefixed(0xFD, code) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            for a in values.iter() {
+                for b in values.iter() {
+                    if let Some(result) = host::binary(opcode, *a, *b) {
+                        assert_eq!(
+                            result,
+                            binary_scalar(opcode, *a, *b).unwrap(),
+                            "{}",
+                            opcode.to_str()
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+// Threads and atomics
+
+use super::opcode::*;
+use super::wasm::*;
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Operands and results of the atomic instructions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AtomicInstruction {
+    /// memarg [i, i32] -> [i32]
+    Notify,
+    /// memarg [i, t, i64] -> [i32]
+    Wait(WasmValType),
+    /// 0x00 [] -> []
+    Fence,
+    /// memarg [i] -> [t], with the access size in bytes
+    Load(WasmValType, usize),
+    /// memarg [i, t] -> []
+    Store(WasmValType, usize),
+    /// memarg [i, t] -> [t]
+    Rmw(WasmValType, usize, WasmAtomicRmw),
+    /// memarg [i, t, t] -> [t]
+    Cmpxchg(WasmValType, usize),
+}
+
+impl AtomicInstruction {
+    /// Types and access sizes of each group of loads, stores and rmw, in the order of the opcodes
+    const SHAPES: [(WasmValType, usize); 7] = [
+        (WasmValType::I32, 4),
+        (WasmValType::I64, 8),
+        (WasmValType::I32, 1),
+        (WasmValType::I32, 2),
+        (WasmValType::I64, 1),
+        (WasmValType::I64, 2),
+        (WasmValType::I64, 4),
+    ];
+
+    const RMW_OPS: [WasmAtomicRmw; 6] = [
+        WasmAtomicRmw::Add,
+        WasmAtomicRmw::Sub,
+        WasmAtomicRmw::And,
+        WasmAtomicRmw::Or,
+        WasmAtomicRmw::Xor,
+        WasmAtomicRmw::Xchg,
+    ];
+
+    pub fn from_opcode(opcode: WasmOpcode) -> Option<Self> {
+        if !matches!(opcode.proposal_type(), WasmProposalType::Threads) {
+            return None;
+        }
+        let index = (opcode as u32 & 0xFF) as usize;
+        match index {
+            0x00 => Some(Self::Notify),
+            0x01 => Some(Self::Wait(WasmValType::I32)),
+            0x02 => Some(Self::Wait(WasmValType::I64)),
+            0x03 => Some(Self::Fence),
+            0x10..=0x16 => {
+                let (val_type, size) = Self::SHAPES[index - 0x10];
+                Some(Self::Load(val_type, size))
+            }
+            0x17..=0x1D => {
+                let (val_type, size) = Self::SHAPES[index - 0x17];
+                Some(Self::Store(val_type, size))
+            }
+            0x1E..=0x47 => {
+                let (val_type, size) = Self::SHAPES[(index - 0x1E) % 7];
+                Some(Self::Rmw(val_type, size, Self::RMW_OPS[(index - 0x1E) / 7]))
+            }
+            0x48..=0x4E => {
+                let (val_type, size) = Self::SHAPES[index - 0x48];
+                Some(Self::Cmpxchg(val_type, size))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the size of the memory access in bytes, which is also the required alignment
+    pub const fn access_size(&self) -> usize {
+        match *self {
+            Self::Notify | Self::Wait(WasmValType::I32) => 4,
+            Self::Wait(_) => 8,
+            Self::Fence => 0,
+            Self::Load(_, size)
+            | Self::Store(_, size)
+            | Self::Rmw(_, size, _)
+            | Self::Cmpxchg(_, size) => size,
+        }
+    }
+}
+
+/// Memory backend shared by the instances on multiple threads
+///
+/// The whole region up to the capacity is reserved at creation so that it never moves,
+/// and every clone refers to the same region.
+/// Give a clone to the allocator of each instance that imports the shared memory.
+#[derive(Clone)]
+pub struct SharedMemoryBackend {
+    region: Arc<SharedRegion>,
+}
+
+struct SharedRegion {
+    base: *mut u8,
+    layout: Layout,
+    len: AtomicUsize,
+    waiter: Box<dyn WasmWaiter + Send + Sync>,
+}
+
+unsafe impl Send for SharedRegion {}
+
+unsafe impl Sync for SharedRegion {}
+
+impl Drop for SharedRegion {
+    fn drop(&mut self) {
+        unsafe {
+            dealloc(self.base, self.layout);
+        }
+    }
+}
+
+impl SharedMemoryBackend {
+    /// Creates the region of `capacity` bytes with the default waiter
+    #[inline]
+    pub fn new(capacity: usize) -> Result<Self, WasmRuntimeError> {
+        Self::with_waiter(capacity, Box::new(StdWaiter::new()))
+    }
+
+    /// Creates the region of `capacity` bytes with the waiter of the host
+    pub fn with_waiter(
+        capacity: usize,
+        waiter: Box<dyn WasmWaiter + Send + Sync>,
+    ) -> Result<Self, WasmRuntimeError> {
+        // Atomic accesses require the base to be aligned to the largest access
+        let layout = Layout::from_size_align(capacity.max(8), 8)
+            .map_err(|_| WasmRuntimeError::OutOfMemory)?;
+        let base = unsafe { alloc_zeroed(layout) };
+        if base.is_null() {
+            return Err(WasmRuntimeError::OutOfMemory);
+        }
+        Ok(Self {
+            region: Arc::new(SharedRegion {
+                base,
+                layout,
+                len: AtomicUsize::new(0),
+                waiter,
+            }),
+        })
+    }
+
+    /// Returns the size of the reserved region
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.region.layout.size()
+    }
+}
+
+unsafe impl MemoryBackend for SharedMemoryBackend {
+    /// Keeps the contents if another instance has already allocated the region
+    fn allocate(&mut self, size: usize) -> Result<(), WasmRuntimeError> {
+        if size > self.capacity() {
+            return Err(WasmRuntimeError::OutOfMemory);
+        }
+        self.region.len.fetch_max(size, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn grow(&mut self, delta: usize, max_size: usize) -> Result<usize, WasmRuntimeError> {
+        let max_size = max_size.min(self.capacity());
+        // Another instance may grow the region at the same time
+        let mut old_size = self.region.len.load(Ordering::SeqCst);
+        loop {
+            let new_size = match old_size.checked_add(delta) {
+                Some(v) if v <= max_size => v,
+                _ => return Err(WasmRuntimeError::OutOfMemory),
+            };
+            // The area beyond the length has never been written since zero-filled
+            match self.region.len.compare_exchange_weak(
+                old_size,
+                new_size,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return Ok(old_size),
+                Err(v) => old_size = v,
+            }
+        }
+    }
+
+    #[inline]
+    fn base_ptr(&self) -> *mut u8 {
+        self.region.base
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.region.len.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    fn waiter(&self) -> Option<&dyn WasmWaiter> {
+        Some(self.region.waiter.as_ref())
+    }
+}
+
+/// Default waiter that blocks the threads of `std`
+#[derive(Default)]
+pub struct StdWaiter {
+    queue: Mutex<WaitQueue>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct WaitQueue {
+    next_ticket: u64,
+    waiting: Vec<Waiting>,
+}
+
+struct Waiting {
+    address: usize,
+    ticket: u64,
+    is_woken: bool,
+}
+
+impl StdWaiter {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl WasmWaiter for StdWaiter {
+    fn wait(&self, address: usize, is_expected: &dyn Fn() -> bool, timeout: i64) -> WasmWaitResult {
+        let mut queue = self.queue.lock().unwrap();
+        if !is_expected() {
+            return WasmWaitResult::NotEqual;
+        }
+        let ticket = queue.next_ticket;
+        queue.next_ticket += 1;
+        queue.waiting.push(Waiting {
+            address,
+            ticket,
+            is_woken: false,
+        });
+        let deadline = u64::try_from(timeout)
+            .ok()
+            .map(|v| Instant::now() + Duration::from_nanos(v));
+        loop {
+            let index = queue
+                .waiting
+                .iter()
+                .position(|v| v.ticket == ticket)
+                .unwrap();
+            if queue.waiting[index].is_woken {
+                queue.waiting.remove(index);
+                return WasmWaitResult::Ok;
+            }
+            queue = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        queue.waiting.remove(index);
+                        return WasmWaitResult::TimedOut;
+                    }
+                    self.condvar.wait_timeout(queue, deadline - now).unwrap().0
+                }
+                None => self.condvar.wait(queue).unwrap(),
+            };
+        }
+    }
+
+    fn notify(&self, address: usize, count: u32) -> u32 {
+        let mut queue = self.queue.lock().unwrap();
+        let mut woken = 0;
+        for waiting in queue
+            .waiting
+            .iter_mut()
+            .filter(|v| v.address == address && !v.is_woken)
+            .take(count as usize)
+        {
+            waiting.is_woken = true;
+            woken += 1;
+        }
+        if woken > 0 {
+            self.condvar.notify_all();
+        }
+        woken
+    }
+}
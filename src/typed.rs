@@ -0,0 +1,497 @@
+// Typed function call API
+
+use super::wasm::*;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Rust types that correspond to a value type of WebAssembly
+pub trait WasmNativeType: Copy + Into<WasmValue> {
+    const VAL_TYPE: WasmValType;
+
+    fn from_value(value: WasmValue) -> Result<Self, WasmRuntimeError>;
+}
+
+macro_rules! native_type {
+    ($type:ty, $val_type:ident, $getter:ident) => {
+        impl WasmNativeType for $type {
+            const VAL_TYPE: WasmValType = WasmValType::$val_type;
+
+            #[inline]
+            fn from_value(value: WasmValue) -> Result<Self, WasmRuntimeError> {
+                value.$getter()
+            }
+        }
+    };
+}
+
+native_type!(i32, I32, get_i32);
+native_type!(u32, I32, get_u32);
+native_type!(i64, I64, get_i64);
+native_type!(u64, I64, get_u64);
+native_type!(f32, F32, get_f32);
+native_type!(f64, F64, get_f64);
+native_type!(u128, V128, get_v128);
+
+/// Parameters of the typed function, a native type or a tuple of native types
+pub trait WasmParams {
+    fn val_types() -> Vec<WasmValType>;
+
+    fn into_values(self) -> Vec<WasmValue>;
+}
+
+/// Results of the typed function, `()` or a native type
+pub trait WasmResults: Sized {
+    fn val_types() -> Vec<WasmValType>;
+
+    fn from_value(value: WasmValue) -> Result<Self, WasmRuntimeError>;
+}
+
+impl<T: WasmNativeType> WasmParams for T {
+    #[inline]
+    fn val_types() -> Vec<WasmValType> {
+        alloc::vec![T::VAL_TYPE]
+    }
+
+    #[inline]
+    fn into_values(self) -> Vec<WasmValue> {
+        alloc::vec![self.into()]
+    }
+}
+
+macro_rules! tuple_params {
+    ($($name:ident),*) => {
+        impl<$($name: WasmNativeType),*> WasmParams for ($($name,)*) {
+            #[inline]
+            fn val_types() -> Vec<WasmValType> {
+                alloc::vec![$($name::VAL_TYPE),*]
+            }
+
+            #[inline]
+            #[allow(non_snake_case)]
+            fn into_values(self) -> Vec<WasmValue> {
+                let ($($name,)*) = self;
+                alloc::vec![$($name.into()),*]
+            }
+        }
+    };
+}
+
+tuple_params!();
+tuple_params!(A);
+tuple_params!(A, B);
+tuple_params!(A, B, C);
+tuple_params!(A, B, C, D);
+tuple_params!(A, B, C, D, E);
+tuple_params!(A, B, C, D, E, F);
+tuple_params!(A, B, C, D, E, F, G);
+tuple_params!(A, B, C, D, E, F, G, H);
+
+impl WasmResults for () {
+    #[inline]
+    fn val_types() -> Vec<WasmValType> {
+        Vec::new()
+    }
+
+    #[inline]
+    fn from_value(_value: WasmValue) -> Result<Self, WasmRuntimeError> {
+        Ok(())
+    }
+}
+
+impl<T: WasmNativeType> WasmResults for T {
+    #[inline]
+    fn val_types() -> Vec<WasmValType> {
+        alloc::vec![T::VAL_TYPE]
+    }
+
+    #[inline]
+    fn from_value(value: WasmValue) -> Result<Self, WasmRuntimeError> {
+        T::from_value(value)
+    }
+}
+
+/// A function whose signature has been checked against the native types
+pub struct WasmTypedFunc<'a, P, R> {
+    runnable: WasmRunnable<'a>,
+    _phantom: PhantomData<fn(P) -> R>,
+}
+
+impl<'a, P: WasmParams, R: WasmResults> WasmTypedFunc<'a, P, R> {
+    /// Returns [WasmRuntimeError::TypeMismatch] if the signature of the function does not match
+    pub fn new(runnable: WasmRunnable<'a>) -> Result<Self, WasmRuntimeError> {
+        let function = runnable.function();
+        if function.param_types() != P::val_types().as_slice()
+            || function.result_types() != R::val_types().as_slice()
+        {
+            return Err(WasmRuntimeError::TypeMismatch);
+        }
+        Ok(Self {
+            runnable,
+            _phantom: PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn call(&self, params: P) -> Result<R, WasmRuntimeError> {
+        self.runnable
+            .invoke(params.into_values().as_slice())
+            .and_then(R::from_value)
+    }
+}
+
+impl<P, R> Clone for WasmTypedFunc<'_, P, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P, R> Copy for WasmTypedFunc<'_, P, R> {}
+
+/// Results of the host function, `()`, a native type, or a `Result` of them
+///
+/// A host function returns at most one value, since multi-value is not supported.
+pub trait WasmHostResult {
+    fn val_type() -> Option<WasmValType>;
+
+    fn into_result(self) -> Result<WasmValue, WasmRuntimeError>;
+}
+
+impl WasmHostResult for () {
+    #[inline]
+    fn val_type() -> Option<WasmValType> {
+        None
+    }
+
+    #[inline]
+    fn into_result(self) -> Result<WasmValue, WasmRuntimeError> {
+        Ok(WasmValue::Empty)
+    }
+}
+
+impl<T: WasmNativeType> WasmHostResult for T {
+    #[inline]
+    fn val_type() -> Option<WasmValType> {
+        Some(T::VAL_TYPE)
+    }
+
+    #[inline]
+    fn into_result(self) -> Result<WasmValue, WasmRuntimeError> {
+        Ok(self.into())
+    }
+}
+
+impl<T: WasmHostResult> WasmHostResult for Result<T, WasmRuntimeError> {
+    #[inline]
+    fn val_type() -> Option<WasmValType> {
+        T::val_type()
+    }
+
+    #[inline]
+    fn into_result(self) -> Result<WasmValue, WasmRuntimeError> {
+        self.and_then(T::into_result)
+    }
+}
+
+/// Host function with the type derived from its Rust signature
+#[derive(Clone)]
+pub struct WasmHostFunc {
+    func_type: WasmType,
+    func: Rc<WasmHostFn>,
+}
+
+impl WasmHostFunc {
+    #[inline]
+    pub const fn func_type(&self) -> &WasmType {
+        &self.func_type
+    }
+
+    #[inline]
+    pub fn func(&self) -> Rc<WasmHostFn> {
+        self.func.clone()
+    }
+}
+
+/// Rust functions and closures that can be host functions
+///
+/// The first parameter is the caller, followed by the native types.
+pub trait WasmIntoHostFunc<Args, R> {
+    fn into_host_func(self) -> WasmHostFunc;
+}
+
+macro_rules! host_func {
+    ($($name:ident),*) => {
+        impl<Func, R, $($name),*> WasmIntoHostFunc<($($name,)*), R> for Func
+        where
+            Func: Fn(&WasmCaller, $($name),*) -> R + 'static,
+            R: WasmHostResult,
+            $($name: WasmNativeType,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_host_func(self) -> WasmHostFunc {
+                let func_type = WasmType::new(
+                    alloc::vec![$($name::VAL_TYPE),*],
+                    R::val_type().into_iter().collect(),
+                );
+                let func = move |caller: &WasmCaller, params: &[WasmValue]| {
+                    let mut params = params.iter().copied();
+                    $(
+                        let $name = params
+                            .next()
+                            .ok_or(WasmRuntimeError::InvalidParameter)
+                            .and_then($name::from_value)?;
+                    )*
+                    self(caller, $($name),*).into_result()
+                };
+                WasmHostFunc {
+                    func_type,
+                    func: Rc::new(func),
+                }
+            }
+        }
+    };
+}
+
+host_func!();
+host_func!(A);
+host_func!(A, B);
+host_func!(A, B, C);
+host_func!(A, B, C, D);
+host_func!(A, B, C, D, E);
+host_func!(A, B, C, D, E, F);
+host_func!(A, B, C, D, E, F, G);
+host_func!(A, B, C, D, E, F, G, H);
+host_func!(A, B, C, D, E, F, G, H, I);
+host_func!(A, B, C, D, E, F, G, H, I, J);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_func() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0A, 0x02, 0x60, 0x02, 0x7F,
+            0x7E, 0x01, 0x7F, 0x60, 0x00, 0x00, 0x03, 0x03, 0x02, 0x00, 0x01, 0x07, 0x0D, 0x02,
+            0x03, 0x61, 0x64, 0x64, 0x00, 0x00, 0x03, 0x6E, 0x6F, 0x70, 0x00, 0x01, 0x0A, 0x0D,
+            0x02, 0x08, 0x00, 0x20, 0x00, 0x20, 0x01, 0xA7, 0x6A, 0x0B, 0x02, 0x00, 0x0B,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        let add = module.typed_func::<(i32, i64), i32>("add").unwrap();
+        assert_eq!(add.call((1234, 5678)).unwrap(), 6912);
+        assert_eq!(add.call((-1, 0x1_0000_0002)).unwrap(), 1);
+
+        let add = module.typed_func::<(u32, u64), u32>("add").unwrap();
+        assert_eq!(add.call((0xFFFF_FFFF, 2)).unwrap(), 1);
+
+        let nop = module.typed_func::<(), ()>("nop").unwrap();
+        nop.call(()).unwrap();
+
+        assert!(matches!(
+            module.typed_func::<(i32, i32), i32>("add"),
+            Err(WasmRuntimeError::TypeMismatch)
+        ));
+        assert!(matches!(
+            module.typed_func::<(i32, i64), ()>("add"),
+            Err(WasmRuntimeError::TypeMismatch)
+        ));
+        assert!(matches!(
+            module.typed_func::<i32, ()>("nop"),
+            Err(WasmRuntimeError::TypeMismatch)
+        ));
+        assert!(matches!(
+            module.typed_func::<(), ()>("missing"),
+            Err(WasmRuntimeError::NoMethod)
+        ));
+    }
+
+    #[test]
+    fn host_func() {
+        use alloc::rc::Rc;
+        use core::cell::Cell;
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x14, 0x04, 0x60, 0x02, 0x7F,
+            0x7E, 0x01, 0x7E, 0x60, 0x01, 0x7C, 0x01, 0x7C, 0x60, 0x00, 0x00, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x02, 0x2E, 0x04, 0x03, 0x65, 0x6E, 0x76, 0x03, 0x61, 0x64, 0x64, 0x00,
+            0x00, 0x03, 0x65, 0x6E, 0x76, 0x05, 0x73, 0x63, 0x61, 0x6C, 0x65, 0x00, 0x01, 0x03,
+            0x65, 0x6E, 0x76, 0x04, 0x66, 0x61, 0x69, 0x6C, 0x00, 0x02, 0x03, 0x65, 0x6E, 0x76,
+            0x05, 0x63, 0x6F, 0x75, 0x6E, 0x74, 0x00, 0x03, 0x03, 0x05, 0x04, 0x00, 0x01, 0x02,
+            0x03, 0x07, 0x1E, 0x04, 0x03, 0x61, 0x64, 0x64, 0x00, 0x04, 0x05, 0x73, 0x63, 0x61,
+            0x6C, 0x65, 0x00, 0x05, 0x04, 0x66, 0x61, 0x69, 0x6C, 0x00, 0x06, 0x05, 0x63, 0x6F,
+            0x75, 0x6E, 0x74, 0x00, 0x07, 0x0A, 0x1D, 0x04, 0x08, 0x00, 0x20, 0x00, 0x20, 0x01,
+            0x10, 0x00, 0x0B, 0x06, 0x00, 0x20, 0x00, 0x10, 0x01, 0x0B, 0x04, 0x00, 0x10, 0x02,
+            0x0B, 0x06, 0x00, 0x20, 0x00, 0x10, 0x03, 0x0B,
+        ];
+        fn add(_: &WasmCaller, a: i32, b: i64) -> i64 {
+            a as i64 + b
+        }
+        let counter = Rc::new(Cell::new(0));
+        let count = counter.clone();
+
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader
+            .define_func("env", "add", add)
+            .define_func("env", "scale", |_: &WasmCaller, a: f64| a * 2.5)
+            .define_func("env", "fail", |_: &WasmCaller| {
+                Err::<(), _>(WasmRuntimeError::InvalidParameter)
+            })
+            .define_func("env", "count", move |_: &WasmCaller, a: u32| {
+                count.set(count.get() + a);
+                count.get()
+            });
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let module = loader.into_module();
+
+        let add = module.typed_func::<(i32, i64), i64>("add").unwrap();
+        assert_eq!(add.call((-1, 0x1_0000_0000)).unwrap(), 0xFFFF_FFFF);
+
+        let scale = module.typed_func::<f64, f64>("scale").unwrap();
+        assert_eq!(scale.call(1.5).unwrap(), 3.75);
+
+        let fail = module.typed_func::<(), ()>("fail").unwrap();
+        assert!(matches!(
+            fail.call(()),
+            Err(WasmRuntimeError::InvalidParameter)
+        ));
+
+        let count = module.typed_func::<u32, u32>("count").unwrap();
+        assert_eq!(count.call(3).unwrap(), 3);
+        assert_eq!(count.call(4).unwrap(), 7);
+        assert_eq!(counter.get(), 7);
+
+        // the type of the host function does not match the import, which the loader names
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader.define_func("env", "add", |_: &WasmCaller, a: i32, b: i32| a + b);
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+        assert_eq!(loader.error_import(), Some(("env", "add")));
+
+        // neither defined nor resolved
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::DynamicLinkError)
+        ));
+        assert_eq!(loader.error_import(), Some(("env", "add")));
+    }
+
+    #[test]
+    fn caller() {
+        use alloc::string::String;
+        use core::cell::RefCell;
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x10, 0x03, 0x60, 0x02, 0x7F,
+            0x7F, 0x01, 0x7F, 0x60, 0x01, 0x7F, 0x01, 0x7F, 0x60, 0x00, 0x01, 0x7F, 0x02, 0x0D,
+            0x01, 0x03, 0x65, 0x6E, 0x76, 0x05, 0x67, 0x72, 0x65, 0x65, 0x74, 0x00, 0x00, 0x03,
+            0x03, 0x02, 0x01, 0x02, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x18, 0x03, 0x06, 0x6D,
+            0x65, 0x6D, 0x6F, 0x72, 0x79, 0x02, 0x00, 0x05, 0x74, 0x77, 0x69, 0x63, 0x65, 0x00,
+            0x01, 0x03, 0x72, 0x75, 0x6E, 0x00, 0x02, 0x0A, 0x12, 0x02, 0x07, 0x00, 0x20, 0x00,
+            0x20, 0x00, 0x6A, 0x0B, 0x08, 0x00, 0x41, 0x10, 0x41, 0x05, 0x10, 0x00, 0x0B, 0x0B,
+            0x0B, 0x01, 0x00, 0x41, 0x10, 0x0B, 0x05, 0x68, 0x65, 0x6C, 0x6C, 0x6F,
+        ];
+        fn greet(caller: &WasmCaller, ptr: u32, len: u32) -> Result<u32, WasmRuntimeError> {
+            // only the exports of the caller are reachable, with their own types
+            assert!(caller.memory("twice").is_none());
+            assert!(matches!(
+                caller.func("greet"),
+                Err(WasmRuntimeError::NoMethod)
+            ));
+            assert!(matches!(
+                caller.typed_func::<(), u32>("twice"),
+                Err(WasmRuntimeError::TypeMismatch)
+            ));
+
+            let memory = caller
+                .memory("memory")
+                .ok_or(WasmRuntimeError::OutOfMemory)?;
+            let bytes = memory.read_bytes(ptr as usize, len as usize)?;
+            let s = core::str::from_utf8(bytes).map_err(|_| WasmRuntimeError::InvalidParameter)?;
+            caller
+                .data::<RefCell<String>>()
+                .ok_or(WasmRuntimeError::InvalidParameter)?
+                .borrow_mut()
+                .push_str(s);
+            caller.typed_func::<u32, u32>("twice")?.call(len)
+        }
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader
+            .set_host_data(RefCell::new(String::new()))
+            .define_func("env", "greet", greet);
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let module = loader.into_module();
+
+        let run = module.typed_func::<(), i32>("run").unwrap();
+        assert_eq!(run.call(()).unwrap(), 10);
+        assert_eq!(run.call(()).unwrap(), 10);
+        assert_eq!(
+            module
+                .host_data::<RefCell<String>>()
+                .unwrap()
+                .borrow()
+                .as_str(),
+            "hellohello"
+        );
+        assert!(module.host_data::<String>().is_none());
+        assert!(module.memory_by_name("memory").is_some());
+        assert!(module.memory_by_name("twice").is_none());
+
+        // the data of another type, or of another instance, is not visible to the caller
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader
+            .set_host_data(String::new())
+            .define_func("env", "greet", greet);
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let other = loader.into_module();
+        let run = other.typed_func::<(), i32>("run").unwrap();
+        assert!(matches!(
+            run.call(()),
+            Err(WasmRuntimeError::InvalidParameter)
+        ));
+        assert_eq!(
+            module
+                .host_data::<RefCell<String>>()
+                .unwrap()
+                .borrow()
+                .as_str(),
+            "hellohello"
+        );
+    }
+}
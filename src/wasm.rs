@@ -1,27 +1,211 @@
 // WebAssembly Loader
 
 use super::opcode::*;
+#[cfg(feature = "simd")]
+use super::simd::SimdSignature;
+#[cfg(feature = "threads")]
+use super::threads::AtomicInstruction;
+use super::typed::*;
 use super::wasmintr::*;
 use crate::*;
-use alloc::collections::BTreeMap;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
 use alloc::string::*;
-use alloc::sync::Arc;
 use alloc::vec::Vec;
 use bitflags::*;
 use byteorder::*;
+use core::any::Any;
 use core::cell::{RefCell, UnsafeCell};
 use core::convert::TryFrom;
 use core::fmt;
 use core::ops::*;
+use core::ptr::NonNull;
 use core::slice;
 use core::str;
+use core::sync::atomic::*;
 
 pub struct WasmLoader {
     module: WasmModule,
+    host_funcs: Vec<(String, String, WasmHostFunc)>,
+    host_memories: Vec<(String, String, Box<dyn MemoryBackend>)>,
+    run_start: bool,
+    decode_only: bool,
+    error_location: Option<(&'static str, usize)>,
+    error_import: Option<(String, String)>,
+}
+
+bitflags! {
+    /// Proposals accepted by the loader
+    ///
+    /// The proposals gated by cargo features can be enabled only if the feature is compiled in.
+    pub struct WasmFeatures: u32 {
+        /// `float` feature
+        const FLOAT                     = 0b0000_0000_0000_0001;
+        /// `sign-ext` feature
+        const SIGN_EXTENSION            = 0b0000_0000_0000_0010;
+        /// `bulk-memory` feature
+        const BULK_MEMORY               = 0b0000_0000_0000_0100;
+        /// `reference-types` feature
+        const REFERENCE_TYPES           = 0b0000_0000_0000_1000;
+        /// `multi-value` feature
+        const MULTI_VALUE               = 0b0000_0000_0001_0000;
+        /// `tail-call` feature
+        const TAIL_CALL                 = 0b0000_0000_0010_0000;
+        /// `simd` feature
+        const SIMD                      = 0b0000_0000_0100_0000;
+        /// `threads` feature
+        const THREADS                   = 0b0000_0000_1000_0000;
+        const NON_TRAPPING_FLOAT_TO_INT = 0b0000_0001_0000_0000;
+        const MEMORY64                  = 0b0000_0010_0000_0000;
+        const MULTI_MEMORY              = 0b0000_0100_0000_0000;
+        const EXTENDED_CONST            = 0b0000_1000_0000_0000;
+        /// `exceptions` feature
+        const EXCEPTIONS                = 0b0001_0000_0000_0000;
+        /// `mutable-globals` feature
+        const MUTABLE_GLOBALS           = 0b0010_0000_0000_0000;
+    }
+}
+
+impl WasmFeatures {
+    /// Proposals compiled in
+    pub const fn available() -> Self {
+        let mut features = Self::NON_TRAPPING_FLOAT_TO_INT
+            .union(Self::MEMORY64)
+            .union(Self::MULTI_MEMORY)
+            .union(Self::EXTENDED_CONST);
+        if cfg!(feature = "float") {
+            features = features.union(Self::FLOAT);
+        }
+        if cfg!(feature = "sign-ext") {
+            features = features.union(Self::SIGN_EXTENSION);
+        }
+        if cfg!(feature = "bulk-memory") {
+            features = features.union(Self::BULK_MEMORY);
+        }
+        if cfg!(feature = "reference-types") {
+            features = features.union(Self::REFERENCE_TYPES);
+        }
+        if cfg!(feature = "multi-value") {
+            features = features.union(Self::MULTI_VALUE);
+        }
+        if cfg!(feature = "tail-call") {
+            features = features.union(Self::TAIL_CALL);
+        }
+        if cfg!(feature = "simd") {
+            features = features.union(Self::SIMD);
+        }
+        if cfg!(feature = "threads") {
+            features = features.union(Self::THREADS);
+        }
+        if cfg!(feature = "exceptions") {
+            features = features.union(Self::EXCEPTIONS);
+        }
+        if cfg!(feature = "mutable-globals") {
+            features = features.union(Self::MUTABLE_GLOBALS);
+        }
+        features
+    }
+
+    /// Returns the proposal required by the opcodes of the type
+    pub const fn from_proposal(proposal: WasmProposalType) -> Self {
+        match proposal {
+            WasmProposalType::Mvp | WasmProposalType::MvpI64 => Self::empty(),
+            WasmProposalType::MvpF32 | WasmProposalType::MvpF64 => Self::FLOAT,
+            WasmProposalType::TailCall => Self::TAIL_CALL,
+            WasmProposalType::SignExtend => Self::SIGN_EXTENSION,
+            WasmProposalType::BulkMemory => Self::BULK_MEMORY,
+            WasmProposalType::NonTrappingFloatToInt => Self::NON_TRAPPING_FLOAT_TO_INT,
+            WasmProposalType::ReferenceTypes => Self::REFERENCE_TYPES,
+            WasmProposalType::Simd => Self::SIMD,
+            WasmProposalType::Threads => Self::THREADS,
+            WasmProposalType::Exceptions => Self::EXCEPTIONS,
+        }
+    }
+
+    /// Names of the proposals, the same as the cargo features if any
+    const NAMES: [(Self, &'static str); 14] = [
+        (Self::FLOAT, "float"),
+        (Self::SIGN_EXTENSION, "sign-ext"),
+        (Self::BULK_MEMORY, "bulk-memory"),
+        (Self::REFERENCE_TYPES, "reference-types"),
+        (Self::MULTI_VALUE, "multi-value"),
+        (Self::TAIL_CALL, "tail-call"),
+        (Self::SIMD, "simd"),
+        (Self::THREADS, "threads"),
+        (Self::NON_TRAPPING_FLOAT_TO_INT, "nontrapping-fptoint"),
+        (Self::MEMORY64, "memory64"),
+        (Self::MULTI_MEMORY, "multi-memory"),
+        (Self::EXTENDED_CONST, "extended-const"),
+        (Self::EXCEPTIONS, "exceptions"),
+        (Self::MUTABLE_GLOBALS, "mutable-globals"),
+    ];
+
+    /// Returns the proposal of the name, such as "simd" or "memory64"
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(_, v)| *v == name)
+            .map(|(feature, _)| *feature)
+    }
+
+    /// Fails with the missing proposals unless all of the required ones are enabled
+    #[inline]
+    pub fn require(&self, required: Self) -> Result<(), WasmDecodeError> {
+        let missing = required - *self;
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(WasmDecodeError::FeatureNotEnabled(missing))
+        }
+    }
+
+    /// Checks the proposals required to use the value types
+    fn require_val_types(&self, val_types: &[WasmValType]) -> Result<(), WasmDecodeError> {
+        if val_types.iter().any(|v| v.is_ref()) {
+            self.require(Self::REFERENCE_TYPES)?;
+        }
+        if val_types.contains(&WasmValType::V128) {
+            self.require(Self::SIMD)?;
+        }
+        if val_types.contains(&WasmValType::ExnRef) {
+            self.require(Self::EXCEPTIONS)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for WasmFeatures {
+    fn default() -> Self {
+        Self::available()
+    }
+}
+
+impl fmt::Display for WasmFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut is_first = true;
+        for (feature, name) in Self::NAMES.iter() {
+            if self.contains(*feature) {
+                if !is_first {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", name)?;
+                is_first = false;
+            }
+        }
+        Ok(())
+    }
 }
 
 pub type WasmDynFunc = fn(&WasmModule, &[WasmValue]) -> Result<WasmValue, WasmRuntimeError>;
+/// Host function of any kind, including closures
+pub type WasmHostFn = dyn Fn(&WasmCaller, &[WasmValue]) -> Result<WasmValue, WasmRuntimeError>;
+/// Hook called before each instruction of the guest functions, see [WasmModule::set_debug_hook]
+pub type WasmDebugHook = dyn Fn(&WasmFrame) -> Result<(), WasmRuntimeError>;
 pub type WasmImportResolver = dyn Fn(&str, &str, &WasmType) -> Result<WasmDynFunc, WasmDecodeError>;
+pub type WasmGlobalResolver =
+    dyn Fn(&str, &str, WasmValType, bool) -> Result<WasmGlobal, WasmDecodeError>;
+pub type WasmMemoryAllocator = dyn Fn(WasmLimit) -> Result<Box<dyn MemoryBackend>, WasmDecodeError>;
 
 impl WasmLoader {
     /// Minimal valid module size, Magic(4) + Version(4) + Empty sections(0) = 8
@@ -34,7 +218,81 @@ impl WasmLoader {
     pub(super) fn new() -> Self {
         Self {
             module: WasmModule::new(),
+            host_funcs: Vec::new(),
+            host_memories: Vec::new(),
+            run_start: true,
+            decode_only: false,
+            error_location: None,
+            error_import: None,
+        }
+    }
+
+    /// Creates a loader which accepts only the specified proposals, within the ones compiled in
+    pub fn with_features(features: WasmFeatures) -> Self {
+        let mut module = WasmModule::new();
+        module.features = features & WasmFeatures::available();
+        Self {
+            module,
+            host_funcs: Vec::new(),
+            host_memories: Vec::new(),
+            run_start: true,
+            decode_only: false,
+            error_location: None,
+            error_import: None,
+        }
+    }
+
+    /// Defines the host function for the import, which takes precedence over the resolver
+    ///
+    /// The type of the function is derived from the Rust types of its parameters and result,
+    /// and must match the type declared by the import.
+    ///
+    /// ```ignore
+    /// loader.define_func("env", "add", |_: &WasmCaller, a: i32, b: i64| a as i64 + b);
+    /// ```
+    pub fn define_func<Args, R, F>(&mut self, mod_name: &str, name: &str, func: F) -> &mut Self
+    where
+        F: WasmIntoHostFunc<Args, R>,
+    {
+        let func = func.into_host_func();
+        match self
+            .host_funcs
+            .iter_mut()
+            .find(|v| v.0 == mod_name && v.1 == name)
+        {
+            Some(v) => v.2 = func,
+            None => self
+                .host_funcs
+                .push((mod_name.to_string(), name.to_string(), func)),
+        }
+        self
+    }
+
+    /// Defines the backend of the imported memory
+    ///
+    /// A backend shared between threads, such as `SharedMemoryBackend`, shares the memory
+    /// with the other instances which import it. The backend is used by the next load only.
+    pub fn define_memory<B>(&mut self, mod_name: &str, name: &str, backend: B) -> &mut Self
+    where
+        B: MemoryBackend + 'static,
+    {
+        let backend = Box::new(backend);
+        match self
+            .host_memories
+            .iter_mut()
+            .find(|v| v.0 == mod_name && v.1 == name)
+        {
+            Some(v) => v.2 = backend,
+            None => self
+                .host_memories
+                .push((mod_name.to_string(), name.to_string(), backend)),
         }
+        self
+    }
+
+    #[inline]
+    pub const fn features(&self) -> WasmFeatures {
+        self.module.features
     }
 
     /// Identify the file format
@@ -48,29 +306,85 @@ impl WasmLoader {
     pub fn instantiate(
         blob: &[u8],
         resolver: &WasmImportResolver,
+    ) -> Result<WasmModule, WasmDecodeError> {
+        Self::instantiate_with_allocator(blob, resolver, &VecMemoryBackend::allocator)
+    }
+
+    /// Instantiate wasm modules from slice, using the specified allocator for linear memories
+    pub fn instantiate_with_allocator(
+        blob: &[u8],
+        resolver: &WasmImportResolver,
+        allocator: &WasmMemoryAllocator,
+    ) -> Result<WasmModule, WasmDecodeError> {
+        Self::instantiate_with_imports(
+            blob,
+            resolver,
+            &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+            allocator,
+        )
+    }
+
+    /// Instantiate wasm modules from slice, with the resolver for imported globals
+    pub fn instantiate_with_imports(
+        blob: &[u8],
+        resolver: &WasmImportResolver,
+        global_resolver: &WasmGlobalResolver,
+        allocator: &WasmMemoryAllocator,
     ) -> Result<WasmModule, WasmDecodeError> {
         if Self::identity(blob) {
             let mut loader = Self::new();
-            loader.load(blob, resolver).map(|_| loader.module)
+            loader
+                .load(blob, resolver, global_resolver, allocator)
+                .map(|_| loader.module)
         } else {
-            return Err(WasmDecodeError::BadExecutable);
+            Err(WasmDecodeError::BadExecutable)
         }
     }
 
-    pub(super) fn load(
+    /// Loads the module to the loader, see also `into_module`
+    pub fn load(
         &mut self,
         blob: &[u8],
         resolver: &WasmImportResolver,
+        global_resolver: &WasmGlobalResolver,
+        allocator: &WasmMemoryAllocator,
     ) -> Result<(), WasmDecodeError> {
+        if !Self::identity(blob) {
+            return Err(WasmDecodeError::BadExecutable);
+        }
+        self.error_location = None;
+        self.error_import = None;
         let mut blob = Leb128Stream::from_slice(&blob[8..]);
-        while let Some(mut section) = blob.next_section()? {
+        loop {
+            let position = blob.position();
+            let mut section = match blob.next_section() {
+                Ok(Some(v)) => v,
+                Ok(None) => break,
+                Err(err) => {
+                    self.error_location = Some(("section header", 8 + position));
+                    return Err(err);
+                }
+            };
             // println!("parse section {:?}", section.section_type);
-            match section.section_type {
+            let mut info = WasmSectionInfo {
+                section_type: section.section_type,
+                offset: 8 + section.offset,
+                size: section.stream.blob.len(),
+                custom_name: None,
+            };
+            let offset = info.offset;
+            if section.section_type == WasmSectionType::Custom {
+                info.custom_name = section.stream.get_string().ok().map(|v| v.to_string());
+            }
+            self.module.sections.push(info);
+            let result = match section.section_type {
                 WasmSectionType::Custom => Ok(()),
                 WasmSectionType::Type => self.parse_sec_type(&mut section),
-                WasmSectionType::Import => self.parse_sec_import(&mut section, resolver),
+                WasmSectionType::Import => {
+                    self.parse_sec_import(&mut section, resolver, global_resolver)
+                }
                 WasmSectionType::Table => self.parse_sec_table(&mut section),
-                WasmSectionType::Memory => self.parse_sec_memory(&mut section),
+                WasmSectionType::Memory => self.parse_sec_memory(&mut section, allocator),
                 WasmSectionType::Element => self.parse_sec_elem(&mut section),
                 WasmSectionType::Function => self.parse_sec_func(&mut section),
                 WasmSectionType::Export => self.parse_sec_export(&mut section),
@@ -78,16 +392,105 @@ impl WasmLoader {
                 WasmSectionType::Data => self.parse_sec_data(&mut section),
                 WasmSectionType::Start => self.parse_sec_start(&mut section),
                 WasmSectionType::Global => self.parse_sec_global(&mut section),
+                WasmSectionType::DataCount => self.parse_sec_data_count(&mut section),
+                WasmSectionType::Tag => self.parse_sec_tag(&mut section),
                 // _ => Err(WasmDecodeError::UnexpectedToken),
-            }?;
+            };
+            if let Err(err) = result {
+                self.error_location = Some((
+                    section.section_type.name(),
+                    offset + section.stream.position(),
+                ));
+                return Err(err);
+            }
+        }
+        if self.run_start && !self.decode_only && self.module.start.is_some() {
+            self.module
+                .entry_point()
+                .and_then(|v| v.invoke(&[]))
+                .map_err(WasmDecodeError::Trap)?;
         }
         Ok(())
     }
 
+    /// Decodes and validates the module to the loader without instantiating it
+    ///
+    /// The imports are not linked, the memories and tables are not allocated,
+    /// the active segments are not applied and the start function does not run.
+    /// The module is only for looking into, and its imported functions trap when called.
+    pub fn decode(&mut self, blob: &[u8]) -> Result<(), WasmDecodeError> {
+        self.decode_only = true;
+        let result = self.load(
+            blob,
+            &|_, _, _| Ok(Self::unlinked),
+            &|_, _, val_type, is_mutable| {
+                WasmGlobal::new(val_type, is_mutable, WasmValue::default_for(val_type))
+                    .map_err(|_| WasmDecodeError::InvalidGlobal)
+            },
+            &|_| Err(WasmDecodeError::OutOfMemory),
+        );
+        self.decode_only = false;
+        result
+    }
+
+    fn unlinked(_: &WasmModule, _: &[WasmValue]) -> Result<WasmValue, WasmRuntimeError> {
+        Err(WasmRuntimeError::NoMethod)
+    }
+
+    /// Sets whether the loader runs the start function at the end of the instantiation
+    ///
+    /// The embedder who defers it calls [WasmModule::entry_point] later.
+    #[inline]
+    pub fn set_run_start(&mut self, run_start: bool) -> &mut Self {
+        self.run_start = run_start;
+        self
+    }
+
     pub fn print_stat(&mut self) {
         self.module.print_stat();
     }
 
+    /// Name of the section and offset in the binary where the last load failed
+    ///
+    /// The offset points to the instruction if the validation of the function body fails,
+    /// otherwise to the byte after the last one the loader read.
+    #[inline]
+    pub const fn error_location(&self) -> Option<(&'static str, usize)> {
+        self.error_location
+    }
+
+    /// Module and field names of the import that failed to link in the last load
+    #[inline]
+    pub fn error_import(&self) -> Option<(&str, &str)> {
+        self.error_import
+            .as_ref()
+            .map(|(mod_name, name)| (mod_name.as_str(), name.as_str()))
+    }
+
+    /// Sets the host-defined data of the instance, see [WasmCaller::data]
+    #[inline]
+    pub fn set_host_data<T: Any>(&mut self, data: T) -> &mut Self {
+        self.module.set_host_data(data);
+        self
+    }
+
+    /// Sets the limit of the nested calls, see [WasmModule::set_max_call_depth]
+    #[inline]
+    pub fn set_max_call_depth(&mut self, depth: usize) -> &mut Self {
+        self.module.set_max_call_depth(depth);
+        self
+    }
+
+    /// Sets the hook called before each instruction, see [WasmModule::set_debug_hook]
+    #[inline]
+    pub fn set_debug_hook<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&WasmFrame) -> Result<(), WasmRuntimeError> + 'static,
+    {
+        self.module.set_debug_hook(hook);
+        self
+    }
+
     #[inline]
     pub const fn module(&self) -> &WasmModule {
         &self.module
@@ -103,6 +506,13 @@ impl WasmLoader {
         let n_items = section.stream.read_unsigned()? as usize;
         for _ in 0..n_items {
             let ft = WasmType::from_stream(&mut section.stream)?;
+            self.module.features.require_val_types(ft.param_types())?;
+            self.module.features.require_val_types(ft.result_types())?;
+            if ft.result_types().len() > 1 {
+                self.module.features.require(WasmFeatures::MULTI_VALUE)?;
+                // Not implemented yet, the interpreter returns at most one value
+                return Err(WasmDecodeError::NotSupprted);
+            }
             self.module.types.push(ft);
         }
         Ok(())
@@ -113,36 +523,108 @@ impl WasmLoader {
         &mut self,
         section: &mut WasmSection,
         resolver: &WasmImportResolver,
+        global_resolver: &WasmGlobalResolver,
     ) -> Result<(), WasmDecodeError> {
         let n_items = section.stream.read_unsigned()? as usize;
         for _ in 0..n_items {
             let mut import = WasmImport::from_stream(&mut section.stream)?;
-            match import.index {
-                WasmImportIndex::Type(index) => {
-                    import.func_ref = self.module.n_ext_func;
-                    let func_type = self
-                        .module
-                        .types
-                        .get(index)
-                        .ok_or(WasmDecodeError::InvalidType)?;
-                    let dlink = resolver(import.mod_name(), import.name(), func_type)?;
-                    self.module.functions.push(WasmFunction::from_import(
-                        index,
-                        func_type,
-                        self.module.n_ext_func,
-                        dlink,
-                    ));
-                    self.module.n_ext_func += 1;
+            if let Err(err) = self.link_import(&mut import, resolver, global_resolver) {
+                self.error_import =
+                    Some((import.mod_name().to_string(), import.name().to_string()));
+                return Err(err);
+            }
+            self.module.imports.push(import);
+        }
+        Ok(())
+    }
+
+    /// Resolves the import, the host function of the same name takes precedence
+    fn link_import(
+        &mut self,
+        import: &mut WasmImport,
+        resolver: &WasmImportResolver,
+        global_resolver: &WasmGlobalResolver,
+    ) -> Result<(), WasmDecodeError> {
+        match import.index {
+            WasmImportIndex::Type(index) => {
+                import.func_ref = self.module.n_ext_func;
+                let func_type = self
+                    .module
+                    .types
+                    .get(index)
+                    .ok_or(WasmDecodeError::InvalidType)?;
+                let dlink = match self
+                    .host_funcs
+                    .iter()
+                    .filter(|_| !self.decode_only)
+                    .find(|v| v.0 == import.mod_name() && v.1 == import.name())
+                {
+                    Some((_, _, host)) => {
+                        if host.func_type().param_types() != func_type.param_types()
+                            || host.func_type().result_types() != func_type.result_types()
+                        {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                        host.func()
+                    }
+                    None => {
+                        let dlink = resolver(import.mod_name(), import.name(), func_type)?;
+                        Rc::new(move |caller: &WasmCaller, params: &[WasmValue]| {
+                            dlink(caller.module(), params)
+                        })
+                    }
+                };
+                // The import is pushed after linking, so its index is the current length
+                self.module.functions.push(WasmFunction::from_import(
+                    index,
+                    func_type,
+                    self.module.imports.len(),
+                    dlink,
+                ));
+                self.module.n_ext_func += 1;
+            }
+            WasmImportIndex::Table(elem_type, limit) => {
+                // The elements are indices of the functions of this module,
+                // so a table cannot be shared with the host or other modules.
+                if !self.decode_only {
+                    return Err(WasmDecodeError::NotSupprted);
+                }
+                let table = self.new_table(elem_type, limit)?;
+                self.module.tables.push(table);
+            }
+            WasmImportIndex::Memory(memtype) => {
+                self.check_memory(memtype)?;
+                let memory = if self.decode_only {
+                    WasmMemory::declared(memtype)?
+                } else {
+                    let index = self
+                        .host_memories
+                        .iter()
+                        .position(|v| v.0 == import.mod_name() && v.1 == import.name())
+                        .ok_or(WasmDecodeError::DynamicLinkError)?;
+                    let (_, _, backend) = self.host_memories.remove(index);
+                    WasmMemory::new(memtype, backend)?
+                };
+                self.module.memories.push(memory);
+            }
+            WasmImportIndex::Global(val_type, is_mutable) => {
+                self.module.features.require_val_types(&[val_type])?;
+                if is_mutable {
+                    self.module
+                        .features
+                        .require(WasmFeatures::MUTABLE_GLOBALS)?;
                 }
-                WasmImportIndex::Memory(memtype) => {
-                    // TODO: import memory
-                    self.module.memories.push(WasmMemory::new(memtype));
+                let global =
+                    global_resolver(import.mod_name(), import.name(), val_type, is_mutable)?;
+                if global.val_type() != val_type || global.is_mutable() != is_mutable {
+                    return Err(WasmDecodeError::InvalidGlobal);
                 }
-
-                #[allow(unreachable_patterns)]
-                _ => (),
+                self.module.globals.push(global);
+            }
+            WasmImportIndex::Tag(type_index) => {
+                let tag = self.check_tag(type_index)?;
+                self.module.tags.push(tag);
             }
-            self.module.imports.push(import);
         }
         Ok(())
     }
@@ -169,11 +651,22 @@ impl WasmLoader {
         let n_items = section.stream.read_unsigned()? as usize;
         for i in 0..n_items {
             let export = WasmExport::from_stream(&mut section.stream)?;
-            if let WasmExportIndex::Function(index) = export.index {
-                self.module
-                    .functions
-                    .get_mut(index)
-                    .map(|v| v.origin = WasmFunctionOrigin::Export(i));
+            match export.index {
+                WasmExportIndex::Function(index) => {
+                    if let Some(function) = self.module.functions.get_mut(index) {
+                        function.origin = WasmFunctionOrigin::Export(i);
+                    }
+                    self.module.declared_funcs.insert(index);
+                }
+                WasmExportIndex::Global(index) => {
+                    let global = self.module.globals.get(index);
+                    if global.is_some_and(|v| v.is_mutable()) {
+                        self.module
+                            .features
+                            .require(WasmFeatures::MUTABLE_GLOBALS)?;
+                    }
+                }
+                _ => (),
             }
             self.module.exports.push(export);
         }
@@ -181,11 +674,16 @@ impl WasmLoader {
     }
 
     /// Parse "memory" section
-    fn parse_sec_memory(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
+    fn parse_sec_memory(
+        &mut self,
+        section: &mut WasmSection,
+        allocator: &WasmMemoryAllocator,
+    ) -> Result<(), WasmDecodeError> {
         let n_items = section.stream.read_unsigned()?;
         for _ in 0..n_items {
             let limit = WasmLimit::from_stream(&mut section.stream)?;
-            self.module.memories.push(WasmMemory::new(limit));
+            let memory = self.new_memory(limit, allocator)?;
+            self.module.memories.push(memory);
         }
         Ok(())
     }
@@ -194,32 +692,197 @@ impl WasmLoader {
     fn parse_sec_table(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
         let n_items = section.stream.read_unsigned()?;
         for _ in 0..n_items {
-            let table = WasmTable::from_stream(&mut section.stream)?;
+            let elem_type = WasmValType::ref_type_from_stream(&mut section.stream)?;
+            let limit = WasmLimit::from_stream(&mut section.stream)?;
+            let table = self.new_table(elem_type, limit)?;
             self.module.tables.push(table);
         }
         Ok(())
     }
 
+    /// Creates the table after checking the proposals required to declare it
+    fn new_table(
+        &self,
+        elem_type: WasmValType,
+        limit: WasmLimit,
+    ) -> Result<WasmTable, WasmDecodeError> {
+        if elem_type != WasmValType::FuncRef || !self.module.tables.is_empty() {
+            self.module
+                .features
+                .require(WasmFeatures::REFERENCE_TYPES)?;
+        }
+        if self.decode_only {
+            WasmTable::new(elem_type, limit, 0)
+        } else {
+            WasmTable::new(elem_type, limit, limit.min as usize)
+        }
+    }
+
+    /// Creates the memory after checking the proposals required to declare it
+    fn new_memory(
+        &self,
+        limit: WasmLimit,
+        allocator: &WasmMemoryAllocator,
+    ) -> Result<WasmMemory, WasmDecodeError> {
+        self.check_memory(limit)?;
+        if self.decode_only {
+            WasmMemory::declared(limit)
+        } else {
+            WasmMemory::new(limit, allocator(limit)?)
+        }
+    }
+
+    /// Checks the proposals required to declare the memory
+    fn check_memory(&self, limit: WasmLimit) -> Result<(), WasmDecodeError> {
+        if limit.is_64() {
+            self.module.features.require(WasmFeatures::MEMORY64)?;
+        }
+        if limit.is_shared() {
+            self.module.features.require(WasmFeatures::THREADS)?;
+        }
+        if !self.module.memories.is_empty() {
+            self.module.features.require(WasmFeatures::MULTI_MEMORY)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the type of the tag, which has no results
+    fn check_tag(&self, type_index: usize) -> Result<WasmTag, WasmDecodeError> {
+        self.module.features.require(WasmFeatures::EXCEPTIONS)?;
+        let func_type = self
+            .module
+            .types
+            .get(type_index)
+            .ok_or(WasmDecodeError::InvalidType)?;
+        if !func_type.result_types().is_empty() {
+            return Err(WasmDecodeError::InvalidType);
+        }
+        Ok(WasmTag { type_index })
+    }
+
+    /// Parse "tag" section
+    fn parse_sec_tag(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
+        let n_items = section.stream.read_unsigned()?;
+        for _ in 0..n_items {
+            if section.stream.read_byte()? != 0 {
+                return Err(WasmDecodeError::UnexpectedToken);
+            }
+            let type_index = section.stream.read_unsigned()? as usize;
+            let tag = self.check_tag(type_index)?;
+            self.module.tags.push(tag);
+        }
+        Ok(())
+    }
+
     /// Parse "elem" section
     fn parse_sec_elem(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
         let n_items = section.stream.read_unsigned()?;
         for _ in 0..n_items {
-            let tabidx = section.stream.read_unsigned()? as usize;
-            let offset = self.eval_offset(&mut section.stream)? as usize;
+            let flags = section.stream.read_unsigned()?;
+            if flags > 7 {
+                return Err(WasmDecodeError::UnexpectedToken);
+            }
+            let is_passive_or_declarative = (flags & 0b001) != 0;
+            let has_table_index = (flags & 0b010) != 0;
+            let has_exprs = (flags & 0b100) != 0;
+            if (is_passive_or_declarative && !has_table_index) || has_exprs {
+                self.module.features.require(WasmFeatures::BULK_MEMORY)?;
+            }
+            if has_table_index {
+                self.module
+                    .features
+                    .require(WasmFeatures::REFERENCE_TYPES)?;
+            }
+
+            let mode = if is_passive_or_declarative {
+                if has_table_index {
+                    WasmSegmentMode::Declarative
+                } else {
+                    WasmSegmentMode::Passive
+                }
+            } else {
+                let tabidx = if has_table_index {
+                    section.stream.read_unsigned()? as usize
+                } else {
+                    0
+                };
+                let offset = self.eval_offset(&mut section.stream)?;
+                WasmSegmentMode::Active(tabidx, offset)
+            };
+
+            // The forms without the table index omit the element kind, which is funcref
+            let elem_type = if is_passive_or_declarative || has_table_index {
+                if has_exprs {
+                    WasmValType::ref_type_from_stream(&mut section.stream)?
+                } else {
+                    Self::read_elemkind(&mut section.stream)?
+                }
+            } else {
+                WasmValType::FuncRef
+            };
+
             let n_elements = section.stream.read_unsigned()? as usize;
-            let table = self
-                .module
-                .tables
-                .get_mut(tabidx)
-                .ok_or(WasmDecodeError::InvalidParameter)?;
-            for i in offset..offset + n_elements {
-                let elem = section.stream.read_unsigned()? as usize;
-                table.table.get_mut(i).map(|v| *v = elem);
+            let mut elements = Vec::with_capacity(n_elements);
+            for _ in 0..n_elements {
+                if has_exprs {
+                    let value = self.eval_expr(&mut section.stream)?;
+                    if !value.is_valid_type(elem_type) {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    if let WasmValue::FuncRef(Some(index)) = value {
+                        self.module.declared_funcs.insert(index);
+                    }
+                    elements.push(value.get_ref().map_err(|_| WasmDecodeError::TypeMismatch)?);
+                } else {
+                    let elem = section.stream.read_unsigned()? as usize;
+                    if elem >= self.module.functions.len() {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    self.module.declared_funcs.insert(elem);
+                    elements.push(Some(elem));
+                }
+            }
+
+            match mode {
+                WasmSegmentMode::Active(tabidx, offset) => {
+                    let table = self
+                        .module
+                        .tables
+                        .get(tabidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    if table.elem_type() != elem_type {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    if !self.decode_only {
+                        table
+                            .init(offset, &elements)
+                            .map_err(|_| WasmDecodeError::OutOfBounds)?;
+                    }
+                    // Active segments are dropped after instantiation
+                    elements.clear();
+                }
+                // Declarative segments only forward-declare the references for ref.func
+                WasmSegmentMode::Declarative => elements.clear(),
+                _ => (),
             }
+
+            self.module.elem_segments.push(WasmElemSegment {
+                mode,
+                elem_type,
+                elements: RefCell::new(elements),
+            });
         }
         Ok(())
     }
 
+    fn read_elemkind(stream: &mut Leb128Stream) -> Result<WasmValType, WasmDecodeError> {
+        match stream.read_byte()? {
+            // funcref
+            0x00 => Ok(WasmValType::FuncRef),
+            _ => Err(WasmDecodeError::UnexpectedToken),
+        }
+    }
+
     /// Parse "code" section
     fn parse_sec_code(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
         let n_items = section.stream.read_unsigned()? as usize;
@@ -244,24 +907,84 @@ impl WasmLoader {
 
     /// Parse "data" section
     fn parse_sec_data(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
-        let n_items = section.stream.read_unsigned()?;
+        let n_items = section.stream.read_unsigned()? as usize;
+        if self
+            .module
+            .data_count
+            .map(|v| v != n_items)
+            .unwrap_or(false)
+        {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
         for _ in 0..n_items {
-            let memidx = section.stream.read_unsigned()? as usize;
-            let offset = self.eval_offset(&mut section.stream)?;
+            let memidx = match section.stream.read_unsigned()? {
+                0 => Some(0),
+                1 => {
+                    self.module.features.require(WasmFeatures::BULK_MEMORY)?;
+                    None
+                }
+                2 => Some(section.stream.read_unsigned()? as usize),
+                _ => return Err(WasmDecodeError::UnexpectedToken),
+            };
+            let mode = match memidx {
+                Some(memidx) => {
+                    let offset = self.eval_expr(&mut section.stream)?;
+                    let memory = self
+                        .module
+                        .memories
+                        .get(memidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let offset = match (memory.limit().is_64(), offset) {
+                        (false, WasmValue::I32(v)) => v as u32 as usize,
+                        (true, WasmValue::I64(v)) => usize::try_from(v as u64)
+                            .map_err(|_| WasmDecodeError::InvalidParameter)?,
+                        _ => return Err(WasmDecodeError::InvalidParameter),
+                    };
+                    WasmSegmentMode::Active(memidx, offset)
+                }
+                None => WasmSegmentMode::Passive,
+            };
             let src = section.stream.read_bytes()?;
-            let memory = self
-                .module
-                .memories
-                .get_mut(memidx)
-                .ok_or(WasmDecodeError::InvalidParameter)?;
-            memory.write_bytes(offset, src).unwrap();
+            let data = match mode {
+                WasmSegmentMode::Active(memidx, offset) => {
+                    if !self.decode_only {
+                        self.module.memories[memidx]
+                            .write_bytes(offset, src)
+                            .map_err(|_| WasmDecodeError::OutOfBounds)?;
+                    }
+                    // Active segments are dropped after instantiation
+                    Vec::new()
+                }
+                _ => src.to_vec(),
+            };
+            self.module.data_segments.push(WasmDataSegment {
+                mode,
+                size: src.len(),
+                data: RefCell::new(data),
+            });
         }
         Ok(())
     }
 
+    /// Parse "data count" section
+    fn parse_sec_data_count(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
+        self.module.features.require(WasmFeatures::BULK_MEMORY)?;
+        let count = section.stream.read_unsigned()? as usize;
+        self.module.data_count = Some(count);
+        Ok(())
+    }
+
     /// Parse "start" section
     fn parse_sec_start(&mut self, section: &mut WasmSection) -> Result<(), WasmDecodeError> {
         let index = section.stream.read_unsigned()? as usize;
+        let function = self
+            .module
+            .functions
+            .get(index)
+            .ok_or(WasmDecodeError::InvalidParameter)?;
+        if !function.param_types().is_empty() || !function.result_types().is_empty() {
+            return Err(WasmDecodeError::TypeMismatch);
+        }
         self.module.start = Some(index);
         Ok(())
     }
@@ -274,51 +997,126 @@ impl WasmLoader {
                 .stream
                 .read_byte()
                 .and_then(|v| WasmValType::from_u64(v as u64))?;
+            self.module.features.require_val_types(&[val_type])?;
             let is_mutable = section.stream.read_byte()? == 1;
             let value = self.eval_expr(&mut section.stream)?;
 
             if !value.is_valid_type(val_type) {
                 return Err(WasmDecodeError::InvalidGlobal);
             }
+            if let WasmValue::FuncRef(Some(index)) = value {
+                self.module.declared_funcs.insert(index);
+            }
 
             let global = WasmGlobal {
                 val_type,
                 is_mutable,
-                value: RefCell::new(value),
+                value: Rc::new(RefCell::new(value)),
             };
             self.module.globals.push(global);
         }
         Ok(())
     }
 
-    fn eval_offset(&self, mut stream: &mut Leb128Stream) -> Result<usize, WasmDecodeError> {
-        self.eval_expr(&mut stream)
+    fn eval_offset(&self, stream: &mut Leb128Stream) -> Result<usize, WasmDecodeError> {
+        self.eval_expr(stream)
             .and_then(|v| v.get_i32().map_err(|_| WasmDecodeError::InvalidParameter))
             .map(|v| v as usize)
     }
 
+    /// Evaluates the constant expression of globals, data and element segments
+    ///
+    /// Supports the extended-const proposal, so i32 and i64 add, sub and mul can be used.
     fn eval_expr(&self, stream: &mut Leb128Stream) -> Result<WasmValue, WasmDecodeError> {
-        stream
-            .read_byte()
-            .and_then(|opc| match WasmOpcode::from_u8(opc) {
-                WasmOpcode::I32Const => stream.read_signed().and_then(|r| {
-                    match stream.read_byte().map(|v| WasmOpcode::from_u8(v)) {
-                        Ok(WasmOpcode::End) => Ok(WasmValue::I32(r as i32)),
-                        _ => Err(WasmDecodeError::UnexpectedToken),
-                    }
-                }),
-                WasmOpcode::I64Const => stream.read_signed().and_then(|r| {
-                    match stream.read_byte().map(|v| WasmOpcode::from_u8(v)) {
-                        Ok(WasmOpcode::End) => Ok(WasmValue::I64(r)),
-                        _ => Err(WasmDecodeError::UnexpectedToken),
-                    }
-                }),
-                _ => Err(WasmDecodeError::UnexpectedToken),
-            })
+        let mut value_stack: Vec<WasmValue> = Vec::new();
+        loop {
+            let value = match stream.read_opcode()? {
+                WasmOpcode::End => break,
+                WasmOpcode::I32Const => WasmValue::I32(stream.read_signed()? as i32),
+                WasmOpcode::I64Const => WasmValue::I64(stream.read_signed()?),
+                WasmOpcode::F32Const => {
+                    self.module.features.require(WasmFeatures::FLOAT)?;
+                    stream
+                        .get_bytes(4)
+                        .map(|v| WasmValue::F32(LE::read_f32(v)))?
+                }
+                WasmOpcode::F64Const => {
+                    self.module.features.require(WasmFeatures::FLOAT)?;
+                    stream
+                        .get_bytes(8)
+                        .map(|v| WasmValue::F64(LE::read_f64(v)))?
+                }
+                WasmOpcode::V128Const => {
+                    self.module.features.require(WasmFeatures::SIMD)?;
+                    stream
+                        .get_bytes(16)
+                        .map(|v| WasmValue::V128(LE::read_u128(v)))?
+                }
+                WasmOpcode::RefNull => {
+                    WasmValType::ref_type_from_stream(stream).map(WasmValue::default_for)?
+                }
+                WasmOpcode::RefFunc => {
+                    let index = stream.read_unsigned()? as usize;
+                    if index >= self.module.functions.len() {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    WasmValue::FuncRef(Some(index))
+                }
+                WasmOpcode::GlobalGet => {
+                    // Only the globals defined before, including imports, are visible
+                    let index = stream.read_unsigned()? as usize;
+                    let global = self
+                        .module
+                        .globals
+                        .get(index)
+                        .ok_or(WasmDecodeError::InvalidGlobal)?;
+                    if global.is_mutable() {
+                        return Err(WasmDecodeError::InvalidGlobal);
+                    }
+                    *global.value().borrow()
+                }
+                opcode @ (WasmOpcode::I32Add | WasmOpcode::I32Sub | WasmOpcode::I32Mul) => {
+                    self.module.features.require(WasmFeatures::EXTENDED_CONST)?;
+                    let b = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let (a, b) = match (a, b) {
+                        (WasmValue::I32(a), WasmValue::I32(b)) => (a, b),
+                        _ => return Err(WasmDecodeError::TypeMismatch),
+                    };
+                    WasmValue::I32(match opcode {
+                        WasmOpcode::I32Add => a.wrapping_add(b),
+                        WasmOpcode::I32Sub => a.wrapping_sub(b),
+                        _ => a.wrapping_mul(b),
+                    })
+                }
+                opcode @ (WasmOpcode::I64Add | WasmOpcode::I64Sub | WasmOpcode::I64Mul) => {
+                    self.module.features.require(WasmFeatures::EXTENDED_CONST)?;
+                    let b = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let (a, b) = match (a, b) {
+                        (WasmValue::I64(a), WasmValue::I64(b)) => (a, b),
+                        _ => return Err(WasmDecodeError::TypeMismatch),
+                    };
+                    WasmValue::I64(match opcode {
+                        WasmOpcode::I64Add => a.wrapping_add(b),
+                        WasmOpcode::I64Sub => a.wrapping_sub(b),
+                        _ => a.wrapping_mul(b),
+                    })
+                }
+                _ => return Err(WasmDecodeError::UnexpectedToken),
+            };
+            value_stack.push(value);
+        }
+        match value_stack.as_slice() {
+            [value] => Ok(*value),
+            [] => Err(WasmDecodeError::OutOfStack),
+            _ => Err(WasmDecodeError::InvalidStackLevel),
+        }
     }
 }
 
 pub struct WasmModule {
+    sections: Vec<WasmSectionInfo>,
     types: Vec<WasmType>,
     imports: Vec<WasmImport>,
     exports: Vec<WasmExport>,
@@ -327,12 +1125,29 @@ pub struct WasmModule {
     functions: Vec<WasmFunction>,
     start: Option<usize>,
     globals: Vec<WasmGlobal>,
+    data_segments: Vec<WasmDataSegment>,
+    elem_segments: Vec<WasmElemSegment>,
+    declared_funcs: BTreeSet<usize>,
+    data_count: Option<usize>,
     n_ext_func: usize,
+    features: WasmFeatures,
+    tags: Vec<WasmTag>,
+    exceptions: RefCell<WasmExceptionStore>,
+    host_data: Option<Box<dyn Any>>,
+    max_call_depth: usize,
+    debug_hook: Option<Rc<WasmDebugHook>>,
+}
+
+impl Default for WasmModule {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WasmModule {
     pub const fn new() -> Self {
         Self {
+            sections: Vec::new(),
             types: Vec::new(),
             memories: Vec::new(),
             imports: Vec::new(),
@@ -341,25 +1156,78 @@ impl WasmModule {
             functions: Vec::new(),
             start: None,
             globals: Vec::new(),
+            data_segments: Vec::new(),
+            elem_segments: Vec::new(),
+            declared_funcs: BTreeSet::new(),
+            data_count: None,
             n_ext_func: 0,
+            features: WasmFeatures::available(),
+            tags: Vec::new(),
+            exceptions: RefCell::new(WasmExceptionStore::new()),
+            host_data: None,
+            max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
+            debug_hook: None,
         }
     }
 
+    /// Default limit of the nested calls, see [WasmModule::max_call_depth]
+    ///
+    /// Each nested frame takes up to about 10 KiB of the native stack in a debug build
+    /// and about 2 KiB in a release build, including the calls back from the host.
+    /// The default keeps them within about 1.3 MiB in either build, which fits in
+    /// the 2 MiB stack of the threads spawned by `std`.
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 128;
+
+    /// Maximum number of the nested frames of the guest functions,
+    /// including the ones called back from the host functions
     #[inline]
-    pub fn types(&self) -> &[WasmType] {
-        self.types.as_slice()
+    pub const fn max_call_depth(&self) -> usize {
+        self.max_call_depth
     }
 
+    /// Sets the limit of the nested calls
+    ///
+    /// The interpreter uses the native stack for each frame,
+    /// so the limit should be small enough for the stack of the thread,
+    /// see [WasmModule::DEFAULT_MAX_CALL_DEPTH] for the size of a frame.
     #[inline]
-    pub fn type_by_ref(&self, index: usize) -> Option<&WasmType> {
-        self.types.get(index)
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Proposals enabled when the module was loaded
+    #[inline]
+    pub const fn features(&self) -> WasmFeatures {
+        self.features
+    }
+
+    #[inline]
+    pub fn types(&self) -> &[WasmType] {
+        self.types.as_slice()
+    }
+
+    #[inline]
+    pub fn type_by_ref(&self, index: usize) -> Option<&WasmType> {
+        self.types.get(index)
+    }
+
+    #[inline]
+    pub fn imports(&self) -> &[WasmImport] {
+        self.imports.as_slice()
+    }
+
+    /// Sections of the binary in the order of appearance
+    #[inline]
+    pub fn sections(&self) -> &[WasmSectionInfo] {
+        self.sections.as_slice()
+    }
+
+    /// Index of the start function
+    #[inline]
+    pub const fn start(&self) -> Option<usize> {
+        self.start
     }
 
-    // #[inline]
-    // pub fn imports(&self) -> &[WasmImport] {
-    //     self.imports.as_slice()
-    // }
-
     #[inline]
     pub fn exports(&self) -> &[WasmExport] {
         self.exports.as_slice()
@@ -375,16 +1243,95 @@ impl WasmModule {
         self.memories.get(index)
     }
 
+    /// Get a reference to the exported memory with the specified name
+    pub fn memory_by_name(&self, name: &str) -> Option<&WasmMemory> {
+        self.exports.iter().find_map(|export| match export.index {
+            WasmExportIndex::Memory(v) if export.name == name => self.memory(v),
+            _ => None,
+        })
+    }
+
+    /// Sets the host-defined data of the instance, replacing the previous one
+    #[inline]
+    pub fn set_host_data<T: Any>(&mut self, data: T) {
+        self.host_data = Some(Box::new(data));
+    }
+
+    /// Returns the host-defined data of the instance, if it is of the type
+    #[inline]
+    pub fn host_data<T: Any>(&self) -> Option<&T> {
+        self.host_data.as_ref().and_then(|v| v.downcast_ref())
+    }
+
+    /// Sets the hook called before each instruction, such as for debuggers
+    ///
+    /// The execution stops with the error if the hook returns it.
+    pub fn set_debug_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&WasmFrame) -> Result<(), WasmRuntimeError> + 'static,
+    {
+        self.debug_hook = Some(Rc::new(hook));
+    }
+
+    /// Removes the debug hook
+    #[inline]
+    pub fn clear_debug_hook(&mut self) {
+        self.debug_hook = None;
+    }
+
+    #[inline]
+    pub fn debug_hook(&self) -> Option<&WasmDebugHook> {
+        self.debug_hook.as_deref()
+    }
+
     #[inline]
     pub fn tables(&mut self) -> &mut [WasmTable] {
         self.tables.as_mut_slice()
     }
 
+    #[inline]
+    pub fn table(&self, index: usize) -> Option<&WasmTable> {
+        self.tables.get(index)
+    }
+
     pub fn elem_by_index(&self, index: usize) -> Option<&WasmFunction> {
+        self.table_elem(0, index)
+    }
+
+    /// Returns the function referenced by the element of the table
+    pub fn table_elem(&self, table_index: usize, index: usize) -> Option<&WasmFunction> {
         self.tables
-            .get(0)
-            .and_then(|v| v.table.get(index))
-            .and_then(|v| self.functions.get(*v))
+            .get(table_index)
+            .filter(|v| v.elem_type == WasmValType::FuncRef)
+            .and_then(|v| v.get_ref(index))
+            .flatten()
+            .and_then(|v| self.functions.get(v))
+    }
+
+    #[inline]
+    pub fn data_segment(&self, index: usize) -> Option<&WasmDataSegment> {
+        self.data_segments.get(index)
+    }
+
+    #[inline]
+    pub fn elem_segment(&self, index: usize) -> Option<&WasmElemSegment> {
+        self.elem_segments.get(index)
+    }
+
+    #[inline]
+    pub fn data_segments(&self) -> &[WasmDataSegment] {
+        self.data_segments.as_slice()
+    }
+
+    #[inline]
+    pub fn elem_segments(&self) -> &[WasmElemSegment] {
+        self.elem_segments.as_slice()
+    }
+
+    /// Number of data segments declared by the "data count" section
+    #[inline]
+    pub const fn data_count(&self) -> Option<usize> {
+        self.data_count
     }
 
     #[inline]
@@ -393,7 +1340,7 @@ impl WasmModule {
     }
 
     #[inline]
-    pub fn func_by_index(&self, index: usize) -> Result<WasmRunnable, WasmRuntimeError> {
+    pub fn func_by_index(&self, index: usize) -> Result<WasmRunnable<'_>, WasmRuntimeError> {
         self.functions
             .get(index)
             .map(|v| WasmRunnable::from_function(v, self))
@@ -401,7 +1348,7 @@ impl WasmModule {
     }
 
     #[inline]
-    pub fn entry_point(&self) -> Result<WasmRunnable, WasmRuntimeError> {
+    pub fn entry_point(&self) -> Result<WasmRunnable<'_>, WasmRuntimeError> {
         self.start
             .ok_or(WasmRuntimeError::NoMethod)
             .and_then(|v| self.func_by_index(v))
@@ -409,7 +1356,7 @@ impl WasmModule {
 
     /// Get a reference to the exported function with the specified name
     #[inline]
-    pub fn func(&self, name: &str) -> Result<WasmRunnable, WasmRuntimeError> {
+    pub fn func(&self, name: &str) -> Result<WasmRunnable<'_>, WasmRuntimeError> {
         for export in &self.exports {
             if let WasmExportIndex::Function(v) = export.index {
                 if export.name == name {
@@ -420,11 +1367,154 @@ impl WasmModule {
         Err(WasmRuntimeError::NoMethod)
     }
 
+    /// Get the exported function with the specified name, checking its signature once
+    ///
+    /// ```ignore
+    /// let add = module.typed_func::<(i32, i64), i32>("add")?;
+    /// let result = add.call((1, 2))?;
+    /// ```
+    #[inline]
+    pub fn typed_func<P: WasmParams, R: WasmResults>(
+        &self,
+        name: &str,
+    ) -> Result<WasmTypedFunc<'_, P, R>, WasmRuntimeError> {
+        self.func(name).and_then(WasmTypedFunc::new)
+    }
+
+    #[inline]
+    pub fn globals(&self) -> &[WasmGlobal] {
+        self.globals.as_slice()
+    }
+
     #[inline]
     pub fn global(&self, index: usize) -> Option<&WasmGlobal> {
         self.globals.get(index)
     }
 
+    pub fn global_by_name(&self, name: &str) -> Option<&WasmGlobal> {
+        for export in &self.exports {
+            if let WasmExportIndex::Global(v) = export.index {
+                if export.name == name {
+                    return self.global(v);
+                }
+            }
+        }
+        None
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[WasmTag] {
+        self.tags.as_slice()
+    }
+
+    #[inline]
+    pub fn tag(&self, index: usize) -> Option<&WasmTag> {
+        self.tags.get(index)
+    }
+
+    /// Returns the index of the exported tag
+    pub fn tag_by_name(&self, name: &str) -> Option<usize> {
+        self.exports.iter().find_map(|export| match export.index {
+            WasmExportIndex::Tag(v) if export.name == name => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Returns the index of the tag imported from the host
+    pub fn tag_by_import(&self, mod_name: &str, name: &str) -> Option<usize> {
+        self.imports
+            .iter()
+            .filter(|import| matches!(import.index, WasmImportIndex::Tag(_)))
+            .position(|import| import.mod_name == mod_name && import.name == name)
+    }
+
+    /// Throws the exception from the host function, which returns the result as its error
+    ///
+    /// ```ignore
+    /// return Err(module.throw(WasmException::new(tag, vec![errno.into()])));
+    /// ```
+    pub fn throw(&self, exception: WasmException) -> WasmRuntimeError {
+        let mut store = self.exceptions.borrow_mut();
+        let index = store.alloc(exception);
+        store.throw(index);
+        WasmRuntimeError::Exception
+    }
+
+    /// Rethrows the exception referenced by the exnref
+    pub fn throw_ref(&self, exnref: usize) -> WasmRuntimeError {
+        let mut store = self.exceptions.borrow_mut();
+        if store.get(exnref).is_some() {
+            store.throw(exnref);
+            WasmRuntimeError::Exception
+        } else {
+            WasmRuntimeError::InvalidParameter
+        }
+    }
+
+    /// Returns the tag of the exception being thrown
+    #[inline]
+    pub fn pending_tag(&self) -> Option<usize> {
+        let store = self.exceptions.borrow();
+        store
+            .pending
+            .and_then(|v| store.get(v))
+            .map(|v| v.exception.tag)
+    }
+
+    /// Catches the exception being thrown, and also returns the exnref if `is_ref`
+    pub fn catch(&self, is_ref: bool) -> Option<(WasmException, Option<usize>)> {
+        self.exceptions.borrow_mut().catch(is_ref)
+    }
+
+    /// Takes the exception that reached the host as [WasmRuntimeError::Exception]
+    #[inline]
+    pub fn take_exception(&self) -> Option<WasmException> {
+        self.catch(false).map(|(exception, _)| exception)
+    }
+
+    /// Returns the exception referenced by the exnref
+    ///
+    /// The exnref returned to the host remains valid until the next invocation returns.
+    pub fn exception(&self, exnref: usize) -> Option<WasmException> {
+        self.exceptions
+            .borrow()
+            .get(exnref)
+            .map(|v| v.exception.clone())
+    }
+
+    #[cfg(all(test, feature = "exceptions"))]
+    pub(crate) fn n_exceptions(&self) -> usize {
+        self.exceptions.borrow().len()
+    }
+
+    fn enter_invocation(&self) {
+        self.exceptions.borrow_mut().n_invocations += 1;
+    }
+
+    /// Releases the exceptions no longer reachable after the outermost invocation returns
+    fn leave_invocation(&self, result: Option<&WasmValue>) {
+        let mut store = self.exceptions.borrow_mut();
+        store.n_invocations -= 1;
+        if store.n_invocations > 0 || store.slots.is_empty() {
+            return;
+        }
+        let mut roots = BTreeSet::new();
+        if let Some(WasmValue::ExnRef(Some(exnref))) = result {
+            roots.insert(*exnref);
+        }
+        for global in &self.globals {
+            if let WasmValue::ExnRef(Some(exnref)) = global.get() {
+                roots.insert(exnref);
+            }
+        }
+        for table in &self.tables {
+            if table.elem_type() == WasmValType::ExnRef {
+                roots.extend(table.table.borrow().iter().flatten());
+            }
+        }
+        store.collect(&roots);
+    }
+
     pub fn print_stat(&mut self) {
         for (func_idx, function) in self.functions.iter().enumerate() {
             let func_type = &function.func_type;
@@ -463,37 +1553,35 @@ impl WasmModule {
             }
         };
         let locals = body.local_types.as_slice();
-        if locals.len() > 0 {
-            let mut local_index = func.param_types().len();
-            for local in locals {
-                println!("  (local ${}, {})", local_index, local);
-                local_index += 1;
-            }
+        for (local_index, local) in locals.iter().enumerate() {
+            println!(
+                "  (local ${}, {})",
+                func.param_types().len() + local_index,
+                local
+            );
         }
         let code_block = body.code_block.borrow();
         let mut stream = Leb128Stream::from_slice(&code_block);
         let mut block_level = 1;
-        while let Ok(opcode) = stream.read_byte() {
-            let op = WasmOpcode::from_u8(opcode);
-
+        while let Ok(op) = stream.read_opcode() {
             match op.operand_type() {
                 WasmOperandType::Else => {
                     block_level -= 1;
-                    Self::nest(block_level);
+                    Self::nest(stream.fetch_position(), block_level);
                     println!("else");
                     block_level += 1;
                 }
                 WasmOperandType::End => {
                     if block_level > 1 {
                         block_level -= 1;
-                        Self::nest(block_level);
+                        Self::nest(stream.fetch_position(), block_level);
                         println!("end");
                     } else {
                         break;
                     }
                 }
                 _ => {
-                    Self::nest(block_level);
+                    Self::nest(stream.fetch_position(), block_level);
                 }
             }
 
@@ -504,8 +1592,7 @@ impl WasmModule {
 
                 WasmOperandType::Block => {
                     let type_ref = stream.read_signed().and_then(|v| {
-                        WasmBlockType::from_i64(v as i64)
-                            .map_err(|_| WasmDecodeError::UnexpectedToken)
+                        WasmBlockType::from_i64(v).map_err(|_| WasmDecodeError::UnexpectedToken)
                     })?;
                     match type_ref {
                         WasmBlockType::Empty => println!("{}", op.to_str(),),
@@ -513,15 +1600,48 @@ impl WasmModule {
                     }
                     block_level += 1;
                 }
+                WasmOperandType::TryTable => {
+                    let type_ref = stream.read_signed().and_then(|v| {
+                        WasmBlockType::from_i64(v).map_err(|_| WasmDecodeError::UnexpectedToken)
+                    })?;
+                    match type_ref {
+                        WasmBlockType::Empty => print!("{}", op.to_str(),),
+                        _ => print!("{} {:?}", op.to_str(), type_ref),
+                    }
+                    let n_vec = stream.read_unsigned()?;
+                    for _ in 0..n_vec {
+                        match WasmCatch::from_stream(&mut stream)? {
+                            WasmCatch::Catch(tag, label) => print!(" (catch {} {})", tag, label),
+                            WasmCatch::CatchRef(tag, label) => {
+                                print!(" (catch_ref {} {})", tag, label)
+                            }
+                            WasmCatch::CatchAll(label) => print!(" (catch_all {})", label),
+                            WasmCatch::CatchAllRef(label) => print!(" (catch_all_ref {})", label),
+                        }
+                    }
+                    println!();
+                    block_level += 1;
+                }
                 WasmOperandType::Br
                 | WasmOperandType::Call
                 | WasmOperandType::Local
                 | WasmOperandType::Global
-                | WasmOperandType::MemSize => {
+                | WasmOperandType::MemSize
+                | WasmOperandType::Fence
+                | WasmOperandType::Tag
+                | WasmOperandType::DataDrop
+                | WasmOperandType::MemoryFill
+                | WasmOperandType::ElemDrop
+                | WasmOperandType::Table
+                | WasmOperandType::RefFunc => {
                     let opr = stream.read_unsigned()?;
                     println!("{} {}", op.to_str(), opr);
                 }
-                WasmOperandType::CallIndirect => {
+                WasmOperandType::CallIndirect
+                | WasmOperandType::MemoryInit
+                | WasmOperandType::MemoryCopy
+                | WasmOperandType::TableInit
+                | WasmOperandType::TableCopy => {
                     let opr1 = stream.read_unsigned()?;
                     let opr2 = stream.read_unsigned()?;
                     println!("{} {} {}", op.to_str(), opr1, opr2);
@@ -537,9 +1657,66 @@ impl WasmModule {
                     println!(" {}", target);
                 }
                 WasmOperandType::Memory => {
-                    let a = stream.read_unsigned()?;
-                    let o = stream.read_unsigned()?;
-                    println!("{} offset={} align={}", op.to_str(), o, a);
+                    let memarg = stream.read_memarg()?;
+                    if memarg.memidx != 0 {
+                        println!(
+                            "{} {} offset={} align={}",
+                            op.to_str(),
+                            memarg.memidx,
+                            memarg.offset,
+                            memarg.align
+                        );
+                    } else {
+                        println!(
+                            "{} offset={} align={}",
+                            op.to_str(),
+                            memarg.offset,
+                            memarg.align
+                        );
+                    }
+                }
+                WasmOperandType::MemoryLane => {
+                    let memarg = stream.read_memarg()?;
+                    let lane = stream.read_byte()?;
+                    println!(
+                        "{} offset={} align={} {}",
+                        op.to_str(),
+                        memarg.offset,
+                        memarg.align,
+                        lane
+                    );
+                }
+                WasmOperandType::Lane => {
+                    let lane = stream.read_byte()?;
+                    println!("{} {}", op.to_str(), lane);
+                }
+                WasmOperandType::V128 => {
+                    let opr = LE::read_u128(stream.get_bytes(16)?);
+                    println!("{} 0x{:032x}", op.to_str(), opr);
+                }
+                WasmOperandType::Shuffle => {
+                    print!("{}", op.to_str());
+                    for lane in stream.get_bytes(16)? {
+                        print!(" {}", lane);
+                    }
+                    println!();
+                }
+                WasmOperandType::RefType => {
+                    let ref_type = stream
+                        .read_byte()
+                        .and_then(|v| WasmValType::from_u64(v as u64))?;
+                    println!("{} {}", op.to_str(), ref_type);
+                }
+                WasmOperandType::SelectT => {
+                    let n_vec = stream.read_unsigned()?;
+                    print!("{}", op.to_str());
+                    for _ in 0..n_vec {
+                        let val_type = stream
+                            .read_byte()
+                            .and_then(|v| WasmValType::from_u64(v as u64))?;
+                        print!(" {}", val_type);
+                    }
+                    println!();
                 }
                 WasmOperandType::I32 => {
                     let opr = stream.read_signed()? as i32;
@@ -549,15 +1726,27 @@ impl WasmModule {
                     let opr = stream.read_signed()?;
                     println!("{} {} ;; 0x{:x}", op.to_str(), opr, opr);
                 }
-
-                WasmOperandType::F32 => todo!(),
-                WasmOperandType::F64 => todo!(),
+                WasmOperandType::F32 => {
+                    let bits = LE::read_u32(stream.get_bytes(4)?);
+                    println!("{} {} ;; 0x{:08x}", op.to_str(), f32::from_bits(bits), bits);
+                }
+                WasmOperandType::F64 => {
+                    let bits = LE::read_u64(stream.get_bytes(8)?);
+                    println!(
+                        "{} {} ;; 0x{:016x}",
+                        op.to_str(),
+                        f64::from_bits(bits),
+                        bits
+                    );
+                }
             }
         }
         Ok(())
     }
 
-    fn nest(level: usize) {
+    /// Prints the offset of the instruction, which is the same as [WasmFrame::position], and the indent
+    fn nest(position: usize, level: usize) {
+        print!("{:04x} ", position);
         let level = usize::min(level, 20);
         for _ in 0..level {
             print!("  ");
@@ -699,27 +1888,43 @@ impl Leb128Stream<'_> {
     #[inline]
     pub fn read_opcode(&mut self) -> Result<WasmOpcode, WasmDecodeError> {
         self.fetch_position = self.position();
-        self.read_byte()
-            .and_then(|v| WasmOpcode::try_from(v).map_err(|_| WasmDecodeError::InvalidBytecode))
+        let lead = self.read_byte()?;
+        if WasmOpcode::is_prefix(lead) {
+            let value = self.read_unsigned()?;
+            u32::try_from(value)
+                .ok()
+                .and_then(|v| WasmOpcode::try_from_prefixed(lead, v).ok())
+                .ok_or(WasmDecodeError::InvalidBytecode)
+        } else {
+            WasmOpcode::try_from(lead).map_err(|_| WasmDecodeError::InvalidBytecode)
+        }
     }
 
     #[inline]
     pub fn read_memarg(&mut self) -> Result<WasmMemArg, WasmDecodeError> {
         let a = self.read_unsigned()? as u32;
-        let o = self.read_unsigned()? as u32;
-        Ok(WasmMemArg::new(o, a))
+        let (a, m) = if (a & WasmMemArg::HAS_MEMIDX) != 0 {
+            (a & !WasmMemArg::HAS_MEMIDX, self.read_unsigned()? as u32)
+        } else {
+            (a, 0)
+        };
+        let o = self.read_unsigned()?;
+        Ok(WasmMemArg::with_memidx(o, a, m))
     }
 
-    fn next_section(&mut self) -> Result<Option<WasmSection>, WasmDecodeError> {
+    fn next_section(&mut self) -> Result<Option<WasmSection<'_>>, WasmDecodeError> {
         let section_type = match self.read_byte().ok() {
             Some(v) => v,
             None => return Ok(None),
         };
 
-        let blob = self.read_bytes()?;
+        let len = self.read_unsigned()? as usize;
+        let offset = self.position;
+        let blob = self.get_bytes(len)?;
         let stream = Leb128Stream::from_slice(blob);
         Ok(Some(WasmSection {
             section_type: section_type.into(),
+            offset,
             stream,
         }))
     }
@@ -728,26 +1933,93 @@ impl Leb128Stream<'_> {
 #[derive(Debug, Copy, Clone)]
 pub struct WasmMemArg {
     pub align: u32,
-    pub offset: u32,
+    pub offset: u64,
+    pub memidx: u32,
 }
 
 impl WasmMemArg {
+    /// The bit of the alignment field indicating that a memory index follows (multi-memory)
+    const HAS_MEMIDX: u32 = 0x40;
+
+    #[inline]
+    pub const fn new(offset: u64, align: u32) -> Self {
+        Self {
+            offset,
+            align,
+            memidx: 0,
+        }
+    }
+
     #[inline]
-    pub const fn new(offset: u32, align: u32) -> Self {
-        Self { offset, align }
+    pub const fn with_memidx(offset: u64, align: u32, memidx: u32) -> Self {
+        Self {
+            offset,
+            align,
+            memidx,
+        }
+    }
+
+    #[inline]
+    pub const fn memidx(&self) -> usize {
+        self.memidx as usize
     }
 
     #[inline]
     pub const fn offset_by(&self, base: u32) -> usize {
-        (self.offset as u64 + base as u64) as usize
+        (self.offset + base as u64) as usize
+    }
+
+    /// Calculates the effective address in the 64-bit memory, `None` if it overflows
+    #[inline]
+    pub fn offset_by64(&self, base: u64) -> Option<usize> {
+        self.offset
+            .checked_add(base)
+            .and_then(|v| usize::try_from(v).ok())
     }
 }
 
 struct WasmSection<'a> {
     section_type: WasmSectionType,
+    /// Offset of the contents in the stream of sections
+    offset: usize,
     stream: Leb128Stream<'a>,
 }
 
+/// Section of the binary, see [WasmModule::sections]
+#[derive(Debug, Clone)]
+pub struct WasmSectionInfo {
+    section_type: WasmSectionType,
+    offset: usize,
+    size: usize,
+    custom_name: Option<String>,
+}
+
+impl WasmSectionInfo {
+    /// Name of the section, such as "type" or "code"
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.section_type.name()
+    }
+
+    /// Offset of the contents of the section from the beginning of the binary
+    #[inline]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Size of the contents of the section in bytes
+    #[inline]
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Name of the custom section, such as "name" or "producers"
+    #[inline]
+    pub fn custom_name(&self) -> Option<&str> {
+        self.custom_name.as_deref()
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
 enum WasmSectionType {
@@ -763,6 +2035,29 @@ enum WasmSectionType {
     Element,
     Code,
     Data,
+    DataCount,
+    Tag,
+}
+
+impl WasmSectionType {
+    const fn name(&self) -> &'static str {
+        match *self {
+            Self::Custom => "custom",
+            Self::Type => "type",
+            Self::Import => "import",
+            Self::Function => "function",
+            Self::Table => "table",
+            Self::Memory => "memory",
+            Self::Global => "global",
+            Self::Export => "export",
+            Self::Start => "start",
+            Self::Element => "element",
+            Self::Code => "code",
+            Self::Data => "data",
+            Self::DataCount => "datacount",
+            Self::Tag => "tag",
+        }
+    }
 }
 
 impl From<u8> for WasmSectionType {
@@ -779,6 +2074,8 @@ impl From<u8> for WasmSectionType {
             9 => WasmSectionType::Element,
             10 => WasmSectionType::Code,
             11 => WasmSectionType::Data,
+            12 => WasmSectionType::DataCount,
+            13 => WasmSectionType::Tag,
             _ => WasmSectionType::Custom,
         }
     }
@@ -791,6 +2088,10 @@ pub enum WasmValType {
     I64 = 0x7E,
     F32 = 0x7D,
     F64 = 0x7C,
+    V128 = 0x7B,
+    FuncRef = 0x70,
+    ExternRef = 0x6F,
+    ExnRef = 0x69,
 }
 
 impl WasmValType {
@@ -800,9 +2101,31 @@ impl WasmValType {
             0x7E => Ok(WasmValType::I64),
             0x7D => Ok(WasmValType::F32),
             0x7C => Ok(WasmValType::F64),
+            0x7B => Ok(WasmValType::V128),
+            0x70 => Ok(WasmValType::FuncRef),
+            0x6F => Ok(WasmValType::ExternRef),
+            0x69 => Ok(WasmValType::ExnRef),
             _ => Err(WasmDecodeError::UnexpectedToken),
         }
     }
+
+    /// Reads the reference type of tables, elements and ref.null
+    fn ref_type_from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
+        match stream.read_byte()? {
+            0x70 => Ok(WasmValType::FuncRef),
+            0x6F => Ok(WasmValType::ExternRef),
+            0x69 => Ok(WasmValType::ExnRef),
+            _ => Err(WasmDecodeError::UnexpectedToken),
+        }
+    }
+
+    #[inline]
+    pub const fn is_ref(&self) -> bool {
+        matches!(
+            *self,
+            WasmValType::FuncRef | WasmValType::ExternRef | WasmValType::ExnRef
+        )
+    }
 }
 
 impl fmt::Display for WasmValType {
@@ -815,6 +2138,10 @@ impl fmt::Display for WasmValType {
                 WasmValType::I64 => "i64",
                 WasmValType::F32 => "f32",
                 WasmValType::F64 => "f64",
+                WasmValType::V128 => "v128",
+                WasmValType::FuncRef => "funcref",
+                WasmValType::ExternRef => "externref",
+                WasmValType::ExnRef => "exnref",
             }
         )
     }
@@ -828,6 +2155,10 @@ pub enum WasmBlockType {
     I64 = -2,
     F32 = -3,
     F64 = -4,
+    V128 = -5,
+    FuncRef = -16,
+    ExternRef = -17,
+    ExnRef = -23,
 }
 
 impl WasmBlockType {
@@ -838,237 +2169,1052 @@ impl WasmBlockType {
             -2 => Ok(Self::I64),
             -3 => Ok(Self::F32),
             -4 => Ok(Self::F64),
+            -5 => Ok(Self::V128),
+            -16 => Ok(Self::FuncRef),
+            -17 => Ok(Self::ExternRef),
+            -23 => Ok(Self::ExnRef),
             _ => Err(WasmDecodeError::InvalidParameter),
         }
     }
 
-    pub const fn into_type(self) -> Option<WasmValType> {
-        match self {
-            WasmBlockType::Empty => None,
-            WasmBlockType::I32 => Some(WasmValType::I32),
-            WasmBlockType::I64 => Some(WasmValType::I64),
-            WasmBlockType::F32 => Some(WasmValType::F32),
-            WasmBlockType::F64 => Some(WasmValType::F64),
+    pub const fn into_type(self) -> Option<WasmValType> {
+        match self {
+            WasmBlockType::Empty => None,
+            WasmBlockType::I32 => Some(WasmValType::I32),
+            WasmBlockType::I64 => Some(WasmValType::I64),
+            WasmBlockType::F32 => Some(WasmValType::F32),
+            WasmBlockType::F64 => Some(WasmValType::F64),
+            WasmBlockType::V128 => Some(WasmValType::V128),
+            WasmBlockType::FuncRef => Some(WasmValType::FuncRef),
+            WasmBlockType::ExternRef => Some(WasmValType::ExternRef),
+            WasmBlockType::ExnRef => Some(WasmValType::ExnRef),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct WasmLimit {
+    min: u64,
+    max: Option<u64>,
+    is_64: bool,
+    is_shared: bool,
+}
+
+impl WasmLimit {
+    fn from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
+        let flags = stream.read_unsigned()?;
+        if flags > 7 {
+            return Err(WasmDecodeError::UnexpectedToken);
+        }
+        let is_64 = (flags & 4) != 0;
+        let is_shared = (flags & 2) != 0;
+        let min = stream.read_unsigned()?;
+        let max = if (flags & 1) != 0 {
+            Some(stream.read_unsigned()?)
+        } else {
+            None
+        };
+        let limit = Self {
+            min,
+            max,
+            is_64,
+            is_shared,
+        };
+        if is_shared && max.is_none() {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        if !is_64 && (min > u32::MAX as u64 || max.unwrap_or(0) > u32::MAX as u64) {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        if max.map(|max| max < min).unwrap_or(false) {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        Ok(limit)
+    }
+
+    #[inline]
+    pub const fn min(&self) -> u64 {
+        self.min
+    }
+
+    #[inline]
+    pub const fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// Returns whether the memory is indexed by i64 (memory64)
+    #[inline]
+    pub const fn is_64(&self) -> bool {
+        self.is_64
+    }
+
+    /// Returns whether the memory is shared between threads (threads)
+    #[inline]
+    pub const fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
+    /// Returns the type of the address operand
+    #[inline]
+    pub const fn index_type(&self) -> WasmValType {
+        if self.is_64 {
+            WasmValType::I64
+        } else {
+            WasmValType::I32
+        }
+    }
+}
+
+/// Backing store of the linear memory
+///
+/// The loader creates a backend for each memory through a [WasmMemoryAllocator],
+/// so that the host can decide where the guest memory lives.
+///
+/// # Safety
+///
+/// The interpreter reads and writes the region through the pointer the backend returns,
+/// without checking it any further. The implementation must guarantee that:
+///
+/// * `base_ptr()` points to the region of `len()` bytes, which is valid for reads and writes
+///   through the pointer and is not accessed through any reference while the memory is in use.
+/// * Only `allocate` and `grow` change the base address and the length, and the area they add
+///   is zero-filled.
+/// * If `is_guarded()` returns true, the base address never moves and the whole reservation
+///   of `MmapMemoryBackend::RESERVED_SIZE` bytes from it belongs to the backend, so that
+///   accesses beyond `len()` fault instead of reaching other objects.
+/// * If `waiter()` returns a waiter, the base address never moves once allocated, and the
+///   length is read and updated atomically since the other threads may grow the region.
+pub unsafe trait MemoryBackend {
+    /// Allocates the initial region of `size` bytes, which must be zero-filled
+    fn allocate(&mut self, size: usize) -> Result<(), WasmRuntimeError>;
+
+    /// Extends the region by `delta` bytes up to `max_size`, and returns the old size
+    ///
+    /// The added area must be zero-filled. The base address may change after growing.
+    /// A backend shared between threads must read and update the size atomically.
+    fn grow(&mut self, delta: usize, max_size: usize) -> Result<usize, WasmRuntimeError>;
+
+    /// Returns the base address of the region
+    fn base_ptr(&self) -> *mut u8;
+
+    /// Returns the current size of the region in bytes
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the region is surrounded by guard pages
+    ///
+    /// A guarded backend must reserve `MmapMemoryBackend::RESERVED_SIZE` bytes
+    /// of address space from a base that never moves, so that accesses beyond `len()` fault.
+    /// It takes effect only for 32-bit memories with the `mmap` feature.
+    #[inline]
+    fn is_guarded(&self) -> bool {
+        false
+    }
+
+    /// Returns the waiter for memory.atomic.wait and memory.atomic.notify
+    ///
+    /// Only a backend whose region can be accessed from multiple threads has one.
+    /// Such a backend must never move the region once allocated.
+    #[inline]
+    fn waiter(&self) -> Option<&dyn WasmWaiter> {
+        None
+    }
+}
+
+/// Suspends and wakes the threads for memory.atomic.wait and memory.atomic.notify
+///
+/// The host can plug its own scheduler in through [MemoryBackend::waiter].
+pub trait WasmWaiter {
+    /// Suspends the current thread until notified at `address` or timed out
+    ///
+    /// `is_expected` compares the value in memory and must be called while holding
+    /// the lock that `notify` also takes, so that no notification is lost.
+    /// A negative `timeout` in nanoseconds means infinity.
+    fn wait(&self, address: usize, is_expected: &dyn Fn() -> bool, timeout: i64) -> WasmWaitResult;
+
+    /// Wakes up to `count` threads waiting at `address`, and returns the number of them
+    fn notify(&self, address: usize, count: u32) -> u32;
+}
+
+/// Result of memory.atomic.wait
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WasmWaitResult {
+    Ok = 0,
+    NotEqual = 1,
+    TimedOut = 2,
+}
+
+/// Read-modify-write operations of the atomic instructions
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WasmAtomicRmw {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Xchg,
+}
+
+/// Accesses the naturally aligned integer of the size at the address atomically
+macro_rules! atomic_access {
+    ($ptr:expr, $size:expr, |$atomic:ident| $body:expr) => {
+        unsafe {
+            match $size {
+                1 => {
+                    let $atomic = &*($ptr as *const AtomicU8);
+                    ($body) as u64
+                }
+                2 => {
+                    let $atomic = &*($ptr as *const AtomicU16);
+                    ($body) as u64
+                }
+                4 => {
+                    let $atomic = &*($ptr as *const AtomicU32);
+                    ($body) as u64
+                }
+                _ => {
+                    let $atomic = &*($ptr as *const AtomicU64);
+                    ($body) as u64
+                }
+            }
+        }
+    };
+}
+
+/// Default memory backend, growable `Vec<u8>`
+pub struct VecMemoryBackend {
+    vec: Vec<u8>,
+    /// Pointer to the buffer of the vector, taken again whenever it reallocates
+    ptr: NonNull<u8>,
+}
+
+impl VecMemoryBackend {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            vec: Vec::new(),
+            ptr: NonNull::dangling(),
+        }
+    }
+
+    /// Default memory allocator
+    pub fn allocator(_limit: WasmLimit) -> Result<Box<dyn MemoryBackend>, WasmDecodeError> {
+        Ok(Box::new(Self::new()))
+    }
+}
+
+impl Default for VecMemoryBackend {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl MemoryBackend for VecMemoryBackend {
+    fn allocate(&mut self, size: usize) -> Result<(), WasmRuntimeError> {
+        self.grow(size, size).map(|_| ())
+    }
+
+    fn grow(&mut self, delta: usize, max_size: usize) -> Result<usize, WasmRuntimeError> {
+        let old_size = self.vec.len();
+        let new_size = match old_size.checked_add(delta) {
+            Some(v) if v <= max_size => v,
+            _ => return Err(WasmRuntimeError::OutOfMemory),
+        };
+        self.vec
+            .try_reserve_exact(delta)
+            .map_err(|_| WasmRuntimeError::OutOfMemory)?;
+        self.vec.resize(new_size, 0);
+        self.ptr = NonNull::from(self.vec.as_mut_slice()).cast();
+        Ok(old_size)
+    }
+
+    #[inline]
+    fn base_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+}
+
+/// Memory backend over a fixed buffer, such as reserved page frames in a kernel
+///
+/// The memory cannot grow beyond the size of the buffer.
+pub struct StaticMemoryBackend {
+    /// The buffer, which is accessed only through the pointer once taken over
+    ptr: NonNull<u8>,
+    capacity: usize,
+    len: usize,
+}
+
+impl StaticMemoryBackend {
+    #[inline]
+    pub fn new(buffer: &'static mut [u8]) -> Self {
+        Self {
+            capacity: buffer.len(),
+            ptr: NonNull::from(buffer).cast(),
+            len: 0,
+        }
+    }
+
+    /// Returns the size of the underlying buffer
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+unsafe impl MemoryBackend for StaticMemoryBackend {
+    fn allocate(&mut self, size: usize) -> Result<(), WasmRuntimeError> {
+        self.len = 0;
+        self.grow(size, size).map(|_| ())
+    }
+
+    fn grow(&mut self, delta: usize, max_size: usize) -> Result<usize, WasmRuntimeError> {
+        let old_size = self.len;
+        let new_size = match old_size.checked_add(delta) {
+            Some(v) if v <= max_size && v <= self.capacity => v,
+            _ => return Err(WasmRuntimeError::OutOfMemory),
+        };
+        unsafe {
+            self.ptr.as_ptr().add(old_size).write_bytes(0, delta);
+        }
+        self.len = new_size;
+        Ok(old_size)
+    }
+
+    #[inline]
+    fn base_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct WasmMemory {
+    limit: WasmLimit,
+    backend: UnsafeCell<Box<dyn MemoryBackend>>,
+    /// Base address of the guarded memory, which never moves
+    guarded: Option<*mut u8>,
+}
+
+impl WasmMemory {
+    const PAGE_SIZE: usize = 0x10000;
+
+    /// Maximum number of pages of the 32-bit memory
+    const MAX_PAGES_32: u64 = 0x1_0000;
+
+    /// Maximum number of pages of the 64-bit memory
+    const MAX_PAGES_64: u64 = 0x1_0000_0000_0000;
+
+    fn new(limit: WasmLimit, mut backend: Box<dyn MemoryBackend>) -> Result<Self, WasmDecodeError> {
+        Self::check_limit(limit)?;
+        let size = usize::try_from(limit.min)
+            .ok()
+            .and_then(|v| v.checked_mul(Self::PAGE_SIZE))
+            .ok_or(WasmDecodeError::OutOfMemory)?;
+        backend
+            .allocate(size)
+            .map_err(|_| WasmDecodeError::OutOfMemory)?;
+        let guarded = if crate::guard::AVAILABLE && backend.is_guarded() && !limit.is_64() {
+            crate::guard::install_trap_handler();
+            Some(backend.base_ptr())
+        } else {
+            None
+        };
+        Ok(Self {
+            limit,
+            backend: UnsafeCell::new(backend),
+            guarded,
+        })
+    }
+
+    /// The memory which is declared but not allocated, for the module only decoded
+    fn declared(limit: WasmLimit) -> Result<Self, WasmDecodeError> {
+        Self::check_limit(limit)?;
+        Ok(Self {
+            limit,
+            backend: UnsafeCell::new(Box::new(VecMemoryBackend::new())),
+            guarded: None,
+        })
+    }
+
+    fn check_limit(limit: WasmLimit) -> Result<(), WasmDecodeError> {
+        let max_pages = Self::max_pages(limit);
+        if limit.min > max_pages || limit.max.unwrap_or(0) > max_pages {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub const fn limit(&self) -> WasmLimit {
+        self.limit
+    }
+
+    /// Returns whether the memory instructions detect out-of-bounds accesses by the guard pages
+    /// instead of range checks
+    #[inline]
+    pub const fn is_guarded(&self) -> bool {
+        self.guarded.is_some()
+    }
+
+    #[inline]
+    fn backend(&self) -> &dyn MemoryBackend {
+        unsafe { self.backend.get().as_ref().unwrap().as_ref() }
+    }
+
+    /// Returns the address of the range after checking the bounds
+    ///
+    /// The accesses go through the raw pointer without making references to the memory,
+    /// which other instances sharing the backend may write at the same time.
+    #[inline]
+    fn range_ptr(&self, offset: usize, size: usize) -> Result<*mut u8, WasmRuntimeError> {
+        let backend = self.backend();
+        if Self::check_range(backend.len(), offset, size) {
+            Ok(unsafe { backend.base_ptr().add(offset) })
+        } else {
+            Err(WasmRuntimeError::OutOfBounds)
+        }
+    }
+
+    /// Returns the base address of the linear memory
+    #[inline]
+    pub fn base_ptr(&self) -> *mut u8 {
+        self.backend().base_ptr()
+    }
+
+    #[inline]
+    const fn max_pages(limit: WasmLimit) -> u64 {
+        if limit.is_64 {
+            Self::MAX_PAGES_64
+        } else {
+            Self::MAX_PAGES_32
+        }
+    }
+
+    pub fn grow(&self, delta: usize) -> isize {
+        // The mutable borrow is of the box of this instance only, and no other borrow of it
+        // is alive here. A shared backend grows its region by atomics, see `MemoryBackend`.
+        let backend = unsafe { self.backend.get().as_mut().unwrap() };
+        let max_pages = self
+            .limit
+            .max
+            .unwrap_or_else(|| Self::max_pages(self.limit));
+        let max_size = usize::try_from(max_pages)
+            .ok()
+            .and_then(|v| v.checked_mul(Self::PAGE_SIZE))
+            .unwrap_or(usize::MAX);
+        let delta = match delta.checked_mul(Self::PAGE_SIZE) {
+            Some(v) => v,
+            None => return -1,
+        };
+        // The backend checks the limit against the old size it reads, which may be shared
+        match backend.grow(delta, max_size) {
+            Ok(old_size) => (old_size / Self::PAGE_SIZE) as isize,
+            Err(_) => -1,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.backend().len() / Self::PAGE_SIZE
+    }
+
+    /// Read the specified range of memory
+    ///
+    /// The slice must not be kept while the guest runs, which may grow the memory
+    /// or write it from another thread. [Self::read_into] copies the range instead.
+    pub fn read_bytes(&self, offset: usize, size: usize) -> Result<&[u8], WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, size)?;
+        unsafe { Ok(slice::from_raw_parts(ptr, size)) }
+    }
+
+    /// Copy the specified range of memory to the buffer
+    pub fn read_into(&self, offset: usize, dest: &mut [u8]) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, dest.len())?;
+        unsafe {
+            dest.as_mut_ptr().copy_from_nonoverlapping(ptr, dest.len());
+        }
+        Ok(())
+    }
+
+    /// Write slice to memory
+    pub fn write_bytes(&self, offset: usize, src: &[u8]) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, src.len())?;
+        unsafe {
+            ptr.copy_from_nonoverlapping(src.as_ptr(), src.len());
+        }
+        Ok(())
+    }
+
+    /// Read the string of (ptr, len)
+    pub fn read_str(&self, offset: usize, size: usize) -> Result<&str, WasmRuntimeError> {
+        self.read_bytes(offset, size)
+            .and_then(|v| str::from_utf8(v).map_err(|_| WasmRuntimeError::InvalidUtf8))
+    }
+
+    /// Read the bytes of the NUL-terminated string, without the terminator
+    pub fn read_cbytes(&self, offset: usize) -> Result<&[u8], WasmRuntimeError> {
+        let limit = self.backend().len();
+        let ptr = self.range_ptr(offset, 0)?;
+        let size = (0..limit - offset)
+            .position(|i| unsafe { ptr.add(i).read() } == 0)
+            .ok_or(WasmRuntimeError::OutOfBounds)?;
+        self.read_bytes(offset, size)
+    }
+
+    /// Read the NUL-terminated string
+    pub fn read_cstr(&self, offset: usize) -> Result<&str, WasmRuntimeError> {
+        self.read_cbytes(offset)
+            .and_then(|v| str::from_utf8(v).map_err(|_| WasmRuntimeError::InvalidUtf8))
+    }
+
+    /// Returns the array of the 32-bit iovec, (buf: u32, buf_len: u32)
+    fn iovecs(
+        &self,
+        iovs: usize,
+        iovs_len: usize,
+    ) -> Result<Vec<(usize, usize)>, WasmRuntimeError> {
+        let entries = iovs_len
+            .checked_mul(8)
+            .ok_or(WasmRuntimeError::OutOfBounds)
+            .and_then(|size| self.read_bytes(iovs, size))?;
+        Ok(entries
+            .chunks_exact(8)
+            .map(|v| {
+                (
+                    LE::read_u32(&v[0..4]) as usize,
+                    LE::read_u32(&v[4..8]) as usize,
+                )
+            })
+            .collect())
+    }
+
+    /// Read the buffers of the iovec array, for fd_write and the like
+    pub fn read_iovecs(
+        &self,
+        iovs: usize,
+        iovs_len: usize,
+    ) -> Result<Vec<&[u8]>, WasmRuntimeError> {
+        self.iovecs(iovs, iovs_len)?
+            .into_iter()
+            .map(|(base, len)| self.read_bytes(base, len))
+            .collect()
+    }
+
+    /// Scatter the bytes to the buffers of the iovec array, for fd_read and the like
+    ///
+    /// Returns the number of bytes written, which is less than the source if the buffers are full.
+    pub fn write_iovecs(
+        &self,
+        iovs: usize,
+        iovs_len: usize,
+        src: &[u8],
+    ) -> Result<usize, WasmRuntimeError> {
+        let mut written = 0;
+        for (base, len) in self.iovecs(iovs, iovs_len)? {
+            let size = usize::min(len, src.len() - written);
+            self.write_bytes(base, &src[written..written + size])?;
+            written += size;
+        }
+        Ok(written)
+    }
+
+    /// Write the bytes to the buffer allocated by the allocator of the guest
+    ///
+    /// The allocator takes the size, and then returns the address of the buffer.
+    pub fn write_allocated<F>(&self, src: &[u8], allocator: F) -> Result<usize, WasmRuntimeError>
+    where
+        F: FnOnce(usize) -> Result<usize, WasmRuntimeError>,
+    {
+        let offset = allocator(src.len())?;
+        if offset == 0 && !src.is_empty() {
+            return Err(WasmRuntimeError::OutOfMemory);
+        }
+        self.write_bytes(offset, src).map(|_| offset)
+    }
+
+    /// Fill the specified range of memory, for memory.fill
+    pub fn fill(&self, offset: usize, val: u8, size: usize) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, size)?;
+        unsafe {
+            ptr.write_bytes(val, size);
+        }
+        Ok(())
+    }
+
+    /// Copy the range of memory, which may be the same memory, for memory.copy
+    pub fn copy_from(
+        &self,
+        dest: usize,
+        src_memory: &WasmMemory,
+        src: usize,
+        size: usize,
+    ) -> Result<(), WasmRuntimeError> {
+        let dest = self.range_ptr(dest, size)?;
+        let src = src_memory.range_ptr(src, size)?;
+        unsafe {
+            core::ptr::copy(src, dest, size);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    const fn check_range(limit: usize, offset: usize, size: usize) -> bool {
+        offset <= limit && size <= limit - offset
+    }
+
+    pub fn read_u8(&self, offset: usize) -> Result<u8, WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 1)?;
+        Ok(unsafe { ptr.read() })
+    }
+
+    pub fn write_u8(&self, offset: usize, val: u8) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 1)?;
+        unsafe { ptr.write(val) };
+        Ok(())
+    }
+
+    pub fn read_u16(&self, offset: usize) -> Result<u16, WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 2)?;
+        Ok(unsafe { u16::from_le(ptr.cast::<u16>().read_unaligned()) })
+    }
+
+    pub fn write_u16(&self, offset: usize, val: u16) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 2)?;
+        unsafe { ptr.cast::<u16>().write_unaligned(val.to_le()) };
+        Ok(())
+    }
+
+    pub fn read_u32(&self, offset: usize) -> Result<u32, WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 4)?;
+        Ok(unsafe { u32::from_le(ptr.cast::<u32>().read_unaligned()) })
+    }
+
+    pub fn write_u32(&self, offset: usize, val: u32) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 4)?;
+        unsafe { ptr.cast::<u32>().write_unaligned(val.to_le()) };
+        Ok(())
+    }
+
+    pub fn read_u64(&self, offset: usize) -> Result<u64, WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 8)?;
+        Ok(unsafe { u64::from_le(ptr.cast::<u64>().read_unaligned()) })
+    }
+
+    pub fn write_u64(&self, offset: usize, val: u64) -> Result<(), WasmRuntimeError> {
+        let ptr = self.range_ptr(offset, 8)?;
+        unsafe { ptr.cast::<u64>().write_unaligned(val.to_le()) };
+        Ok(())
+    }
+
+    /// Reads the operand of the memory instruction at the effective address
+    ///
+    /// The effective address of a 32-bit memory is below 2^33 and a guarded memory reserves
+    /// all of that range, so the guard pages catch out-of-bounds accesses without a range check.
+    #[inline]
+    pub(crate) fn load_u8(&self, ea: usize) -> Result<u8, WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::load_u8(base.add(ea)) },
+            None => self.read_u8(ea),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load_u16(&self, ea: usize) -> Result<u16, WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::load_u16(base.add(ea)) },
+            None => self.read_u16(ea),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load_u32(&self, ea: usize) -> Result<u32, WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::load_u32(base.add(ea)) },
+            None => self.read_u32(ea),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn load_u64(&self, ea: usize) -> Result<u64, WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::load_u64(base.add(ea)) },
+            None => self.read_u64(ea),
+        }
+    }
+
+    /// Writes the operand of the memory instruction at the effective address, see [Self::load_u8]
+    #[inline]
+    pub(crate) fn store_u8(&self, ea: usize, val: u8) -> Result<(), WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::store_u8(base.add(ea), val) },
+            None => self.write_u8(ea, val),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn store_u16(&self, ea: usize, val: u16) -> Result<(), WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::store_u16(base.add(ea), val) },
+            None => self.write_u16(ea, val),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn store_u32(&self, ea: usize, val: u32) -> Result<(), WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::store_u32(base.add(ea), val) },
+            None => self.write_u32(ea, val),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn store_u64(&self, ea: usize, val: u64) -> Result<(), WasmRuntimeError> {
+        match self.guarded {
+            Some(base) => unsafe { crate::guard::store_u64(base.add(ea), val) },
+            None => self.write_u64(ea, val),
         }
     }
-}
 
-#[derive(Debug, Copy, Clone)]
-pub struct WasmLimit {
-    min: u32,
-    max: u32,
-}
+    /// Returns the address of the atomic access, which must be naturally aligned
+    fn atomic_ptr(&self, offset: usize, size: usize) -> Result<*mut u8, WasmRuntimeError> {
+        if (offset & (size - 1)) != 0 {
+            return Err(WasmRuntimeError::UnalignedAccess);
+        }
+        let ptr = self.range_ptr(offset, size)?;
+        if (ptr as usize & (size - 1)) != 0 {
+            // The backend does not align its base address
+            return Err(WasmRuntimeError::UnalignedAccess);
+        }
+        Ok(ptr)
+    }
+
+    /// Loads the integer of `size` bytes atomically, for *.atomic.load
+    pub fn atomic_load(&self, offset: usize, size: usize) -> Result<u64, WasmRuntimeError> {
+        let ptr = self.atomic_ptr(offset, size)?;
+        Ok(atomic_access!(ptr, size, |atomic| atomic.load(Ordering::SeqCst)))
+    }
+
+    /// Stores the integer of `size` bytes atomically, for *.atomic.store
+    pub fn atomic_store(
+        &self,
+        offset: usize,
+        size: usize,
+        val: u64,
+    ) -> Result<(), WasmRuntimeError> {
+        let ptr = self.atomic_ptr(offset, size)?;
+        atomic_access!(ptr, size, |atomic| {
+            atomic.store(val as _, Ordering::SeqCst);
+            0
+        });
+        Ok(())
+    }
 
-impl WasmLimit {
-    fn from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
-        match stream.read_unsigned() {
-            Ok(0) => stream.read_unsigned().map(|min| Self {
-                min: min as u32,
-                max: min as u32,
-            }),
-            Ok(1) => {
-                let min = stream.read_unsigned()? as u32;
-                let max = stream.read_unsigned()? as u32;
-                Ok(Self { min, max })
-            }
-            Err(err) => Err(err),
-            _ => Err(WasmDecodeError::UnexpectedToken),
+    /// Read-modify-write of `size` bytes, and returns the old value, for *.atomic.rmw.*
+    pub fn atomic_rmw(
+        &self,
+        offset: usize,
+        size: usize,
+        op: WasmAtomicRmw,
+        val: u64,
+    ) -> Result<u64, WasmRuntimeError> {
+        let ptr = self.atomic_ptr(offset, size)?;
+        Ok(atomic_access!(ptr, size, |atomic| match op {
+            WasmAtomicRmw::Add => atomic.fetch_add(val as _, Ordering::SeqCst),
+            WasmAtomicRmw::Sub => atomic.fetch_sub(val as _, Ordering::SeqCst),
+            WasmAtomicRmw::And => atomic.fetch_and(val as _, Ordering::SeqCst),
+            WasmAtomicRmw::Or => atomic.fetch_or(val as _, Ordering::SeqCst),
+            WasmAtomicRmw::Xor => atomic.fetch_xor(val as _, Ordering::SeqCst),
+            WasmAtomicRmw::Xchg => atomic.swap(val as _, Ordering::SeqCst),
+        }))
+    }
+
+    /// Compare and exchange of `size` bytes, and returns the old value, for *.atomic.rmw.cmpxchg
+    pub fn atomic_cmpxchg(
+        &self,
+        offset: usize,
+        size: usize,
+        expected: u64,
+        replacement: u64,
+    ) -> Result<u64, WasmRuntimeError> {
+        let ptr = self.atomic_ptr(offset, size)?;
+        Ok(atomic_access!(
+            ptr,
+            size,
+            |atomic| match atomic.compare_exchange(
+                expected as _,
+                replacement as _,
+                Ordering::SeqCst,
+                Ordering::SeqCst
+            ) {
+                Ok(v) | Err(v) => v,
+            }
+        ))
+    }
+
+    /// Waits for the notification if the value of `size` bytes is `expected`, for memory.atomic.wait*
+    ///
+    /// Waiting on an unshared memory traps.
+    pub fn atomic_wait(
+        &self,
+        offset: usize,
+        size: usize,
+        expected: u64,
+        timeout: i64,
+    ) -> Result<WasmWaitResult, WasmRuntimeError> {
+        if !self.limit.is_shared() {
+            return Err(WasmRuntimeError::ExpectedSharedMemory);
+        }
+        let ptr = self.atomic_ptr(offset, size)?;
+        let is_expected =
+            || atomic_access!(ptr, size, |atomic| atomic.load(Ordering::SeqCst)) == expected;
+        match self.backend().waiter() {
+            Some(waiter) => Ok(waiter.wait(offset, &is_expected, timeout)),
+            // No other thread can reach this memory to notify
+            None if is_expected() => Ok(WasmWaitResult::TimedOut),
+            None => Ok(WasmWaitResult::NotEqual),
         }
     }
+
+    /// Wakes up to `count` waiters at the address, for memory.atomic.notify
+    pub fn atomic_notify(&self, offset: usize, count: u32) -> Result<u32, WasmRuntimeError> {
+        self.atomic_ptr(offset, 4)?;
+        Ok(self
+            .backend()
+            .waiter()
+            .map(|waiter| waiter.notify(offset, count))
+            .unwrap_or(0))
+    }
 }
 
-#[allow(dead_code)]
-pub struct WasmMemory {
+pub struct WasmTable {
     limit: WasmLimit,
-    memory: Arc<UnsafeCell<Vec<u8>>>,
+    elem_type: WasmValType,
+    table: RefCell<Vec<Option<usize>>>,
 }
 
-impl WasmMemory {
-    const PAGE_SIZE: usize = 0x10000;
+impl WasmTable {
+    /// Maximum number of elements if the limit does not specify
+    const MAX_SIZE: u64 = 0xFFFF_FFFF;
 
-    fn new(limit: WasmLimit) -> Self {
-        let size = limit.min as usize * Self::PAGE_SIZE;
-        let mut memory = Vec::with_capacity(size);
-        memory.resize(size, 0);
-        Self {
-            limit,
-            memory: Arc::new(UnsafeCell::new(memory)),
+    /// The table of `size` elements, which is less than the minimum if it is only decoded
+    fn new(elem_type: WasmValType, limit: WasmLimit, size: usize) -> Result<Self, WasmDecodeError> {
+        if limit.is_64 || limit.is_shared {
+            return Err(WasmDecodeError::UnexpectedToken);
         }
+        let table = RefCell::new(vec![None; size]);
+        Ok(Self {
+            limit,
+            elem_type,
+            table,
+        })
     }
 
-    #[inline]
-    pub const fn limit(&self) -> WasmLimit {
+    pub fn limit(&self) -> WasmLimit {
         self.limit
     }
 
-    // fn memory_arc(&self) -> Arc<UnsafeCell<Vec<u8>>> {
-    //     self.memory.clone()
-    // }
+    #[inline]
+    pub const fn elem_type(&self) -> WasmValType {
+        self.elem_type
+    }
+
+    pub fn table(&mut self) -> &mut [Option<usize>] {
+        self.table.get_mut().as_mut_slice()
+    }
 
     #[inline]
-    fn memory(&self) -> &[u8] {
-        unsafe { self.memory.get().as_ref().unwrap() }
+    pub fn size(&self) -> usize {
+        self.table.borrow().len()
     }
 
+    /// Returns the raw reference at the index, `None` if out of bounds
     #[inline]
-    fn memory_mut(&self) -> &mut [u8] {
-        unsafe { self.memory.get().as_mut().unwrap() }
+    pub fn get_ref(&self, index: usize) -> Option<Option<usize>> {
+        self.table.borrow().get(index).copied()
     }
 
-    pub fn grow(&self, delta: usize) -> isize {
-        if Arc::strong_count(&self.memory) > 1 {
-            return -1;
-        }
-        let memory = unsafe { self.memory.get().as_mut().unwrap() };
-        let old_size = memory.len();
-        let additional = delta * Self::PAGE_SIZE;
-        if memory.try_reserve_exact(additional).is_err() {
-            return -1;
-        }
-        memory.resize(old_size + additional, 0);
-        (old_size / Self::PAGE_SIZE) as isize
+    /// Returns the element at the index as a value of the element type
+    pub fn get(&self, index: usize) -> Option<WasmValue> {
+        self.get_ref(index)
+            .map(|v| WasmValue::from_ref(self.elem_type, v))
     }
 
-    #[inline]
-    pub fn size(&self) -> usize {
-        let memory = self.memory();
-        memory.len() / Self::PAGE_SIZE
+    /// Stores the raw reference at the index
+    pub fn set_ref(&self, index: usize, value: Option<usize>) -> Result<(), WasmRuntimeError> {
+        self.table
+            .borrow_mut()
+            .get_mut(index)
+            .map(|v| *v = value)
+            .ok_or(WasmRuntimeError::OutOfBounds)
     }
 
-    /// Read the specified range of memory
-    pub fn read_bytes(&self, offset: usize, size: usize) -> Result<&[u8], WasmRuntimeError> {
-        let memory = self.memory();
-        let limit = memory.len();
-        if offset < limit && size < limit && offset + size < limit {
-            unsafe { Ok(slice::from_raw_parts(&memory[offset] as *const _, size)) }
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
+    /// Stores the value of the element type at the index
+    pub fn set(&self, index: usize, value: WasmValue) -> Result<(), WasmRuntimeError> {
+        if !value.is_valid_type(self.elem_type) {
+            return Err(WasmRuntimeError::TypeMismatch);
         }
+        self.set_ref(index, value.get_ref()?)
     }
 
-    /// Write slice to memory
-    pub fn write_bytes(&mut self, offset: usize, src: &[u8]) -> Result<(), WasmRuntimeError> {
-        let memory = self.memory_mut();
-        let size = src.len();
-        let limit = memory.len();
-        if offset < limit && size < limit && offset + size < limit {
-            let dest = &mut memory[offset] as *mut u8;
-            let src = &src[0] as *const u8;
-            unsafe {
-                dest.copy_from_nonoverlapping(src, size);
-            }
+    /// Grows the table by delta elements, returns the previous size or -1 on failure
+    pub fn grow(&self, delta: usize, value: Option<usize>) -> isize {
+        let mut table = self.table.borrow_mut();
+        let old_size = table.len();
+        let max_size = self.limit.max.unwrap_or(Self::MAX_SIZE);
+        let new_size = match old_size.checked_add(delta) {
+            Some(v) if v as u64 <= max_size => v,
+            _ => return -1,
+        };
+        if table.try_reserve_exact(delta).is_err() {
+            return -1;
+        }
+        table.resize(new_size, value);
+        old_size as isize
+    }
+
+    /// Fills the range of the table, for table.fill
+    pub fn fill(
+        &self,
+        offset: usize,
+        value: Option<usize>,
+        size: usize,
+    ) -> Result<(), WasmRuntimeError> {
+        let mut table = self.table.borrow_mut();
+        if Self::check_range(table.len(), offset, size) {
+            table[offset..offset + size].fill(value);
             Ok(())
         } else {
             Err(WasmRuntimeError::OutOfBounds)
         }
     }
 
-    pub fn read_u8(&self, offset: usize) -> Result<u8, WasmRuntimeError> {
-        let slice = self.memory();
-        slice
-            .get(offset)
-            .map(|v| *v)
-            .ok_or(WasmRuntimeError::OutOfBounds)
-    }
-
-    pub fn write_u8(&self, offset: usize, val: u8) -> Result<(), WasmRuntimeError> {
-        let slice = self.memory_mut();
-        slice
-            .get_mut(offset)
-            .map(|v| *v = val)
-            .ok_or(WasmRuntimeError::OutOfBounds)
+    /// Copy elements to the table, for table.init
+    pub fn init(&self, offset: usize, src: &[Option<usize>]) -> Result<(), WasmRuntimeError> {
+        let mut table = self.table.borrow_mut();
+        let dest = offset
+            .checked_add(src.len())
+            .and_then(|end| table.get_mut(offset..end))
+            .ok_or(WasmRuntimeError::OutOfBounds)?;
+        dest.copy_from_slice(src);
+        Ok(())
     }
 
-    pub fn read_u16(&self, offset: usize) -> Result<u16, WasmRuntimeError> {
-        let slice = self.memory();
-        let limit = slice.len();
-        if offset + 1 < limit {
-            Ok(LE::read_u16(&slice[offset..offset + 2]))
+    /// Copy elements between tables, for table.copy
+    pub fn copy_from(
+        &self,
+        dest: usize,
+        src_table: &WasmTable,
+        src: usize,
+        size: usize,
+    ) -> Result<(), WasmRuntimeError> {
+        if core::ptr::eq(self, src_table) {
+            let mut table = self.table.borrow_mut();
+            let limit = table.len();
+            if Self::check_range(limit, dest, size) && Self::check_range(limit, src, size) {
+                table.copy_within(src..src + size, dest);
+                Ok(())
+            } else {
+                Err(WasmRuntimeError::OutOfBounds)
+            }
         } else {
-            Err(WasmRuntimeError::OutOfBounds)
+            let src_table = src_table.table.borrow();
+            let src = src
+                .checked_add(size)
+                .and_then(|end| src_table.get(src..end))
+                .ok_or(WasmRuntimeError::OutOfBounds)?;
+            self.init(dest, src)
         }
     }
 
-    pub fn write_u16(&self, offset: usize, val: u16) -> Result<(), WasmRuntimeError> {
-        let slice = self.memory_mut();
-        let limit = slice.len();
-        if offset + 1 < limit {
-            LE::write_u16(&mut slice[offset..offset + 2], val);
-            Ok(())
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
-        }
+    #[inline]
+    fn check_range(limit: usize, offset: usize, size: usize) -> bool {
+        offset <= limit && size <= limit - offset
     }
+}
 
-    pub fn read_u32(&self, offset: usize) -> Result<u32, WasmRuntimeError> {
-        let slice = self.memory();
-        let limit = slice.len();
-        if offset + 3 < limit {
-            Ok(LE::read_u32(&slice[offset..offset + 4]))
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
-        }
+/// Mode of the data and element segments
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WasmSegmentMode {
+    /// Copied at instantiation to the memory or table of the index, at the offset
+    Active(usize, usize),
+    /// Copied at runtime by memory.init or table.init
+    Passive,
+    /// Not available at runtime, declares references for ref.func (element segments only)
+    Declarative,
+}
+
+pub struct WasmDataSegment {
+    mode: WasmSegmentMode,
+    size: usize,
+    data: RefCell<Vec<u8>>,
+}
+
+impl WasmDataSegment {
+    #[inline]
+    pub const fn mode(&self) -> WasmSegmentMode {
+        self.mode
     }
 
-    pub fn write_u32(&self, offset: usize, val: u32) -> Result<(), WasmRuntimeError> {
-        let slice = self.memory_mut();
-        let limit = slice.len();
-        if offset + 3 < limit {
-            LE::write_u32(&mut slice[offset..offset + 4], val);
-            Ok(())
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
-        }
+    /// Size of the segment in the binary, which remains after dropped
+    #[inline]
+    pub const fn size(&self) -> usize {
+        self.size
     }
 
-    pub fn read_u64(&self, offset: usize) -> Result<u64, WasmRuntimeError> {
-        let slice = self.memory();
-        let limit = slice.len();
-        if offset + 7 < limit {
-            Ok(LE::read_u64(&slice[offset..offset + 8]))
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
-        }
+    /// Contents of the segment, empty after dropped
+    #[inline]
+    pub const fn data(&self) -> &RefCell<Vec<u8>> {
+        &self.data
     }
 
-    pub fn write_u64(&self, offset: usize, val: u64) -> Result<(), WasmRuntimeError> {
-        let slice = self.memory_mut();
-        let limit = slice.len();
-        if offset + 7 < limit {
-            LE::write_u64(&mut slice[offset..offset + 8], val);
-            Ok(())
-        } else {
-            Err(WasmRuntimeError::OutOfBounds)
-        }
+    /// data.drop
+    #[inline]
+    pub fn drop_data(&self) {
+        self.data.replace(Vec::new());
     }
 }
 
-pub struct WasmTable {
-    limit: WasmLimit,
-    table: Vec<usize>,
+pub struct WasmElemSegment {
+    mode: WasmSegmentMode,
+    elem_type: WasmValType,
+    elements: RefCell<Vec<Option<usize>>>,
 }
 
-impl WasmTable {
-    fn from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
-        match stream.read_unsigned() {
-            Ok(0x70) => (),
-            Err(err) => return Err(err),
-            _ => return Err(WasmDecodeError::UnexpectedToken),
-        };
-        WasmLimit::from_stream(stream).map(|limit| {
-            let size = limit.min as usize;
-            let mut table = Vec::with_capacity(size);
-            table.resize(size, 0);
-            Self { limit, table }
-        })
+impl WasmElemSegment {
+    #[inline]
+    pub const fn mode(&self) -> WasmSegmentMode {
+        self.mode
     }
 
-    pub fn limit(&self) -> WasmLimit {
-        self.limit
+    #[inline]
+    pub const fn elem_type(&self) -> WasmValType {
+        self.elem_type
+    }
+
+    /// References of the segment, empty after dropped
+    #[inline]
+    pub const fn elements(&self) -> &RefCell<Vec<Option<usize>>> {
+        &self.elements
     }
 
-    pub fn table(&mut self) -> &mut [usize] {
-        self.table.as_mut_slice()
+    /// elem.drop
+    #[inline]
+    pub fn drop_elements(&self) {
+        self.elements.replace(Vec::new());
     }
 }
 
@@ -1077,7 +3223,7 @@ pub struct WasmFunction {
     func_type: WasmType,
     origin: WasmFunctionOrigin,
     body: Option<WasmFunctionBody>,
-    dlink: Option<WasmDynFunc>,
+    dlink: Option<Rc<WasmHostFn>>,
 }
 
 impl WasmFunction {
@@ -1085,7 +3231,7 @@ impl WasmFunction {
         type_index: usize,
         func_type: &WasmType,
         index: usize,
-        dlink: WasmDynFunc,
+        dlink: Rc<WasmHostFn>,
     ) -> Self {
         Self {
             type_index,
@@ -1110,6 +3256,11 @@ impl WasmFunction {
         self.type_index
     }
 
+    #[inline]
+    pub const fn func_type(&self) -> &WasmType {
+        &self.func_type
+    }
+
     pub fn param_types(&self) -> &[WasmValType] {
         self.func_type.param_types.as_slice()
     }
@@ -1126,8 +3277,8 @@ impl WasmFunction {
         self.body.as_ref()
     }
 
-    pub fn dlink(&self) -> Option<WasmDynFunc> {
-        self.dlink
+    pub fn dlink(&self) -> Option<&WasmHostFn> {
+        self.dlink.as_deref()
     }
 }
 
@@ -1138,7 +3289,7 @@ pub enum WasmFunctionOrigin {
     Import(usize),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WasmType {
     param_types: Vec<WasmValType>,
     result_types: Vec<WasmValType>,
@@ -1156,7 +3307,7 @@ impl WasmType {
         for _ in 0..n_params {
             stream
                 .read_unsigned()
-                .and_then(|v| WasmValType::from_u64(v))
+                .and_then(WasmValType::from_u64)
                 .map(|v| params.push(v))?;
         }
         let n_result = stream.read_unsigned()? as usize;
@@ -1164,7 +3315,7 @@ impl WasmType {
         for _ in 0..n_result {
             stream
                 .read_unsigned()
-                .and_then(|v| WasmValType::from_u64(v))
+                .and_then(WasmValType::from_u64)
                 .map(|v| result.push(v))?;
         }
         Ok(Self {
@@ -1173,6 +3324,14 @@ impl WasmType {
         })
     }
 
+    #[inline]
+    pub const fn new(param_types: Vec<WasmValType>, result_types: Vec<WasmValType>) -> Self {
+        Self {
+            param_types,
+            result_types,
+        }
+    }
+
     pub fn param_types(&self) -> &[WasmValType] {
         self.param_types.as_slice()
     }
@@ -1184,14 +3343,14 @@ impl WasmType {
 
 impl fmt::Display for WasmType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.param_types.len() > 0 {
+        if !self.param_types.is_empty() {
             write!(f, " (param")?;
             for param in &self.param_types {
                 write!(f, " {}", param)?;
             }
             write!(f, ")")?;
         }
-        if self.result_types.len() > 0 {
+        if !self.result_types.is_empty() {
             write!(f, " (result")?;
             for result in &self.result_types {
                 write!(f, " {}", result)?;
@@ -1240,23 +3399,246 @@ impl WasmImport {
 #[derive(Debug, Copy, Clone)]
 pub enum WasmImportIndex {
     Type(usize),
-    // Table(usize),
+    /// Element type and limit of the table
+    Table(WasmValType, WasmLimit),
     Memory(WasmLimit),
-    // Global(usize),
+    Global(WasmValType, bool),
+    /// Type of the tag (exception handling)
+    Tag(usize),
 }
 
 impl WasmImportIndex {
-    fn from_stream(mut stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
+    fn from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
         stream.read_unsigned().and_then(|v| match v {
             0 => stream.read_unsigned().map(|v| Self::Type(v as usize)),
-            // 1 => stream.read_unsigned().map(|v| Self::Table(v as usize)),
-            2 => WasmLimit::from_stream(&mut stream).map(|v| Self::Memory(v)),
-            // 3 => stream.read_unsigned().map(|v| Self::Global(v as usize)),
+            1 => {
+                let elem_type = WasmValType::ref_type_from_stream(stream)?;
+                WasmLimit::from_stream(stream).map(|v| Self::Table(elem_type, v))
+            }
+            2 => WasmLimit::from_stream(stream).map(Self::Memory),
+            3 => {
+                let val_type = stream
+                    .read_byte()
+                    .and_then(|v| WasmValType::from_u64(v as u64))?;
+                match stream.read_byte()? {
+                    0 => Ok(Self::Global(val_type, false)),
+                    1 => Ok(Self::Global(val_type, true)),
+                    _ => Err(WasmDecodeError::UnexpectedToken),
+                }
+            }
+            4 => match stream.read_byte()? {
+                0 => stream.read_unsigned().map(|v| Self::Tag(v as usize)),
+                _ => Err(WasmDecodeError::UnexpectedToken),
+            },
             _ => Err(WasmDecodeError::UnexpectedToken),
         })
     }
 }
 
+/// Exception tag
+#[derive(Debug, Copy, Clone)]
+pub struct WasmTag {
+    type_index: usize,
+}
+
+impl WasmTag {
+    /// Index of the function type, whose parameters are the values of the exception
+    #[inline]
+    pub const fn type_index(&self) -> usize {
+        self.type_index
+    }
+}
+
+/// Catch clause of try_table
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WasmCatch {
+    /// catch tagidx labelidx
+    Catch(usize, usize),
+    /// catch_ref tagidx labelidx
+    CatchRef(usize, usize),
+    /// catch_all labelidx
+    CatchAll(usize),
+    /// catch_all_ref labelidx
+    CatchAllRef(usize),
+}
+
+impl WasmCatch {
+    pub fn from_stream(stream: &mut Leb128Stream) -> Result<Self, WasmDecodeError> {
+        match stream.read_byte()? {
+            0 => Ok(Self::Catch(
+                stream.read_unsigned()? as usize,
+                stream.read_unsigned()? as usize,
+            )),
+            1 => Ok(Self::CatchRef(
+                stream.read_unsigned()? as usize,
+                stream.read_unsigned()? as usize,
+            )),
+            2 => Ok(Self::CatchAll(stream.read_unsigned()? as usize)),
+            3 => Ok(Self::CatchAllRef(stream.read_unsigned()? as usize)),
+            _ => Err(WasmDecodeError::UnexpectedToken),
+        }
+    }
+
+    /// Returns whether the clause catches the exception of the tag
+    #[inline]
+    pub const fn matches(&self, tag: usize) -> bool {
+        match *self {
+            Self::Catch(v, _) | Self::CatchRef(v, _) => v == tag,
+            Self::CatchAll(_) | Self::CatchAllRef(_) => true,
+        }
+    }
+
+    #[inline]
+    pub const fn label(&self) -> usize {
+        match *self {
+            Self::Catch(_, v) | Self::CatchRef(_, v) => v,
+            Self::CatchAll(v) | Self::CatchAllRef(v) => v,
+        }
+    }
+
+    /// Returns whether the clause also passes the exnref
+    #[inline]
+    pub const fn is_ref(&self) -> bool {
+        matches!(*self, Self::CatchRef(_, _) | Self::CatchAllRef(_))
+    }
+}
+
+/// Exception thrown by the guest or the host
+#[derive(Debug, Clone, PartialEq)]
+pub struct WasmException {
+    tag: usize,
+    values: Vec<WasmValue>,
+}
+
+impl WasmException {
+    #[inline]
+    pub const fn new(tag: usize, values: Vec<WasmValue>) -> Self {
+        Self { tag, values }
+    }
+
+    /// Index of the tag in the module
+    #[inline]
+    pub const fn tag(&self) -> usize {
+        self.tag
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[WasmValue] {
+        self.values.as_slice()
+    }
+
+    #[inline]
+    pub fn into_values(self) -> Vec<WasmValue> {
+        self.values
+    }
+}
+
+/// Exceptions being thrown or referenced by exnref
+///
+/// An exception caught without exnref is released at once. The interpreter does not trace
+/// the references on the stack, so the one referenced by exnref lives until the outermost
+/// invocation from the host returns, unless a global, a table or the result still holds it.
+struct WasmExceptionStore {
+    slots: Vec<Option<WasmExceptionSlot>>,
+    pending: Option<usize>,
+    n_invocations: usize,
+}
+
+struct WasmExceptionSlot {
+    exception: WasmException,
+    is_referenced: bool,
+}
+
+impl WasmExceptionStore {
+    const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            pending: None,
+            n_invocations: 0,
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&WasmExceptionSlot> {
+        self.slots.get(index).and_then(|v| v.as_ref())
+    }
+
+    fn alloc(&mut self, exception: WasmException) -> usize {
+        let slot = Some(WasmExceptionSlot {
+            exception,
+            is_referenced: false,
+        });
+        match self.slots.iter().position(|v| v.is_none()) {
+            Some(index) => {
+                self.slots[index] = slot;
+                index
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Releases the exception unless exnref in a running invocation may reference it
+    fn release(&mut self, index: usize) {
+        let is_running = self.n_invocations > 0;
+        if let Some(slot) = self.slots.get_mut(index) {
+            if !slot
+                .as_ref()
+                .map(|v| v.is_referenced && is_running)
+                .unwrap_or(true)
+            {
+                *slot = None;
+            }
+        }
+        self.shrink();
+    }
+
+    /// Releases the exceptions except the pending one and the ones in `roots`
+    fn collect(&mut self, roots: &BTreeSet<usize>) {
+        let pending = self.pending;
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if Some(index) != pending && !roots.contains(&index) {
+                *slot = None;
+            }
+        }
+        self.shrink();
+    }
+
+    fn shrink(&mut self) {
+        while let Some(None) = self.slots.last() {
+            self.slots.pop();
+        }
+    }
+
+    /// Returns the number of the exceptions alive
+    #[cfg(all(test, feature = "exceptions"))]
+    fn len(&self) -> usize {
+        self.slots.iter().filter(|v| v.is_some()).count()
+    }
+
+    fn throw(&mut self, index: usize) {
+        // The previous exception has been abandoned by the host
+        if let Some(prev) = self.pending.replace(index) {
+            if prev != index {
+                self.release(prev);
+            }
+        }
+    }
+
+    fn catch(&mut self, is_ref: bool) -> Option<(WasmException, Option<usize>)> {
+        let index = self.pending.take()?;
+        let slot = self.slots.get_mut(index)?.as_mut()?;
+        if is_ref {
+            slot.is_referenced = true;
+            return Some((slot.exception.clone(), Some(index)));
+        }
+        let exception = slot.exception.clone();
+        self.release(index);
+        Some((exception, None))
+    }
+}
+
 pub struct WasmExport {
     name: String,
     index: WasmExportIndex,
@@ -1284,6 +3666,7 @@ pub enum WasmExportIndex {
     Table(usize),
     Memory(usize),
     Global(usize),
+    Tag(usize),
 }
 
 impl WasmExportIndex {
@@ -1293,6 +3676,7 @@ impl WasmExportIndex {
             1 => stream.read_unsigned().map(|v| Self::Table(v as usize)),
             2 => stream.read_unsigned().map(|v| Self::Memory(v as usize)),
             3 => stream.read_unsigned().map(|v| Self::Global(v as usize)),
+            4 => stream.read_unsigned().map(|v| Self::Tag(v as usize)),
             _ => Err(WasmDecodeError::UnexpectedToken),
         })
     }
@@ -1300,40 +3684,42 @@ impl WasmExportIndex {
 
 pub struct WasmFunctionBody {
     local_types: Vec<WasmValType>,
-    code_block: Arc<RefCell<Vec<u8>>>,
+    code_block: Rc<RefCell<Vec<u8>>>,
     block_info: WasmBlockInfo,
 }
 
 impl WasmFunctionBody {
     fn from_stream(
         func_index: usize,
-        stream: &mut Leb128Stream,
+        section_stream: &mut Leb128Stream,
         param_types: &[WasmValType],
         result_types: &[WasmValType],
         module: &WasmModule,
     ) -> Result<Self, WasmDecodeError> {
-        let blob = stream.read_bytes()?;
+        let blob = section_stream.read_bytes()?;
+        let blob_len = blob.len();
         let mut stream = Leb128Stream::from_slice(blob);
         let n_locals = stream.read_unsigned()? as usize;
         let mut locals = Vec::with_capacity(n_locals);
         for _ in 0..n_locals {
             let repeat = stream.read_unsigned()?;
-            let val = stream
-                .read_unsigned()
-                .and_then(|v| WasmValType::from_u64(v))?;
+            let val = stream.read_unsigned().and_then(WasmValType::from_u64)?;
+            module.features.require_val_types(&[val])?;
             for _ in 0..repeat {
                 locals.push(val);
             }
         }
-        let code_block = Arc::new(RefCell::new(blob[stream.position..].to_vec()));
+        let locals_len = stream.position;
+        let code_block = Rc::new(RefCell::new(blob[locals_len..].to_vec()));
+        let code_position = section_stream.position() - blob_len + locals_len;
 
         let block_info = {
             let mut local_types = Vec::with_capacity(param_types.len() + locals.len());
             for param_type in param_types {
-                local_types.push(param_type.clone());
+                local_types.push(*param_type);
             }
             for local in &locals {
-                local_types.push(local.clone());
+                local_types.push(*local);
             }
             let code_ref = code_block.borrow();
             let mut code_block = Leb128Stream::from_slice(&code_ref);
@@ -1344,9 +3730,9 @@ impl WasmFunctionBody {
                 result_types,
                 module,
             )
-            .map_err(|err| {
-                println!("analyze error {:?} ad {}", err, code_block.fetch_position());
-                err
+            .inspect_err(|_| {
+                // points to the instruction for WasmLoader::error_location
+                section_stream.set_position(code_position + code_block.fetch_position());
             })
         }?;
 
@@ -1365,19 +3751,38 @@ impl WasmFunctionBody {
         &self.block_info
     }
 
-    pub fn code_block(&self) -> Arc<RefCell<Vec<u8>>> {
+    pub fn code_block(&self) -> Rc<RefCell<Vec<u8>>> {
         self.code_block.clone()
     }
 }
 
-#[allow(dead_code)]
+/// Global variable
+///
+/// Clones share the same value, so the host can observe the global imported to or exported from the module.
+#[derive(Clone)]
 pub struct WasmGlobal {
     val_type: WasmValType,
     is_mutable: bool,
-    value: RefCell<WasmValue>,
+    value: Rc<RefCell<WasmValue>>,
 }
 
 impl WasmGlobal {
+    /// Creates a global to be imported to modules
+    pub fn new(
+        val_type: WasmValType,
+        is_mutable: bool,
+        value: WasmValue,
+    ) -> Result<Self, WasmRuntimeError> {
+        if !value.is_valid_type(val_type) {
+            return Err(WasmRuntimeError::TypeMismatch);
+        }
+        Ok(Self {
+            val_type,
+            is_mutable,
+            value: Rc::new(RefCell::new(value)),
+        })
+    }
+
     #[inline]
     pub const fn val_type(&self) -> WasmValType {
         self.val_type
@@ -1389,9 +3794,30 @@ impl WasmGlobal {
     }
 
     #[inline]
-    pub const fn value(&self) -> &RefCell<WasmValue> {
+    pub fn value(&self) -> &RefCell<WasmValue> {
         &self.value
     }
+
+    #[inline]
+    pub fn get(&self) -> WasmValue {
+        *self.value.borrow()
+    }
+
+    /// Sets the value from the host, the global must be mutable and the value must be of its type
+    pub fn set(&self, value: WasmValue) -> Result<(), WasmRuntimeError> {
+        if !self.is_mutable {
+            return Err(WasmRuntimeError::WriteProtected);
+        }
+        if !value.is_valid_type(self.val_type) {
+            return Err(WasmRuntimeError::TypeMismatch);
+        }
+        let mut var = self
+            .value
+            .try_borrow_mut()
+            .map_err(|_| WasmRuntimeError::WriteProtected)?;
+        *var = value;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -1413,6 +3839,13 @@ pub enum WasmDecodeError {
     DynamicLinkError,
     NotSupprted,
     BadExecutable,
+    OutOfMemory,
+    /// The proposals are required but not enabled in the loader
+    FeatureNotEnabled(WasmFeatures),
+    /// An active segment does not fit in the memory or table at instantiation
+    OutOfBounds,
+    /// The start function traps at instantiation
+    Trap(WasmRuntimeError),
 }
 
 #[allow(dead_code)]
@@ -1429,15 +3862,39 @@ pub enum WasmRuntimeError {
     TypeMismatch,
     InternalInconsistency,
     WriteProtected,
+    /// The atomic access is not naturally aligned
+    UnalignedAccess,
+    /// memory.atomic.wait on an unshared memory
+    ExpectedSharedMemory,
+    /// The exception is being thrown, [WasmModule::take_exception] takes it
+    Exception,
+    /// throw_ref of null
+    NullReference,
+    /// The nested calls exceed [WasmModule::max_call_depth]
+    StackOverflow,
+    /// The string in the memory is not valid UTF-8
+    InvalidUtf8,
+    /// The host function terminates the instance with the exit code, such as proc_exit of WASI
+    Exit(i32),
+    /// The debug hook stops the execution
+    Interrupted,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum WasmValue {
     Empty,
     I32(i32),
     I64(i64),
     F32(f32),
     F64(f64),
+    /// 128-bit vector, lane 0 is the least significant
+    V128(u128),
+    /// Function index, or null
+    FuncRef(Option<usize>),
+    /// Host object, or null
+    ExternRef(Option<WasmExternRef>),
+    /// Caught exception held by the module, or null
+    ExnRef(Option<usize>),
 }
 
 impl WasmValue {
@@ -1447,6 +3904,10 @@ impl WasmValue {
             WasmValType::I64 => Self::I64(0),
             WasmValType::F32 => Self::F32(0.0),
             WasmValType::F64 => Self::F64(0.0),
+            WasmValType::V128 => Self::V128(0),
+            WasmValType::FuncRef => Self::FuncRef(None),
+            WasmValType::ExternRef => Self::ExternRef(None),
+            WasmValType::ExnRef => Self::ExnRef(None),
         }
     }
 
@@ -1457,22 +3918,52 @@ impl WasmValue {
             WasmValue::I64(_) => val_type == WasmValType::I64,
             WasmValue::F32(_) => val_type == WasmValType::F32,
             WasmValue::F64(_) => val_type == WasmValType::F64,
+            WasmValue::V128(_) => val_type == WasmValType::V128,
+            WasmValue::FuncRef(_) => val_type == WasmValType::FuncRef,
+            WasmValue::ExternRef(_) => val_type == WasmValType::ExternRef,
+            WasmValue::ExnRef(_) => val_type == WasmValType::ExnRef,
         }
     }
 
+    /// Returns the raw representation of the reference in tables
     #[inline]
-    pub const fn is_empty(&self) -> bool {
-        match *self {
-            Self::Empty => true,
-            _ => false,
+    pub fn get_ref(self) -> Result<Option<usize>, WasmRuntimeError> {
+        match self {
+            Self::FuncRef(a) => Ok(a),
+            Self::ExternRef(a) => Ok(a.map(|v| v.handle())),
+            Self::ExnRef(a) => Ok(a),
+            _ => Err(WasmRuntimeError::TypeMismatch),
+        }
+    }
+
+    #[inline]
+    pub fn get_extern_ref(self) -> Result<Option<WasmExternRef>, WasmRuntimeError> {
+        match self {
+            Self::ExternRef(a) => Ok(a),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
+    /// Makes the reference of the type from its raw representation
+    #[inline]
+    pub fn from_ref(val_type: WasmValType, raw: Option<usize>) -> Self {
+        match val_type {
+            WasmValType::ExternRef => Self::ExternRef(raw.and_then(WasmExternRef::new)),
+            WasmValType::ExnRef => Self::ExnRef(raw),
+            _ => Self::FuncRef(raw),
+        }
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        matches!(*self, Self::Empty)
+    }
+
     #[inline]
     pub fn get_i32(self) -> Result<i32, WasmRuntimeError> {
         match self {
             Self::I32(a) => Ok(a),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
@@ -1480,7 +3971,7 @@ impl WasmValue {
     pub fn get_u32(self) -> Result<u32, WasmRuntimeError> {
         match self {
             Self::I32(a) => Ok(a as u32),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
@@ -1488,7 +3979,7 @@ impl WasmValue {
     pub fn get_i64(self) -> Result<i64, WasmRuntimeError> {
         match self {
             Self::I64(a) => Ok(a),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
@@ -1496,7 +3987,31 @@ impl WasmValue {
     pub fn get_u64(self) -> Result<u64, WasmRuntimeError> {
         match self {
             Self::I64(a) => Ok(a as u64),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
+        }
+    }
+
+    #[inline]
+    pub fn get_f32(self) -> Result<f32, WasmRuntimeError> {
+        match self {
+            Self::F32(a) => Ok(a),
+            _ => Err(WasmRuntimeError::TypeMismatch),
+        }
+    }
+
+    #[inline]
+    pub fn get_f64(self) -> Result<f64, WasmRuntimeError> {
+        match self {
+            Self::F64(a) => Ok(a),
+            _ => Err(WasmRuntimeError::TypeMismatch),
+        }
+    }
+
+    #[inline]
+    pub fn get_v128(self) -> Result<u128, WasmRuntimeError> {
+        match self {
+            Self::V128(a) => Ok(a),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
@@ -1507,7 +4022,7 @@ impl WasmValue {
     {
         match self {
             Self::I32(a) => Ok(f(a).into()),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 
@@ -1518,7 +4033,7 @@ impl WasmValue {
     {
         match self {
             Self::I64(a) => Ok(f(a).into()),
-            _ => return Err(WasmRuntimeError::TypeMismatch),
+            _ => Err(WasmRuntimeError::TypeMismatch),
         }
     }
 }
@@ -1559,6 +4074,18 @@ impl From<f64> for WasmValue {
     }
 }
 
+impl From<u128> for WasmValue {
+    fn from(v: u128) -> Self {
+        Self::V128(v)
+    }
+}
+
+impl From<WasmExternRef> for WasmValue {
+    fn from(v: WasmExternRef) -> Self {
+        Self::ExternRef(Some(v))
+    }
+}
+
 impl From<bool> for WasmValue {
     fn from(v: bool) -> Self {
         Self::I32(if v { 1 } else { 0 })
@@ -1571,10 +4098,40 @@ impl fmt::Display for WasmValue {
             Self::Empty => write!(f, "()"),
             Self::I32(v) => write!(f, "{}", v),
             Self::I64(v) => write!(f, "{}", v),
-            Self::F32(_) => write!(f, "(#!F32)"),
-            Self::F64(_) => write!(f, "(#!F64)"),
+            Self::F32(v) => write!(f, "{}", v),
+            Self::F64(v) => write!(f, "{}", v),
+            Self::V128(v) => write!(f, "0x{:032x}", v),
+            Self::FuncRef(Some(v)) => write!(f, "(ref.func {})", v),
+            Self::FuncRef(None) => write!(f, "(ref.null func)"),
+            Self::ExternRef(Some(v)) => write!(f, "(ref.extern {})", v.handle()),
+            Self::ExternRef(None) => write!(f, "(ref.null extern)"),
+            Self::ExnRef(Some(v)) => write!(f, "(ref.exn {})", v),
+            Self::ExnRef(None) => write!(f, "(ref.null exn)"),
+        }
+    }
+}
+
+/// Opaque handle of the host object passed to the guest as externref
+///
+/// The guest can only store and pass it around, the host gives meaning to the handle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WasmExternRef(usize);
+
+impl WasmExternRef {
+    /// Returns `None` for `usize::MAX`, which represents null in the interpreter
+    #[inline]
+    pub const fn new(handle: usize) -> Option<Self> {
+        if handle == usize::MAX {
+            None
+        } else {
+            Some(Self(handle))
         }
     }
+
+    #[inline]
+    pub const fn handle(&self) -> usize {
+        self.0
+    }
 }
 
 pub struct WasmCodeBlock<'a> {
@@ -1618,27 +4175,37 @@ impl<'a> WasmCodeBlock<'a> {
 
     #[inline]
     pub fn read_opcode(&mut self) -> Result<WasmOpcode, WasmRuntimeError> {
-        self.code.read_opcode().map_err(|err| Self::map_err(err))
+        self.code.read_opcode().map_err(Self::map_err)
     }
 
     #[inline]
     pub fn read_signed(&mut self) -> Result<i64, WasmRuntimeError> {
-        self.code.read_signed().map_err(|err| Self::map_err(err))
+        self.code.read_signed().map_err(Self::map_err)
     }
 
     #[inline]
     pub fn read_unsigned(&mut self) -> Result<u64, WasmRuntimeError> {
-        self.code.read_unsigned().map_err(|err| Self::map_err(err))
+        self.code.read_unsigned().map_err(Self::map_err)
     }
 
     #[inline]
     pub fn read_byte(&mut self) -> Result<u8, WasmRuntimeError> {
-        self.code.read_byte().map_err(|err| Self::map_err(err))
+        self.code.read_byte().map_err(Self::map_err)
+    }
+
+    #[inline]
+    pub fn get_bytes(&mut self, size: usize) -> Result<&[u8], WasmRuntimeError> {
+        self.code.get_bytes(size).map_err(Self::map_err)
     }
 
     #[inline]
     pub fn read_memarg(&mut self) -> Result<WasmMemArg, WasmRuntimeError> {
-        self.code.read_memarg().map_err(|err| Self::map_err(err))
+        self.code.read_memarg().map_err(Self::map_err)
+    }
+
+    #[inline]
+    pub fn read_catch(&mut self) -> Result<WasmCatch, WasmRuntimeError> {
+        WasmCatch::from_stream(&mut self.code).map_err(Self::map_err)
     }
 
     #[inline]
@@ -1666,6 +4233,140 @@ bitflags! {
     }
 }
 
+/// Type of the operand on the stack of the validation
+#[derive(Debug, Clone, Copy)]
+enum StackType {
+    Known(WasmValType),
+    /// Popped from the polymorphic stack of an unreachable code, matches any type
+    Unknown,
+}
+
+impl StackType {
+    #[inline]
+    fn is_ref(&self) -> bool {
+        match self {
+            Self::Known(v) => v.is_ref(),
+            Self::Unknown => false,
+        }
+    }
+}
+
+impl From<WasmValType> for StackType {
+    #[inline]
+    fn from(v: WasmValType) -> Self {
+        Self::Known(v)
+    }
+}
+
+impl PartialEq<WasmValType> for StackType {
+    #[inline]
+    fn eq(&self, other: &WasmValType) -> bool {
+        match self {
+            Self::Known(v) => v == other,
+            Self::Unknown => true,
+        }
+    }
+}
+
+impl PartialEq for StackType {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Known(a), Self::Known(b)) => a == b,
+            _ => true,
+        }
+    }
+}
+
+/// Operand stack of the validation
+///
+/// Each block can only pop the operands it pushed. After an unconditional branch,
+/// the rest of the block is unreachable and the stack becomes polymorphic:
+/// popping beyond the operands of the block yields [StackType::Unknown].
+struct OperandStack {
+    types: Vec<StackType>,
+    /// Height of the stack at the start of the current block
+    floor: usize,
+    is_unreachable: bool,
+    /// Floors and states of the outer blocks
+    outer: Vec<(usize, bool)>,
+}
+
+impl OperandStack {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            types: Vec::new(),
+            floor: 0,
+            is_unreachable: false,
+            outer: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    #[inline]
+    fn push<T: Into<StackType>>(&mut self, v: T) {
+        self.types.push(v.into());
+    }
+
+    fn pop(&mut self) -> Option<StackType> {
+        if self.types.len() > self.floor {
+            self.types.pop()
+        } else if self.is_unreachable {
+            Some(StackType::Unknown)
+        } else {
+            None
+        }
+    }
+
+    fn last(&self) -> Option<StackType> {
+        if self.types.len() > self.floor {
+            self.types.last().copied()
+        } else if self.is_unreachable {
+            Some(StackType::Unknown)
+        } else {
+            None
+        }
+    }
+
+    /// Starts the block with the operands above the current height
+    fn enter_block(&mut self) {
+        self.outer.push((self.floor, self.is_unreachable));
+        self.floor = self.types.len();
+        self.is_unreachable = false;
+    }
+
+    /// Starts the else arm of the if block, dropping the operands of the then arm
+    fn enter_else(&mut self) {
+        self.types.truncate(self.floor);
+        self.is_unreachable = false;
+    }
+
+    /// Ends the block, dropping its operands
+    fn leave_block(&mut self) {
+        self.types.truncate(self.floor);
+        if let Some((floor, is_unreachable)) = self.outer.pop() {
+            self.floor = floor;
+            self.is_unreachable = is_unreachable;
+        }
+    }
+
+    /// Marks the rest of the block unreachable after an unconditional branch
+    fn set_unreachable(&mut self) {
+        self.types.truncate(self.floor);
+        self.is_unreachable = true;
+    }
+}
+
 impl WasmBlockInfo {
     /// Analyze block info
     pub fn analyze(
@@ -1677,7 +4378,7 @@ impl WasmBlockInfo {
     ) -> Result<Self, WasmDecodeError> {
         let mut blocks = Vec::new();
         let mut block_stack = Vec::new();
-        let mut value_stack = Vec::new();
+        let mut value_stack = OperandStack::new();
         let mut max_stack = 0;
         let mut max_block_level = 0;
         let mut flags = WasmBlockFlag::LEAF_FUNCTION;
@@ -1689,23 +4390,17 @@ impl WasmBlockInfo {
             let opcode = code_block.read_opcode()?;
             // let old_values = value_stack.clone();
 
-            match opcode.proposal_type() {
-                WasmProposalType::Mvp | WasmProposalType::MvpI64 => {}
-                WasmProposalType::SignExtend => {}
-                #[cfg(feature = "float")]
-                WasmProposalType::MvpF32 | WasmProposalType::MvpF64 => {}
-                _ => return Err(WasmDecodeError::NotSupprted),
-            }
+            module
+                .features
+                .require(WasmFeatures::from_proposal(opcode.proposal_type()))?;
 
             match opcode {
-                WasmOpcode::Unreachable => (),
+                WasmOpcode::Unreachable => value_stack.set_unreachable(),
 
                 WasmOpcode::Nop => (),
 
                 WasmOpcode::Block => {
-                    let block_type = code_block
-                        .read_signed()
-                        .and_then(|v| WasmBlockType::from_i64(v))?;
+                    let block_type = code_block.read_signed().and_then(WasmBlockType::from_i64)?;
                     let block = RefCell::new(WasmBlockContext {
                         inst_type: BlockInstType::Block,
                         block_type,
@@ -1715,12 +4410,11 @@ impl WasmBlockInfo {
                         else_position: 0,
                     });
                     block_stack.push(blocks.len());
+                    value_stack.enter_block();
                     blocks.push(block);
                 }
                 WasmOpcode::Loop => {
-                    let block_type = code_block
-                        .read_signed()
-                        .and_then(|v| WasmBlockType::from_i64(v))?;
+                    let block_type = code_block.read_signed().and_then(WasmBlockType::from_i64)?;
                     let block = RefCell::new(WasmBlockContext {
                         inst_type: BlockInstType::Loop,
                         block_type,
@@ -1730,6 +4424,7 @@ impl WasmBlockInfo {
                         else_position: 0,
                     });
                     block_stack.push(blocks.len());
+                    value_stack.enter_block();
                     blocks.push(block);
                 }
                 WasmOpcode::If => {
@@ -1737,9 +4432,7 @@ impl WasmBlockInfo {
                     if cc != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
-                    let block_type = code_block
-                        .read_signed()
-                        .and_then(|v| WasmBlockType::from_i64(v))?;
+                    let block_type = code_block.read_signed().and_then(WasmBlockType::from_i64)?;
                     let block = RefCell::new(WasmBlockContext {
                         inst_type: BlockInstType::If,
                         block_type,
@@ -1749,8 +4442,73 @@ impl WasmBlockInfo {
                         else_position: 0,
                     });
                     block_stack.push(blocks.len());
+                    value_stack.enter_block();
+                    blocks.push(block);
+                }
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::TryTable => {
+                    let block_type = code_block.read_signed().and_then(WasmBlockType::from_i64)?;
+                    let n_catches = code_block.read_unsigned()?;
+                    for _ in 0..n_catches {
+                        let catch = WasmCatch::from_stream(code_block)?;
+                        let mut types = match catch {
+                            WasmCatch::Catch(tag, _) | WasmCatch::CatchRef(tag, _) => {
+                                Self::tag_params(module, tag)?.to_vec()
+                            }
+                            WasmCatch::CatchAll(_) | WasmCatch::CatchAllRef(_) => Vec::new(),
+                        };
+                        if catch.is_ref() {
+                            types.push(WasmValType::ExnRef);
+                        }
+                        // The labels are relative to the outside of the try_table
+                        let label = catch.label();
+                        let label_types = match block_stack.len().checked_sub(label) {
+                            None => return Err(WasmDecodeError::OutOfBranch),
+                            Some(0) => result_types.to_vec(),
+                            Some(v) => {
+                                let block = blocks.get(block_stack[v - 1]).unwrap().borrow();
+                                match block.inst_type {
+                                    BlockInstType::Loop => Vec::new(),
+                                    _ => block.block_type.into_type().into_iter().collect(),
+                                }
+                            }
+                        };
+                        if types != label_types {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                    }
+                    let block = RefCell::new(WasmBlockContext {
+                        inst_type: BlockInstType::TryTable,
+                        block_type,
+                        stack_level: value_stack.len(),
+                        start_position: position,
+                        end_position: 0,
+                        else_position: 0,
+                    });
+                    block_stack.push(blocks.len());
+                    value_stack.enter_block();
                     blocks.push(block);
                 }
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::Throw => {
+                    let tag = code_block.read_unsigned()? as usize;
+                    for param in Self::tag_params(module, tag)?.iter().rev() {
+                        let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                        if val != *param {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                    }
+                    value_stack.set_unreachable();
+                }
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::ThrowRef => {
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if val != WasmValType::ExnRef {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.set_unreachable();
+                }
+
                 WasmOpcode::Else => {
                     let block_ref = block_stack.last().ok_or(WasmDecodeError::ElseWithoutIf)?;
                     let mut block = blocks.get(*block_ref).unwrap().borrow_mut();
@@ -1758,23 +4516,17 @@ impl WasmBlockInfo {
                         return Err(WasmDecodeError::ElseWithoutIf);
                     }
                     block.else_position = position;
-                    let n_drops = value_stack.len() - block.stack_level;
-                    for _ in 0..n_drops {
-                        value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    }
+                    value_stack.enter_else();
                 }
                 WasmOpcode::End => {
-                    if block_stack.len() > 0 {
+                    if !block_stack.is_empty() {
                         let block_ref = block_stack.pop().ok_or(WasmDecodeError::BlockMismatch)?;
                         let mut block = blocks.get(block_ref).unwrap().borrow_mut();
                         block.end_position = code_block.position();
-                        let n_drops = value_stack.len() - block.stack_level;
-                        for _ in 0..n_drops {
-                            value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                        }
-                        block.block_type.into_type().map(|v| {
+                        value_stack.leave_block();
+                        if let Some(v) = block.block_type.into_type() {
                             value_stack.push(v);
-                        });
+                        }
                     // TODO: type check
                     } else {
                         break;
@@ -1786,6 +4538,7 @@ impl WasmBlockInfo {
                     if block_stack.len() < br {
                         return Err(WasmDecodeError::OutOfBranch);
                     }
+                    value_stack.set_unreachable();
                 }
                 WasmOpcode::BrIf => {
                     let br = code_block.read_unsigned()? as usize;
@@ -1809,10 +4562,17 @@ impl WasmBlockInfo {
                     if block_stack.len() < br {
                         return Err(WasmDecodeError::OutOfBranch);
                     }
+                    value_stack.set_unreachable();
                 }
 
                 WasmOpcode::Return => {
-                    // TODO: type check
+                    for result_type in result_types.iter().rev() {
+                        let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                        if val != *result_type {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                    }
+                    value_stack.set_unreachable();
                 }
 
                 WasmOpcode::Call => {
@@ -1827,13 +4587,14 @@ impl WasmBlockInfo {
                         value_stack.pop();
                     }
                     for result in function.result_types() {
-                        value_stack.push(result.clone());
+                        value_stack.push(*result);
                     }
                 }
                 WasmOpcode::CallIndirect => {
                     flags.remove(WasmBlockFlag::LEAF_FUNCTION);
                     let type_ref = code_block.read_unsigned()? as usize;
-                    let _reserved = code_block.read_unsigned()? as usize;
+                    let table_index = code_block.read_unsigned()? as usize;
+                    Self::check_func_table(module, table_index)?;
                     let func_type = module
                         .type_by_ref(type_ref)
                         .ok_or(WasmDecodeError::InvalidParameter)?;
@@ -1846,12 +4607,46 @@ impl WasmBlockInfo {
                         value_stack.pop();
                     }
                     for result in func_type.result_types() {
-                        value_stack.push(result.clone());
+                        value_stack.push(*result);
+                    }
+                }
+
+                #[cfg(feature = "tail-call")]
+                WasmOpcode::ReturnCall => {
+                    flags.remove(WasmBlockFlag::LEAF_FUNCTION);
+                    let func_index = code_block.read_unsigned()? as usize;
+                    let function = module
+                        .functions
+                        .get(func_index)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    Self::check_tail_call(
+                        &mut value_stack,
+                        function.param_types(),
+                        function.result_types(),
+                        result_types,
+                    )?;
+                }
+                #[cfg(feature = "tail-call")]
+                WasmOpcode::ReturnCallIndirect => {
+                    flags.remove(WasmBlockFlag::LEAF_FUNCTION);
+                    let type_ref = code_block.read_unsigned()? as usize;
+                    let table_index = code_block.read_unsigned()? as usize;
+                    Self::check_func_table(module, table_index)?;
+                    let func_type = module
+                        .type_by_ref(type_ref)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let index = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if index != WasmValType::I32 {
+                        return Err(WasmDecodeError::TypeMismatch);
                     }
+                    Self::check_tail_call(
+                        &mut value_stack,
+                        func_type.param_types(),
+                        func_type.result_types(),
+                        result_types,
+                    )?;
                 }
 
-                // WasmOpcode::ReturnCall
-                // WasmOpcode::ReturnCallIndirect
                 WasmOpcode::Drop => {
                     value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
                 }
@@ -1862,6 +4657,24 @@ impl WasmBlockInfo {
                     if a != b || cc != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
+                    if a.is_ref() {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(a);
+                }
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::SelectT => {
+                    let n_types = code_block.read_unsigned()?;
+                    if n_types != 1 {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    let val_type = code_block.read_unsigned().and_then(WasmValType::from_u64)?;
+                    let cc = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let b = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != val_type || b != val_type || cc != WasmValType::I32 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
                     value_stack.push(a);
                 }
 
@@ -1887,7 +4700,7 @@ impl WasmBlockInfo {
                     let val = *local_types
                         .get(local_ref)
                         .ok_or(WasmDecodeError::InvalidLocal)?;
-                    let stack = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let stack = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if stack != val {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -1919,9 +4732,9 @@ impl WasmBlockInfo {
                 | WasmOpcode::I32Load8U
                 | WasmOpcode::I32Load16S
                 | WasmOpcode::I32Load16U => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if a != WasmValType::I32 {
+                    if a != index_type {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                     value_stack.push(WasmValType::I32);
@@ -1934,19 +4747,19 @@ impl WasmBlockInfo {
                 | WasmOpcode::I64Load16U
                 | WasmOpcode::I64Load32S
                 | WasmOpcode::I64Load32U => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if a != WasmValType::I32 {
+                    if a != index_type {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                     value_stack.push(WasmValType::I64);
                 }
 
                 WasmOpcode::I32Store | WasmOpcode::I32Store8 | WasmOpcode::I32Store16 => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
                     let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if i != d && i != WasmValType::I32 {
+                    if i != index_type || d != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                 }
@@ -1954,58 +4767,294 @@ impl WasmBlockInfo {
                 | WasmOpcode::I64Store8
                 | WasmOpcode::I64Store16
                 | WasmOpcode::I64Store32 => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
                     let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if i != WasmValType::I32 && d != WasmValType::I64 {
+                    if i != index_type || d != WasmValType::I64 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                 }
 
                 WasmOpcode::F32Load => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if a != WasmValType::I32 {
+                    if a != index_type {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                     value_stack.push(WasmValType::F32);
                 }
                 WasmOpcode::F64Load => {
-                    let _ = code_block.read_memarg()?;
+                    let index_type = Self::read_memarg(code_block, module)?;
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if a != WasmValType::I32 {
+                    if a != index_type {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(WasmValType::F64);
+                }
+                WasmOpcode::F32Store => {
+                    let index_type = Self::read_memarg(code_block, module)?;
+                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if i != index_type || d != WasmValType::F32 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+                WasmOpcode::F64Store => {
+                    let index_type = Self::read_memarg(code_block, module)?;
+                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if i != index_type || d != WasmValType::F64 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+
+                WasmOpcode::MemorySize => {
+                    let memidx = code_block.read_unsigned()? as usize;
+                    let memory = module
+                        .memory(memidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    value_stack.push(memory.limit().index_type());
+                }
+
+                WasmOpcode::MemoryGrow => {
+                    let memidx = code_block.read_unsigned()? as usize;
+                    let memory = module
+                        .memory(memidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let a = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != memory.limit().index_type() {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+
+                // [f32] -> [i32]
+                WasmOpcode::I32TruncSatF32S | WasmOpcode::I32TruncSatF32U => {
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != WasmValType::F32 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(WasmValType::I32);
+                }
+                // [f64] -> [i32]
+                WasmOpcode::I32TruncSatF64S | WasmOpcode::I32TruncSatF64U => {
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != WasmValType::F64 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(WasmValType::I32);
+                }
+                // [f32] -> [i64]
+                WasmOpcode::I64TruncSatF32S | WasmOpcode::I64TruncSatF32U => {
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != WasmValType::F32 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(WasmValType::I64);
+                }
+                // [f64] -> [i64]
+                WasmOpcode::I64TruncSatF64S | WasmOpcode::I64TruncSatF64U => {
+                    let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if a != WasmValType::F64 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(WasmValType::I64);
+                }
+
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::MemoryInit => {
+                    let dataidx = code_block.read_unsigned()? as usize;
+                    let memidx = code_block.read_unsigned()? as usize;
+                    if dataidx
+                        >= module
+                            .data_count()
+                            .ok_or(WasmDecodeError::InvalidParameter)?
+                    {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    let memory = module
+                        .memory(memidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let index_type = memory.limit().index_type();
+                    let n = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let s = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if n != WasmValType::I32 || s != WasmValType::I32 || d != index_type {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::DataDrop => {
+                    let dataidx = code_block.read_unsigned()? as usize;
+                    if dataidx
+                        >= module
+                            .data_count()
+                            .ok_or(WasmDecodeError::InvalidParameter)?
+                    {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::MemoryCopy => {
+                    let dest_type = module
+                        .memory(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?
+                        .limit()
+                        .index_type();
+                    let src_type = module
+                        .memory(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?
+                        .limit()
+                        .index_type();
+                    let size_type = if dest_type == WasmValType::I64 {
+                        src_type
+                    } else {
+                        dest_type
+                    };
+                    let n = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let s = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if n != size_type || s != src_type || d != dest_type {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::MemoryFill => {
+                    let memidx = code_block.read_unsigned()? as usize;
+                    let memory = module
+                        .memory(memidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let index_type = memory.limit().index_type();
+                    let n = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if n != index_type || val != WasmValType::I32 || d != index_type {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::TableInit => {
+                    let elemidx = code_block.read_unsigned()? as usize;
+                    let tableidx = code_block.read_unsigned()? as usize;
+                    let segment = module
+                        .elem_segment(elemidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let table = module
+                        .table(tableidx)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    if segment.elem_type() != table.elem_type() {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    for _ in 0..3 {
+                        let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                        if a != WasmValType::I32 {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::ElemDrop => {
+                    let elemidx = code_block.read_unsigned()? as usize;
+                    if module.elem_segment(elemidx).is_none() {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                }
+                #[cfg(feature = "bulk-memory")]
+                WasmOpcode::TableCopy => {
+                    let dest = code_block.read_unsigned()? as usize;
+                    let src = code_block.read_unsigned()? as usize;
+                    let dest = module
+                        .table(dest)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let src = module.table(src).ok_or(WasmDecodeError::InvalidParameter)?;
+                    if dest.elem_type() != src.elem_type() {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    for _ in 0..3 {
+                        let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                        if a != WasmValType::I32 {
+                            return Err(WasmDecodeError::TypeMismatch);
+                        }
+                    }
+                }
+
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::TableGet => {
+                    let table = module
+                        .table(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let index = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if index != WasmValType::I32 {
+                        return Err(WasmDecodeError::TypeMismatch);
+                    }
+                    value_stack.push(table.elem_type());
+                }
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::TableSet => {
+                    let table = module
+                        .table(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let index = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if val != table.elem_type() || index != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
-                    value_stack.push(WasmValType::F64);
                 }
-                WasmOpcode::F32Store => {
-                    let _ = code_block.read_memarg()?;
-                    let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if i != WasmValType::I32 && d != WasmValType::F32 {
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::TableSize => {
+                    module
+                        .table(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    value_stack.push(WasmValType::I32);
+                }
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::TableGrow => {
+                    let table = module
+                        .table(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let n = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if n != WasmValType::I32 || val != table.elem_type() {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
+                    value_stack.push(WasmValType::I32);
                 }
-                WasmOpcode::F64Store => {
-                    let _ = code_block.read_memarg()?;
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::TableFill => {
+                    let table = module
+                        .table(code_block.read_unsigned()? as usize)
+                        .ok_or(WasmDecodeError::InvalidParameter)?;
+                    let n = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
                     let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    if i != WasmValType::I32 && d != WasmValType::F64 {
+                    if n != WasmValType::I32 || val != table.elem_type() || d != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
                 }
 
-                WasmOpcode::MemorySize => {
-                    let _ = code_block.read_unsigned()?;
-                    value_stack.push(WasmValType::I32);
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::RefNull => {
+                    let val_type = WasmValType::ref_type_from_stream(code_block)?;
+                    value_stack.push(val_type);
                 }
-
-                WasmOpcode::MemoryGrow => {
-                    let _ = code_block.read_unsigned()?;
-                    let a = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
-                    if a != WasmValType::I32 {
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::RefIsNull => {
+                    let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                    if !val.is_ref() {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
+                    value_stack.push(WasmValType::I32);
+                }
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::RefFunc => {
+                    let func_index = code_block.read_unsigned()? as usize;
+                    if func_index >= module.functions.len() {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    // The function must be declared in an element segment, an export or a global
+                    if !module.declared_funcs.contains(&func_index) {
+                        return Err(WasmDecodeError::InvalidParameter);
+                    }
+                    value_stack.push(WasmValType::FuncRef);
                 }
 
                 WasmOpcode::I32Const => {
@@ -2035,7 +5084,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::I32Popcnt
                 | WasmOpcode::I32Extend8S
                 | WasmOpcode::I32Extend16S => {
-                    let a = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2068,7 +5117,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::I32Rotl
                 | WasmOpcode::I32Rotr => {
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let b = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let b = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != b || a != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2100,7 +5149,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::I64Extend8S
                 | WasmOpcode::I64Extend16S
                 | WasmOpcode::I64Extend32S => {
-                    let a = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != WasmValType::I64 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2123,7 +5172,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::I64Rotl
                 | WasmOpcode::I64Rotr => {
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let b = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let b = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != b || a != WasmValType::I64 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2181,7 +5230,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::F32Trunc
                 | WasmOpcode::F32Nearest
                 | WasmOpcode::F32Sqrt => {
-                    let a = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != WasmValType::I32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2196,7 +5245,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::F32Max
                 | WasmOpcode::F32Copysign => {
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let b = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let b = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != b || a != WasmValType::F32 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2247,7 +5296,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::F64Trunc
                 | WasmOpcode::F64Nearest
                 | WasmOpcode::F64Sqrt => {
-                    let a = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let a = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != WasmValType::F64 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2262,7 +5311,7 @@ impl WasmBlockInfo {
                 | WasmOpcode::F64Max
                 | WasmOpcode::F64Copysign => {
                     let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                    let b = *value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
+                    let b = value_stack.last().ok_or(WasmDecodeError::OutOfStack)?;
                     if a != b || a != WasmValType::F64 {
                         return Err(WasmDecodeError::TypeMismatch);
                     }
@@ -2326,6 +5375,16 @@ impl WasmBlockInfo {
                     value_stack.push(WasmValType::F64);
                 }
 
+                #[cfg(feature = "simd")]
+                _ if matches!(opcode.proposal_type(), WasmProposalType::Simd) => {
+                    Self::analyze_simd(opcode, code_block, &mut value_stack, module)?;
+                }
+
+                #[cfg(feature = "threads")]
+                _ if matches!(opcode.proposal_type(), WasmProposalType::Threads) => {
+                    Self::analyze_atomic(opcode, code_block, &mut value_stack, module)?;
+                }
+
                 #[allow(unreachable_patterns)]
                 _ => return Err(WasmDecodeError::UnreachableTrap),
             }
@@ -2342,28 +5401,25 @@ impl WasmBlockInfo {
             // );
         }
 
-        if result_types.len() > 0 {
-            if result_types.len() != value_stack.len() {
+        for result_type in result_types.iter().rev() {
+            let val = value_stack.pop().ok_or(WasmDecodeError::TypeMismatch)?;
+            if val != *result_type {
                 return Err(WasmDecodeError::TypeMismatch);
             }
-
-            for result_type in result_types {
-                let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
-                if *result_type != val {
-                    return Err(WasmDecodeError::TypeMismatch);
-                }
-            }
-        } else {
-            if value_stack.len() > 0 {
-                return Err(WasmDecodeError::InvalidStackLevel);
-            }
+        }
+        if !value_stack.is_empty() {
+            return Err(if result_types.is_empty() {
+                WasmDecodeError::InvalidStackLevel
+            } else {
+                WasmDecodeError::TypeMismatch
+            });
         }
 
         let blocks = {
             let mut output = BTreeMap::new();
             for block in blocks {
                 let block = block.borrow();
-                output.insert(block.start_position, block.clone());
+                output.insert(block.start_position, *block);
             }
             output
         };
@@ -2377,6 +5433,241 @@ impl WasmBlockInfo {
         })
     }
 
+    /// The table of call_indirect must exist and contain funcref
+    fn check_func_table(module: &WasmModule, table_index: usize) -> Result<(), WasmDecodeError> {
+        let table = module
+            .table(table_index)
+            .ok_or(WasmDecodeError::InvalidParameter)?;
+        if table.elem_type() != WasmValType::FuncRef {
+            return Err(WasmDecodeError::TypeMismatch);
+        }
+        Ok(())
+    }
+
+    /// The callee of return_call must return the same types as the caller
+    #[cfg(feature = "tail-call")]
+    fn check_tail_call(
+        value_stack: &mut OperandStack,
+        param_types: &[WasmValType],
+        callee_result_types: &[WasmValType],
+        result_types: &[WasmValType],
+    ) -> Result<(), WasmDecodeError> {
+        if callee_result_types != result_types {
+            return Err(WasmDecodeError::TypeMismatch);
+        }
+        for param_type in param_types.iter().rev() {
+            let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+            if val != *param_type {
+                return Err(WasmDecodeError::TypeMismatch);
+            }
+        }
+        // The callee returns to the caller, the rest of the block is unreachable
+        value_stack.set_unreachable();
+        Ok(())
+    }
+
+    /// Returns the types of the values of the exception of the tag
+    #[cfg(feature = "exceptions")]
+    fn tag_params(module: &WasmModule, tag: usize) -> Result<&[WasmValType], WasmDecodeError> {
+        module
+            .tag(tag)
+            .and_then(|v| module.type_by_ref(v.type_index()))
+            .map(|v| v.param_types())
+            .ok_or(WasmDecodeError::InvalidParameter)
+    }
+
+    /// Validates the atomic instruction
+    #[cfg(feature = "threads")]
+    fn analyze_atomic(
+        opcode: WasmOpcode,
+        code_block: &mut Leb128Stream,
+        value_stack: &mut OperandStack,
+        module: &WasmModule,
+    ) -> Result<(), WasmDecodeError> {
+        let instruction =
+            AtomicInstruction::from_opcode(opcode).ok_or(WasmDecodeError::UnreachableTrap)?;
+        if instruction == AtomicInstruction::Fence {
+            return match code_block.read_byte()? {
+                0 => Ok(()),
+                _ => Err(WasmDecodeError::UnexpectedToken),
+            };
+        }
+        let memarg = code_block.read_memarg()?;
+        // The alignment of the atomic access must be exactly the natural one
+        if 1u64.checked_shl(memarg.align) != Some(instruction.access_size() as u64) {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        let index_type = Self::check_memarg(&memarg, module)?;
+        // Operands following the address, and the result
+        let (params, result): (&[WasmValType], _) = match instruction {
+            AtomicInstruction::Notify => (&[WasmValType::I32], Some(WasmValType::I32)),
+            AtomicInstruction::Wait(WasmValType::I32) => (
+                &[WasmValType::I32, WasmValType::I64],
+                Some(WasmValType::I32),
+            ),
+            AtomicInstruction::Wait(_) => (
+                &[WasmValType::I64, WasmValType::I64],
+                Some(WasmValType::I32),
+            ),
+            AtomicInstruction::Fence => (&[], None),
+            AtomicInstruction::Load(val_type, _) => (&[], Some(val_type)),
+            AtomicInstruction::Store(WasmValType::I32, _) => (&[WasmValType::I32], None),
+            AtomicInstruction::Store(_, _) => (&[WasmValType::I64], None),
+            AtomicInstruction::Rmw(val_type, _, _) => match val_type {
+                WasmValType::I32 => (&[WasmValType::I32], Some(val_type)),
+                _ => (&[WasmValType::I64], Some(val_type)),
+            },
+            AtomicInstruction::Cmpxchg(val_type, _) => match val_type {
+                WasmValType::I32 => (&[WasmValType::I32, WasmValType::I32], Some(val_type)),
+                _ => (&[WasmValType::I64, WasmValType::I64], Some(val_type)),
+            },
+        };
+        for param in params.iter().rev() {
+            let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+            if val != *param {
+                return Err(WasmDecodeError::TypeMismatch);
+            }
+        }
+        let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+        if a != index_type {
+            return Err(WasmDecodeError::TypeMismatch);
+        }
+        if let Some(result) = result {
+            value_stack.push(result);
+        }
+        Ok(())
+    }
+
+    /// Validates the SIMD instruction
+    #[cfg(feature = "simd")]
+    fn analyze_simd(
+        opcode: WasmOpcode,
+        code_block: &mut Leb128Stream,
+        value_stack: &mut OperandStack,
+        module: &WasmModule,
+    ) -> Result<(), WasmDecodeError> {
+        let signature =
+            SimdSignature::from_opcode(opcode).ok_or(WasmDecodeError::UnreachableTrap)?;
+        let (params, result): (&[WasmValType], _) = match signature {
+            SimdSignature::Const => {
+                let _ = code_block.get_bytes(16)?;
+                (&[], Some(WasmValType::V128))
+            }
+            SimdSignature::Unary => (&[WasmValType::V128], Some(WasmValType::V128)),
+            SimdSignature::Binary => (
+                &[WasmValType::V128, WasmValType::V128],
+                Some(WasmValType::V128),
+            ),
+            SimdSignature::Ternary => (
+                &[WasmValType::V128, WasmValType::V128, WasmValType::V128],
+                Some(WasmValType::V128),
+            ),
+            SimdSignature::Test => (&[WasmValType::V128], Some(WasmValType::I32)),
+            SimdSignature::Shift => (
+                &[WasmValType::V128, WasmValType::I32],
+                Some(WasmValType::V128),
+            ),
+            SimdSignature::Shuffle => {
+                if code_block.get_bytes(16)?.iter().any(|lane| *lane >= 32) {
+                    return Err(WasmDecodeError::InvalidParameter);
+                }
+                (
+                    &[WasmValType::V128, WasmValType::V128],
+                    Some(WasmValType::V128),
+                )
+            }
+            SimdSignature::Splat(val_type) => {
+                let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                if val != val_type {
+                    return Err(WasmDecodeError::TypeMismatch);
+                }
+                (&[], Some(WasmValType::V128))
+            }
+            SimdSignature::ExtractLane(val_type, n_lanes) => {
+                if code_block.read_byte()? >= n_lanes {
+                    return Err(WasmDecodeError::InvalidParameter);
+                }
+                (&[WasmValType::V128], Some(val_type))
+            }
+            SimdSignature::ReplaceLane(val_type, n_lanes) => {
+                if code_block.read_byte()? >= n_lanes {
+                    return Err(WasmDecodeError::InvalidParameter);
+                }
+                let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                if val != val_type {
+                    return Err(WasmDecodeError::TypeMismatch);
+                }
+                (&[WasmValType::V128], Some(WasmValType::V128))
+            }
+            SimdSignature::Load => {
+                let index_type = Self::read_memarg(code_block, module)?;
+                let a = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                if a != index_type {
+                    return Err(WasmDecodeError::TypeMismatch);
+                }
+                (&[], Some(WasmValType::V128))
+            }
+            SimdSignature::Store => {
+                let index_type = Self::read_memarg(code_block, module)?;
+                let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                if i != index_type || d != WasmValType::V128 {
+                    return Err(WasmDecodeError::TypeMismatch);
+                }
+                (&[], None)
+            }
+            SimdSignature::LoadLane(n_lanes) | SimdSignature::StoreLane(n_lanes) => {
+                let index_type = Self::read_memarg(code_block, module)?;
+                if code_block.read_byte()? >= n_lanes {
+                    return Err(WasmDecodeError::InvalidParameter);
+                }
+                let d = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                let i = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+                if i != index_type || d != WasmValType::V128 {
+                    return Err(WasmDecodeError::TypeMismatch);
+                }
+                match signature {
+                    SimdSignature::LoadLane(_) => (&[], Some(WasmValType::V128)),
+                    _ => (&[], None),
+                }
+            }
+        };
+        for param in params.iter().rev() {
+            let val = value_stack.pop().ok_or(WasmDecodeError::OutOfStack)?;
+            if val != *param {
+                return Err(WasmDecodeError::TypeMismatch);
+            }
+        }
+        if let Some(result) = result {
+            value_stack.push(result);
+        }
+        Ok(())
+    }
+
+    /// Reads the memarg and returns the type of the address operand
+    fn read_memarg(
+        code_block: &mut Leb128Stream,
+        module: &WasmModule,
+    ) -> Result<WasmValType, WasmDecodeError> {
+        let memarg = code_block.read_memarg()?;
+        Self::check_memarg(&memarg, module)
+    }
+
+    /// Checks the memory and the offset of the memarg, and returns the type of the address
+    fn check_memarg(
+        memarg: &WasmMemArg,
+        module: &WasmModule,
+    ) -> Result<WasmValType, WasmDecodeError> {
+        let memory = module
+            .memory(memarg.memidx())
+            .ok_or(WasmDecodeError::InvalidParameter)?;
+        let limit = memory.limit();
+        if !limit.is_64() && memarg.offset > u32::MAX as u64 {
+            return Err(WasmDecodeError::InvalidParameter);
+        }
+        Ok(limit.index_type())
+    }
+
     #[inline]
     pub const fn func_index(&self) -> usize {
         self.func_index
@@ -2408,6 +5699,7 @@ pub enum BlockInstType {
     Block,
     Loop,
     If,
+    TryTable,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -2431,37 +5723,135 @@ impl WasmBlockContext {
     }
 }
 
+/// The instance that calls the host function
+#[derive(Copy, Clone)]
+pub struct WasmCaller<'a> {
+    module: &'a WasmModule,
+    context: Option<&'a WasmCallContext>,
+}
+
+impl<'a> WasmCaller<'a> {
+    #[inline]
+    pub(crate) const fn new(module: &'a WasmModule, context: Option<&'a WasmCallContext>) -> Self {
+        Self { module, context }
+    }
+
+    #[inline]
+    pub const fn module(&self) -> &'a WasmModule {
+        self.module
+    }
+
+    /// Get a reference to the exported memory of the caller, e.g. "memory"
+    #[inline]
+    pub fn memory(&self, name: &str) -> Option<&'a WasmMemory> {
+        self.module.memory_by_name(name)
+    }
+
+    /// Get a reference to the exported function of the caller, to call back into the guest
+    ///
+    /// The function runs on the stack of the caller, within the limit of the call depth.
+    #[inline]
+    pub fn func(&self, name: &str) -> Result<WasmRunnable<'a>, WasmRuntimeError> {
+        self.module.func(name).map(|v| WasmRunnable {
+            context: self.context,
+            ..v
+        })
+    }
+
+    #[inline]
+    pub fn typed_func<P: WasmParams, R: WasmResults>(
+        &self,
+        name: &str,
+    ) -> Result<WasmTypedFunc<'a, P, R>, WasmRuntimeError> {
+        self.func(name).and_then(WasmTypedFunc::new)
+    }
+
+    /// Returns the host-defined data of the caller, if it is of the type
+    #[inline]
+    pub fn data<T: Any>(&self) -> Option<&'a T> {
+        self.module.host_data()
+    }
+
+    /// Copies the bytes to the buffer allocated by the exported allocator of the caller
+    ///
+    /// The allocator has the signature of `malloc`, `(i32) -> i32`, and returns 0 on failure.
+    /// Returns the address of the buffer in the exported memory.
+    pub fn alloc_bytes(
+        &self,
+        memory: &str,
+        allocator: &str,
+        src: &[u8],
+    ) -> Result<u32, WasmRuntimeError> {
+        let memory = self.memory(memory).ok_or(WasmRuntimeError::OutOfMemory)?;
+        let allocator = self.typed_func::<u32, u32>(allocator)?;
+        memory
+            .write_allocated(src, |size| {
+                u32::try_from(size)
+                    .map_err(|_| WasmRuntimeError::OutOfMemory)
+                    .and_then(|size| allocator.call(size))
+                    .map(|v| v as usize)
+            })
+            .map(|v| v as u32)
+    }
+
+    /// Copies the string to the buffer allocated by the exported allocator of the caller
+    #[inline]
+    pub fn alloc_str(
+        &self,
+        memory: &str,
+        allocator: &str,
+        src: &str,
+    ) -> Result<u32, WasmRuntimeError> {
+        self.alloc_bytes(memory, allocator, src.as_bytes())
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct WasmRunnable<'a> {
     function: &'a WasmFunction,
     module: &'a WasmModule,
+    context: Option<&'a WasmCallContext>,
 }
 
 impl<'a> WasmRunnable<'a> {
     fn from_function(function: &'a WasmFunction, module: &'a WasmModule) -> Self {
-        Self { function, module }
+        Self {
+            function,
+            module,
+            context: None,
+        }
     }
 }
 
 impl WasmRunnable<'_> {
+    #[inline]
+    pub const fn function(&self) -> &WasmFunction {
+        self.function
+    }
+
     pub fn invoke(&self, params: &[WasmValue]) -> Result<WasmValue, WasmRuntimeError> {
-        let body = self
-            .function
-            .body
-            .as_ref()
-            .ok_or(WasmRuntimeError::NoMethod)?;
+        let param_types = self.function.param_types();
+        if params.len() < param_types.len()
+            || !params
+                .iter()
+                .zip(param_types)
+                .all(|(param, param_type)| param.is_valid_type(*param_type))
+        {
+            return Err(WasmRuntimeError::InvalidParameter);
+        }
+        let params = &params[..param_types.len()];
 
-        let mut locals =
-            Vec::with_capacity(self.function.param_types().len() + body.local_types.len());
-        for (index, param_type) in self.function.param_types().iter().enumerate() {
-            let param = params
-                .get(index)
-                .ok_or(WasmRuntimeError::InvalidParameter)?;
-            if !param.is_valid_type(*param_type) {
-                return Err(WasmRuntimeError::InvalidParameter);
+        let body = match self.function.body.as_ref() {
+            Some(v) => v,
+            None => {
+                // The imported function, which may be exported again or be the start function
+                let dlink = self.function.dlink().ok_or(WasmRuntimeError::NoMethod)?;
+                return dlink(&WasmCaller::new(self.module, self.context), params);
             }
-            locals.push(param.clone());
-        }
+        };
+
+        let mut locals = Vec::with_capacity(params.len() + body.local_types.len());
+        locals.extend_from_slice(params);
         for local in &body.local_types {
             locals.push(WasmValue::default_for(*local));
         }
@@ -2470,13 +5860,21 @@ impl WasmRunnable<'_> {
 
         let code_ref = body.code_block.borrow();
         let mut code_block = WasmCodeBlock::from_slice(&code_ref, body.block_info());
-        let mut interp = WasmInterpreter::new(self.module);
-        interp
-            .invoke(&mut code_block, locals.as_slice(), result_types)
-            .map_err(|err| {
-                println!("err {:?} at {}", err, code_block.fetch_position());
-                err
-            })
+        match self.context {
+            Some(context) => context.invoke(
+                self.module,
+                &mut code_block,
+                locals.as_slice(),
+                result_types,
+            ),
+            None => {
+                self.module.enter_invocation();
+                let mut interp = WasmInterpreter::new(self.module);
+                let result = interp.invoke(&mut code_block, locals.as_slice(), result_types);
+                self.module.leave_invocation(result.as_ref().ok());
+                result
+            }
+        }
     }
 }
 
@@ -2503,6 +5901,820 @@ mod tests {
         super::WasmLoader::instantiate(&minimal_bad, &|_, _, _| unreachable!()).unwrap();
     }
 
+    #[test]
+    fn memory_backend() {
+        use super::*;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x0B,
+            0x0A, 0x01, 0x00, 0x41, 0x10, 0x0B, 0x04, 0x61, 0x62, 0x63, 0x64,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.size(), 1);
+        assert_eq!(memory.read_bytes(0x10, 4).unwrap(), b"abcd");
+        assert_eq!(memory.grow(2), 1);
+        assert_eq!(memory.size(), 3);
+        assert_eq!(memory.read_u32(0x2FFF0).unwrap(), 0);
+
+        let module = WasmLoader::instantiate_with_allocator(
+            &slice,
+            &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+            &|_| {
+                let buffer = Box::leak(vec![0xCC; 0x20000].into_boxed_slice());
+                Ok(Box::new(StaticMemoryBackend::new(buffer)))
+            },
+        )
+        .unwrap();
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.size(), 1);
+        assert_eq!(memory.read_u8(0).unwrap(), 0);
+        assert_eq!(memory.read_bytes(0x10, 4).unwrap(), b"abcd");
+        assert_eq!(memory.grow(1), 1);
+        assert_eq!(memory.read_u32(0x1FFF0).unwrap(), 0);
+        assert_eq!(memory.grow(1), -1);
+        assert_eq!(memory.size(), 2);
+    }
+
+    #[test]
+    fn import_memory() {
+        use super::*;
+        // (import "env" "memory" (memory 1)) and the active segment
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x02, 0x0F, 0x01, 0x03, 0x65, 0x6E,
+            0x76, 0x06, 0x6D, 0x65, 0x6D, 0x6F, 0x72, 0x79, 0x02, 0x00, 0x01, 0x0B, 0x0A, 0x01,
+            0x00, 0x41, 0x10, 0x0B, 0x04, 0x61, 0x62, 0x63, 0x64,
+        ];
+        let load = |loader: &mut WasmLoader| {
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+        };
+
+        // not defined by the host
+        let mut loader = WasmLoader::new();
+        assert!(matches!(
+            load(&mut loader),
+            Err(WasmDecodeError::DynamicLinkError)
+        ));
+        assert_eq!(loader.error_import(), Some(("env", "memory")));
+
+        let buffer = Box::leak(vec![0xCC; 0x20000].into_boxed_slice());
+        let mut loader = WasmLoader::new();
+        loader.define_memory("env", "memory", StaticMemoryBackend::new(buffer));
+        load(&mut loader).unwrap();
+        let module = loader.into_module();
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.size(), 1);
+        assert_eq!(memory.read_bytes(0x10, 4).unwrap(), b"abcd");
+        assert_eq!(memory.grow(1), 1);
+        assert_eq!(memory.grow(1), -1);
+
+        // decoded without the backend
+        let mut loader = WasmLoader::new();
+        loader.decode(&slice).unwrap();
+        assert_eq!(loader.into_module().memory(0).unwrap().size(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "mutable-globals")]
+    fn start() {
+        use super::*;
+        // (start) adds the byte at 0 from the data segment to the global "g"
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x06, 0x06, 0x01, 0x7F, 0x01,
+            0x41, 0x00, 0x0B, 0x07, 0x0D, 0x02, 0x01, 0x67, 0x03, 0x00, 0x05, 0x73, 0x74, 0x61,
+            0x72, 0x74, 0x00, 0x00, 0x08, 0x01, 0x00, 0x0A, 0x0E, 0x01, 0x0C, 0x00, 0x23, 0x00,
+            0x41, 0x00, 0x2D, 0x00, 0x00, 0x6A, 0x24, 0x00, 0x0B, 0x0B, 0x07, 0x01, 0x00, 0x41,
+            0x00, 0x0B, 0x01, 0x2A,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        assert_eq!(
+            module.global_by_name("g").unwrap().get(),
+            WasmValue::I32(42)
+        );
+
+        // deferred by the embedder
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader
+            .set_run_start(false)
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let module = loader.into_module();
+        assert_eq!(module.global_by_name("g").unwrap().get(), WasmValue::I32(0));
+        module.entry_point().unwrap().invoke(&[]).unwrap();
+        assert_eq!(
+            module.global_by_name("g").unwrap().get(),
+            WasmValue::I32(42)
+        );
+        module.func("start").unwrap().invoke(&[]).unwrap();
+        assert_eq!(
+            module.global_by_name("g").unwrap().get(),
+            WasmValue::I32(84)
+        );
+
+        // (start) traps by i32.div_s
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x06, 0x06, 0x01, 0x7F, 0x01,
+            0x41, 0x00, 0x0B, 0x07, 0x0D, 0x02, 0x01, 0x67, 0x03, 0x00, 0x05, 0x73, 0x74, 0x61,
+            0x72, 0x74, 0x00, 0x00, 0x08, 0x01, 0x00, 0x0A, 0x0A, 0x01, 0x08, 0x00, 0x41, 0x01,
+            0x41, 0x00, 0x6D, 0x1A, 0x0B, 0x0B, 0x07, 0x01, 0x00, 0x41, 0x00, 0x0B, 0x01, 0x2A,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::Trap(WasmRuntimeError::DivideByZero))
+        ));
+
+        // the start function must be [] -> []
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x03, 0x02, 0x01, 0x00, 0x08, 0x01, 0x00, 0x0A, 0x06, 0x01, 0x04, 0x00, 0x41,
+            0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // the start function out of range
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x08, 0x01, 0x01, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // (start) calls the function in the table through the element segment, to set "g" to 7
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x03, 0x02, 0x00, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x06, 0x06, 0x01,
+            0x7F, 0x01, 0x41, 0x00, 0x0B, 0x07, 0x05, 0x01, 0x01, 0x67, 0x03, 0x00, 0x08, 0x01,
+            0x00, 0x09, 0x07, 0x01, 0x00, 0x41, 0x00, 0x0B, 0x01, 0x01, 0x0A, 0x10, 0x02, 0x07,
+            0x00, 0x41, 0x00, 0x11, 0x00, 0x00, 0x0B, 0x06, 0x00, 0x41, 0x07, 0x24, 0x00, 0x0B,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        assert_eq!(module.global_by_name("g").unwrap().get(), WasmValue::I32(7));
+    }
+
+    #[test]
+    fn decode() {
+        use super::*;
+        // (import "env" "f"), (import "env" "g" i32), the table of 0xFFFF_FFFF elements,
+        // the memory of 0x8000 pages, (start) calls "f", and the active segments, whose data
+        // is out of bounds
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x02, 0x12, 0x02, 0x03, 0x65, 0x6E, 0x76, 0x01, 0x66, 0x00, 0x00, 0x03, 0x65, 0x6E,
+            0x76, 0x01, 0x67, 0x03, 0x7F, 0x00, 0x04, 0x08, 0x01, 0x70, 0x00, 0xFF, 0xFF, 0xFF,
+            0xFF, 0x0F, 0x05, 0x05, 0x01, 0x00, 0x80, 0x80, 0x02, 0x08, 0x01, 0x00, 0x09, 0x07,
+            0x01, 0x00, 0x41, 0x00, 0x0B, 0x01, 0x00, 0x0B, 0x26, 0x01, 0x00, 0x41, 0x70, 0x0B,
+            0x20, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+            0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+            0xAA, 0xAA, 0xAA, 0xAA, 0xAA,
+        ];
+        let mut loader = WasmLoader::new();
+        // The host function does not take part in decoding, even if its type does not match
+        loader.define_func("env", "f", |_: &WasmCaller, a: i32| a);
+        loader.decode(&slice).unwrap();
+        let module = loader.into_module();
+
+        assert_eq!(module.imports().len(), 2);
+        assert_eq!(module.start(), Some(0));
+        let table = module.table(0).unwrap();
+        assert_eq!(table.limit().min(), 0xFFFF_FFFF);
+        assert_eq!(table.size(), 0);
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.limit().min(), 0x8000);
+        assert_eq!(memory.size(), 0);
+        assert_eq!(module.data_segments()[0].size(), 32);
+        assert_eq!(module.globals()[0].get(), WasmValue::I32(0));
+        assert!(matches!(
+            module.func_by_index(0).unwrap().invoke(&[]),
+            Err(WasmRuntimeError::NoMethod)
+        ));
+
+        // The instantiation links the imports and applies the segments
+        let mut loader = WasmLoader::new();
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::DynamicLinkError)
+        ));
+
+        // The invalid module is still rejected
+        assert!(matches!(
+            WasmLoader::new().decode(&slice[..slice.len() - 1]),
+            Err(WasmDecodeError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "mutable-globals")]
+    fn sections() {
+        use super::*;
+        // the module of the start test followed by the custom section "name"
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x06, 0x06, 0x01, 0x7F, 0x01,
+            0x41, 0x00, 0x0B, 0x07, 0x0D, 0x02, 0x01, 0x67, 0x03, 0x00, 0x05, 0x73, 0x74, 0x61,
+            0x72, 0x74, 0x00, 0x00, 0x08, 0x01, 0x00, 0x0A, 0x0E, 0x01, 0x0C, 0x00, 0x23, 0x00,
+            0x41, 0x00, 0x2D, 0x00, 0x00, 0x6A, 0x24, 0x00, 0x0B, 0x0B, 0x07, 0x01, 0x00, 0x41,
+            0x00, 0x0B, 0x01, 0x2A, 0x00, 0x05, 0x04, 0x6E, 0x61, 0x6D, 0x65,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let sections = module
+            .sections()
+            .iter()
+            .map(|v| (v.name(), v.offset(), v.size()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            [
+                ("type", 10, 4),
+                ("function", 16, 2),
+                ("memory", 20, 3),
+                ("global", 25, 6),
+                ("export", 33, 13),
+                ("start", 48, 1),
+                ("code", 51, 14),
+                ("data", 67, 7),
+                ("custom", 76, 5),
+            ]
+        );
+        assert_eq!(module.sections()[8].custom_name(), Some("name"));
+        assert_eq!(module.sections()[0].custom_name(), None);
+        assert_eq!(module.start(), Some(0));
+        assert!(module.imports().is_empty());
+        // the size remains after the active segment is dropped
+        assert_eq!(module.data_segments()[0].size(), 1);
+        assert!(module.data_segments()[0].data().borrow().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn disassemble_float() {
+        use super::*;
+        // f32.const 1.5, drop, f64.const -2.25
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7C, 0x03, 0x02, 0x01, 0x00, 0x07, 0x0A, 0x01, 0x06, 0x5F, 0x73, 0x74, 0x61, 0x72,
+            0x74, 0x00, 0x00, 0x0A, 0x13, 0x01, 0x11, 0x00, 0x43, 0x00, 0x00, 0xC0, 0x3F, 0x1A,
+            0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xC0, 0x0B,
+        ];
+        let mut loader = WasmLoader::new();
+        loader.decode(&slice).unwrap();
+        loader.into_module().disassemble(0).unwrap();
+    }
+
+    #[test]
+    fn marshalling() {
+        use super::*;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0A, 0x02, 0x60, 0x00, 0x01,
+            0x7F, 0x60, 0x01, 0x7F, 0x01, 0x7F, 0x02, 0x0C, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x04,
+            0x6D, 0x61, 0x6B, 0x65, 0x00, 0x00, 0x03, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01,
+            0x00, 0x01, 0x06, 0x07, 0x01, 0x7F, 0x01, 0x41, 0x80, 0x08, 0x0B, 0x07, 0x19, 0x03,
+            0x06, 0x6D, 0x65, 0x6D, 0x6F, 0x72, 0x79, 0x02, 0x00, 0x06, 0x6D, 0x61, 0x6C, 0x6C,
+            0x6F, 0x63, 0x00, 0x01, 0x03, 0x72, 0x75, 0x6E, 0x00, 0x02, 0x0A, 0x12, 0x02, 0x0B,
+            0x00, 0x23, 0x00, 0x23, 0x00, 0x20, 0x00, 0x6A, 0x24, 0x00, 0x0B, 0x04, 0x00, 0x10,
+            0x00, 0x0B, 0x0B, 0x2E, 0x03, 0x00, 0x41, 0x10, 0x0B, 0x09, 0x68, 0x65, 0x6C, 0x6C,
+            0x6F, 0x00, 0x61, 0x62, 0x63, 0x00, 0x41, 0xC0, 0x00, 0x0B, 0x10, 0x10, 0x00, 0x00,
+            0x00, 0x05, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x41, 0xC8, 0x01, 0x0B, 0x03, 0xFF, 0xFE, 0x00,
+        ];
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader.define_func("env", "make", |caller: &WasmCaller| {
+            caller.alloc_str("memory", "malloc", "world")
+        });
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let module = loader.into_module();
+        let memory = module.memory_by_name("memory").unwrap();
+
+        assert_eq!(memory.read_str(16, 5).unwrap(), "hello");
+        assert_eq!(memory.read_cstr(16).unwrap(), "hello");
+        assert_eq!(memory.read_cbytes(22).unwrap(), b"abc");
+        assert_eq!(memory.read_cstr(21).unwrap(), "");
+        assert!(matches!(
+            memory.read_str(200, 2),
+            Err(WasmRuntimeError::InvalidUtf8)
+        ));
+        assert!(matches!(
+            memory.read_cstr(200),
+            Err(WasmRuntimeError::InvalidUtf8)
+        ));
+        assert!(matches!(
+            memory.read_str(0xFFFE, 5),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        // no terminator until the end of the memory
+        memory.fill(0xFFF0, 0x20, 0x10).unwrap();
+        assert!(matches!(
+            memory.read_cstr(0xFFF0),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            memory.read_cstr(0x10000),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        let iovecs = memory.read_iovecs(64, 2).unwrap();
+        assert_eq!(iovecs, [&b"hello"[..], &b"abc"[..]]);
+        assert!(matches!(
+            memory.read_iovecs(0xFFF8, 2),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            memory.read_iovecs(64, usize::MAX),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert_eq!(memory.write_iovecs(64, 2, b"HELLO").unwrap(), 5);
+        assert_eq!(memory.write_iovecs(64, 2, b"0123456789").unwrap(), 8);
+        assert_eq!(memory.read_bytes(16, 9).unwrap(), b"01234\x00567");
+
+        // the guest allocates the buffer for the host
+        let run = module.typed_func::<(), u32>("run").unwrap();
+        assert_eq!(run.call(()).unwrap(), 1024);
+        assert_eq!(memory.read_str(1024, 5).unwrap(), "world");
+        assert_eq!(run.call(()).unwrap(), 1029);
+        assert!(matches!(
+            memory.write_allocated(b"abc", |_| Ok(0)),
+            Err(WasmRuntimeError::OutOfMemory)
+        ));
+        assert!(matches!(
+            memory.write_allocated(b"abc", |_| Ok(0xFFFF)),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "bulk-memory", feature = "reference-types"))]
+    fn elem_segments() {
+        use super::*;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x04, 0x07, 0x02, 0x70, 0x00, 0x08, 0x6F, 0x00,
+            0x02, 0x09, 0x39, 0x08, 0x00, 0x41, 0x00, 0x0B, 0x01, 0x01, 0x01, 0x00, 0x01, 0x02,
+            0x02, 0x00, 0x41, 0x01, 0x0B, 0x00, 0x02, 0x00, 0x01, 0x03, 0x00, 0x01, 0x02, 0x04,
+            0x41, 0x03, 0x0B, 0x02, 0xD2, 0x02, 0x0B, 0xD0, 0x70, 0x0B, 0x05, 0x6F, 0x01, 0xD0,
+            0x6F, 0x0B, 0x06, 0x01, 0x41, 0x01, 0x0B, 0x6F, 0x01, 0xD0, 0x6F, 0x0B, 0x07, 0x70,
+            0x01, 0xD2, 0x00, 0x0B, 0x0A, 0x0A, 0x03, 0x02, 0x00, 0x0B, 0x02, 0x00, 0x0B, 0x02,
+            0x00, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let segments = module.elem_segments();
+        assert_eq!(segments.len(), 8);
+        let modes = segments.iter().map(|v| v.mode()).collect::<Vec<_>>();
+        assert_eq!(
+            modes,
+            [
+                WasmSegmentMode::Active(0, 0),
+                WasmSegmentMode::Passive,
+                WasmSegmentMode::Active(0, 1),
+                WasmSegmentMode::Declarative,
+                WasmSegmentMode::Active(0, 3),
+                WasmSegmentMode::Passive,
+                WasmSegmentMode::Active(1, 1),
+                WasmSegmentMode::Declarative,
+            ]
+        );
+        assert_eq!(segments[5].elem_type(), WasmValType::ExternRef);
+        assert_eq!(*segments[1].elements().borrow(), [Some(2)]);
+        assert_eq!(*segments[5].elements().borrow(), [None]);
+        assert!(segments[3].elements().borrow().is_empty());
+        assert!(segments[4].elements().borrow().is_empty());
+
+        let table = module.table(0).unwrap();
+        assert_eq!(
+            &table.table.borrow()[..5],
+            [Some(1), Some(0), Some(1), Some(2), None]
+        );
+
+        // out of bounds of the table
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x08, 0x09, 0x08,
+            0x01, 0x00, 0x41, 0x07, 0x0B, 0x02, 0x00, 0x01, 0x0A, 0x0A, 0x03, 0x02, 0x00, 0x0B,
+            0x02, 0x00, 0x0B, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::OutOfBounds)
+        ));
+
+        // funcref to the table of externref
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x04, 0x07, 0x02, 0x70, 0x00, 0x08, 0x6F, 0x00,
+            0x02, 0x09, 0x0B, 0x01, 0x06, 0x01, 0x41, 0x00, 0x0B, 0x70, 0x01, 0xD2, 0x00, 0x0B,
+            0x0A, 0x0A, 0x03, 0x02, 0x00, 0x0B, 0x02, 0x00, 0x0B, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // the empty segment may start at the end of the table, but not beyond
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x06, 0x01, 0x00,
+            0x41, 0x01, 0x0B, 0x00, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .is_ok()
+        );
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x06, 0x01, 0x00,
+            0x41, 0x02, 0x0B, 0x00, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::OutOfBounds)
+        ));
+
+        // the function index out of range
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x07, 0x01, 0x00,
+            0x41, 0x00, 0x0B, 0x01, 0x05, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // the table index out of range
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x09, 0x01, 0x02,
+            0x01, 0x41, 0x00, 0x0B, 0x00, 0x01, 0x00, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // no encoding of the flags 8
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x07, 0x01, 0x08,
+            0x41, 0x00, 0x0B, 0x01, 0x00, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::UnexpectedToken)
+        ));
+
+        // out of bounds of the memory
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x0B,
+            0x0A, 0x01, 0x00, 0x41, 0xFF, 0xFF, 0x03, 0x0B, 0x02, 0x61, 0x62,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "reference-types", feature = "float"))]
+    fn const_expr() {
+        use super::*;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x70, 0x00, 0x04, 0x05, 0x03, 0x01, 0x00,
+            0x01, 0x06, 0x3C, 0x08, 0x7F, 0x00, 0x41, 0x0A, 0x0B, 0x7F, 0x00, 0x23, 0x00, 0x41,
+            0x05, 0x6C, 0x41, 0x02, 0x6B, 0x0B, 0x7E, 0x00, 0x42, 0x03, 0x42, 0x04, 0x7C, 0x0B,
+            0x7D, 0x00, 0x43, 0x00, 0x00, 0xC0, 0x3F, 0x0B, 0x7C, 0x00, 0x44, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x02, 0x40, 0x0B, 0x70, 0x00, 0xD2, 0x00, 0x0B, 0x6F, 0x00, 0xD0,
+            0x6F, 0x0B, 0x7F, 0x01, 0x41, 0x01, 0x0B, 0x09, 0x0A, 0x01, 0x00, 0x23, 0x00, 0x41,
+            0x08, 0x6B, 0x0B, 0x01, 0x00, 0x0A, 0x04, 0x01, 0x02, 0x00, 0x0B, 0x0B, 0x0B, 0x01,
+            0x00, 0x23, 0x01, 0x41, 0x02, 0x6A, 0x0B, 0x02, 0x78, 0x79,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let values = (0..8)
+            .map(|i| *module.global(i).unwrap().value().borrow())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                WasmValue::I32(10),
+                WasmValue::I32(48),
+                WasmValue::I64(7),
+                WasmValue::F32(1.5),
+                WasmValue::F64(2.25),
+                WasmValue::FuncRef(Some(0)),
+                WasmValue::ExternRef(None),
+                WasmValue::I32(1),
+            ]
+        );
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.read_bytes(50, 2).unwrap(), b"xy");
+        let table = module.table(0).unwrap();
+        assert_eq!(table.get_ref(2), Some(Some(0)));
+
+        // global.get of the mutable global
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x06, 0x0B, 0x02, 0x7F, 0x01, 0x41,
+            0x01, 0x0B, 0x7F, 0x00, 0x23, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidGlobal)
+        ));
+
+        // two values left on the stack
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x06, 0x08, 0x01, 0x7F, 0x00, 0x41,
+            0x01, 0x41, 0x02, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidStackLevel)
+        ));
+
+        // i32.add with i64
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x06, 0x09, 0x01, 0x7F, 0x00, 0x41,
+            0x01, 0x42, 0x02, 0x6A, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn features() {
+        use super::*;
+        let load = |slice: &[u8], features: WasmFeatures| {
+            let mut loader = WasmLoader::with_features(features);
+            loader
+                .load(
+                    slice,
+                    &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &VecMemoryBackend::allocator,
+                )
+                .map(|_| loader.into_module())
+        };
+
+        // i32.extend8_s
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x07, 0x01, 0x05, 0x00, 0x20, 0x00, 0xC0,
+            0x0B,
+        ];
+        let features = WasmFeatures::available() - WasmFeatures::SIGN_EXTENSION;
+        match load(&slice, features) {
+            Err(WasmDecodeError::FeatureNotEnabled(v)) => {
+                assert_eq!(v, WasmFeatures::SIGN_EXTENSION);
+                assert_eq!(format!("{}", v), "sign-ext");
+            }
+            _ => unreachable!(),
+        }
+        #[cfg(feature = "sign-ext")]
+        {
+            let module = load(&slice, WasmFeatures::default()).unwrap();
+            assert!(module.features().contains(WasmFeatures::SIGN_EXTENSION));
+        }
+
+        // v128.const
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x08, 0x01, 0x06, 0x00, 0x20, 0x00, 0xFD,
+            0x0C, 0x0B,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::SIMD),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::SIMD))
+        ));
+
+        // local of v128
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x0A, 0x06, 0x01, 0x04, 0x01, 0x01, 0x7B, 0x0B,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::SIMD),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::SIMD))
+        ));
+        #[cfg(feature = "simd")]
+        assert!(load(&slice, WasmFeatures::available()).is_ok());
+
+        // local of externref
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x0A, 0x06, 0x01, 0x04, 0x01, 0x01, 0x6F, 0x0B,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::REFERENCE_TYPES),
+            Err(WasmDecodeError::FeatureNotEnabled(
+                WasmFeatures::REFERENCE_TYPES
+            ))
+        ));
+
+        // memory of i64
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x04, 0x01,
+        ];
+        assert!(load(&slice, WasmFeatures::available()).is_ok());
+        assert!(matches!(
+            load(&slice, WasmFeatures::empty()),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::MEMORY64))
+        ));
+
+        // shared memory, which requires the maximum
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x04, 0x01, 0x03, 0x01, 0x01,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::THREADS),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::THREADS))
+        ));
+        #[cfg(feature = "threads")]
+        assert!(load(&slice, WasmFeatures::available()).is_ok());
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x02, 0x01,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all()),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // type with two results
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x00, 0x02,
+            0x7F, 0x7F,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::MULTI_VALUE),
+            Err(WasmDecodeError::FeatureNotEnabled(
+                WasmFeatures::MULTI_VALUE
+            ))
+        ));
+        // multi-value is not implemented yet
+        #[cfg(feature = "multi-value")]
+        assert!(matches!(
+            load(&slice, WasmFeatures::available()),
+            Err(WasmDecodeError::NotSupprted)
+        ));
+
+        // (global (export "g") (mut i32) (i32.const 0))
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x06, 0x06, 0x01, 0x7F, 0x01, 0x41,
+            0x00, 0x0B, 0x07, 0x05, 0x01, 0x01, 0x67, 0x03, 0x00,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::MUTABLE_GLOBALS),
+            Err(WasmDecodeError::FeatureNotEnabled(
+                WasmFeatures::MUTABLE_GLOBALS
+            ))
+        ));
+        #[cfg(feature = "mutable-globals")]
+        assert!(load(&slice, WasmFeatures::available()).is_ok());
+
+        // (global f32 (f32.const 1.5))
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x06, 0x09, 0x01, 0x7D, 0x00, 0x43,
+            0x00, 0x00, 0xC0, 0x3F, 0x0B,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::FLOAT),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::FLOAT))
+        ));
+        #[cfg(feature = "float")]
+        assert_eq!(
+            load(&slice, WasmFeatures::available()).unwrap().globals()[0].get(),
+            WasmValue::F32(1.5)
+        );
+
+        // tag section
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x0D, 0x03, 0x01, 0x00, 0x00,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::all() - WasmFeatures::EXCEPTIONS),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::EXCEPTIONS))
+        ));
+        #[cfg(feature = "exceptions")]
+        assert_eq!(
+            load(&slice, WasmFeatures::available())
+                .unwrap()
+                .tags()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        use super::*;
+        let load = |slice: &[u8], features: WasmFeatures| {
+            let mut loader = WasmLoader::with_features(features);
+            loader
+                .load(
+                    slice,
+                    &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &VecMemoryBackend::allocator,
+                )
+                .map_err(|err| (err, loader.error_location()))
+        };
+
+        assert_eq!(
+            WasmFeatures::from_name("memory64"),
+            Some(WasmFeatures::MEMORY64)
+        );
+        assert_eq!(
+            WasmFeatures::from_name("sign-ext"),
+            Some(WasmFeatures::SIGN_EXTENSION)
+        );
+        assert_eq!(WasmFeatures::from_name("gc"), None);
+
+        // memory of i64 after the type section
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x05, 0x03, 0x01, 0x04, 0x01,
+        ];
+        assert!(load(&slice, WasmFeatures::available()).is_ok());
+        assert!(matches!(
+            load(&slice, WasmFeatures::empty()),
+            Err((
+                WasmDecodeError::FeatureNotEnabled(WasmFeatures::MEMORY64),
+                Some(("memory", 19))
+            ))
+        ));
+
+        // i32.add without operands, which points to the instruction
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x05, 0x01, 0x03, 0x00, 0x6A, 0x0B,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::available()),
+            Err((WasmDecodeError::OutOfStack, Some(("code", 24))))
+        ));
+
+        // the size of the section exceeds the binary
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x05, 0x04, 0x01, 0x04, 0x01,
+        ];
+        assert!(matches!(
+            load(&slice, WasmFeatures::available()),
+            Err((WasmDecodeError::UnexpectedEof, Some(("section header", 14))))
+        ));
+
+        // bad magic
+        assert!(matches!(
+            load(
+                &[0x00, 0x61, 0x73, 0x6E, 0x01, 0x00, 0x00, 0x00],
+                WasmFeatures::available()
+            ),
+            Err((WasmDecodeError::BadExecutable, None))
+        ));
+    }
+
     #[test]
     fn leb128() {
         let data = [
@@ -2,30 +2,63 @@
 
 use super::opcode::*;
 use super::wasm::*;
+#[cfg(feature = "simd")]
+use crate::simd::{self, SimdSignature};
+#[cfg(feature = "threads")]
+use crate::threads::AtomicInstruction;
 use crate::*;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
-use core::{cell::UnsafeCell, mem::align_of, mem::transmute};
+#[cfg(feature = "simd")]
+use byteorder::{ByteOrder, LE};
+use core::{
+    cell::RefCell,
+    convert::TryFrom,
+    mem::{align_of, size_of},
+    slice,
+};
 
 pub struct WasmInterpreter<'a> {
     module: &'a WasmModule,
+    /// The function called by return_call, which replaces the current frame
+    tail_call: Option<&'a WasmFunction>,
+    /// Parameters of the pending tail call
+    tail_args: Vec<WasmStackValue>,
+    /// Number of the frames of the guest functions, including the ones below the host functions
+    depth: usize,
 }
 
 impl<'a> WasmInterpreter<'a> {
     pub const fn new(module: &'a WasmModule) -> Self {
-        Self { module }
+        Self {
+            module,
+            tail_call: None,
+            tail_args: Vec::new(),
+            depth: 0,
+        }
     }
 }
 
-impl WasmInterpreter<'_> {
+impl<'a> WasmInterpreter<'a> {
     /// Interpret WebAssembly code blocks
     pub fn invoke(
         &mut self,
-        mut code_block: &mut WasmCodeBlock,
+        code_block: &mut WasmCodeBlock,
         locals: &[WasmValue],
         result_types: &[WasmValType],
     ) -> Result<WasmValue, WasmRuntimeError> {
-        let mut stack = SharedStack::with_capacity(0x10000);
+        let mut stack = SharedStack::new();
+        self.invoke_with_stack(code_block, locals, result_types, &mut stack)
+    }
 
+    /// Interpret WebAssembly code blocks on the specified stack
+    pub fn invoke_with_stack(
+        &mut self,
+        code_block: &mut WasmCodeBlock,
+        locals: &[WasmValue],
+        result_types: &[WasmValType],
+        stack: &mut SharedStack,
+    ) -> Result<WasmValue, WasmRuntimeError> {
         let mut locals = {
             let mut output = Vec::with_capacity(locals.len());
             for local in locals {
@@ -33,17 +66,51 @@ impl WasmInterpreter<'_> {
             }
             output
         };
-        self.run(
-            &mut code_block,
-            locals.as_mut_slice(),
-            result_types,
-            &mut stack,
-        )
+        if self.depth >= self.module.max_call_depth() {
+            return Err(WasmRuntimeError::StackOverflow);
+        }
+        self.depth += 1;
+        let result = self.run(code_block, locals.as_mut_slice(), result_types, stack);
+        self.depth -= 1;
+        let result = result?;
+        match self.tail_call.take() {
+            Some(func) => {
+                let args = core::mem::take(&mut self.tail_args);
+                self.call_function(func, &args, stack)
+            }
+            None => Ok(result),
+        }
+    }
+
+    /// Calculates the effective address from the address operand of the memory
+    #[inline]
+    fn effective_address(
+        memory: &WasmMemory,
+        memarg: WasmMemArg,
+        base: WasmStackValue,
+    ) -> Result<usize, WasmRuntimeError> {
+        if memory.limit().is_64() {
+            memarg
+                .offset_by64(base.get_u64())
+                .ok_or(WasmRuntimeError::OutOfBounds)
+        } else {
+            Ok(memarg.offset_by(base.get_u32()))
+        }
+    }
+
+    /// Converts the operand of the index type of the memory
+    #[inline]
+    fn memory_offset(memory: &WasmMemory, value: WasmStackValue) -> usize {
+        if memory.limit().is_64() {
+            usize::try_from(value.get_u64()).unwrap_or(usize::MAX)
+        } else {
+            value.get_u32() as usize
+        }
     }
 
     pub fn run(
         &mut self,
-        mut code_block: &mut WasmCodeBlock,
+        code_block: &mut WasmCodeBlock,
         locals: &mut [WasmStackValue],
         result_types: &[WasmValType],
         stack: &mut SharedStack,
@@ -54,10 +121,24 @@ impl WasmInterpreter<'_> {
             stack.alloc_stack(code_block.info().max_stack());
         let mut block_stack = stack.alloc_stack(code_block.info().max_block_level());
 
+        let debug_hook = module.debug_hook();
+
         code_block.reset();
         loop {
             let opcode = code_block.read_opcode()?;
 
+            if let Some(hook) = debug_hook {
+                hook(&WasmFrame {
+                    module,
+                    func_index: code_block.info().func_index(),
+                    position: code_block.fetch_position(),
+                    opcode,
+                    depth: self.depth,
+                    locals,
+                    value_stack: value_stack.as_slice(),
+                })?;
+            }
+
             // println!(
             //     "{}:{:04x} {:02x} {}",
             //     code_block.info().func_index(),
@@ -67,8 +148,6 @@ impl WasmInterpreter<'_> {
             // );
 
             match opcode {
-                WasmOpcode::Nop => (),
-
                 WasmOpcode::Block => {
                     let _ = code_block.read_unsigned()?;
                     block_stack
@@ -103,14 +182,15 @@ impl WasmInterpreter<'_> {
                             block_stack
                                 .push(position)
                                 .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                            code_block.set_position(else_position);
+                            // Resume after the "else" opcode
+                            code_block.set_position(else_position + 1);
                         } else {
                             code_block.set_position(end_position);
                         }
                     }
                 }
                 WasmOpcode::Else => {
-                    Self::branch(0, &mut block_stack, &mut value_stack, &mut code_block)?;
+                    Self::branch(0, &mut block_stack, &mut value_stack, code_block)?;
                 }
                 WasmOpcode::End => {
                     if block_stack.pop().is_none() {
@@ -119,7 +199,7 @@ impl WasmInterpreter<'_> {
                 }
                 WasmOpcode::Br => {
                     let target = code_block.read_unsigned()? as usize;
-                    Self::branch(target, &mut block_stack, &mut value_stack, &mut code_block)?;
+                    Self::branch(target, &mut block_stack, &mut value_stack, code_block)?;
                 }
                 WasmOpcode::BrIf => {
                     let target = code_block.read_unsigned()? as usize;
@@ -128,7 +208,7 @@ impl WasmInterpreter<'_> {
                         .map(|v| v.get_bool())
                         .ok_or(WasmRuntimeError::InternalInconsistency)?;
                     if cc {
-                        Self::branch(target, &mut block_stack, &mut value_stack, &mut code_block)?;
+                        Self::branch(target, &mut block_stack, &mut value_stack, code_block)?;
                     }
                 }
                 WasmOpcode::BrTable => {
@@ -144,7 +224,7 @@ impl WasmInterpreter<'_> {
                         let _ = code_block.read_unsigned()?;
                     }
                     let target = code_block.read_unsigned()? as usize;
-                    Self::branch(target, &mut block_stack, &mut value_stack, &mut code_block)?;
+                    Self::branch(target, &mut block_stack, &mut value_stack, code_block)?;
                 }
 
                 WasmOpcode::Return => {
@@ -157,28 +237,137 @@ impl WasmInterpreter<'_> {
                         .functions()
                         .get(index)
                         .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    self.call(func, &mut value_stack, stack)?;
+                    if let Err(err) = self.call(func, &mut value_stack, stack) {
+                        if Self::handle_exception(
+                            err,
+                            module,
+                            code_block,
+                            &mut block_stack,
+                            &mut value_stack,
+                        )? {
+                            break;
+                        }
+                    }
                 }
                 WasmOpcode::CallIndirect => {
                     let type_index = code_block.read_unsigned()? as usize;
-                    let _reserved = code_block.read_unsigned()? as usize;
+                    let table_index = code_block.read_unsigned()? as usize;
                     let index = value_stack
                         .pop()
-                        .map(|v| v.get_i32() as usize)
+                        .map(|v| v.get_u32() as usize)
                         .ok_or(WasmRuntimeError::InternalInconsistency)?;
                     let func = module
-                        .elem_by_index(index)
+                        .table_elem(table_index, index)
                         .ok_or(WasmRuntimeError::NoMethod)?;
-                    if func.type_index() != type_index {
+                    // Types of different indices match if their signatures are identical
+                    if module.type_by_ref(type_index) != Some(func.func_type()) {
                         return Err(WasmRuntimeError::TypeMismatch);
                     }
-                    self.call(func, &mut value_stack, stack)?;
+                    if let Err(err) = self.call(func, &mut value_stack, stack) {
+                        if Self::handle_exception(
+                            err,
+                            module,
+                            code_block,
+                            &mut block_stack,
+                            &mut value_stack,
+                        )? {
+                            break;
+                        }
+                    }
+                }
+
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::TryTable => {
+                    let _ = code_block.read_unsigned()?;
+                    let n_catches = code_block.read_unsigned()?;
+                    for _ in 0..n_catches {
+                        let _ = code_block.read_catch()?;
+                    }
+                    block_stack
+                        .push(code_block.fetch_position())
+                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+                }
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::Throw => {
+                    let tag = code_block.read_unsigned()? as usize;
+                    let param_types = module
+                        .tag(tag)
+                        .and_then(|v| module.type_by_ref(v.type_index()))
+                        .ok_or(WasmRuntimeError::InternalInconsistency)?
+                        .param_types();
+                    let new_len = value_stack
+                        .len()
+                        .checked_sub(param_types.len())
+                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                    let values = value_stack.as_slice()[new_len..]
+                        .iter()
+                        .zip(param_types)
+                        .map(|(v, t)| v.get_by_type(*t))
+                        .collect();
+                    value_stack.resize(new_len, WasmStackValue::zero());
+                    let err = module.throw(WasmException::new(tag, values));
+                    if Self::handle_exception(
+                        err,
+                        module,
+                        code_block,
+                        &mut block_stack,
+                        &mut value_stack,
+                    )? {
+                        break;
+                    }
+                }
+                #[cfg(feature = "exceptions")]
+                WasmOpcode::ThrowRef => {
+                    let exnref = value_stack
+                        .pop()
+                        .ok_or(WasmRuntimeError::InternalInconsistency)?
+                        .get_ref()
+                        .ok_or(WasmRuntimeError::NullReference)?;
+                    let err = module.throw_ref(exnref);
+                    if Self::handle_exception(
+                        err,
+                        module,
+                        code_block,
+                        &mut block_stack,
+                        &mut value_stack,
+                    )? {
+                        break;
+                    }
                 }
 
-                WasmOpcode::Drop => {
-                    let _ = value_stack.pop();
+                #[cfg(feature = "tail-call")]
+                WasmOpcode::ReturnCall => {
+                    let index = code_block.read_unsigned()? as usize;
+                    let func = module
+                        .functions()
+                        .get(index)
+                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                    return self.prepare_tail_call(func, &mut value_stack);
+                }
+                #[cfg(feature = "tail-call")]
+                WasmOpcode::ReturnCallIndirect => {
+                    let type_index = code_block.read_unsigned()? as usize;
+                    let table_index = code_block.read_unsigned()? as usize;
+                    let index = value_stack
+                        .pop()
+                        .map(|v| v.get_u32() as usize)
+                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                    let func = module
+                        .table_elem(table_index, index)
+                        .ok_or(WasmRuntimeError::NoMethod)?;
+                    // Types of different indices match if their signatures are identical
+                    if module.type_by_ref(type_index) != Some(func.func_type()) {
+                        return Err(WasmRuntimeError::TypeMismatch);
+                    }
+                    return self.prepare_tail_call(func, &mut value_stack);
                 }
-                WasmOpcode::Select => {
+
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::SelectT => {
+                    let n_types = code_block.read_unsigned()? as usize;
+                    for _ in 0..n_types {
+                        let _ = code_block.read_byte()?;
+                    }
                     let cc = value_stack
                         .pop()
                         .map(|v| v.get_bool())
@@ -199,7 +388,7 @@ impl WasmInterpreter<'_> {
                     let local_ref = code_block.read_unsigned()? as usize;
                     let val = *unsafe { locals.get_unchecked(local_ref) };
                     value_stack
-                        .push(val.into())
+                        .push(val)
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
                 WasmOpcode::LocalSet => {
@@ -247,271 +436,25 @@ impl WasmInterpreter<'_> {
                     *var = val.into_value(global.val_type());
                 }
 
-                WasmOpcode::I32Load => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u32(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I32Store => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u32(memarg.offset_by(offset), val)?;
-                }
-                WasmOpcode::I64Load => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u64(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Store => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u64(memarg.offset_by(offset), val)?;
-                }
-
-                WasmOpcode::I32Load8S => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u8(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as i8 as i32))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I32Load8U => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u8(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as u32))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I32Load16S => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u16(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as i16 as i32))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I32Load16U => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u16(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as u32))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-
-                WasmOpcode::I32Store8 => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u8())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u8(memarg.offset_by(offset), val)?;
-                }
-                WasmOpcode::I32Store16 => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u16())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u16(memarg.offset_by(offset), val)?;
-                }
-
-                WasmOpcode::I64Load8S => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u8(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as i8 as i64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Load8U => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u8(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as u64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Load16S => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u16(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as i16 as i64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Load16U => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u16(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as u64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Load32S => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u32(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as i32 as i64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                WasmOpcode::I64Load32U => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let val = memory.read_u32(memarg.offset_by(offset))?;
-                    value_stack
-                        .push(WasmStackValue::from(val as u64))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-
-                WasmOpcode::I64Store8 => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u8())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u8(memarg.offset_by(offset), val)?;
-                }
-                WasmOpcode::I64Store16 => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u16())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u16(memarg.offset_by(offset), val)?;
-                }
-                WasmOpcode::I64Store32 => {
-                    let memarg = code_block.read_memarg()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let offset = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    memory.write_u32(memarg.offset_by(offset), val)?;
-                }
-
-                WasmOpcode::MemorySize => {
-                    let _ = code_block.read_unsigned()?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::RefNull => {
+                    let _ = code_block.read_byte()?;
                     value_stack
-                        .push(WasmStackValue::from(memory.size()))
+                        .push(WasmStackValue::from_ref(None))
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
-
-                WasmOpcode::MemoryGrow => {
-                    let _ = code_block.read_unsigned()?;
-                    let val = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let memory = module.memory(0).ok_or(WasmRuntimeError::OutOfMemory)?;
-                    let result = memory.grow(val as usize);
+                #[cfg(feature = "reference-types")]
+                WasmOpcode::RefFunc => {
+                    let index = code_block.read_unsigned()? as usize;
                     value_stack
-                        .push(WasmStackValue { i32: result as i32 })
+                        .push(WasmStackValue::from_ref(Some(index)))
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
 
                 WasmOpcode::I32Const => {
                     let val = code_block.read_signed()? as i32;
                     value_stack
-                        .push(WasmStackValue { i32: val })
+                        .push(WasmStackValue::from_i32(val))
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
                 WasmOpcode::I64Const => {
@@ -521,723 +464,1709 @@ impl WasmInterpreter<'_> {
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
 
-                WasmOpcode::I32Eqz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from(last.get_i32() == 0);
-                }
-                WasmOpcode::I32Eq => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() == b);
-                }
-                WasmOpcode::I32Ne => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() != b);
-                }
-                WasmOpcode::I32LtS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() < b);
-                }
-                WasmOpcode::I32LtU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u32() < b);
-                }
-                WasmOpcode::I32LeS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() <= b);
-                }
-                WasmOpcode::I32LeU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u32() <= b);
-                }
-                WasmOpcode::I32GtS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() > b);
-                }
-                WasmOpcode::I32GtU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u32() > b);
-                }
-                WasmOpcode::I32GeS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i32() >= b);
-                }
-                WasmOpcode::I32GeU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u32() >= b);
+                #[cfg(feature = "simd")]
+                _ if matches!(opcode.proposal_type(), WasmProposalType::Simd) => {
+                    Self::run_simd(opcode, code_block, &mut value_stack, module)?;
                 }
 
-                WasmOpcode::I64Eqz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from(last.get_i64() == 0);
-                }
-                WasmOpcode::I64Eq => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() == b);
-                }
-                WasmOpcode::I64Ne => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() != b);
-                }
-                WasmOpcode::I64LtS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() < b);
-                }
-                WasmOpcode::I64LtU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u64() < b);
-                }
-                WasmOpcode::I64LeS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() <= b);
-                }
-                WasmOpcode::I64LeU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u64() <= b);
-                }
-                WasmOpcode::I64GtS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() > b);
-                }
-                WasmOpcode::I64GtU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u64() > b);
-                }
-                WasmOpcode::I64GeS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_i64() >= b);
-                }
-                WasmOpcode::I64GeU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *a = WasmStackValue::from(a.get_u64() >= b);
+                #[cfg(feature = "threads")]
+                _ if matches!(opcode.proposal_type(), WasmProposalType::Threads) => {
+                    Self::run_atomic(opcode, code_block, &mut value_stack, module)?;
                 }
 
-                WasmOpcode::I32Clz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u32(|v| v.leading_zeros());
-                }
-                WasmOpcode::I32Ctz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u32(|v| v.trailing_zeros());
-                }
-                WasmOpcode::I32Popcnt => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u32(|v| v.count_ones());
-                }
+                _ => Self::run_memory(opcode, code_block, &mut value_stack, module)?,
+            }
+        }
+        if let Some(result_type) = result_types.first() {
+            let val = value_stack
+                .pop()
+                .ok_or(WasmRuntimeError::InternalInconsistency)?;
+            Ok(val.into_value(*result_type))
+        } else {
+            Ok(WasmValue::Empty)
+        }
+    }
 
-                WasmOpcode::I32Add => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v.wrapping_add(b));
-                }
-                WasmOpcode::I32Sub => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v.wrapping_sub(b));
-                }
-                WasmOpcode::I32Mul => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v.wrapping_mul(b));
-                }
-                WasmOpcode::I32DivS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_i32(|v| v.wrapping_div(b));
-                }
-                WasmOpcode::I32DivU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_u32(|v| v.wrapping_div(b));
-                }
-                WasmOpcode::I32RemS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_i32(|v| v.wrapping_rem(b));
-                }
-                WasmOpcode::I32RemU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_u32(|v| v.wrapping_rem(b));
-                }
+    /// Executes the memory and table instructions, or the others by [Self::run_numeric]
+    ///
+    /// The instructions which do not change the control flow are apart from [Self::run]
+    /// to keep its native stack frame small, which every nested call of the guest takes.
+    fn run_memory(
+        opcode: WasmOpcode,
+        code_block: &mut WasmCodeBlock,
+        value_stack: &mut FixedStack<WasmStackValue>,
+        module: &WasmModule,
+    ) -> Result<(), WasmRuntimeError> {
+        match opcode {
+            WasmOpcode::I32Load => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u32(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I32Store => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u32(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
+            WasmOpcode::I64Load => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u64(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Store => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u64(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
 
-                WasmOpcode::I32And => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v & b);
-                }
-                WasmOpcode::I32Or => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v | b);
-                }
-                WasmOpcode::I32Xor => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v ^ b);
-                }
+            WasmOpcode::I32Load8S => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u8(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as i8 as i32))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I32Load8U => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u8(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as u32))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I32Load16S => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u16(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as i16 as i32))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I32Load16U => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u16(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as u32))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
 
-                WasmOpcode::I32Shl => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v << b);
-                }
-                WasmOpcode::I32ShrS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v >> b);
-                }
-                WasmOpcode::I32ShrU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_i32(|v| v >> b);
-                }
-                WasmOpcode::I32Rotl => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v.rotate_left(b));
-                }
-                WasmOpcode::I32Rotr => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u32())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u32(|v| v.rotate_right(b));
-                }
+            WasmOpcode::I32Store8 => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u8())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u8(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
+            WasmOpcode::I32Store16 => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u16())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u16(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
 
-                WasmOpcode::I64Clz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u64(|v| v.leading_zeros() as u64);
-                }
-                WasmOpcode::I64Ctz => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u64(|v| v.trailing_zeros() as u64);
-                }
-                WasmOpcode::I64Popcnt => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    last.map_u64(|v| v.count_ones() as u64);
-                }
+            WasmOpcode::I64Load8S => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u8(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as i8 as i64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Load8U => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u8(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as u64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Load16S => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u16(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as i16 as i64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Load16U => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u16(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as u64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Load32S => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u32(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as i32 as i64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I64Load32U => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = memory.load_u32(Self::effective_address(memory, memarg, offset)?)?;
+                value_stack
+                    .push(WasmStackValue::from(val as u64))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
 
-                WasmOpcode::I64Add => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v.wrapping_add(b));
-                }
-                WasmOpcode::I64Sub => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v.wrapping_sub(b));
-                }
-                WasmOpcode::I64Mul => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v.wrapping_mul(b));
-                }
-                WasmOpcode::I64DivS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_i64(|v| v.wrapping_div(b));
+            WasmOpcode::I64Store8 => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u8())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u8(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
+            WasmOpcode::I64Store16 => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u16())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u16(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
+            WasmOpcode::I64Store32 => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.store_u32(Self::effective_address(memory, memarg, offset)?, val)?;
+            }
+
+            WasmOpcode::MemorySize => {
+                let memidx = code_block.read_unsigned()? as usize;
+                let memory = module.memory(memidx).ok_or(WasmRuntimeError::OutOfMemory)?;
+                let size = memory.size();
+                let val = if memory.limit().is_64() {
+                    WasmStackValue::from_u64(size as u64)
+                } else {
+                    WasmStackValue::from_u32(size as u32)
+                };
+                value_stack
+                    .push(val)
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+
+            WasmOpcode::MemoryGrow => {
+                let memidx = code_block.read_unsigned()? as usize;
+                let val = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let memory = module.memory(memidx).ok_or(WasmRuntimeError::OutOfMemory)?;
+                let result = memory.grow(Self::memory_offset(memory, val));
+                // The old size, or -1 if failed
+                let val = if memory.limit().is_64() {
+                    WasmStackValue::from_i64(result as i64)
+                } else {
+                    WasmStackValue::from_i32(result as i32)
+                };
+                value_stack
+                    .push(val)
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::MemoryInit => {
+                let dataidx = code_block.read_unsigned()? as usize;
+                let memidx = code_block.read_unsigned()? as usize;
+                let memory = module.memory(memidx).ok_or(WasmRuntimeError::OutOfMemory)?;
+                let segment = module
+                    .data_segment(dataidx)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let n = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let s = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| Self::memory_offset(memory, v))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let data = segment.data().borrow();
+                let src = data
+                    .get(s..s.wrapping_add(n))
+                    .ok_or(WasmRuntimeError::OutOfBounds)?;
+                memory.write_bytes(d, src)?;
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::DataDrop => {
+                let dataidx = code_block.read_unsigned()? as usize;
+                let segment = module
+                    .data_segment(dataidx)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                segment.drop_data();
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::MemoryCopy => {
+                let dest_memory = module
+                    .memory(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let src_memory = module
+                    .memory(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let n = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let n = if dest_memory.limit().is_64() && src_memory.limit().is_64() {
+                    usize::try_from(n.get_u64()).unwrap_or(usize::MAX)
+                } else {
+                    n.get_u32() as usize
+                };
+                let s = value_stack
+                    .pop()
+                    .map(|v| Self::memory_offset(src_memory, v))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| Self::memory_offset(dest_memory, v))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                dest_memory.copy_from(d, src_memory, s, n)?;
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::MemoryFill => {
+                let memidx = code_block.read_unsigned()? as usize;
+                let memory = module.memory(memidx).ok_or(WasmRuntimeError::OutOfMemory)?;
+                let n = value_stack
+                    .pop()
+                    .map(|v| Self::memory_offset(memory, v))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_u8())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| Self::memory_offset(memory, v))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                memory.fill(d, val, n)?;
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::TableInit => {
+                let elemidx = code_block.read_unsigned()? as usize;
+                let tableidx = code_block.read_unsigned()? as usize;
+                let segment = module
+                    .elem_segment(elemidx)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let table = module
+                    .table(tableidx)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let n = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let s = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let elements = segment.elements().borrow();
+                let src = elements
+                    .get(s..s.wrapping_add(n))
+                    .ok_or(WasmRuntimeError::OutOfBounds)?;
+                table.init(d, src)?;
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::ElemDrop => {
+                let elemidx = code_block.read_unsigned()? as usize;
+                let segment = module
+                    .elem_segment(elemidx)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                segment.drop_elements();
+            }
+            #[cfg(feature = "bulk-memory")]
+            WasmOpcode::TableCopy => {
+                let dest_table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let src_table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let n = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let s = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                dest_table.copy_from(d, src_table, s, n)?;
+            }
+
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::TableGet => {
+                let table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = table
+                    .get_ref(last.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::OutOfBounds)?;
+                *last = WasmStackValue::from_ref(val);
+            }
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::TableSet => {
+                let table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_ref())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let index = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                table.set_ref(index, val)?;
+            }
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::TableSize => {
+                let table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                value_stack
+                    .push(WasmStackValue::from(table.size() as u32))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::TableGrow => {
+                let table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let delta = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let result = table.grow(delta, last.get_ref());
+                *last = WasmStackValue::from(result as i32);
+            }
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::TableFill => {
+                let table = module
+                    .table(code_block.read_unsigned()? as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let n = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_ref())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let d = value_stack
+                    .pop()
+                    .map(|v| v.get_u32() as usize)
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                table.fill(d, val, n)?;
+            }
+
+            _ => Self::run_numeric(opcode, value_stack)?,
+        }
+        Ok(())
+    }
+
+    /// Executes the numeric instruction, which takes its operands only from the value stack
+    fn run_numeric(
+        opcode: WasmOpcode,
+        value_stack: &mut FixedStack<WasmStackValue>,
+    ) -> Result<(), WasmRuntimeError> {
+        match opcode {
+            WasmOpcode::Nop => (),
+
+            WasmOpcode::Drop => {
+                let _ = value_stack.pop();
+            }
+            WasmOpcode::Select => {
+                let cc = value_stack
+                    .pop()
+                    .map(|v| v.get_bool())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let b = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let c = if cc { a } else { b };
+                value_stack
+                    .push(c)
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            WasmOpcode::I32TruncSatF32S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f32() as i32);
+            }
+            WasmOpcode::I32TruncSatF32U => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f32() as u32);
+            }
+            WasmOpcode::I32TruncSatF64S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f64() as i32);
+            }
+            WasmOpcode::I32TruncSatF64U => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f64() as u32);
+            }
+            WasmOpcode::I64TruncSatF32S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f32() as i64);
+            }
+            WasmOpcode::I64TruncSatF32U => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f32() as u64);
+            }
+            WasmOpcode::I64TruncSatF64S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f64() as i64);
+            }
+            WasmOpcode::I64TruncSatF64U => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_f64() as u64);
+            }
+
+            #[cfg(feature = "reference-types")]
+            WasmOpcode::RefIsNull => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_ref().is_none());
+            }
+            WasmOpcode::I32Eqz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_i32() == 0);
+            }
+            WasmOpcode::I32Eq => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() == b);
+            }
+            WasmOpcode::I32Ne => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() != b);
+            }
+            WasmOpcode::I32LtS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() < b);
+            }
+            WasmOpcode::I32LtU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u32() < b);
+            }
+            WasmOpcode::I32LeS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() <= b);
+            }
+            WasmOpcode::I32LeU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u32() <= b);
+            }
+            WasmOpcode::I32GtS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() > b);
+            }
+            WasmOpcode::I32GtU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u32() > b);
+            }
+            WasmOpcode::I32GeS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i32() >= b);
+            }
+            WasmOpcode::I32GeU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u32() >= b);
+            }
+
+            WasmOpcode::I64Eqz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from(last.get_i64() == 0);
+            }
+            WasmOpcode::I64Eq => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() == b);
+            }
+            WasmOpcode::I64Ne => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() != b);
+            }
+            WasmOpcode::I64LtS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() < b);
+            }
+            WasmOpcode::I64LtU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u64() < b);
+            }
+            WasmOpcode::I64LeS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() <= b);
+            }
+            WasmOpcode::I64LeU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u64() <= b);
+            }
+            WasmOpcode::I64GtS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() > b);
+            }
+            WasmOpcode::I64GtU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u64() > b);
+            }
+            WasmOpcode::I64GeS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_i64() >= b);
+            }
+            WasmOpcode::I64GeU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from(a.get_u64() >= b);
+            }
+
+            WasmOpcode::I32Clz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u32(|v| v.leading_zeros());
+            }
+            WasmOpcode::I32Ctz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u32(|v| v.trailing_zeros());
+            }
+            WasmOpcode::I32Popcnt => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u32(|v| v.count_ones());
+            }
+
+            WasmOpcode::I32Add => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v.wrapping_add(b));
+            }
+            WasmOpcode::I32Sub => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v.wrapping_sub(b));
+            }
+            WasmOpcode::I32Mul => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v.wrapping_mul(b));
+            }
+            WasmOpcode::I32DivS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64DivU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_u64(|v| v.wrapping_div(b));
+                a.map_i32(|v| v.wrapping_div(b));
+            }
+            WasmOpcode::I32DivU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64RemS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_i64(|v| v.wrapping_rem(b));
+                a.map_u32(|v| v.wrapping_div(b));
+            }
+            WasmOpcode::I32RemS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64RemU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    if b == 0 {
-                        return Err(WasmRuntimeError::DivideByZero);
-                    }
-                    a.map_u64(|v| v.wrapping_rem(b));
+                a.map_i32(|v| v.wrapping_rem(b));
+            }
+            WasmOpcode::I32RemU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
+                a.map_u32(|v| v.wrapping_rem(b));
+            }
 
-                WasmOpcode::I64And => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v & b);
-                }
-                WasmOpcode::I64Or => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v | b);
-                }
-                WasmOpcode::I64Xor => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v ^ b);
-                }
+            WasmOpcode::I32And => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v & b);
+            }
+            WasmOpcode::I32Or => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v | b);
+            }
+            WasmOpcode::I32Xor => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v ^ b);
+            }
 
-                WasmOpcode::I64Shl => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v << b);
-                }
-                WasmOpcode::I64ShrS => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_i64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_i64(|v| v >> b);
+            WasmOpcode::I32Shl => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v << b);
+            }
+            WasmOpcode::I32ShrS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v >> b);
+            }
+            WasmOpcode::I32ShrU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_i32(|v| v >> b);
+            }
+            WasmOpcode::I32Rotl => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v.rotate_left(b));
+            }
+            WasmOpcode::I32Rotr => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u32(|v| v.rotate_right(b));
+            }
+
+            WasmOpcode::I64Clz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u64(|v| v.leading_zeros() as u64);
+            }
+            WasmOpcode::I64Ctz => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u64(|v| v.trailing_zeros() as u64);
+            }
+            WasmOpcode::I64Popcnt => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                last.map_u64(|v| v.count_ones() as u64);
+            }
+
+            WasmOpcode::I64Add => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v.wrapping_add(b));
+            }
+            WasmOpcode::I64Sub => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v.wrapping_sub(b));
+            }
+            WasmOpcode::I64Mul => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v.wrapping_mul(b));
+            }
+            WasmOpcode::I64DivS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64ShrU => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v >> b);
+                a.map_i64(|v| v.wrapping_div(b));
+            }
+            WasmOpcode::I64DivU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64Rotl => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v.rotate_left(b as u32));
+                a.map_u64(|v| v.wrapping_div(b));
+            }
+            WasmOpcode::I64RemS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
-                WasmOpcode::I64Rotr => {
-                    let b = value_stack
-                        .pop()
-                        .map(|v| v.get_u64())
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    let a = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    a.map_u64(|v| v.rotate_right(b as u32));
+                a.map_i64(|v| v.wrapping_rem(b));
+            }
+            WasmOpcode::I64RemU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                if b == 0 {
+                    return Err(WasmRuntimeError::DivideByZero);
                 }
+                a.map_u64(|v| v.wrapping_rem(b));
+            }
 
-                WasmOpcode::I32WrapI64 => {
-                    // NOP
-                }
-                WasmOpcode::I64ExtendI32S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i64(last.get_i32() as i64);
-                }
-                WasmOpcode::I64ExtendI32U => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_u64(last.get_u32() as u64);
-                }
+            WasmOpcode::I64And => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v & b);
+            }
+            WasmOpcode::I64Or => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v | b);
+            }
+            WasmOpcode::I64Xor => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v ^ b);
+            }
+
+            WasmOpcode::I64Shl => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v << b);
+            }
+            WasmOpcode::I64ShrS => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_i64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_i64(|v| v >> b);
+            }
+            WasmOpcode::I64ShrU => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v >> b);
+            }
+            WasmOpcode::I64Rotl => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v.rotate_left(b as u32));
+            }
+            WasmOpcode::I64Rotr => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_u64())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                a.map_u64(|v| v.rotate_right(b as u32));
+            }
+
+            WasmOpcode::I32WrapI64 => {
+                // NOP
+            }
+            WasmOpcode::I64ExtendI32S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i64(last.get_i32() as i64);
+            }
+            WasmOpcode::I64ExtendI32U => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_u64(last.get_u32() as u64);
+            }
+
+            #[cfg(feature = "sign-ext")]
+            WasmOpcode::I32Extend8S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i32((last.get_i32() as i8) as i32);
+            }
+            #[cfg(feature = "sign-ext")]
+            WasmOpcode::I32Extend16S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i32((last.get_i32() as i16) as i32);
+            }
 
-                WasmOpcode::I32Extend8S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i32((last.get_i32() as i8) as i32);
-                }
-                WasmOpcode::I32Extend16S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i32((last.get_i32() as i16) as i32);
-                }
+            #[cfg(feature = "sign-ext")]
+            WasmOpcode::I64Extend8S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i64((last.get_i64() as i8) as i64);
+            }
+            #[cfg(feature = "sign-ext")]
+            WasmOpcode::I64Extend16S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i64((last.get_i64() as i16) as i64);
+            }
+            #[cfg(feature = "sign-ext")]
+            WasmOpcode::I64Extend32S => {
+                let last = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *last = WasmStackValue::from_i64((last.get_i64() as i32) as i64);
+            }
 
-                WasmOpcode::I64Extend8S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i64((last.get_i64() as i8) as i64);
-                }
-                WasmOpcode::I64Extend16S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i64((last.get_i64() as i16) as i64);
-                }
-                WasmOpcode::I64Extend32S => {
-                    let last = value_stack
-                        .last_mut()
-                        .ok_or(WasmRuntimeError::InternalInconsistency)?;
-                    *last = WasmStackValue::from_i64((last.get_i64() as i32) as i64);
-                }
+            _ => return Err(WasmRuntimeError::InvalidBytecode),
+        }
+        Ok(())
+    }
 
-                _ => return Err(WasmRuntimeError::InvalidBytecode),
+    /// Executes the SIMD instruction
+    #[cfg(feature = "simd")]
+    fn run_simd(
+        opcode: WasmOpcode,
+        code_block: &mut WasmCodeBlock,
+        value_stack: &mut FixedStack<WasmStackValue>,
+        module: &WasmModule,
+    ) -> Result<(), WasmRuntimeError> {
+        let signature =
+            SimdSignature::from_opcode(opcode).ok_or(WasmRuntimeError::InvalidBytecode)?;
+        match signature {
+            SimdSignature::Const => {
+                let val = LE::read_u128(code_block.get_bytes(16)?);
+                value_stack
+                    .push(WasmStackValue::from_v128(val))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            SimdSignature::Unary => {
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::unary(opcode, a.get_v128())?);
+            }
+            SimdSignature::Binary => {
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::binary(opcode, a.get_v128(), b)?);
+            }
+            SimdSignature::Ternary => {
+                let c = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::bitselect(a.get_v128(), b, c));
+            }
+            SimdSignature::Test => {
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_i32(simd::test(opcode, a.get_v128())?);
+            }
+            SimdSignature::Shift => {
+                let count = value_stack
+                    .pop()
+                    .map(|v| v.get_u32())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::shift(opcode, a.get_v128(), count)?);
+            }
+            SimdSignature::Shuffle => {
+                let lanes = code_block.get_bytes(16)?;
+                let b = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::shuffle(a.get_v128(), b, lanes));
+            }
+            SimdSignature::Splat(val_type) => {
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::splat(opcode, a.get_bits(val_type)));
+            }
+            SimdSignature::ExtractLane(val_type, _) => {
+                let lane = code_block.read_byte()?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let raw = simd::extract_lane(opcode, a.get_v128(), lane);
+                *a = WasmStackValue::from_bits(raw, val_type);
+            }
+            SimdSignature::ReplaceLane(val_type, _) => {
+                let lane = code_block.read_byte()?;
+                let raw = value_stack
+                    .pop()
+                    .map(|v| v.get_bits(val_type))
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let a = value_stack
+                    .last_mut()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                *a = WasmStackValue::from_v128(simd::replace_lane(opcode, a.get_v128(), lane, raw));
+            }
+            SimdSignature::Load => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = Self::effective_address(memory, memarg, offset)?;
+                let val = simd::load(opcode, memory, offset)?;
+                value_stack
+                    .push(WasmStackValue::from_v128(val))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            SimdSignature::Store => {
+                let memarg = code_block.read_memarg()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = Self::effective_address(memory, memarg, offset)?;
+                memory.write_bytes(offset, &val.to_le_bytes())?;
+            }
+            SimdSignature::LoadLane(_) => {
+                let memarg = code_block.read_memarg()?;
+                let lane = code_block.read_byte()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = Self::effective_address(memory, memarg, offset)?;
+                let val = simd::load_lane(opcode, memory, offset, val, lane)?;
+                value_stack
+                    .push(WasmStackValue::from_v128(val))
+                    .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+            }
+            SimdSignature::StoreLane(_) => {
+                let memarg = code_block.read_memarg()?;
+                let lane = code_block.read_byte()?;
+                let memory = module
+                    .memory(memarg.memidx())
+                    .ok_or(WasmRuntimeError::OutOfMemory)?;
+                let val = value_stack
+                    .pop()
+                    .map(|v| v.get_v128())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = value_stack
+                    .pop()
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                let offset = Self::effective_address(memory, memarg, offset)?;
+                simd::store_lane(opcode, memory, offset, val, lane)?;
             }
         }
-        if let Some(result_type) = result_types.first() {
-            let val = value_stack
+        Ok(())
+    }
+
+    /// Executes the atomic instruction
+    #[cfg(feature = "threads")]
+    fn run_atomic(
+        opcode: WasmOpcode,
+        code_block: &mut WasmCodeBlock,
+        value_stack: &mut FixedStack<WasmStackValue>,
+        module: &WasmModule,
+    ) -> Result<(), WasmRuntimeError> {
+        let instruction =
+            AtomicInstruction::from_opcode(opcode).ok_or(WasmRuntimeError::InvalidBytecode)?;
+        if instruction == AtomicInstruction::Fence {
+            code_block.read_byte()?;
+            core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            return Ok(());
+        }
+        let memarg = code_block.read_memarg()?;
+        let memory = module
+            .memory(memarg.memidx())
+            .ok_or(WasmRuntimeError::OutOfMemory)?;
+        // Operands following the address
+        let n_params = match instruction {
+            AtomicInstruction::Load(_, _) | AtomicInstruction::Fence => 0,
+            AtomicInstruction::Notify
+            | AtomicInstruction::Store(_, _)
+            | AtomicInstruction::Rmw(_, _, _) => 1,
+            AtomicInstruction::Wait(_) | AtomicInstruction::Cmpxchg(_, _) => 2,
+        };
+        let mut params = [WasmStackValue::zero(); 2];
+        for param in params[..n_params].iter_mut().rev() {
+            *param = value_stack
                 .pop()
                 .ok_or(WasmRuntimeError::InternalInconsistency)?;
-            match result_type {
-                WasmValType::I32 => Ok(WasmValue::I32(val.get_i32())),
-                WasmValType::I64 => Ok(WasmValue::I64(val.get_i64())),
-                // WasmValType::F32 => {}
-                // WasmValType::F64 => {}
-                _ => Err(WasmRuntimeError::InvalidParameter),
+        }
+        let offset = value_stack
+            .pop()
+            .ok_or(WasmRuntimeError::InternalInconsistency)?;
+        let offset = Self::effective_address(memory, memarg, offset)?;
+        let result = match instruction {
+            AtomicInstruction::Notify => {
+                let count = memory.atomic_notify(offset, params[0].get_u32())?;
+                Some(WasmStackValue::from_u32(count))
+            }
+            AtomicInstruction::Wait(val_type) => {
+                let size = if val_type == WasmValType::I32 { 4 } else { 8 };
+                let expected = params[0].get_bits(val_type);
+                let result = memory.atomic_wait(offset, size, expected, params[1].get_i64())?;
+                Some(WasmStackValue::from_u32(result as u32))
+            }
+            AtomicInstruction::Fence => None,
+            AtomicInstruction::Load(val_type, size) => {
+                let val = memory.atomic_load(offset, size)?;
+                Some(WasmStackValue::from_bits(val, val_type))
+            }
+            AtomicInstruction::Store(val_type, size) => {
+                memory.atomic_store(offset, size, params[0].get_bits(val_type))?;
+                None
+            }
+            AtomicInstruction::Rmw(val_type, size, op) => {
+                let val = memory.atomic_rmw(offset, size, op, params[0].get_bits(val_type))?;
+                Some(WasmStackValue::from_bits(val, val_type))
+            }
+            AtomicInstruction::Cmpxchg(val_type, size) => {
+                let val = memory.atomic_cmpxchg(
+                    offset,
+                    size,
+                    params[0].get_bits(val_type),
+                    params[1].get_bits(val_type),
+                )?;
+                Some(WasmStackValue::from_bits(val, val_type))
+            }
+        };
+        if let Some(result) = result {
+            value_stack
+                .push(result)
+                .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+        }
+        Ok(())
+    }
+
+    /// Looks for the handler of the exception being thrown in the try_table blocks of the current frame
+    ///
+    /// Returns whether the handler leaves the function, otherwise execution resumes at its label.
+    /// The exception goes on to the caller unless handled, and other errors pass through.
+    #[cfg(feature = "exceptions")]
+    fn handle_exception(
+        err: WasmRuntimeError,
+        module: &WasmModule,
+        code_block: &mut WasmCodeBlock,
+        block_stack: &mut FixedStack<usize>,
+        value_stack: &mut FixedStack<WasmStackValue>,
+    ) -> Result<bool, WasmRuntimeError> {
+        if !matches!(err, WasmRuntimeError::Exception) {
+            return Err(err);
+        }
+        let tag = module
+            .pending_tag()
+            .ok_or(WasmRuntimeError::InternalInconsistency)?;
+        while let Some(position) = block_stack.pop() {
+            let block = *code_block
+                .info()
+                .block_info(position)
+                .ok_or(WasmRuntimeError::InternalInconsistency)?;
+            if block.inst_type != BlockInstType::TryTable {
+                continue;
+            }
+            code_block.set_position(position);
+            let _ = code_block.read_opcode()?;
+            let _ = code_block.read_signed()?;
+            let n_catches = code_block.read_unsigned()?;
+            for _ in 0..n_catches {
+                let catch = code_block.read_catch()?;
+                if !catch.matches(tag) {
+                    continue;
+                }
+                let (exception, exnref) = module
+                    .catch(catch.is_ref())
+                    .ok_or(WasmRuntimeError::InternalInconsistency)?;
+                value_stack.resize(block.stack_level, WasmStackValue::zero());
+                if let WasmCatch::Catch(_, _) | WasmCatch::CatchRef(_, _) = catch {
+                    for value in exception.values() {
+                        value_stack
+                            .push(WasmStackValue::from(*value))
+                            .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+                    }
+                }
+                if let Some(exnref) = exnref {
+                    value_stack
+                        .push(WasmStackValue::from_ref(Some(exnref)))
+                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+                }
+                if catch.label() == block_stack.len() {
+                    return Ok(true);
+                }
+                Self::branch(catch.label(), block_stack, value_stack, code_block)?;
+                return Ok(false);
             }
-        } else {
-            Ok(WasmValue::Empty)
         }
+        Err(err)
+    }
+
+    /// Without the exceptions feature, no guest code can catch the exception
+    #[cfg(not(feature = "exceptions"))]
+    #[inline]
+    fn handle_exception(
+        err: WasmRuntimeError,
+        _module: &WasmModule,
+        _code_block: &mut WasmCodeBlock,
+        _block_stack: &mut FixedStack<usize>,
+        _value_stack: &mut FixedStack<WasmStackValue>,
+    ) -> Result<bool, WasmRuntimeError> {
+        Err(err)
     }
 
     fn call(
         &mut self,
-        func: &WasmFunction,
+        func: &'a WasmFunction,
         value_stack: &mut FixedStack<WasmStackValue>,
         stack: &mut SharedStack,
     ) -> Result<(), WasmRuntimeError> {
-        let module = self.module;
-        let result_types = func.result_types();
-
         let param_len = func.param_types().len();
         if value_stack.len() < param_len {
             return Err(WasmRuntimeError::InternalInconsistency);
         }
 
+        let new_stack_len = value_stack.len() - param_len;
+        let params = &value_stack.as_slice()[new_stack_len..];
+        let result = self.call_function(func, params, stack)?;
+        value_stack.resize(new_stack_len, WasmStackValue::from_usize(0));
+        if !result.is_empty() {
+            value_stack
+                .push(WasmStackValue::from(result))
+                .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+        }
+        Ok(())
+    }
+
+    /// Calls the function, and then the functions tail-called from it in the same frame
+    fn call_function(
+        &mut self,
+        func: &'a WasmFunction,
+        params: &[WasmStackValue],
+        stack: &mut SharedStack,
+    ) -> Result<WasmValue, WasmRuntimeError> {
+        let mut func = func;
+        let mut args: Option<Vec<WasmStackValue>> = None;
+        loop {
+            let params = args.as_deref().unwrap_or(params);
+            let result = self.call_frame(func, params, stack)?;
+            match self.tail_call.take() {
+                Some(next) => {
+                    // Swap the buffers, so that the parameters survive the next tail call
+                    let next_args = core::mem::take(&mut self.tail_args);
+                    if let Some(mut prev) = args.replace(next_args) {
+                        prev.clear();
+                        self.tail_args = prev;
+                    }
+                    func = next;
+                }
+                None => return Ok(result),
+            }
+        }
+    }
+
+    fn call_frame(
+        &mut self,
+        func: &'a WasmFunction,
+        params: &[WasmStackValue],
+        stack: &mut SharedStack,
+    ) -> Result<WasmValue, WasmRuntimeError> {
+        let module = self.module;
+        let result_types = func.result_types();
+
         if let Some(body) = func.body() {
-            stack.snapshot(|stack| {
-                let mut locals = stack.alloc_stack(param_len + body.local_types().len());
-                let value_stack_len = value_stack.len();
-                let new_stack_len = value_stack_len - param_len;
-                let params = &value_stack.as_slice()[new_stack_len..];
+            if self.depth >= module.max_call_depth() {
+                return Err(WasmRuntimeError::StackOverflow);
+            }
+            self.depth += 1;
+            let result = stack.snapshot(|stack| {
+                let mut locals = stack.alloc_stack(params.len() + body.local_types().len());
                 for param in params {
                     locals
                         .push(*param)
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
-                for _ in body.local_types() {
+                for val_type in body.local_types() {
                     locals
-                        .push(WasmStackValue::zero())
+                        .push(WasmStackValue::default_for(*val_type))
                         .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
                 }
-                value_stack.resize(new_stack_len, WasmStackValue::from_usize(0));
 
                 let cb = body.code_block();
                 let cb_ref = cb.borrow();
                 let slice = cb_ref.as_slice();
                 let mut code_block = WasmCodeBlock::from_slice(slice, body.block_info());
-                let result =
-                    self.run(&mut code_block, locals.as_mut_slice(), result_types, stack)?;
-                if !result.is_empty() {
-                    value_stack
-                        .push(WasmStackValue::from(result))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                }
-                Ok(())
-            })
+                self.run(&mut code_block, locals.as_mut_slice(), result_types, stack)
+            });
+            self.depth -= 1;
+            result
         } else if let Some(dlink) = func.dlink() {
-            let mut locals = Vec::with_capacity(param_len);
-            let new_stack_len = value_stack.len() - param_len;
-            let params = &value_stack.as_slice()[new_stack_len..];
+            let mut locals = Vec::with_capacity(params.len());
             for (index, val_type) in func.param_types().iter().enumerate() {
                 locals.push(params[index].get_by_type(*val_type));
             }
-            value_stack.resize(new_stack_len, WasmStackValue::from_usize(0));
 
-            let result = dlink(module, &locals)?;
-            if let Some(t) = result_types.first() {
-                if result.is_valid_type(*t) {
-                    value_stack
-                        .push(WasmStackValue::from(result))
-                        .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
-                } else {
-                    return Err(WasmRuntimeError::TypeMismatch);
-                }
+            let result = WasmCallContext::enter(stack, self.depth, |context| {
+                dlink(&WasmCaller::new(module, Some(context)), &locals)
+            })?;
+            match result_types.first() {
+                Some(t) if result.is_valid_type(*t) => Ok(result),
+                Some(_) => Err(WasmRuntimeError::TypeMismatch),
+                None => Ok(WasmValue::Empty),
             }
-            Ok(())
         } else {
-            return Err(WasmRuntimeError::NoMethod);
+            Err(WasmRuntimeError::NoMethod)
+        }
+    }
+
+    /// Moves the parameters of return_call to the interpreter, then the caller of the current frame calls it
+    #[cfg(feature = "tail-call")]
+    fn prepare_tail_call(
+        &mut self,
+        func: &'a WasmFunction,
+        value_stack: &mut FixedStack<WasmStackValue>,
+    ) -> Result<WasmValue, WasmRuntimeError> {
+        let param_len = func.param_types().len();
+        if value_stack.len() < param_len {
+            return Err(WasmRuntimeError::InternalInconsistency);
         }
+        let new_stack_len = value_stack.len() - param_len;
+        self.tail_args.clear();
+        self.tail_args
+            .extend_from_slice(&value_stack.as_slice()[new_stack_len..]);
+        self.tail_call = Some(func);
+        Ok(WasmValue::Empty)
     }
 
     fn branch(
@@ -1284,13 +2213,22 @@ pub union WasmStackValue {
     f64: f64,
     usize: usize,
     isize: isize,
+    /// v128 in little endian, as an array to keep the alignment of the stack
+    #[cfg(feature = "simd")]
+    v128: [u8; 16],
 }
 
 impl WasmStackValue {
+    #[cfg(not(feature = "simd"))]
     pub const fn zero() -> Self {
         Self { u64: 0 }
     }
 
+    #[cfg(feature = "simd")]
+    pub const fn zero() -> Self {
+        Self { v128: [0; 16] }
+    }
+
     #[inline]
     pub const fn from_bool(v: bool) -> Self {
         if v {
@@ -1310,14 +2248,16 @@ impl WasmStackValue {
         Self { isize: v }
     }
 
+    /// Sign-extends to 64 bits, so that the raw bits of the stack are always initialized
     #[inline]
     pub const fn from_i32(v: i32) -> Self {
-        Self { i32: v }
+        Self { i64: v as i64 }
     }
 
+    /// Zero-extends to 64 bits, so that the raw bits of the stack are always initialized
     #[inline]
     pub const fn from_u32(v: u32) -> Self {
-        Self { u32: v }
+        Self { u64: v as u64 }
     }
 
     #[inline]
@@ -1330,6 +2270,42 @@ impl WasmStackValue {
         Self { u64: v }
     }
 
+    #[inline]
+    #[cfg(feature = "simd")]
+    pub const fn from_v128(v: u128) -> Self {
+        Self {
+            v128: v.to_le_bytes(),
+        }
+    }
+
+    /// Makes the scalar of the type from its raw bits
+    #[inline]
+    pub const fn from_bits(v: u64, val_type: WasmValType) -> Self {
+        match val_type {
+            WasmValType::I32 | WasmValType::F32 => Self::from_u32(v as u32),
+            _ => Self::from_u64(v),
+        }
+    }
+
+    #[inline]
+    pub const fn from_ref(v: Option<usize>) -> Self {
+        match v {
+            Some(v) => Self { usize: v },
+            None => Self { usize: usize::MAX },
+        }
+    }
+
+    /// Returns the initial value of the local variable of the type
+    #[inline]
+    pub const fn default_for(val_type: WasmValType) -> Self {
+        match val_type {
+            WasmValType::FuncRef | WasmValType::ExternRef | WasmValType::ExnRef => {
+                Self::from_ref(None)
+            }
+            _ => Self::zero(),
+        }
+    }
+
     #[inline]
     pub fn get_bool(&self) -> bool {
         unsafe { self.i32 != 0 }
@@ -1381,7 +2357,7 @@ impl WasmStackValue {
         F: FnOnce(i32) -> i32,
     {
         let val = unsafe { self.i32 };
-        self.i32 = f(val);
+        *self = Self::from_i32(f(val));
     }
 
     #[inline]
@@ -1390,7 +2366,7 @@ impl WasmStackValue {
         F: FnOnce(u32) -> u32,
     {
         let val = unsafe { self.u32 };
-        self.u32 = f(val);
+        *self = Self::from_u32(f(val));
     }
 
     #[inline]
@@ -1411,25 +2387,50 @@ impl WasmStackValue {
         self.u64 = f(val);
     }
 
-    pub fn get_by_type(&self, val_type: WasmValType) -> WasmValue {
+    #[inline]
+    #[cfg(feature = "simd")]
+    pub fn get_v128(&self) -> u128 {
+        u128::from_le_bytes(unsafe { self.v128 })
+    }
+
+    /// Returns the raw bits of the scalar of the type
+    #[inline]
+    pub fn get_bits(&self, val_type: WasmValType) -> u64 {
         match val_type {
-            WasmValType::I32 => WasmValue::I32(self.get_i32()),
-            WasmValType::I64 => WasmValue::I64(self.get_i64()),
-            // WasmValType::F32 => {}
-            // WasmValType::F64 => {}
-            _ => todo!(),
+            WasmValType::I32 | WasmValType::F32 => self.get_u32() as u64,
+            _ => self.get_u64(),
         }
     }
 
-    pub fn into_value(&self, val_type: WasmValType) -> WasmValue {
+    /// Returns the reference, `usize::MAX` represents null
+    #[inline]
+    pub fn get_ref(&self) -> Option<usize> {
+        match unsafe { self.usize } {
+            usize::MAX => None,
+            v => Some(v),
+        }
+    }
+
+    pub fn get_by_type(&self, val_type: WasmValType) -> WasmValue {
         match val_type {
             WasmValType::I32 => WasmValue::I32(self.get_i32()),
             WasmValType::I64 => WasmValue::I64(self.get_i64()),
-            // WasmValType::F32 => {}
-            // WasmValType::F64 => {}
-            _ => todo!(),
+            WasmValType::F32 => WasmValue::F32(self.get_f32()),
+            WasmValType::F64 => WasmValue::F64(self.get_f64()),
+            #[cfg(feature = "simd")]
+            WasmValType::V128 => WasmValue::V128(self.get_v128()),
+            // v128 never reaches the stack without the simd feature
+            #[cfg(not(feature = "simd"))]
+            WasmValType::V128 => WasmValue::V128(0),
+            WasmValType::FuncRef | WasmValType::ExternRef | WasmValType::ExnRef => {
+                WasmValue::from_ref(val_type, self.get_ref())
+            }
         }
     }
+
+    pub fn into_value(&self, val_type: WasmValType) -> WasmValue {
+        self.get_by_type(val_type)
+    }
 }
 
 impl From<bool> for WasmStackValue {
@@ -1474,11 +2475,81 @@ impl From<WasmValue> for WasmStackValue {
             WasmValue::Empty => Self::from_i64(0),
             WasmValue::I32(v) => Self::from_i64(v as i64),
             WasmValue::I64(v) => Self::from_i64(v),
-            _ => todo!(),
+            WasmValue::F32(v) => Self::from_u64(v.to_bits() as u64),
+            WasmValue::F64(v) => Self::from_u64(v.to_bits()),
+            #[cfg(feature = "simd")]
+            WasmValue::V128(v) => Self::from_v128(v),
+            #[cfg(not(feature = "simd"))]
+            WasmValue::V128(_) => Self::zero(),
+            WasmValue::FuncRef(v) => Self::from_ref(v),
+            WasmValue::ExternRef(v) => Self::from_ref(v.map(|v| v.handle())),
+            WasmValue::ExnRef(v) => Self::from_ref(v),
         }
     }
 }
 
+/// State of the guest function about to execute the instruction, passed to [WasmDebugHook]
+pub struct WasmFrame<'a> {
+    module: &'a WasmModule,
+    func_index: usize,
+    position: usize,
+    opcode: WasmOpcode,
+    depth: usize,
+    locals: &'a [WasmStackValue],
+    value_stack: &'a [WasmStackValue],
+}
+
+impl WasmFrame<'_> {
+    #[inline]
+    pub const fn module(&self) -> &WasmModule {
+        self.module
+    }
+
+    #[inline]
+    pub const fn func_index(&self) -> usize {
+        self.func_index
+    }
+
+    /// Offset of the instruction in the code of the function, after the declarations of the locals
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    #[inline]
+    pub const fn opcode(&self) -> WasmOpcode {
+        self.opcode
+    }
+
+    /// Number of the nested frames of the guest functions, 1 for the outermost one
+    #[inline]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Parameters and locals of the function
+    pub fn locals(&self) -> Vec<WasmValue> {
+        let function = match self.module.functions().get(self.func_index) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+        let local_types = function.body().map(|v| v.local_types()).unwrap_or_default();
+        function
+            .param_types()
+            .iter()
+            .chain(local_types)
+            .zip(self.locals)
+            .map(|(val_type, value)| value.get_by_type(*val_type))
+            .collect()
+    }
+
+    /// Operands on the value stack from the bottom, whose types are not known at runtime
+    #[inline]
+    pub const fn value_stack(&self) -> &[WasmStackValue] {
+        self.value_stack
+    }
+}
+
 /// Fixed Size Stack
 pub struct FixedStack<'a, T> {
     slice: &'a mut [T],
@@ -1498,6 +2569,10 @@ impl<T> FixedStack<'_, T> {
     pub const fn len(&self) -> usize {
         self.stack_pointer
     }
+
+    pub const fn is_empty(&self) -> bool {
+        self.stack_pointer == 0
+    }
 }
 
 impl<T: Sized + Copy + Clone> FixedStack<'_, T> {
@@ -1529,6 +2604,7 @@ impl<T: Sized + Copy + Clone> FixedStack<'_, T> {
         }
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn push(&mut self, data: T) -> Result<(), ()> {
         if self.stack_pointer < self.slice.len() {
             self.slice
@@ -1544,9 +2620,8 @@ impl<T: Sized + Copy + Clone> FixedStack<'_, T> {
     pub fn pop(&mut self) -> Option<T> {
         if self.stack_pointer > 0 {
             let new_sp = self.stack_pointer - 1;
-            self.slice.get(new_sp).map(|v| *v).map(|v| {
+            self.slice.get(new_sp).copied().inspect(|_v| {
                 self.stack_pointer = new_sp;
-                v
             })
         } else {
             None
@@ -1565,25 +2640,44 @@ impl<T: Sized + Copy + Clone> FixedStack<'_, T> {
 }
 
 /// Shared Stack
+///
+/// The stack consists of chunks which are never reallocated,
+/// so that the slices allocated from it remain valid while the stack grows.
 pub struct SharedStack {
-    vec: UnsafeCell<Vec<u8>>,
+    chunks: Vec<Box<[StackUnit]>>,
+    /// Index of the current chunk
+    chunk_index: usize,
+    /// Offset in bytes in the current chunk
     stack_pointer: usize,
+    /// Size in bytes of each chunk
+    chunk_size: usize,
+}
+
+/// The unit of the allocation, which also determines the alignment
+type StackUnit = u128;
+
+impl Default for SharedStack {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SharedStack {
+    const DEFAULT_CHUNK_SIZE: usize = 0x10000;
+
     #[inline]
     pub const fn new() -> Self {
-        Self {
-            vec: UnsafeCell::new(Vec::new()),
-            stack_pointer: 0,
-        }
+        Self::with_capacity(Self::DEFAULT_CHUNK_SIZE)
     }
 
+    /// Creates a stack which allocates the chunks of the specified size in bytes
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub const fn with_capacity(capacity: usize) -> Self {
         Self {
-            vec: UnsafeCell::new(Vec::with_capacity(capacity)),
+            chunks: Vec::new(),
+            chunk_index: 0,
             stack_pointer: 0,
+            chunk_size: capacity,
         }
     }
 
@@ -1592,22 +2686,13 @@ impl SharedStack {
     where
         F: FnOnce(&mut Self) -> R,
     {
-        let Self { vec, stack_pointer } = self;
-
-        let vec = unsafe {
-            let dummy = Vec::new();
-            vec.get().replace(dummy)
-        };
+        let chunk_index = self.chunk_index;
+        let stack_pointer = self.stack_pointer;
 
-        let mut child = Self {
-            vec: UnsafeCell::new(vec),
-            stack_pointer: *stack_pointer,
-        };
-        let r = f(&mut child);
+        let r = f(self);
 
-        unsafe {
-            self.vec.get().replace(child.vec.into_inner());
-        }
+        self.chunk_index = chunk_index;
+        self.stack_pointer = stack_pointer;
 
         r
     }
@@ -1616,20 +2701,38 @@ impl SharedStack {
     where
         T: Sized + Copy + Clone,
     {
-        const PADDING: usize = 16;
-        let item_size = align_of::<T>();
+        const PADDING: usize = size_of::<StackUnit>();
+        assert!(align_of::<T>() <= align_of::<StackUnit>());
+        let item_size = size_of::<T>();
         let vec_size = item_size * size;
         let succ = (vec_size + PADDING - 1) & !(PADDING - 1);
-        let new_size = self.stack_pointer + succ;
 
-        if self.vec.get_mut().len() < new_size {
-            self.vec.get_mut().resize(new_size, 0);
+        let is_fit = self
+            .chunks
+            .get(self.chunk_index)
+            .map(|chunk| self.stack_pointer + succ <= chunk.len() * PADDING)
+            .unwrap_or(false);
+        if !is_fit {
+            // The chunks above the current one are no longer in use
+            if !self.chunks.is_empty() {
+                self.chunk_index += 1;
+            }
+            self.stack_pointer = 0;
+            let len = usize::max(self.chunk_size, succ) / PADDING;
+            match self.chunks.get(self.chunk_index) {
+                Some(chunk) if chunk.len() >= len => (),
+                _ => {
+                    self.chunks.truncate(self.chunk_index);
+                    self.chunks.push(alloc::vec![0; len].into_boxed_slice());
+                }
+            }
         }
 
-        let raw = self.vec.get_mut()[self.stack_pointer..new_size].as_mut();
-        let slice = unsafe { transmute(raw) };
+        let chunk = &mut self.chunks[self.chunk_index];
+        let raw = unsafe { (chunk.as_mut_ptr() as *mut u8).add(self.stack_pointer) };
+        let slice = unsafe { slice::from_raw_parts_mut(raw as *mut T, size) };
 
-        self.stack_pointer = new_size;
+        self.stack_pointer += succ;
 
         slice
     }
@@ -1644,11 +2747,51 @@ impl SharedStack {
     }
 }
 
+/// The state of the interpreter shared with the guest functions called back from the host
+pub struct WasmCallContext {
+    stack: RefCell<SharedStack>,
+    depth: usize,
+}
+
+impl WasmCallContext {
+    /// Runs the function in the context of the frame, and then the frame takes back the stack
+    fn enter<F, R>(stack: &mut SharedStack, depth: usize, f: F) -> R
+    where
+        F: FnOnce(&Self) -> R,
+    {
+        let context = Self {
+            stack: RefCell::new(core::mem::take(stack)),
+            depth,
+        };
+        let r = f(&context);
+        *stack = context.stack.into_inner();
+        r
+    }
+
+    /// Invokes the function re-entrantly, on the same stack and with the current call depth
+    pub(crate) fn invoke(
+        &self,
+        module: &WasmModule,
+        code_block: &mut WasmCodeBlock,
+        locals: &[WasmValue],
+        result_types: &[WasmValType],
+    ) -> Result<WasmValue, WasmRuntimeError> {
+        let mut stack = self
+            .stack
+            .try_borrow_mut()
+            .map_err(|_| WasmRuntimeError::InternalInconsistency)?;
+        let mut interp = WasmInterpreter::new(module);
+        interp.depth = self.depth;
+        stack.snapshot(|stack| interp.invoke_with_stack(code_block, locals, result_types, stack))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{FixedStack, SharedStack, WasmInterpreter};
     use crate::wasm::{
-        Leb128Stream, WasmBlockInfo, WasmDecodeError, WasmLoader, WasmModule, WasmValType,
+        Leb128Stream, VecMemoryBackend, WasmBlockInfo, WasmCaller, WasmDecodeError, WasmFeatures,
+        WasmLoader, WasmModule, WasmRuntimeError, WasmValType, WasmValue,
     };
 
     #[test]
@@ -1663,10 +2806,91 @@ mod tests {
             assert_eq!(stack1.stack_pointer, 0);
             assert_eq!(stack1.pop(), None);
 
-            stack1.push(123).unwrap();
-            assert_eq!(stack1.stack_pointer, 1);
-        });
-        assert_eq!(pool.stack_pointer, 0);
+            stack1.push(123).unwrap();
+            assert_eq!(stack1.stack_pointer, 1);
+        });
+        assert_eq!(pool.stack_pointer, 0);
+
+        // growing the stack does not move the slices already allocated
+        let mut pool = SharedStack::with_capacity(256);
+        pool.snapshot(|stack| {
+            let mut stack1: FixedStack<u64> = stack.alloc_stack(16);
+            stack1.push(0x1234_5678_9ABC_DEF0).unwrap();
+            pool_fill(stack, 100);
+            let mut stack2: FixedStack<u64> = stack.alloc_stack(1000);
+            stack2.push(1).unwrap();
+            assert_eq!(stack1.pop(), Some(0x1234_5678_9ABC_DEF0));
+            assert_eq!(stack2.pop(), Some(1));
+        });
+        assert_eq!(pool.stack_pointer, 0);
+        assert_eq!(pool.chunk_index, 0);
+
+        fn pool_fill(stack: &mut SharedStack, count: usize) {
+            for i in 0..count {
+                let mut fill: FixedStack<u32> = stack.alloc_stack(10);
+                fill.push(i as u32).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn if_else() {
+        let slice = [0x20, 0, 0x04, 0x7F, 0x41, 1, 0x05, 0x41, 2, 0x0B, 0x0B];
+        let local_types = [WasmValType::I32];
+        let result_types = [WasmValType::I32];
+        let mut stream = Leb128Stream::from_slice(&slice);
+        let module = WasmModule::new();
+        let block_info =
+            WasmBlockInfo::analyze(0, &mut stream, &local_types, &result_types, &module).unwrap();
+        let mut code_block = super::WasmCodeBlock::from_slice(&slice, &block_info);
+        let mut interp = WasmInterpreter::new(&module);
+
+        let params = [1.into()];
+        let result = interp
+            .invoke(&mut code_block, &params, &result_types)
+            .unwrap()
+            .get_i32()
+            .unwrap();
+        assert_eq!(result, 1);
+
+        let params = [0.into()];
+        let result = interp
+            .invoke(&mut code_block, &params, &result_types)
+            .unwrap()
+            .get_i32()
+            .unwrap();
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn i32_slots() {
+        use super::WasmStackValue;
+
+        assert_eq!(WasmStackValue::from_i32(-1).get_u64(), u64::MAX);
+        assert_eq!(WasmStackValue::from_u32(u32::MAX).get_u64(), 0xFFFF_FFFF);
+        let mut value = WasmStackValue::from_u64(0x1234_5678_0000_0001);
+        value.map_i32(|v| v - 2);
+        assert_eq!(value.get_i32(), -1);
+        assert_eq!(value.get_u64(), u64::MAX);
+        let mut value = WasmStackValue::from_u64(0x1234_5678_8000_0000);
+        value.map_u32(|v| v >> 31);
+        assert_eq!(value.get_u64(), 1);
+
+        // i32.const -1, i32.const 0, i32.add, i64.extend_i32_u
+        let slice = [0x41, 0x7F, 0x41, 0, 0x6A, 0xAD, 0x0B];
+        let result_types = [WasmValType::I64];
+        let mut stream = Leb128Stream::from_slice(&slice);
+        let module = WasmModule::new();
+        let block_info =
+            WasmBlockInfo::analyze(0, &mut stream, &[], &result_types, &module).unwrap();
+        let mut code_block = super::WasmCodeBlock::from_slice(&slice, &block_info);
+        let mut interp = WasmInterpreter::new(&module);
+        let result = interp
+            .invoke(&mut code_block, &[], &result_types)
+            .unwrap()
+            .get_u64()
+            .unwrap();
+        assert_eq!(result, 0xFFFF_FFFF);
     }
 
     #[test]
@@ -1838,6 +3062,59 @@ mod tests {
         assert_eq!(result, 715827875);
     }
 
+    #[test]
+    fn trunc_sat() {
+        let slice = [0x20, 0, 0xFC, 0x00, 0x0B];
+        let local_types = [WasmValType::F32];
+        let result_types = [WasmValType::I32];
+        let mut stream = Leb128Stream::from_slice(&slice);
+        let module = WasmModule::new();
+        let block_info =
+            WasmBlockInfo::analyze(0, &mut stream, &local_types, &result_types, &module).unwrap();
+        let mut code_block = super::WasmCodeBlock::from_slice(&slice, &block_info);
+        let mut interp = WasmInterpreter::new(&module);
+
+        for (param, expected) in [
+            (1.5f32, 1),
+            (-3.9, -3),
+            (f32::NAN, 0),
+            (1e10, i32::MAX),
+            (-1e10, i32::MIN),
+        ] {
+            let result = interp
+                .invoke(&mut code_block, &[param.into()], &result_types)
+                .unwrap()
+                .get_i32()
+                .unwrap();
+            assert_eq!(result, expected);
+        }
+
+        let slice = [0x20, 0, 0xFC, 0x07, 0x0B];
+        let local_types = [WasmValType::F64];
+        let result_types = [WasmValType::I64];
+        let mut stream = Leb128Stream::from_slice(&slice);
+        let block_info =
+            WasmBlockInfo::analyze(0, &mut stream, &local_types, &result_types, &module).unwrap();
+        let mut code_block = super::WasmCodeBlock::from_slice(&slice, &block_info);
+
+        for (param, expected) in [(42.9f64, 42u64), (-1.0, 0), (f64::NAN, 0), (1e30, u64::MAX)] {
+            let result = interp
+                .invoke(&mut code_block, &[param.into()], &result_types)
+                .unwrap()
+                .get_i64()
+                .unwrap();
+            assert_eq!(result as u64, expected);
+        }
+
+        let slice = [0x20, 0, 0xFC, 0x07, 0x0B];
+        let local_types = [WasmValType::F32];
+        let mut stream = Leb128Stream::from_slice(&slice);
+        assert!(matches!(
+            WasmBlockInfo::analyze(0, &mut stream, &local_types, &result_types, &module),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+    }
+
     #[test]
     fn br_table() {
         let slice = [
@@ -1965,4 +3242,1062 @@ mod tests {
         let result = runnable.invoke(&[20.into()]).unwrap().get_i32().unwrap();
         assert_eq!(result, 6765);
     }
+
+    #[test]
+    fn memory64() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7E,
+            0x01, 0x7E, 0x03, 0x03, 0x02, 0x00, 0x00, 0x05, 0x04, 0x01, 0x05, 0x01, 0x02, 0x0A,
+            0x1B, 0x02, 0x12, 0x00, 0x20, 0x00, 0x42, 0xB4, 0x24, 0x37, 0x03, 0x08, 0x20, 0x00,
+            0x29, 0x03, 0x08, 0x3F, 0x00, 0x7C, 0x0B, 0x06, 0x00, 0x20, 0x00, 0x40, 0x00, 0x0B,
+            0x0B, 0x0A, 0x01, 0x00, 0x42, 0x20, 0x0B, 0x04, 0x61, 0x62, 0x63, 0x64,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let memory = module.memory(0).unwrap();
+        assert!(memory.limit().is_64());
+        assert_eq!(memory.read_bytes(0x20, 4).unwrap(), b"abcd");
+
+        let store_load = module.func_by_index(0).unwrap();
+        let result = store_load
+            .invoke(&[0x100i64.into()])
+            .unwrap()
+            .get_i64()
+            .unwrap();
+        assert_eq!(result, 0x1235);
+        assert_eq!(memory.read_u64(0x108).unwrap(), 0x1234);
+        assert!(matches!(
+            store_load.invoke(&[0x1_0000_0000i64.into()]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+        assert!(matches!(
+            store_load.invoke(&[(-1i64).into()]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        let grow = module.func_by_index(1).unwrap();
+        assert_eq!(grow.invoke(&[1i64.into()]).unwrap().get_i64().unwrap(), 1);
+        assert_eq!(grow.invoke(&[1i64.into()]).unwrap().get_i64().unwrap(), -1);
+        let result = store_load
+            .invoke(&[0x1_0000i64.into()])
+            .unwrap()
+            .get_i64()
+            .unwrap();
+        assert_eq!(result, 0x1236);
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7E,
+            0x01, 0x7E, 0x03, 0x02, 0x01, 0x00, 0x05, 0x04, 0x01, 0x05, 0x01, 0x02, 0x0A, 0x09,
+            0x01, 0x07, 0x00, 0x41, 0x00, 0x29, 0x03, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn multi_memory() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x05, 0x05, 0x02, 0x00, 0x01, 0x00, 0x01, 0x0A,
+            0x1B, 0x01, 0x19, 0x00, 0x20, 0x00, 0x41, 0xD5, 0x00, 0x36, 0x42, 0x01, 0x04, 0x20,
+            0x00, 0x28, 0x42, 0x01, 0x04, 0x41, 0x01, 0x40, 0x01, 0x6A, 0x3F, 0x00, 0x6A, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let runnable = module.func_by_index(0).unwrap();
+        let result = runnable.invoke(&[0x100.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0x57);
+
+        let memory0 = module.memory(0).unwrap();
+        let memory1 = module.memory(1).unwrap();
+        assert_eq!(memory0.read_u32(0x104).unwrap(), 0);
+        assert_eq!(memory1.read_u32(0x104).unwrap(), 0x55);
+        assert_eq!(memory0.size(), 1);
+        assert_eq!(memory1.size(), 2);
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x05, 0x05, 0x02, 0x00, 0x01, 0x00, 0x01, 0x0A,
+            0x0A, 0x01, 0x08, 0x00, 0x20, 0x00, 0x28, 0x42, 0x02, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "bulk-memory")]
+    fn bulk_memory() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0A, 0x02, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x00, 0x01, 0x7F, 0x03, 0x06, 0x05, 0x00, 0x01, 0x01, 0x01, 0x01,
+            0x04, 0x04, 0x01, 0x70, 0x00, 0x04, 0x05, 0x03, 0x01, 0x00, 0x01, 0x09, 0x0C, 0x02,
+            0x00, 0x41, 0x00, 0x0B, 0x01, 0x02, 0x01, 0x00, 0x02, 0x02, 0x03, 0x0C, 0x01, 0x02,
+            0x0A, 0x68, 0x05, 0x2E, 0x00, 0x20, 0x00, 0x41, 0x01, 0x41, 0x03, 0xFC, 0x08, 0x01,
+            0x00, 0x20, 0x00, 0x41, 0x10, 0x6A, 0x20, 0x00, 0x41, 0x03, 0xFC, 0x0A, 0x00, 0x00,
+            0x20, 0x00, 0x41, 0x20, 0x6A, 0x41, 0xFA, 0x00, 0x41, 0x02, 0xFC, 0x0B, 0x00, 0xFC,
+            0x09, 0x01, 0x20, 0x00, 0x28, 0x02, 0x10, 0x0B, 0x0E, 0x00, 0x41, 0x00, 0x41, 0x00,
+            0x41, 0x01, 0xFC, 0x08, 0x01, 0x00, 0x41, 0x00, 0x0B, 0x04, 0x00, 0x41, 0x16, 0x0B,
+            0x04, 0x00, 0x41, 0x21, 0x0B, 0x1E, 0x00, 0x41, 0x01, 0x41, 0x00, 0x41, 0x02, 0xFC,
+            0x0C, 0x01, 0x00, 0x41, 0x03, 0x41, 0x02, 0x41, 0x01, 0xFC, 0x0E, 0x00, 0x00, 0xFC,
+            0x0D, 0x01, 0x41, 0x03, 0x11, 0x01, 0x00, 0x0B, 0x0B, 0x0F, 0x02, 0x00, 0x41, 0x00,
+            0x0B, 0x02, 0x41, 0x42, 0x01, 0x05, 0x68, 0x65, 0x6C, 0x6C, 0x6F,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        assert_eq!(module.data_count(), Some(2));
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.read_bytes(0, 2).unwrap(), b"AB");
+
+        // memory.init, memory.copy, memory.fill and data.drop
+        let runnable = module.func_by_index(0).unwrap();
+        let result = runnable.invoke(&[0x100.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0x006C_6C65);
+        assert_eq!(memory.read_bytes(0x100, 3).unwrap(), b"ell");
+        assert_eq!(memory.read_bytes(0x120, 3).unwrap(), b"zz\0");
+        assert!(module.data_segment(1).unwrap().data().borrow().is_empty());
+
+        // memory.init from the dropped segment
+        let runnable = module.func_by_index(1).unwrap();
+        assert!(matches!(
+            runnable.invoke(&[]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        // table.init, table.copy and elem.drop
+        let runnable = module.func_by_index(4).unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 33);
+        let table = module.table(0).unwrap();
+        assert_eq!(table.get(0), Some(WasmValue::FuncRef(Some(2))));
+        assert_eq!(table.get(1), Some(WasmValue::FuncRef(Some(2))));
+        assert_eq!(table.get(2), Some(WasmValue::FuncRef(Some(3))));
+        assert_eq!(table.get(3), Some(WasmValue::FuncRef(Some(3))));
+        assert!(module
+            .elem_segment(1)
+            .unwrap()
+            .elements()
+            .borrow()
+            .is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "tail-call")]
+    fn tail_call() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0x02, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x00, 0x00, 0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x04, 0x04, 0x01,
+            0x70, 0x00, 0x01, 0x09, 0x07, 0x01, 0x00, 0x41, 0x00, 0x0B, 0x01, 0x00, 0x0A, 0x31,
+            0x03, 0x12, 0x00, 0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x01, 0x05, 0x20, 0x00, 0x41,
+            0x01, 0x6B, 0x12, 0x01, 0x0B, 0x0B, 0x12, 0x00, 0x20, 0x00, 0x45, 0x04, 0x7F, 0x41,
+            0x00, 0x05, 0x20, 0x00, 0x41, 0x01, 0x6B, 0x12, 0x00, 0x0B, 0x0B, 0x09, 0x00, 0x20,
+            0x00, 0x41, 0x00, 0x13, 0x00, 0x00, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        // is_even and is_odd calling each other
+        let is_even = module.func_by_index(0).unwrap();
+        let result = is_even
+            .invoke(&[100_000.into()])
+            .unwrap()
+            .get_i32()
+            .unwrap();
+        assert_eq!(result, 1);
+        let is_odd = module.func_by_index(1).unwrap();
+        let result = is_odd.invoke(&[100_001.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 1);
+
+        // return_call_indirect
+        let runnable = module.func_by_index(2).unwrap();
+        let result = runnable.invoke(&[10.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 1);
+        let result = runnable.invoke(&[7.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0);
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0x02, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x00, 0x00, 0x03, 0x03, 0x02, 0x00, 0x01, 0x0A, 0x1B, 0x02, 0x12,
+            0x00, 0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x01, 0x05, 0x20, 0x00, 0x41, 0x01, 0x6B,
+            0x12, 0x00, 0x0B, 0x0B, 0x06, 0x00, 0x41, 0x01, 0x12, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "tail-call")]
+    fn tail_call_validation() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0F, 0x03, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x01, 0x7F, 0x01, 0x7F, 0x60, 0x00, 0x01, 0x7F, 0x03, 0x06, 0x05,
+            0x00, 0x00, 0x00, 0x01, 0x02, 0x04, 0x04, 0x01, 0x70, 0x00, 0x01, 0x09, 0x07, 0x01,
+            0x00, 0x41, 0x00, 0x0B, 0x01, 0x00, 0x0A, 0x26, 0x05, 0x07, 0x00, 0x20, 0x00, 0x41,
+            0x01, 0x6A, 0x0B, 0x06, 0x00, 0x20, 0x00, 0x12, 0x00, 0x0B, 0x07, 0x00, 0x20, 0x00,
+            0x12, 0x00, 0x6A, 0x0B, 0x09, 0x00, 0x20, 0x00, 0x41, 0x00, 0x13, 0x01, 0x00, 0x0B,
+            0x03, 0x00, 0x00, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        // return_call leaves no values on the stack of the caller
+        let runnable = module.func_by_index(1).unwrap();
+        let result = runnable.invoke(&[1.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 2);
+
+        // the stack is polymorphic in the dead code after return_call
+        let runnable = module.func_by_index(2).unwrap();
+        let result = runnable.invoke(&[5.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 6);
+
+        // identical signatures with different type indices match
+        let runnable = module.func_by_index(3).unwrap();
+        let result = runnable.invoke(&[41.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 42);
+
+        // unreachable satisfies the result type of the function
+        let runnable = module.func_by_index(4).unwrap();
+        assert!(runnable.invoke(&[]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "reference-types")]
+    fn reference_types() {
+        use crate::wasm::WasmExternRef;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0F, 0x03, 0x60, 0x01, 0x6F,
+            0x01, 0x6F, 0x60, 0x00, 0x01, 0x7F, 0x60, 0x01, 0x7F, 0x01, 0x7F, 0x03, 0x08, 0x07,
+            0x00, 0x01, 0x02, 0x02, 0x02, 0x01, 0x01, 0x04, 0x08, 0x02, 0x70, 0x00, 0x02, 0x6F,
+            0x01, 0x01, 0x04, 0x09, 0x05, 0x01, 0x03, 0x00, 0x01, 0x03, 0x0A, 0x56, 0x07, 0x0C,
+            0x00, 0x41, 0x00, 0x20, 0x00, 0x26, 0x01, 0x41, 0x00, 0x25, 0x01, 0x0B, 0x0D, 0x00,
+            0xD0, 0x6F, 0x41, 0x02, 0xFC, 0x0F, 0x01, 0xFC, 0x10, 0x01, 0x6A, 0x0B, 0x12, 0x00,
+            0x41, 0x00, 0xD2, 0x03, 0x41, 0x02, 0xFC, 0x11, 0x00, 0x20, 0x00, 0x41, 0x01, 0x11,
+            0x02, 0x00, 0x0B, 0x07, 0x00, 0x20, 0x00, 0x20, 0x00, 0x6A, 0x0B, 0x0C, 0x00, 0xD2,
+            0x03, 0xD0, 0x70, 0x20, 0x00, 0x1C, 0x01, 0x70, 0xD1, 0x0B, 0x07, 0x01, 0x01, 0x70,
+            0x20, 0x00, 0xD1, 0x0B, 0x09, 0x00, 0xD0, 0x6F, 0x41, 0x05, 0xFC, 0x0F, 0x01, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        // externref from the host goes through table.set and table.get
+        assert_eq!(WasmExternRef::new(usize::MAX), None);
+        let handle = WasmExternRef::new(0x1234).unwrap();
+        let runnable = module.func_by_index(0).unwrap();
+        let result = runnable.invoke(&[handle.into()]).unwrap();
+        assert_eq!(result, WasmValue::ExternRef(Some(handle)));
+        let result = runnable.invoke(&[WasmValue::ExternRef(None)]).unwrap();
+        assert_eq!(result, WasmValue::ExternRef(None));
+        assert!(matches!(
+            runnable.invoke(&[1.into()]),
+            Err(WasmRuntimeError::InvalidParameter)
+        ));
+
+        // table.grow and table.size on the second table
+        let runnable = module.func_by_index(1).unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 4);
+        let table = module.table(1).unwrap();
+        assert_eq!(table.elem_type(), WasmValType::ExternRef);
+        assert_eq!(table.size(), 3);
+        assert_eq!(table.get(2), Some(WasmValue::ExternRef(None)));
+        let runnable = module.func_by_index(6).unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, -1);
+        assert_eq!(table.size(), 3);
+
+        // table.fill with ref.func, then call_indirect
+        let runnable = module.func_by_index(2).unwrap();
+        let result = runnable.invoke(&[21.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 42);
+        let table = module.table(0).unwrap();
+        assert_eq!(table.get(1), Some(WasmValue::FuncRef(Some(3))));
+        assert_eq!(table.get(2), None);
+        assert!(matches!(
+            table.set(0, WasmValue::ExternRef(None)),
+            Err(WasmRuntimeError::TypeMismatch)
+        ));
+
+        // typed select and ref.is_null
+        let runnable = module.func_by_index(4).unwrap();
+        let result = runnable.invoke(&[1.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0);
+        let result = runnable.invoke(&[0.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 1);
+
+        // locals of reference types are initialized with null
+        let runnable = module.func_by_index(5).unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 1);
+
+        // table.set of funcref to the table of externref
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x6F, 0x00, 0x01, 0x09, 0x05, 0x01, 0x03,
+            0x00, 0x01, 0x00, 0x0A, 0x0A, 0x01, 0x08, 0x00, 0x41, 0x00, 0xD2, 0x00, 0x26, 0x00,
+            0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // call_indirect through the table of externref
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x04, 0x04, 0x01, 0x6F, 0x00, 0x01, 0x0A, 0x09, 0x01, 0x07,
+            0x00, 0x41, 0x00, 0x11, 0x00, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // ref.func of the function not declared in any segment or export
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+            0x03, 0x02, 0x01, 0x00, 0x0A, 0x07, 0x01, 0x05, 0x00, 0xD2, 0x00, 0x1A, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn import_table() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0A, 0x02, 0x60, 0x00, 0x01,
+            0x7F, 0x60, 0x01, 0x7F, 0x01, 0x7F, 0x02, 0x0F, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x05,
+            0x74, 0x61, 0x62, 0x6C, 0x65, 0x01, 0x70, 0x00, 0x02, 0x03, 0x03, 0x02, 0x00, 0x01,
+            0x09, 0x07, 0x01, 0x00, 0x41, 0x01, 0x0B, 0x01, 0x00, 0x0A, 0x0E, 0x02, 0x04, 0x00,
+            0x41, 0x07, 0x0B, 0x07, 0x00, 0x20, 0x00, 0x11, 0x00, 0x00, 0x0B,
+        ];
+
+        // the elements of a table are the functions of the module, so it cannot be imported
+        let mut loader = WasmLoader::new();
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::NotSupprted)
+        ));
+        assert_eq!(loader.error_import(), Some(("env", "table")));
+
+        // but it can be decoded
+        let mut loader = WasmLoader::new();
+        loader.decode(&slice).unwrap();
+        let module = loader.into_module();
+        assert_eq!(module.table(0).unwrap().limit().min(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "mutable-globals")]
+    fn import_export_globals() {
+        use crate::wasm::WasmGlobal;
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x02, 0x1C, 0x02, 0x03, 0x65, 0x6E, 0x76, 0x07, 0x63, 0x6F, 0x75, 0x6E, 0x74,
+            0x65, 0x72, 0x03, 0x7F, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x04, 0x62, 0x61, 0x73, 0x65,
+            0x03, 0x7F, 0x00, 0x03, 0x02, 0x01, 0x00, 0x06, 0x09, 0x01, 0x7F, 0x00, 0x23, 0x01,
+            0x41, 0x01, 0x6A, 0x0B, 0x07, 0x1B, 0x03, 0x07, 0x63, 0x6F, 0x75, 0x6E, 0x74, 0x65,
+            0x72, 0x03, 0x00, 0x07, 0x64, 0x65, 0x72, 0x69, 0x76, 0x65, 0x64, 0x03, 0x02, 0x03,
+            0x69, 0x6E, 0x63, 0x00, 0x00, 0x0A, 0x10, 0x01, 0x0E, 0x00, 0x23, 0x00, 0x41, 0x01,
+            0x6A, 0x24, 0x00, 0x23, 0x00, 0x23, 0x02, 0x6A, 0x0B,
+        ];
+
+        let counter = WasmGlobal::new(WasmValType::I32, true, 5.into()).unwrap();
+        let shared = counter.clone();
+        let module = WasmLoader::instantiate_with_imports(
+            &slice,
+            &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+            &move |mod_name, name, val_type, is_mutable| match (mod_name, name) {
+                ("env", "counter") => Ok(shared.clone()),
+                ("env", "base") => WasmGlobal::new(val_type, is_mutable, 100.into())
+                    .map_err(|_| WasmDecodeError::InvalidGlobal),
+                _ => Err(WasmDecodeError::DynamicLinkError),
+            },
+            &VecMemoryBackend::allocator,
+        )
+        .unwrap();
+
+        let derived = module.global_by_name("derived").unwrap();
+        assert_eq!(derived.get(), WasmValue::I32(101));
+        assert!(module.global_by_name("inc").is_none());
+
+        // the guest writes to the global shared with the host
+        let runnable = module.func("inc").unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 107);
+        assert_eq!(counter.get(), WasmValue::I32(6));
+
+        // the host writes to the exported global
+        let exported = module.global_by_name("counter").unwrap();
+        exported.set(41.into()).unwrap();
+        let result = runnable.invoke(&[]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 143);
+        assert!(matches!(
+            exported.set(1i64.into()),
+            Err(WasmRuntimeError::TypeMismatch)
+        ));
+        assert!(matches!(
+            derived.set(1.into()),
+            Err(WasmRuntimeError::WriteProtected)
+        ));
+
+        // the mutability of the import does not match
+        assert!(matches!(
+            WasmLoader::instantiate_with_imports(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, val_type, _| WasmGlobal::new(val_type, false, 0.into())
+                    .map_err(|_| WasmDecodeError::InvalidGlobal),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::InvalidGlobal)
+        ));
+
+        // the type of the import does not match
+        assert!(matches!(
+            WasmLoader::instantiate_with_imports(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, is_mutable| WasmGlobal::new(WasmValType::I64, is_mutable, 0i64.into())
+                    .map_err(|_| WasmDecodeError::InvalidGlobal),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::InvalidGlobal)
+        ));
+
+        // the import is not resolved
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::DynamicLinkError)
+        ));
+        assert_eq!(loader.error_import(), Some(("env", "counter")));
+        assert!(module.global_by_name("base").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "simd")]
+    fn simd() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x15, 0x04, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x00, 0x01, 0x7B, 0x60, 0x02, 0x7B, 0x7D, 0x01, 0x7D, 0x60, 0x01,
+            0x7F, 0x01, 0x7E, 0x03, 0x06, 0x05, 0x00, 0x01, 0x00, 0x02, 0x03, 0x05, 0x03, 0x01,
+            0x00, 0x01, 0x07, 0x2A, 0x05, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, 0x07, 0x73, 0x68,
+            0x75, 0x66, 0x66, 0x6C, 0x65, 0x00, 0x01, 0x07, 0x62, 0x69, 0x74, 0x6D, 0x61, 0x73,
+            0x6B, 0x00, 0x02, 0x04, 0x66, 0x61, 0x64, 0x64, 0x00, 0x03, 0x05, 0x73, 0x70, 0x6C,
+            0x61, 0x74, 0x00, 0x04, 0x0A, 0x9B, 0x01, 0x05, 0x20, 0x00, 0x20, 0x00, 0x20, 0x00,
+            0xFD, 0x00, 0x04, 0x00, 0x20, 0x00, 0xFD, 0x00, 0x04, 0x10, 0xFD, 0xAE, 0x01, 0xFD,
+            0x0B, 0x04, 0x20, 0x20, 0x00, 0xFD, 0x00, 0x04, 0x20, 0xFD, 0x1B, 0x03, 0x0B, 0x3E,
+            0x01, 0x01, 0x7B, 0xFD, 0x0C, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+            0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x21, 0x00, 0x20, 0x00, 0xFD, 0x0C, 0x10,
+            0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E,
+            0x1F, 0xFD, 0x0D, 0x0F, 0x10, 0x01, 0x11, 0x02, 0x12, 0x03, 0x13, 0x04, 0x14, 0x05,
+            0x15, 0x06, 0x16, 0x07, 0x1F, 0x0B, 0x1E, 0x00, 0x20, 0x00, 0xFD, 0x11, 0xFD, 0x0C,
+            0x01, 0x00, 0x00, 0x00, 0xFE, 0xFF, 0xFF, 0xFF, 0x03, 0x00, 0x00, 0x00, 0xFC, 0xFF,
+            0xFF, 0xFF, 0xFD, 0xB5, 0x01, 0xFD, 0xA4, 0x01, 0x0B, 0x0E, 0x00, 0x20, 0x00, 0x20,
+            0x01, 0xFD, 0x13, 0xFD, 0xE4, 0x01, 0xFD, 0x1F, 0x02, 0x0B, 0x0B, 0x00, 0x20, 0x00,
+            0xFD, 0x09, 0x02, 0x00, 0xFD, 0x1D, 0x01, 0x0B, 0x0B, 0x26, 0x01, 0x00, 0x41, 0x00,
+            0x0B, 0x20, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x04, 0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x1E, 0x00,
+            0x00, 0x00, 0x28, 0x00, 0x00, 0x00,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        // v128.load, i32x4.add, v128.store and i32x4.extract_lane
+        let runnable = module.func("add").unwrap();
+        let result = runnable.invoke(&[0.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 44);
+        let memory = module.memory(0).unwrap();
+        assert_eq!(memory.read_u32(32).unwrap(), 11);
+        assert_eq!(memory.read_u32(40).unwrap(), 33);
+
+        // v128.const, v128 locals and i8x16.shuffle
+        let runnable = module.func("shuffle").unwrap();
+        let result = runnable.invoke(&[]).unwrap();
+        assert_eq!(
+            result,
+            WasmValue::V128(0x1F07_1606_1505_1404_1303_1202_1101_100F)
+        );
+
+        // i32x4.splat, i32x4.mul and i32x4.bitmask
+        let runnable = module.func("bitmask").unwrap();
+        let result = runnable.invoke(&[5.into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0b1010);
+        let result = runnable.invoke(&[(-1).into()]).unwrap().get_i32().unwrap();
+        assert_eq!(result, 0b0101);
+
+        // v128 parameter, f32x4.splat, f32x4.add and f32x4.extract_lane
+        let runnable = module.func("fadd").unwrap();
+        let v = 0x4080_0000_4040_0000_4000_0000_3F80_0000u128;
+        let result = runnable.invoke(&[v.into(), 0.5f32.into()]).unwrap();
+        assert_eq!(result, WasmValue::F32(3.5));
+
+        // v128.load32_splat and i64x2.extract_lane
+        let runnable = module.func("splat").unwrap();
+        let result = runnable.invoke(&[4.into()]).unwrap();
+        assert_eq!(result, WasmValue::I64(0x0000_0002_0000_0002));
+        assert!(matches!(
+            runnable.invoke(&[0xFFFE.into()]),
+            Err(WasmRuntimeError::OutOfBounds)
+        ));
+
+        // i8x16.extract_lane_s 16
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x19, 0x01, 0x17, 0x00, 0xFD, 0x0C, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xFD, 0x15, 0x10, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "threads")]
+    fn threads() {
+        use crate::threads::SharedMemoryBackend;
+        use crate::wasm::MemoryBackend;
+        use std::thread;
+
+        const SLICE: &[u8] = &[
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x15, 0x04, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x01, 0x7E, 0x01, 0x7F, 0x60, 0x00, 0x01, 0x7F, 0x60, 0x02, 0x7F,
+            0x7F, 0x01, 0x7F, 0x02, 0x10, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x06, 0x6D, 0x65, 0x6D,
+            0x6F, 0x72, 0x79, 0x02, 0x03, 0x01, 0x01, 0x03, 0x06, 0x05, 0x00, 0x01, 0x02, 0x03,
+            0x00, 0x07, 0x2A, 0x05, 0x05, 0x63, 0x6F, 0x75, 0x6E, 0x74, 0x00, 0x00, 0x04, 0x77,
+            0x61, 0x69, 0x74, 0x00, 0x01, 0x06, 0x6E, 0x6F, 0x74, 0x69, 0x66, 0x79, 0x00, 0x02,
+            0x07, 0x63, 0x6D, 0x70, 0x78, 0x63, 0x68, 0x67, 0x00, 0x03, 0x04, 0x6C, 0x6F, 0x61,
+            0x64, 0x00, 0x04, 0x0A, 0x58, 0x05, 0x25, 0x00, 0x02, 0x40, 0x03, 0x40, 0x20, 0x00,
+            0x45, 0x0D, 0x01, 0x41, 0x00, 0x41, 0x01, 0xFE, 0x1E, 0x02, 0x00, 0x1A, 0x20, 0x00,
+            0x41, 0x01, 0x6B, 0x21, 0x00, 0x0C, 0x00, 0x0B, 0x0B, 0x41, 0x00, 0xFE, 0x10, 0x02,
+            0x00, 0x0B, 0x0C, 0x00, 0x41, 0x08, 0x41, 0x00, 0x20, 0x00, 0xFE, 0x01, 0x02, 0x00,
+            0x0B, 0x0D, 0x00, 0xFE, 0x03, 0x00, 0x41, 0x08, 0x41, 0x01, 0xFE, 0x00, 0x02, 0x00,
+            0x0B, 0x0C, 0x00, 0x41, 0x10, 0x20, 0x00, 0x20, 0x01, 0xFE, 0x48, 0x02, 0x00, 0x0B,
+            0x08, 0x00, 0x20, 0x00, 0xFE, 0x10, 0x02, 0x00, 0x0B,
+        ];
+        let shared = SharedMemoryBackend::new(0x10000).unwrap();
+        let instantiate = |shared: SharedMemoryBackend| {
+            let mut loader = WasmLoader::with_features(WasmFeatures::available());
+            loader
+                .define_memory("env", "memory", shared)
+                .load(
+                    SLICE,
+                    &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                    &VecMemoryBackend::allocator,
+                )
+                .unwrap();
+            loader.into_module()
+        };
+        let module = instantiate(shared.clone());
+        assert!(module.memory(0).unwrap().limit().is_shared());
+
+        // i32.atomic.rmw.add from two instances on their own threads
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let module = instantiate(shared);
+                    let runnable = module.func("count").unwrap();
+                    runnable.invoke(&[1000.into()]).unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let runnable = module.func("load").unwrap();
+        assert_eq!(runnable.invoke(&[0.into()]).unwrap(), WasmValue::I32(2000));
+        assert!(matches!(
+            runnable.invoke(&[2.into()]),
+            Err(WasmRuntimeError::UnalignedAccess)
+        ));
+
+        // i32.atomic.rmw.cmpxchg
+        let runnable = module.func("cmpxchg").unwrap();
+        assert_eq!(
+            runnable.invoke(&[1.into(), 2.into()]).unwrap(),
+            WasmValue::I32(0)
+        );
+        assert_eq!(
+            runnable.invoke(&[0.into(), 3.into()]).unwrap(),
+            WasmValue::I32(0)
+        );
+        assert_eq!(
+            runnable.invoke(&[0.into(), 4.into()]).unwrap(),
+            WasmValue::I32(3)
+        );
+
+        // memory.atomic.wait32 timed out, and woken by memory.atomic.notify
+        let runnable = module.func("wait").unwrap();
+        assert_eq!(
+            runnable.invoke(&[1000i64.into()]).unwrap(),
+            WasmValue::I32(2)
+        );
+        let waiter = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let module = instantiate(shared);
+                let runnable = module.func("wait").unwrap();
+                runnable.invoke(&[(-1i64).into()]).unwrap()
+            })
+        };
+        let runnable = module.func("notify").unwrap();
+        while runnable.invoke(&[]).unwrap() != WasmValue::I32(1) {
+            thread::yield_now();
+        }
+        assert_eq!(waiter.join().unwrap(), WasmValue::I32(0));
+
+        // memory.atomic.wait32 on an unshared memory
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x08, 0x01, 0x04,
+            0x77, 0x61, 0x69, 0x74, 0x00, 0x00, 0x0A, 0x0E, 0x01, 0x0C, 0x00, 0x41, 0x00, 0x41,
+            0x00, 0x42, 0x00, 0xFE, 0x01, 0x02, 0x00, 0x0B,
+        ];
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+        let runnable = module.func("wait").unwrap();
+        assert!(matches!(
+            runnable.invoke(&[]),
+            Err(WasmRuntimeError::ExpectedSharedMemory)
+        ));
+
+        // i32.atomic.load with the alignment less than the natural one
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x03, 0x02, 0x01, 0x00, 0x05, 0x04, 0x01, 0x03, 0x01, 0x01, 0x0A, 0x0A, 0x01,
+            0x08, 0x00, 0x41, 0x00, 0xFE, 0x10, 0x01, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // growing the shared region from many threads never returns the same old size twice
+        const PAGE_SIZE: usize = 0x10000;
+        let shared = SharedMemoryBackend::new(PAGE_SIZE * 32).unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mut shared = shared.clone();
+                thread::spawn(move || {
+                    (0..8)
+                        .map(|_| shared.grow(PAGE_SIZE, usize::MAX).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut old_sizes: Vec<_> = threads
+            .into_iter()
+            .flat_map(|v| v.join().unwrap())
+            .collect();
+        old_sizes.sort_unstable();
+        assert_eq!(
+            old_sizes,
+            (0..32).map(|v| v * PAGE_SIZE).collect::<Vec<_>>()
+        );
+        assert_eq!(shared.len(), PAGE_SIZE * 32);
+        let mut shared = shared;
+        assert!(shared.grow(PAGE_SIZE, usize::MAX).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "exceptions")]
+    fn exception_store() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0D, 0x03, 0x60, 0x00, 0x00,
+            0x60, 0x01, 0x7F, 0x01, 0x7F, 0x60, 0x00, 0x01, 0x69, 0x03, 0x03, 0x02, 0x01, 0x02,
+            0x0D, 0x03, 0x01, 0x00, 0x00, 0x0A, 0x36, 0x02, 0x25, 0x00, 0x02, 0x40, 0x03, 0x40,
+            0x20, 0x00, 0x45, 0x0D, 0x01, 0x02, 0x69, 0x1F, 0x40, 0x01, 0x03, 0x00, 0x08, 0x00,
+            0x0B, 0x00, 0x0B, 0x1A, 0x20, 0x00, 0x41, 0x01, 0x6B, 0x21, 0x00, 0x0C, 0x00, 0x0B,
+            0x0B, 0x41, 0x00, 0x0B, 0x0E, 0x00, 0x02, 0x69, 0x1F, 0x40, 0x01, 0x03, 0x00, 0x08,
+            0x00, 0x0B, 0x00, 0x0B, 0x0B,
+        ];
+
+        let module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        // throw and catch_all_ref in a loop, the exnrefs are released after the invocation
+        let runnable = module.func_by_index(0).unwrap();
+        for _ in 0..10 {
+            runnable.invoke(&[100.into()]).unwrap();
+            assert_eq!(module.n_exceptions(), 0);
+        }
+
+        // the exnref returned to the host is valid until the next invocation returns
+        let runnable = module.func_by_index(1).unwrap();
+        let exnref = match runnable.invoke(&[]).unwrap() {
+            WasmValue::ExnRef(Some(v)) => v,
+            _ => unreachable!(),
+        };
+        assert_eq!(module.n_exceptions(), 1);
+        assert_eq!(module.exception(exnref).unwrap().tag(), 0);
+        module
+            .func_by_index(0)
+            .unwrap()
+            .invoke(&[1.into()])
+            .unwrap();
+        assert_eq!(module.n_exceptions(), 0);
+        assert!(module.exception(exnref).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "exceptions")]
+    fn exceptions() {
+        use crate::wasm::WasmException;
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0A, 0x02, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x60, 0x01, 0x7F, 0x00, 0x02, 0x19, 0x02, 0x03, 0x65, 0x6E, 0x76, 0x04,
+            0x68, 0x6F, 0x73, 0x74, 0x00, 0x00, 0x03, 0x65, 0x6E, 0x76, 0x05, 0x65, 0x72, 0x72,
+            0x6F, 0x72, 0x04, 0x00, 0x01, 0x03, 0x07, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x0D, 0x03, 0x01, 0x00, 0x01, 0x07, 0x3E, 0x06, 0x01, 0x65, 0x04, 0x01, 0x05, 0x63,
+            0x61, 0x74, 0x63, 0x68, 0x00, 0x02, 0x0A, 0x63, 0x61, 0x74, 0x63, 0x68, 0x5F, 0x68,
+            0x6F, 0x73, 0x74, 0x00, 0x03, 0x08, 0x75, 0x6E, 0x63, 0x61, 0x75, 0x67, 0x68, 0x74,
+            0x00, 0x04, 0x07, 0x72, 0x65, 0x74, 0x68, 0x72, 0x6F, 0x77, 0x00, 0x05, 0x0C, 0x63,
+            0x61, 0x74, 0x63, 0x68, 0x5F, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6E, 0x00, 0x06, 0x0A,
+            0x69, 0x06, 0x08, 0x00, 0x20, 0x00, 0x08, 0x01, 0x41, 0x00, 0x0B, 0x18, 0x00, 0x02,
+            0x7F, 0x1F, 0x40, 0x01, 0x00, 0x01, 0x00, 0x20, 0x00, 0x10, 0x01, 0x1A, 0x0B, 0x41,
+            0x7F, 0x0C, 0x00, 0x0B, 0x41, 0x01, 0x6A, 0x0B, 0x18, 0x00, 0x02, 0x7F, 0x1F, 0x40,
+            0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0x10, 0x00, 0x1A, 0x0B, 0x41, 0x7F, 0x0C, 0x00,
+            0x0B, 0x41, 0x01, 0x6A, 0x0B, 0x06, 0x00, 0x20, 0x00, 0x10, 0x01, 0x0B, 0x14, 0x00,
+            0x02, 0x69, 0x1F, 0x40, 0x01, 0x03, 0x00, 0x20, 0x00, 0x10, 0x01, 0x1A, 0x0B, 0x00,
+            0x0B, 0x0A, 0x41, 0x00, 0x0B, 0x10, 0x00, 0x1F, 0x40, 0x01, 0x00, 0x01, 0x00, 0x20,
+            0x00, 0x10, 0x01, 0x1A, 0x0B, 0x41, 0x7F, 0x0B,
+        ];
+        fn host(module: &WasmModule, params: &[WasmValue]) -> Result<WasmValue, WasmRuntimeError> {
+            let tag = module.tag_by_import("env", "error").unwrap();
+            let val = params[0].get_i32()?;
+            Err(module.throw(WasmException::new(tag, vec![(val * 2).into()])))
+        }
+        let module = WasmLoader::instantiate(&slice, &|_, _, _| Ok(host)).unwrap();
+        let tag = module.tag_by_name("e").unwrap();
+        assert_eq!(tag, 1);
+        assert_eq!(module.tag_by_import("env", "error"), Some(0));
+
+        // throw in the callee, and catch
+        let runnable = module.func("catch").unwrap();
+        assert_eq!(runnable.invoke(&[5.into()]).unwrap(), WasmValue::I32(6));
+
+        // throw from the host, and catch in the guest
+        let runnable = module.func("catch_host").unwrap();
+        assert_eq!(runnable.invoke(&[5.into()]).unwrap(), WasmValue::I32(11));
+
+        // the guest exception reaches the host
+        let runnable = module.func("uncaught").unwrap();
+        assert!(matches!(
+            runnable.invoke(&[7.into()]),
+            Err(WasmRuntimeError::Exception)
+        ));
+        let exception = module.take_exception().unwrap();
+        assert_eq!(exception.tag(), tag);
+        assert_eq!(exception.values(), &[WasmValue::I32(7)]);
+        assert!(module.take_exception().is_none());
+
+        // catch_all_ref and throw_ref
+        let runnable = module.func("rethrow").unwrap();
+        assert!(matches!(
+            runnable.invoke(&[9.into()]),
+            Err(WasmRuntimeError::Exception)
+        ));
+        let exception = module.take_exception().unwrap();
+        assert_eq!(exception, WasmException::new(tag, vec![9.into()]));
+
+        // the label of the function itself
+        let runnable = module.func("catch_return").unwrap();
+        assert_eq!(runnable.invoke(&[4.into()]).unwrap(), WasmValue::I32(4));
+
+        // catch_all to the label of [i32]
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x02, 0x01, 0x00, 0x0A, 0x0C, 0x01, 0x0A, 0x00, 0x1F, 0x40, 0x01,
+            0x02, 0x00, 0x0B, 0x41, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // throw with the value of another type
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x01, 0x7F,
+            0x00, 0x03, 0x02, 0x01, 0x00, 0x0D, 0x03, 0x01, 0x00, 0x00, 0x0A, 0x08, 0x01, 0x06,
+            0x00, 0x42, 0x00, 0x08, 0x00, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::TypeMismatch)
+        ));
+
+        // throw of the undefined tag
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x01, 0x7F,
+            0x00, 0x03, 0x02, 0x01, 0x00, 0x0D, 0x03, 0x01, 0x00, 0x00, 0x0A, 0x08, 0x01, 0x06,
+            0x00, 0x20, 0x00, 0x08, 0x01, 0x0B,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidParameter)
+        ));
+
+        // the type of the tag has a result
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01,
+            0x7F, 0x0D, 0x03, 0x01, 0x00, 0x00,
+        ];
+        assert!(matches!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError)),
+            Err(WasmDecodeError::InvalidType)
+        ));
+
+        // the tag section requires the proposal
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x01, 0x7F,
+            0x00, 0x0D, 0x03, 0x01, 0x00, 0x00,
+        ];
+        assert!(
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .is_ok()
+        );
+        let mut loader =
+            WasmLoader::with_features(WasmFeatures::available() - WasmFeatures::EXCEPTIONS);
+        assert!(matches!(
+            loader.load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            ),
+            Err(WasmDecodeError::FeatureNotEnabled(WasmFeatures::EXCEPTIONS))
+        ));
+    }
+
+    #[test]
+    fn reentrancy() {
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x02, 0x0C, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x04, 0x62, 0x61, 0x63, 0x6B,
+            0x00, 0x00, 0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x06, 0x07, 0x01, 0x7F, 0x01, 0x41,
+            0x80, 0x08, 0x0B, 0x07, 0x18, 0x03, 0x03, 0x72, 0x65, 0x63, 0x00, 0x01, 0x05, 0x63,
+            0x6F, 0x75, 0x6E, 0x74, 0x00, 0x02, 0x06, 0x6D, 0x61, 0x6C, 0x6C, 0x6F, 0x63, 0x00,
+            0x03, 0x0A, 0x39, 0x03, 0x15, 0x00, 0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x00, 0x05,
+            0x20, 0x00, 0x41, 0x01, 0x6B, 0x10, 0x00, 0x41, 0x01, 0x6A, 0x0B, 0x0B, 0x15, 0x00,
+            0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x00, 0x05, 0x20, 0x00, 0x41, 0x01, 0x6B, 0x10,
+            0x02, 0x41, 0x01, 0x6A, 0x0B, 0x0B, 0x0B, 0x00, 0x23, 0x00, 0x23, 0x00, 0x20, 0x00,
+            0x6A, 0x24, 0x00, 0x0B,
+        ];
+        let mut loader = WasmLoader::with_features(WasmFeatures::available());
+        loader
+            .set_max_call_depth(32)
+            .define_func("env", "back", |caller: &WasmCaller, n: i32| {
+                let _ = caller.typed_func::<i32, i32>("malloc")?.call(4)?;
+                caller.typed_func::<i32, i32>("rec")?.call(n)
+            });
+        loader
+            .load(
+                &slice,
+                &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                &VecMemoryBackend::allocator,
+            )
+            .unwrap();
+        let module = loader.into_module();
+
+        // guest -> host -> guest
+        let rec = module.typed_func::<i32, i32>("rec").unwrap();
+        assert_eq!(rec.call(10).unwrap(), 10);
+        let malloc = module.typed_func::<i32, i32>("malloc").unwrap();
+        assert_eq!(malloc.call(0).unwrap(), 1024 + 40);
+
+        // the frames below the host functions count toward the limit
+        assert_eq!(module.max_call_depth(), 32);
+        assert_eq!(rec.call(31).unwrap(), 31);
+        assert!(matches!(rec.call(32), Err(WasmRuntimeError::StackOverflow)));
+
+        let count = module.typed_func::<i32, i32>("count").unwrap();
+        assert_eq!(count.call(31).unwrap(), 31);
+        assert!(matches!(
+            count.call(100_000),
+            Err(WasmRuntimeError::StackOverflow)
+        ));
+    }
+
+    #[test]
+    fn default_call_depth() {
+        use std::thread;
+
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x02, 0x0C, 0x01, 0x03, 0x65, 0x6E, 0x76, 0x04, 0x62, 0x61, 0x63, 0x6B,
+            0x00, 0x00, 0x03, 0x04, 0x03, 0x00, 0x00, 0x00, 0x06, 0x07, 0x01, 0x7F, 0x01, 0x41,
+            0x80, 0x08, 0x0B, 0x07, 0x18, 0x03, 0x03, 0x72, 0x65, 0x63, 0x00, 0x01, 0x05, 0x63,
+            0x6F, 0x75, 0x6E, 0x74, 0x00, 0x02, 0x06, 0x6D, 0x61, 0x6C, 0x6C, 0x6F, 0x63, 0x00,
+            0x03, 0x0A, 0x39, 0x03, 0x15, 0x00, 0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x00, 0x05,
+            0x20, 0x00, 0x41, 0x01, 0x6B, 0x10, 0x00, 0x41, 0x01, 0x6A, 0x0B, 0x0B, 0x15, 0x00,
+            0x20, 0x00, 0x45, 0x04, 0x7F, 0x41, 0x00, 0x05, 0x20, 0x00, 0x41, 0x01, 0x6B, 0x10,
+            0x02, 0x41, 0x01, 0x6A, 0x0B, 0x0B, 0x0B, 0x00, 0x23, 0x00, 0x23, 0x00, 0x20, 0x00,
+            0x6A, 0x24, 0x00, 0x0B,
+        ];
+
+        // the default limit traps before the frames overflow the stack of a spawned thread
+        let thread = thread::Builder::new()
+            .stack_size(0x20_0000)
+            .spawn(move || {
+                let mut loader = WasmLoader::with_features(WasmFeatures::available());
+                loader.define_func("env", "back", |caller: &WasmCaller, n: i32| {
+                    caller.typed_func::<i32, i32>("rec")?.call(n)
+                });
+                loader
+                    .load(
+                        &slice,
+                        &|_, _, _| Err(WasmDecodeError::DynamicLinkError),
+                        &|_, _, _, _| Err(WasmDecodeError::DynamicLinkError),
+                        &VecMemoryBackend::allocator,
+                    )
+                    .unwrap();
+                let module = loader.into_module();
+                let depth = WasmModule::DEFAULT_MAX_CALL_DEPTH;
+                assert_eq!(module.max_call_depth(), depth);
+
+                let count = module.typed_func::<i32, i32>("count").unwrap();
+                assert_eq!(count.call(depth as i32 - 1).unwrap(), depth as i32 - 1);
+                assert!(matches!(
+                    count.call(100_000),
+                    Err(WasmRuntimeError::StackOverflow)
+                ));
+
+                // guest -> host -> guest
+                let rec = module.typed_func::<i32, i32>("rec").unwrap();
+                assert!(matches!(
+                    rec.call(100_000),
+                    Err(WasmRuntimeError::StackOverflow)
+                ));
+            })
+            .unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn debug_hook() {
+        use crate::opcode::WasmOpcode;
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+
+        // (func $add3 (param i32) (result i32) local.get 0 call 1 i32.const 2 i32.add)
+        // (func (param i32) (result i32) (local i64) local.get 0 i32.const -1 i32.add i32.const 2 i32.add)
+        let slice = [
+            0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7F,
+            0x01, 0x7F, 0x03, 0x03, 0x02, 0x00, 0x00, 0x07, 0x08, 0x01, 0x04, 0x61, 0x64, 0x64,
+            0x33, 0x00, 0x00, 0x0A, 0x18, 0x02, 0x09, 0x00, 0x20, 0x00, 0x10, 0x01, 0x41, 0x02,
+            0x6A, 0x0B, 0x0C, 0x01, 0x01, 0x7E, 0x20, 0x00, 0x41, 0x7F, 0x6A, 0x41, 0x02, 0x6A,
+            0x0B,
+        ];
+        let mut module =
+            WasmLoader::instantiate(&slice, &|_, _, _| Err(WasmDecodeError::DynamicLinkError))
+                .unwrap();
+
+        let trace = Rc::new(RefCell::new(Vec::new()));
+        let locals = Rc::new(RefCell::new(Vec::new()));
+        let stack = Rc::new(RefCell::new(Vec::new()));
+        {
+            let trace = trace.clone();
+            let locals = locals.clone();
+            let stack = stack.clone();
+            module.set_debug_hook(move |frame| {
+                trace.borrow_mut().push((
+                    frame.func_index(),
+                    frame.position(),
+                    frame.depth(),
+                    frame.value_stack().len(),
+                ));
+                if frame.func_index() == 1
+                    && frame.position() == 4
+                    && matches!(frame.opcode(), WasmOpcode::I32Add)
+                {
+                    locals.replace(frame.locals());
+                    stack.replace(
+                        frame
+                            .value_stack()
+                            .iter()
+                            .map(|v| v.get_u64())
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                Ok(())
+            });
+        }
+        let add3 = module.typed_func::<i32, i32>("add3").unwrap();
+        assert_eq!(add3.call(5).unwrap(), 8);
+        assert_eq!(
+            trace.borrow().as_slice(),
+            &[
+                (0, 0, 1, 0),
+                (0, 2, 1, 1),
+                (1, 0, 2, 0),
+                (1, 2, 2, 1),
+                (1, 4, 2, 2),
+                (1, 5, 2, 1),
+                (1, 7, 2, 2),
+                (1, 8, 2, 1),
+                (0, 4, 1, 1),
+                (0, 6, 1, 2),
+                (0, 7, 1, 1),
+            ]
+        );
+        assert_eq!(
+            locals.borrow().as_slice(),
+            &[WasmValue::I32(5), WasmValue::I64(0)]
+        );
+        // the raw bits of i32 are sign-extended
+        assert_eq!(stack.borrow().as_slice(), &[5, u64::MAX]);
+
+        // the hook stops the execution
+        module.set_debug_hook(|frame| {
+            if frame.func_index() == 1 {
+                Err(WasmRuntimeError::Interrupted)
+            } else {
+                Ok(())
+            }
+        });
+        let add3 = module.typed_func::<i32, i32>("add3").unwrap();
+        assert!(matches!(add3.call(5), Err(WasmRuntimeError::Interrupted)));
+
+        module.clear_debug_hook();
+        let add3 = module.typed_func::<i32, i32>("add3").unwrap();
+        assert_eq!(add3.call(5).unwrap(), 8);
+    }
 }